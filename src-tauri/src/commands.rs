@@ -1,7 +1,27 @@
-use tauri::State;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
 use crate::database::{
-    ConnectionManager, CreateConnectionParams, TestConnectionParams,
+    benchmark, cells, completer, ddl, diff, fanout, formatter, graph, introspection, objects, search, snippets,
+    validator,
+};
+use crate::database::connection::DatabasePool;
+use crate::database::{
+    ConnectionError, ConnectionManager, CreateConnectionParams, TestConnectionParams,
     ExecuteQueryParams, ConnectionConfig, TestConnectionResult, QueryResult, TableInfo,
+    QueryHistory, HistoryEntry, ListHistoryParams, SearchHistoryParams,
+    SnippetLibrary, SavedQuery, SaveQueryParams, ListSavedQueriesParams,
+    NotificationHub, ChannelNotification,
+    JobManager, JobInfo, SubmitQueryJobParams, GetJobResultParams, JobResultPage,
+    ResultCache, GetResultPageParams, ResultPage, FetchCellValueParams, CellValue,
+    BenchmarkParams, BenchmarkResult, FanOutQueryParams, FanOutResult,
+    FormatSqlParams, DatabaseType, SchemaCache, ValidateSqlParams, ValidateSqlResult,
+    DiagnosticSeverity, CompletionResult, SchemaCacheInfo, TableColumn,
+    TableStructure, ListObjectsParams, DatabaseObject, GetObjectDefinitionParams,
+    DatabaseInfo, SchemaInfo, RunningQueries, CountRowsParams, RowCountResult, GenerateDdlParams,
+    SchemaRef, DiffScope, SchemaDiff, RelationshipGraphParams, RelationshipGraph, GraphFormat,
+    SearchObjectsParams, ObjectSearchHit, SearchDataParams, DataSearchSummary,
 };
 
 /// Create a new connection configuration
@@ -9,10 +29,8 @@ use crate::database::{
 pub async fn create_connection(
     manager: State<'_, ConnectionManager>,
     params: CreateConnectionParams,
-) -> Result<String, String> {
-    manager
-        .create_connection(params)
-        .map_err(|e| e.to_string())
+) -> Result<String, ConnectionError> {
+    manager.create_connection(params)
 }
 
 /// Test a connection without saving
@@ -20,7 +38,7 @@ pub async fn create_connection(
 pub async fn test_connection(
     manager: State<'_, ConnectionManager>,
     params: TestConnectionParams,
-) -> Result<TestConnectionResult, String> {
+) -> Result<TestConnectionResult, ConnectionError> {
     Ok(manager.test_connection(params).await)
 }
 
@@ -29,29 +47,28 @@ pub async fn test_connection(
 pub async fn connect(
     manager: State<'_, ConnectionManager>,
     connection_id: String,
-) -> Result<(), String> {
-    manager
-        .connect(&connection_id)
-        .await
-        .map_err(|e| e.to_string())
+) -> Result<(), ConnectionError> {
+    manager.connect(&connection_id).await
 }
 
 /// Disconnect from a connection
 #[tauri::command]
 pub async fn disconnect(
     manager: State<'_, ConnectionManager>,
+    hub: State<'_, NotificationHub>,
+    schemas: State<'_, SchemaCache>,
     connection_id: String,
-) -> Result<(), String> {
-    manager
-        .disconnect(&connection_id)
-        .map_err(|e| e.to_string())
+) -> Result<(), ConnectionError> {
+    hub.stop(&connection_id).await;
+    schemas.invalidate(&connection_id);
+    manager.disconnect(&connection_id)
 }
 
 /// List all saved connections
 #[tauri::command]
 pub async fn list_connections(
     manager: State<'_, ConnectionManager>,
-) -> Result<Vec<ConnectionConfig>, String> {
+) -> Result<Vec<ConnectionConfig>, ConnectionError> {
     Ok(manager.list_connections())
 }
 
@@ -59,33 +76,642 @@ pub async fn list_connections(
 #[tauri::command]
 pub async fn delete_connection(
     manager: State<'_, ConnectionManager>,
+    hub: State<'_, NotificationHub>,
+    schemas: State<'_, SchemaCache>,
     connection_id: String,
-) -> Result<(), String> {
-    manager
-        .delete_connection(&connection_id)
-        .map_err(|e| e.to_string())
+) -> Result<(), ConnectionError> {
+    hub.stop(&connection_id).await;
+    schemas.invalidate(&connection_id);
+    manager.delete_connection(&connection_id)
 }
 
-/// Execute a SQL query
+/// Execute a SQL query and record it in history
 #[tauri::command]
 pub async fn execute_query(
     manager: State<'_, ConnectionManager>,
-    params: ExecuteQueryParams,
-) -> Result<QueryResult, String> {
-    manager
-        .execute_query(params)
-        .await
-        .map_err(|e| e.to_string())
+    history: State<'_, QueryHistory>,
+    cache: State<'_, ResultCache>,
+    schemas: State<'_, SchemaCache>,
+    mut params: ExecuteQueryParams,
+) -> Result<QueryResult, ConnectionError> {
+    if !params.variables.is_empty() {
+        params.sql = snippets::render_template(&params.sql, &params.variables)?;
+    }
+
+    let connection_id = params.connection_id.clone();
+    let sql = params.sql.clone();
+    let started_at = chrono::Utc::now();
+    let start = Instant::now();
+
+    let result = if params.cache {
+        manager.execute_cached(params, &cache).await
+    } else {
+        manager.execute_query(params).await
+    };
+
+    // Even a failed script may have run some DDL before the error
+    if let Ok(config) = manager.get_config(&connection_id) {
+        schemas.invalidate_after(&connection_id, &sql, config.db_type);
+    }
+
+    // History is best-effort and must never fail the query itself
+    let duration_ms = start.elapsed().as_millis() as i64;
+    let _ = history
+        .record(&connection_id, &sql, started_at, duration_ms, &result)
+        .await;
+
+    result
 }
 
-/// List tables for a connection
+/// List tables for a connection, optionally in another database or one schema
 #[tauri::command]
 pub async fn list_tables(
     manager: State<'_, ConnectionManager>,
     connection_id: String,
-) -> Result<Vec<TableInfo>, String> {
+    database: Option<String>,
+    schema: Option<String>,
+) -> Result<Vec<TableInfo>, ConnectionError> {
     manager
-        .list_tables(&connection_id)
+        .list_tables(&connection_id, database.as_deref(), schema.as_deref())
         .await
-        .map_err(|e| e.to_string())
+}
+
+/// Count a table's rows exactly, for when the estimate from `list_tables`
+/// isn't good enough. Stops after `timeoutMs` or on `cancel_query`.
+#[tauri::command]
+pub async fn count_rows_exact(
+    manager: State<'_, ConnectionManager>,
+    running: State<'_, RunningQueries>,
+    params: CountRowsParams,
+) -> Result<RowCountResult, ConnectionError> {
+    let pool = manager
+        .get_target_pool(&params.connection_id, params.database.as_deref(), None)
+        .await?;
+    let query_id = params
+        .query_id
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let handle = running.track(&query_id, pool.clone())?;
+
+    let start = Instant::now();
+    let count = handle
+        .run(
+            Duration::from_millis(params.timeout_ms),
+            introspection::count_rows(&pool, params.schema.as_deref(), &params.table_name, |backend_id| {
+                handle.started(backend_id)
+            }),
+        )
+        .await?;
+    Ok(RowCountResult {
+        count,
+        execution_time_ms: start.elapsed().as_millis(),
+    })
+}
+
+/// Scan tables for a value, emitting each match as a `data-search-match`
+/// event tagged with the query ID as soon as its table has been searched
+#[tauri::command]
+pub async fn search_data(
+    app: AppHandle,
+    manager: State<'_, ConnectionManager>,
+    running: State<'_, RunningQueries>,
+    params: SearchDataParams,
+) -> Result<DataSearchSummary, ConnectionError> {
+    let pool = manager
+        .get_target_pool(&params.connection_id, params.database.as_deref(), None)
+        .await?;
+    let query_id = params
+        .query_id
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let handle = running.track(&query_id, pool.clone())?;
+
+    search::data(&pool, &params, &query_id, &handle, |found| {
+        let _ = app.emit("data-search-match", found);
+    })
+    .await
+}
+
+/// Cancel a statement started with a query ID, such as `count_rows_exact`
+/// or `search_data`
+#[tauri::command]
+pub async fn cancel_query(
+    running: State<'_, RunningQueries>,
+    query_id: String,
+) -> Result<(), ConnectionError> {
+    running.cancel(&query_id).await
+}
+
+/// List the databases on a connection's server
+#[tauri::command]
+pub async fn list_databases(
+    manager: State<'_, ConnectionManager>,
+    connection_id: String,
+) -> Result<Vec<DatabaseInfo>, ConnectionError> {
+    let pool = manager.get_pool(&connection_id)?;
+    let config = manager.get_config(&connection_id)?;
+    introspection::databases(&pool, &config.database).await
+}
+
+/// List the schemas of a connection's database, or of another database on
+/// the same server
+#[tauri::command]
+pub async fn list_schemas(
+    manager: State<'_, ConnectionManager>,
+    connection_id: String,
+    database: Option<String>,
+) -> Result<Vec<SchemaInfo>, ConnectionError> {
+    let pool = manager
+        .get_target_pool(&connection_id, database.as_deref(), None)
+        .await?;
+    introspection::schemas(&pool).await
+}
+
+/// Describe the columns of a table or view
+#[tauri::command]
+pub async fn get_table_schema(
+    manager: State<'_, ConnectionManager>,
+    connection_id: String,
+    table_name: String,
+    schema: Option<String>,
+    database: Option<String>,
+) -> Result<Vec<TableColumn>, ConnectionError> {
+    let pool = manager.get_target_pool(&connection_id, database.as_deref(), None).await?;
+    introspection::table_columns(&pool, schema.as_deref(), &table_name).await
+}
+
+/// Describe the indexes, constraints and foreign keys of a table
+#[tauri::command]
+pub async fn get_table_structure(
+    manager: State<'_, ConnectionManager>,
+    connection_id: String,
+    table_name: String,
+    schema: Option<String>,
+    database: Option<String>,
+) -> Result<TableStructure, ConnectionError> {
+    let pool = manager.get_target_pool(&connection_id, database.as_deref(), None).await?;
+    introspection::table_structure(&pool, schema.as_deref(), &table_name).await
+}
+
+/// List views, routines, triggers, sequences, types and events as well as tables
+#[tauri::command]
+pub async fn list_objects(
+    manager: State<'_, ConnectionManager>,
+    params: ListObjectsParams,
+) -> Result<Vec<DatabaseObject>, ConnectionError> {
+    let pool = manager
+        .get_target_pool(&params.connection_id, params.database.as_deref(), None)
+        .await?;
+    objects::list(&pool, &params).await
+}
+
+/// Get the source of a view, routine, trigger, sequence, type or event
+#[tauri::command]
+pub async fn get_object_definition(
+    manager: State<'_, ConnectionManager>,
+    params: GetObjectDefinitionParams,
+) -> Result<String, ConnectionError> {
+    let pool = manager
+        .get_target_pool(&params.connection_id, params.database.as_deref(), None)
+        .await?;
+    objects::definition(&pool, &params).await
+}
+
+/// Generate runnable CREATE DDL for a table, view, routine, sequence, type or trigger
+#[tauri::command]
+pub async fn generate_ddl(
+    manager: State<'_, ConnectionManager>,
+    params: GenerateDdlParams,
+) -> Result<String, ConnectionError> {
+    let pool = manager
+        .get_target_pool(&params.object.connection_id, params.object.database.as_deref(), None)
+        .await?;
+    ddl::generate(&pool, &params).await
+}
+
+/// Compare two schemas, on the same or different connections, and generate
+/// the SQL that migrates `target` to match `source`
+#[tauri::command]
+pub async fn diff_schemas(
+    manager: State<'_, ConnectionManager>,
+    source: SchemaRef,
+    target: SchemaRef,
+    scope: Option<DiffScope>,
+) -> Result<SchemaDiff, ConnectionError> {
+    let source_pool = manager
+        .get_target_pool(&source.connection_id, source.database.as_deref(), None)
+        .await?;
+    let target_pool = manager
+        .get_target_pool(&target.connection_id, target.database.as_deref(), None)
+        .await?;
+    diff::compare(&source_pool, &source, &target_pool, &target, &scope.unwrap_or_default()).await
+}
+
+/// Tables and the foreign keys between them, for drawing ER diagrams
+#[tauri::command]
+pub async fn get_relationship_graph(
+    manager: State<'_, ConnectionManager>,
+    params: RelationshipGraphParams,
+) -> Result<RelationshipGraph, ConnectionError> {
+    let pool = manager
+        .get_target_pool(&params.connection_id, params.database.as_deref(), None)
+        .await?;
+    graph::relationships(&pool, &params).await
+}
+
+/// Render the relationship graph as Graphviz DOT or Mermaid source
+#[tauri::command]
+pub async fn export_relationship_graph(
+    manager: State<'_, ConnectionManager>,
+    params: RelationshipGraphParams,
+    format: GraphFormat,
+) -> Result<String, ConnectionError> {
+    let pool = manager
+        .get_target_pool(&params.connection_id, params.database.as_deref(), None)
+        .await?;
+    let relationships = graph::relationships(&pool, &params).await?;
+    Ok(match format {
+        GraphFormat::Dot => graph::to_dot(&relationships),
+        GraphFormat::Mermaid => graph::to_mermaid(&relationships),
+    })
+}
+
+/// List recent query history, optionally for one connection
+#[tauri::command]
+pub async fn list_history(
+    history: State<'_, QueryHistory>,
+    params: ListHistoryParams,
+) -> Result<Vec<HistoryEntry>, ConnectionError> {
+    Ok(history.list(params).await?)
+}
+
+/// Full-text search over query history
+#[tauri::command]
+pub async fn search_history(
+    history: State<'_, QueryHistory>,
+    params: SearchHistoryParams,
+) -> Result<Vec<HistoryEntry>, ConnectionError> {
+    Ok(history.search(params).await?)
+}
+
+/// Clear query history for one connection, or all of it
+#[tauri::command]
+pub async fn clear_history(
+    history: State<'_, QueryHistory>,
+    connection_id: Option<String>,
+) -> Result<(), ConnectionError> {
+    Ok(history.clear(connection_id.as_deref()).await?)
+}
+
+/// Number of history entries kept before the oldest are pruned
+#[tauri::command]
+pub async fn get_history_retention(history: State<'_, QueryHistory>) -> Result<i64, ConnectionError> {
+    Ok(history.retention())
+}
+
+/// Change how many history entries are kept, pruning any beyond the new limit
+#[tauri::command]
+pub async fn set_history_retention(
+    history: State<'_, QueryHistory>,
+    retention: i64,
+) -> Result<(), ConnectionError> {
+    Ok(history.set_retention(retention).await?)
+}
+
+/// List saved queries, optionally scoped to a folder or connection
+#[tauri::command]
+pub async fn list_saved_queries(
+    manager: State<'_, ConnectionManager>,
+    library: State<'_, SnippetLibrary>,
+    mut params: ListSavedQueriesParams,
+) -> Result<Vec<SavedQuery>, ConnectionError> {
+    // Snippets scoped to a database type apply to every connection of that type
+    if params.db_type.is_none() {
+        if let Some(connection_id) = &params.connection_id {
+            params.db_type = manager.get_config(connection_id).ok().map(|c| c.db_type);
+        }
+    }
+
+    Ok(library.list(params).await?)
+}
+
+/// List the folders used by saved queries
+#[tauri::command]
+pub async fn list_snippet_folders(
+    library: State<'_, SnippetLibrary>,
+) -> Result<Vec<String>, ConnectionError> {
+    Ok(library.folders().await?)
+}
+
+/// Get a single saved query
+#[tauri::command]
+pub async fn get_saved_query(
+    library: State<'_, SnippetLibrary>,
+    id: String,
+) -> Result<SavedQuery, ConnectionError> {
+    Ok(library.get(&id).await?)
+}
+
+/// Create or update a saved query
+#[tauri::command]
+pub async fn save_query(
+    library: State<'_, SnippetLibrary>,
+    params: SaveQueryParams,
+) -> Result<SavedQuery, ConnectionError> {
+    Ok(library.save(params).await?)
+}
+
+/// Delete a saved query
+#[tauri::command]
+pub async fn delete_saved_query(
+    library: State<'_, SnippetLibrary>,
+    id: String,
+) -> Result<(), ConnectionError> {
+    Ok(library.delete(&id).await?)
+}
+
+/// Resolve a saved query's template variables into runnable SQL
+#[tauri::command]
+pub async fn render_saved_query(
+    library: State<'_, SnippetLibrary>,
+    id: String,
+    variables: HashMap<String, String>,
+) -> Result<String, ConnectionError> {
+    Ok(library.render(&id, &variables).await?)
+}
+
+/// Import saved queries from a `.sql` file or a directory of them
+#[tauri::command]
+pub async fn import_saved_queries(
+    library: State<'_, SnippetLibrary>,
+    path: PathBuf,
+) -> Result<Vec<SavedQuery>, ConnectionError> {
+    Ok(library.import(&path).await?)
+}
+
+/// Export saved queries as `.sql` files into a directory
+#[tauri::command]
+pub async fn export_saved_queries(
+    library: State<'_, SnippetLibrary>,
+    path: PathBuf,
+    folder: Option<String>,
+) -> Result<Vec<PathBuf>, ConnectionError> {
+    Ok(library.export(&path, folder).await?)
+}
+
+/// LISTEN on a Postgres channel; notifications arrive as `pg-notification` events
+#[tauri::command]
+pub async fn subscribe_channel(
+    manager: State<'_, ConnectionManager>,
+    hub: State<'_, NotificationHub>,
+    connection_id: String,
+    channel: String,
+) -> Result<(), ConnectionError> {
+    let pool = manager.get_pool(&connection_id)?;
+    let DatabasePool::Postgres(pool) = pool.as_ref() else {
+        return Err(ConnectionError::UnsupportedType);
+    };
+
+    hub.subscribe(&connection_id, pool, &channel).await
+}
+
+/// UNLISTEN from a Postgres channel
+#[tauri::command]
+pub async fn unsubscribe_channel(
+    hub: State<'_, NotificationHub>,
+    connection_id: String,
+    channel: String,
+) -> Result<(), ConnectionError> {
+    hub.unsubscribe(&connection_id, &channel).await
+}
+
+/// List the channels a connection is subscribed to
+#[tauri::command]
+pub async fn list_subscriptions(
+    hub: State<'_, NotificationHub>,
+    connection_id: String,
+) -> Result<Vec<String>, ConnectionError> {
+    Ok(hub.subscriptions(&connection_id).await)
+}
+
+/// Recent notifications received on a connection, oldest first
+#[tauri::command]
+pub async fn list_notifications(
+    hub: State<'_, NotificationHub>,
+    connection_id: String,
+) -> Result<Vec<ChannelNotification>, ConnectionError> {
+    Ok(hub.recent(&connection_id))
+}
+
+/// Run a query in the background; progress is reported through `query-job` events
+#[tauri::command]
+pub async fn submit_query_job(
+    manager: State<'_, ConnectionManager>,
+    jobs: State<'_, JobManager>,
+    params: SubmitQueryJobParams,
+) -> Result<JobInfo, ConnectionError> {
+    let sql = if params.variables.is_empty() {
+        params.sql
+    } else {
+        snippets::render_template(&params.sql, &params.variables)?
+    };
+
+    let pool = manager.get_pool(&params.connection_id)?;
+    Ok(jobs.submit(&params.connection_id, pool, sql))
+}
+
+/// List background jobs started this session
+#[tauri::command]
+pub async fn list_jobs(
+    jobs: State<'_, JobManager>,
+    connection_id: Option<String>,
+) -> Result<Vec<JobInfo>, ConnectionError> {
+    Ok(jobs.list(connection_id.as_deref()))
+}
+
+/// Read a page of a finished job's result
+#[tauri::command]
+pub async fn get_job_result(
+    jobs: State<'_, JobManager>,
+    params: GetJobResultParams,
+) -> Result<JobResultPage, ConnectionError> {
+    jobs.get_result(params).await
+}
+
+/// Cancel a running background job
+#[tauri::command]
+pub async fn cancel_job(
+    jobs: State<'_, JobManager>,
+    job_id: String,
+) -> Result<JobInfo, ConnectionError> {
+    jobs.cancel(&job_id).await
+}
+
+/// Read a page of a cached query result, optionally sorted by a column
+#[tauri::command]
+pub async fn get_result_page(
+    cache: State<'_, ResultCache>,
+    params: GetResultPageParams,
+) -> Result<ResultPage, ConnectionError> {
+    cache.page(params).await
+}
+
+/// Drop a cached query result once the grid no longer needs it
+#[tauri::command]
+pub async fn release_result(
+    cache: State<'_, ResultCache>,
+    result_id: String,
+) -> Result<(), ConnectionError> {
+    cache.release(&result_id);
+    Ok(())
+}
+
+/// Read a single cell in full, from a cached result or by primary key,
+/// optionally writing it to a file
+#[tauri::command]
+pub async fn fetch_cell_value(
+    manager: State<'_, ConnectionManager>,
+    cache: State<'_, ResultCache>,
+    params: FetchCellValueParams,
+) -> Result<CellValue, ConnectionError> {
+    if let Some(result_id) = &params.result_id {
+        let row = params
+            .row
+            .ok_or_else(|| ConnectionError::InvalidInput("A row number is required".to_string()))?;
+        let (column, value) = cache.cell(result_id, row, &params.column).await?;
+        return cells::from_cached(&column, value, params.output_path.as_deref()).await;
+    }
+
+    let connection_id = params
+        .connection_id
+        .as_deref()
+        .ok_or_else(|| ConnectionError::InvalidInput("A connection or result ID is required".to_string()))?;
+    let pool = manager.get_pool(connection_id)?;
+    cells::fetch_by_key(&pool, &params).await
+}
+
+/// Run a statement repeatedly and report latency percentiles
+#[tauri::command]
+pub async fn benchmark_query(
+    manager: State<'_, ConnectionManager>,
+    params: BenchmarkParams,
+) -> Result<BenchmarkResult, ConnectionError> {
+    let pool = manager.get_pool(&params.connection_id)?;
+    benchmark::run(&pool, &params).await
+}
+
+/// Run one query on several connections and merge the rows
+#[tauri::command]
+pub async fn execute_fan_out(
+    manager: State<'_, ConnectionManager>,
+    schemas: State<'_, SchemaCache>,
+    mut params: FanOutQueryParams,
+) -> Result<FanOutResult, ConnectionError> {
+    if !params.variables.is_empty() {
+        params.sql = snippets::render_template(&params.sql, &params.variables)?;
+    }
+
+    let result = fanout::run(&manager, &params).await;
+    for connection_id in &params.connection_ids {
+        if let Ok(config) = manager.get_config(connection_id) {
+            schemas.invalidate_after(connection_id, &params.sql, config.db_type);
+        }
+    }
+    Ok(result)
+}
+
+/// Pretty-print SQL for a connection's dialect
+#[tauri::command]
+pub async fn format_sql(
+    manager: State<'_, ConnectionManager>,
+    params: FormatSqlParams,
+) -> Result<String, ConnectionError> {
+    let dialect = match &params.connection_id {
+        Some(connection_id) => manager.get_config(connection_id)?.db_type,
+        None => params.dialect.unwrap_or(DatabaseType::Postgres),
+    };
+
+    Ok(formatter::format_sql(&params.sql, dialect, &params.options))
+}
+
+/// Check SQL for syntax errors and, on a connected connection, unknown
+/// tables and columns, without running it
+#[tauri::command]
+pub async fn validate_sql(
+    manager: State<'_, ConnectionManager>,
+    schemas: State<'_, SchemaCache>,
+    params: ValidateSqlParams,
+) -> Result<ValidateSqlResult, ConnectionError> {
+    let mut schema = None;
+    let dialect = match &params.connection_id {
+        Some(connection_id) => {
+            if params.check_references && manager.is_connected(connection_id) {
+                let target = manager.target(connection_id, params.database.as_deref(), params.schema.as_deref())?;
+                let pool = manager.target_pool(&target).await?;
+                schema = Some(schemas.get(&target, &pool).await?);
+            }
+            manager.get_config(connection_id)?.db_type
+        }
+        None => params.dialect.unwrap_or(DatabaseType::Postgres),
+    };
+
+    let diagnostics = validator::validate(&params.sql, dialect, schema.as_deref());
+    Ok(ValidateSqlResult {
+        valid: diagnostics.iter().all(|d| d.severity != DiagnosticSeverity::Error),
+        diagnostics,
+        references_checked: schema.is_some(),
+    })
+}
+
+/// Suggest completions for the cursor at character offset `cursor_offset`,
+/// based on the cached schema of the connection or one of its targets
+#[tauri::command]
+pub async fn complete_sql(
+    manager: State<'_, ConnectionManager>,
+    schemas: State<'_, SchemaCache>,
+    connection_id: String,
+    sql: String,
+    cursor_offset: usize,
+    database: Option<String>,
+    schema: Option<String>,
+) -> Result<CompletionResult, ConnectionError> {
+    let target = manager.target(&connection_id, database.as_deref(), schema.as_deref())?;
+    let pool = manager.target_pool(&target).await?;
+    let schema = schemas.get(&target, &pool).await?;
+    Ok(completer::complete(&sql, cursor_offset, &schema))
+}
+
+/// Find schemas, tables, columns, functions and indexes by name or comment,
+/// using the connection's cached schema
+#[tauri::command]
+pub async fn search_objects(
+    manager: State<'_, ConnectionManager>,
+    schemas: State<'_, SchemaCache>,
+    params: SearchObjectsParams,
+) -> Result<Vec<ObjectSearchHit>, ConnectionError> {
+    let target = manager.target(&params.connection_id, params.database.as_deref(), None)?;
+    let pool = manager.target_pool(&target).await?;
+    let schema = schemas.get(&target, &pool).await?;
+    Ok(search::objects(&schema, &params))
+}
+
+/// Reload a connection's schema metadata, e.g. after changes made elsewhere
+#[tauri::command]
+pub async fn refresh_schema_cache(
+    manager: State<'_, ConnectionManager>,
+    schemas: State<'_, SchemaCache>,
+    connection_id: String,
+    database: Option<String>,
+    schema: Option<String>,
+) -> Result<SchemaCacheInfo, ConnectionError> {
+    let target = manager.target(&connection_id, database.as_deref(), schema.as_deref())?;
+    let pool = manager.target_pool(&target).await?;
+    let schema = schemas.refresh(&target, &pool).await?;
+    Ok(SchemaCacheInfo {
+        loaded_at: schema.loaded_at,
+        schemas: schema.schemas.len(),
+        tables: schema.tables.len(),
+        columns: schema.tables.iter().map(|t| t.columns.len()).sum(),
+        functions: schema.functions.len(),
+    })
 }
@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use futures_util::future::try_join_all;
+use sqlx::{Database, Executor, IntoArguments, Pool};
+
+use super::connection::{ConnectionError, DatabasePool};
+use super::types::*;
+
+/// Run a statement repeatedly and report its latency distribution. Rows are
+/// discarded. Unless `allow_writes` is set every run happens inside a
+/// transaction that is rolled back (MySQL DDL still commits implicitly).
+pub async fn run(pool: &DatabasePool, params: &BenchmarkParams) -> Result<BenchmarkResult, ConnectionError> {
+    if params.iterations == 0 || params.concurrency == 0 {
+        return Err(ConnectionError::InvalidInput(
+            "Iterations and concurrency must be at least 1".to_string(),
+        ));
+    }
+
+    let rollback = !params.allow_writes;
+
+    for _ in 0..params.warmup {
+        run_once(pool, &params.sql, rollback).await?;
+    }
+
+    let next = AtomicUsize::new(0);
+    let start = Instant::now();
+    let workers = (0..params.concurrency.min(params.iterations)).map(|_| async {
+        let mut samples = Vec::new();
+        while next.fetch_add(1, Ordering::Relaxed) < params.iterations {
+            samples.push(run_once(pool, &params.sql, rollback).await?);
+        }
+        Ok::<_, ConnectionError>(samples)
+    });
+
+    let samples: Vec<f64> = try_join_all(workers)
+        .await?
+        .into_iter()
+        .flatten()
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .collect();
+    let total = start.elapsed();
+
+    Ok(BenchmarkResult {
+        iterations: params.iterations,
+        concurrency: params.concurrency,
+        warmup: params.warmup,
+        min_ms: percentile(&samples, 0.0),
+        median_ms: percentile(&samples, 50.0),
+        mean_ms: samples.iter().sum::<f64>() / samples.len() as f64,
+        p95_ms: percentile(&samples, 95.0),
+        p99_ms: percentile(&samples, 99.0),
+        max_ms: percentile(&samples, 100.0),
+        total_ms: total.as_secs_f64() * 1000.0,
+        queries_per_second: samples.len() as f64 / total.as_secs_f64(),
+        rolled_back: rollback,
+    })
+}
+
+/// Nearest-rank percentile of non-empty samples in any order
+fn percentile(samples: &[f64], p: f64) -> f64 {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+async fn run_once(pool: &DatabasePool, sql: &str, rollback: bool) -> Result<Duration, ConnectionError> {
+    let elapsed = match pool {
+        DatabasePool::Postgres(pool) => time_statement(pool, sql, rollback).await,
+        DatabasePool::MySql(pool) => time_statement(pool, sql, rollback).await,
+    };
+    elapsed.map_err(|e| ConnectionError::from(e).with_sql(sql))
+}
+
+/// Time one execution of `sql`, excluding pool acquire and transaction control
+async fn time_statement<DB>(pool: &Pool<DB>, sql: &str, rollback: bool) -> Result<Duration, sqlx::Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    if rollback {
+        let mut tx = pool.begin().await?;
+        let start = Instant::now();
+        sqlx::query(sql).execute(&mut *tx).await?;
+        let elapsed = start.elapsed();
+        tx.rollback().await?;
+        Ok(elapsed)
+    } else {
+        let mut conn = pool.acquire().await?;
+        let start = Instant::now();
+        sqlx::query(sql).execute(&mut *conn).await?;
+        Ok(start.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_of_single_sample() {
+        for p in [0.0, 50.0, 99.0, 100.0] {
+            assert_eq!(percentile(&[7.5], p), 7.5);
+        }
+    }
+
+    #[test]
+    fn percentile_bounds_are_min_and_max() {
+        let samples = [3.0, 1.0, 4.0, 1.5, 9.0];
+        assert_eq!(percentile(&samples, 0.0), 1.0);
+        assert_eq!(percentile(&samples, 100.0), 9.0);
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let samples: Vec<f64> = (1..=20).map(f64::from).collect();
+        assert_eq!(percentile(&samples, 50.0), 10.0);
+        assert_eq!(percentile(&samples, 95.0), 19.0);
+        assert_eq!(percentile(&samples, 99.0), 20.0);
+        assert_eq!(percentile(&samples, 5.0), 1.0);
+        assert_eq!(percentile(&samples, 5.1), 2.0);
+    }
+
+    #[test]
+    fn percentile_sorts_unsorted_input() {
+        let samples = [50.0, 10.0, 40.0, 20.0, 30.0];
+        assert_eq!(percentile(&samples, 20.0), 10.0);
+        assert_eq!(percentile(&samples, 50.0), 30.0);
+        assert_eq!(percentile(&samples, 80.0), 40.0);
+    }
+}
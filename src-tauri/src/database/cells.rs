@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use base64::Engine;
+use sqlx::{Column, PgPool, Row, TypeInfo};
+
+use super::connection::{bind_json, extract_mysql_value, extract_postgres_value, ConnectionError, DatabasePool};
+use super::types::*;
+
+/// Raw content of a cell before it is returned or written out
+enum CellContent {
+    Text(String),
+    Json(serde_json::Value),
+    Binary(Vec<u8>),
+}
+
+/// Re-read a single column of the row identified by `params.key`
+pub async fn fetch_by_key(pool: &DatabasePool, params: &FetchCellValueParams) -> Result<CellValue, ConnectionError> {
+    let table = params
+        .table
+        .as_deref()
+        .ok_or_else(|| ConnectionError::InvalidInput("A table or result ID is required".to_string()))?;
+    if params.key.is_empty() {
+        return Err(ConnectionError::InvalidInput("A primary key is required to locate the row".to_string()));
+    }
+
+    let table = match &params.schema {
+        Some(schema) => format!("{}.{}", pool.quote_ident(schema), pool.quote_ident(table)),
+        None => pool.quote_ident(table),
+    };
+
+    // Sort for a stable statement text; bind order follows the same sequence
+    let mut key: Vec<(&String, &serde_json::Value)> = params.key.iter().collect();
+    key.sort_by_key(|(name, _)| *name);
+
+    let key_types = match pool {
+        DatabasePool::Postgres(pool) => postgres_column_types(pool, &table, &key).await?,
+        DatabasePool::MySql(_) => HashMap::new(),
+    };
+
+    let (conditions, bound) = key_conditions(pool, &key, &key_types);
+
+    // Fetch two rows so a key that isn't unique is reported rather than guessed at
+    let sql = format!(
+        "SELECT {} FROM {} WHERE {} LIMIT 2",
+        pool.quote_ident(&params.column),
+        table,
+        conditions.join(" AND ")
+    );
+
+    let content = match pool {
+        DatabasePool::Postgres(pool) => {
+            let rows = bind_json(sqlx::query(&sql), &bound)
+                .fetch_all(pool).await.map_err(|e| ConnectionError::from(e).with_sql(&sql))?;
+            let row = single_row(&rows)?;
+            match row.column(0).type_info().name() {
+                "BYTEA" => CellContent::Binary(row.try_get::<Option<Vec<u8>>, _>(0)?.unwrap_or_default()),
+                "JSON" | "JSONB" => CellContent::Json(row.try_get::<Option<serde_json::Value>, _>(0)?.unwrap_or_default()),
+                _ => match row.try_get::<Option<String>, _>(0) {
+                    Ok(text) => text.map(CellContent::Text).unwrap_or(CellContent::Json(serde_json::Value::Null)),
+                    Err(_) => CellContent::Json(extract_postgres_value(row, 0)),
+                },
+            }
+        }
+        DatabasePool::MySql(pool) => {
+            let rows = bind_json(sqlx::query(&sql), &bound)
+                .fetch_all(pool).await.map_err(|e| ConnectionError::from(e).with_sql(&sql))?;
+            let row = single_row(&rows)?;
+            let type_name = row.column(0).type_info().name();
+            if is_binary_type(type_name) {
+                CellContent::Binary(row.try_get::<Option<Vec<u8>>, _>(0)?.unwrap_or_default())
+            } else if type_name == "JSON" {
+                CellContent::Json(row.try_get::<Option<serde_json::Value>, _>(0)?.unwrap_or_default())
+            } else {
+                match row.try_get::<Option<String>, _>(0) {
+                    Ok(text) => text.map(CellContent::Text).unwrap_or(CellContent::Json(serde_json::Value::Null)),
+                    Err(_) => CellContent::Json(extract_mysql_value(row, 0)),
+                }
+            }
+        }
+    };
+
+    deliver(content, params.output_path.as_deref()).await
+}
+
+/// WHERE conditions locating the row by `key`, with the values they bind
+fn key_conditions(
+    pool: &DatabasePool,
+    key: &[(&String, &serde_json::Value)],
+    key_types: &HashMap<String, String>,
+) -> (Vec<String>, Vec<serde_json::Value>) {
+    let mut conditions = Vec::with_capacity(key.len());
+    let mut bound = Vec::new();
+    for (name, value) in key {
+        let column = pool.quote_ident(name);
+        if value.is_null() {
+            conditions.push(format!("{} IS NULL", column));
+            continue;
+        }
+
+        match pool {
+            // Postgres doesn't convert between text and numbers implicitly, so
+            // keys are bound as text and cast to the column's type. That finds
+            // a text key given as a number, and keeps the key's index usable.
+            DatabasePool::Postgres(_) => {
+                let text = match value {
+                    serde_json::Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                bound.push(serde_json::Value::String(text));
+                conditions.push(match key_types.get(name.as_str()) {
+                    Some(data_type) => format!("{} = CAST(${} AS {})", column, bound.len(), data_type),
+                    None => format!("{}::text = ${}", column, bound.len()),
+                });
+            }
+            DatabasePool::MySql(_) => {
+                bound.push((*value).clone());
+                conditions.push(format!("{} = ?", column));
+            }
+        }
+    }
+
+    (conditions, bound)
+}
+
+/// Types of the key columns of `table`, a quoted and possibly qualified name,
+/// without modifiers so a cast can't truncate the key. System columns such as
+/// `ctid` are included; a table the catalog can't resolve gives no types.
+async fn postgres_column_types(
+    pool: &PgPool,
+    table: &str,
+    key: &[(&String, &serde_json::Value)],
+) -> Result<HashMap<String, String>, ConnectionError> {
+    let names: Vec<&str> = key.iter().map(|(name, _)| name.as_str()).collect();
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT attname::text, atttypid::regtype::text FROM pg_attribute
+         WHERE attrelid = to_regclass($1) AND attname = ANY($2) AND NOT attisdropped",
+    )
+    .bind(table)
+    .bind(names)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Return a value read from a cached result. Binary columns are cached as hex
+/// strings, so they are decoded back to their bytes.
+pub async fn from_cached(
+    column: &ColumnInfo,
+    value: serde_json::Value,
+    output_path: Option<&str>,
+) -> Result<CellValue, ConnectionError> {
+    let content = match value {
+        serde_json::Value::String(text) if is_binary_type(&column.data_type) => match decode_hex(&text) {
+            Some(bytes) => CellContent::Binary(bytes),
+            None => CellContent::Text(text),
+        },
+        serde_json::Value::String(text) => CellContent::Text(text),
+        other => CellContent::Json(other),
+    };
+    deliver(content, output_path).await
+}
+
+fn is_binary_type(type_name: &str) -> bool {
+    type_name == "BYTEA" || type_name.contains("BLOB") || type_name.contains("BINARY")
+}
+
+/// Decode a `\x`- or `0x`-prefixed hex string
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    let digits = text.strip_prefix("\\x").or_else(|| text.strip_prefix("0x"))?;
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn single_row<R>(rows: &[R]) -> Result<&R, ConnectionError> {
+    match rows {
+        [row] => Ok(row),
+        [] => Err(ConnectionError::InvalidInput("No row matches the given key".to_string())),
+        _ => Err(ConnectionError::InvalidInput("The given key matches more than one row".to_string())),
+    }
+}
+
+/// Either return the content inline or write it to `output_path`. Binary
+/// content is written as-is but returned base64-encoded.
+async fn deliver(content: CellContent, output_path: Option<&str>) -> Result<CellValue, ConnectionError> {
+    let encoding = match &content {
+        CellContent::Text(_) => CellEncoding::Text,
+        CellContent::Json(_) => CellEncoding::Json,
+        CellContent::Binary(_) => CellEncoding::Base64,
+    };
+
+    if let Some(path) = output_path {
+        let bytes = match content {
+            CellContent::Text(text) => text.into_bytes(),
+            CellContent::Json(value) => value.to_string().into_bytes(),
+            CellContent::Binary(bytes) => bytes,
+        };
+        tokio::fs::write(path, &bytes).await?;
+
+        return Ok(CellValue {
+            value: serde_json::Value::Null,
+            encoding,
+            length: bytes.len(),
+            path: Some(path.to_string()),
+        });
+    }
+
+    let (length, value) = match content {
+        CellContent::Text(text) => (text.len(), serde_json::Value::String(text)),
+        CellContent::Json(value) => (value.to_string().len(), value),
+        CellContent::Binary(bytes) => (
+            bytes.len(),
+            serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(&bytes)),
+        ),
+    };
+
+    Ok(CellValue { value, encoding, length, path: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn column(data_type: &str) -> ColumnInfo {
+        ColumnInfo {
+            name: "value".to_string(),
+            data_type: data_type.to_string(),
+            nullable: true,
+        }
+    }
+
+    #[test]
+    fn decodes_both_hex_prefixes() {
+        assert_eq!(decode_hex("\\xdead01"), Some(vec![0xde, 0xad, 0x01]));
+        assert_eq!(decode_hex("0xFF"), Some(vec![0xff]));
+        assert_eq!(decode_hex("0x"), Some(vec![]));
+        assert_eq!(decode_hex("0xabc"), None);
+        assert_eq!(decode_hex("0xzz"), None);
+        assert_eq!(decode_hex("plain text"), None);
+    }
+
+    #[test]
+    fn recognises_binary_types() {
+        assert!(is_binary_type("BYTEA"));
+        assert!(is_binary_type("MEDIUMBLOB"));
+        assert!(is_binary_type("VARBINARY"));
+        assert!(!is_binary_type("TEXT"));
+        assert!(!is_binary_type("JSONB"));
+    }
+
+    #[test]
+    fn single_row_rejects_missing_and_ambiguous_keys() {
+        assert_eq!(single_row(&[7]).unwrap(), &7);
+        assert!(matches!(single_row::<i32>(&[]), Err(ConnectionError::InvalidInput(_))));
+        assert!(matches!(single_row(&[1, 2]), Err(ConnectionError::InvalidInput(_))));
+    }
+
+    fn pool(postgres: bool) -> DatabasePool {
+        if postgres {
+            DatabasePool::Postgres(sqlx::postgres::PgPoolOptions::new().connect_lazy("postgres://localhost/db").unwrap())
+        } else {
+            DatabasePool::MySql(sqlx::mysql::MySqlPoolOptions::new().connect_lazy("mysql://localhost/db").unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn postgres_keys_are_cast_to_the_column_type() {
+        let (code, id, gone, note) = ("code".to_string(), "id".to_string(), "gone".to_string(), "note".to_string());
+        let (number, text, null) = (json!(42), json!("7"), json!(null));
+        let key = [(&code, &number), (&gone, &null), (&id, &text), (&note, &number)];
+        let types = HashMap::from([
+            ("code".to_string(), "character varying".to_string()),
+            ("id".to_string(), "uuid".to_string()),
+        ]);
+
+        let (conditions, bound) = key_conditions(&pool(true), &key, &types);
+        assert_eq!(
+            conditions,
+            [
+                r#""code" = CAST($1 AS character varying)"#,
+                r#""gone" IS NULL"#,
+                r#""id" = CAST($2 AS uuid)"#,
+                r#""note"::text = $3"#,
+            ]
+        );
+        assert_eq!(bound, [json!("42"), json!("7"), json!("42")]);
+    }
+
+    #[tokio::test]
+    async fn mysql_keys_keep_their_json_type() {
+        let id = "id".to_string();
+        let number = json!(42);
+        let (conditions, bound) = key_conditions(&pool(false), &[(&id, &number)], &HashMap::new());
+        assert_eq!(conditions, ["`id` = ?"]);
+        assert_eq!(bound, [json!(42)]);
+    }
+
+    #[tokio::test]
+    async fn cached_values_keep_their_encoding() {
+        let text = from_cached(&column("TEXT"), json!("héllo"), None).await.unwrap();
+        assert_eq!(text.encoding, CellEncoding::Text);
+        assert_eq!(text.value, json!("héllo"));
+        assert_eq!(text.length, 6);
+
+        let document = from_cached(&column("JSONB"), json!({"a": [1]}), None).await.unwrap();
+        assert_eq!(document.encoding, CellEncoding::Json);
+        assert_eq!(document.value, json!({"a": [1]}));
+
+        let binary = from_cached(&column("BYTEA"), json!("\\x68690a"), None).await.unwrap();
+        assert_eq!(binary.encoding, CellEncoding::Base64);
+        assert_eq!(binary.value, json!("aGkK"));
+        assert_eq!(binary.length, 3);
+    }
+
+    #[tokio::test]
+    async fn writes_raw_bytes_to_output_path() {
+        let path = std::env::temp_dir().join(format!("mesagrid-cell-{}", uuid::Uuid::new_v4()));
+        let path = path.to_string_lossy().to_string();
+
+        let written = from_cached(&column("BLOB"), json!("0x00ff10"), Some(&path)).await.unwrap();
+        assert_eq!(written.value, serde_json::Value::Null);
+        assert_eq!(written.path.as_deref(), Some(path.as_str()));
+        assert_eq!(written.length, 3);
+        assert_eq!(std::fs::read(&path).unwrap(), vec![0x00, 0xff, 0x10]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
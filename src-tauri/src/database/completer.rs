@@ -0,0 +1,635 @@
+use std::collections::HashSet;
+
+use super::lexer::{dotted_name, ident, matching_paren, skip_words, tokenize, Token, TokenKind};
+use super::schema_cache::{CachedTable, SchemaMetadata};
+use super::types::*;
+
+/// Most completions returned for one request
+const MAX_COMPLETIONS: usize = 100;
+
+/// Keywords after which a table name is expected
+const TABLE_KEYWORDS: &[&str] = &[
+    "DESCRIBE", "FROM", "INTO", "JOIN", "LATERAL", "ONLY", "TABLE", "TRUNCATE", "UPDATE", "USING",
+];
+
+/// Keywords that open a clause, used to find the clause the cursor is in
+const CLAUSE_KEYWORDS: &[&str] = &[
+    "BY", "FROM", "HAVING", "INTO", "JOIN", "LIMIT", "OFFSET", "ON", "RETURNING", "SELECT", "SET",
+    "TABLE", "UPDATE", "USING", "VALUES", "WHERE", "WINDOW",
+];
+
+/// Words that end a table reference rather than naming its alias
+const ALIAS_STOP_WORDS: &[&str] = &[
+    "CROSS", "DEFAULT", "EXCEPT", "FOR", "FROM", "FULL", "GROUP", "HAVING", "INNER", "INTERSECT",
+    "JOIN", "LEFT", "LIMIT", "NATURAL", "OFFSET", "ON", "ORDER", "OUTER", "RETURNING", "RIGHT",
+    "SELECT", "SET", "UNION", "USING", "VALUES", "WHERE", "WINDOW",
+];
+
+const STATEMENT_KEYWORDS: &[&str] = &[
+    "ALTER", "BEGIN", "COMMIT", "CREATE", "DELETE", "DROP", "EXPLAIN", "GRANT", "INSERT", "REVOKE",
+    "ROLLBACK", "SELECT", "SET", "SHOW", "TRUNCATE", "UPDATE", "WITH",
+];
+
+/// What may follow a complete table reference
+const AFTER_TABLE_KEYWORDS: &[&str] = &[
+    "AS", "CROSS JOIN", "FULL JOIN", "GROUP BY", "INNER JOIN", "JOIN", "LEFT JOIN", "LIMIT", "ON",
+    "ORDER BY", "RIGHT JOIN", "SET", "USING", "WHERE",
+];
+
+/// Syntactic position of the cursor
+enum Context<'m> {
+    StatementStart,
+    /// A table name is expected
+    Table,
+    /// After a complete table reference in FROM or JOIN
+    AfterTable,
+    /// After `qualifier.`, given as name parts
+    Qualified(Vec<(String, bool)>),
+    /// In the condition of `JOIN ... ON`
+    JoinCondition { on: usize },
+    /// In the column list of `INSERT INTO t (...)`
+    InsertColumns(&'m CachedTable),
+    Expression,
+}
+
+/// A table or derived table visible in the statement
+struct ScopeEntry<'m> {
+    /// Alias, or the table name when there is none
+    name: String,
+    /// `None` for CTEs, subqueries and unknown tables
+    table: Option<&'m CachedTable>,
+    cte: bool,
+    /// Index of the reference among the statement's tokens
+    position: usize,
+}
+
+/// Completions for the cursor at character offset `cursor` in `sql`
+pub fn complete(sql: &str, cursor: usize, schema: &SchemaMetadata) -> CompletionResult {
+    let cursor = sql.char_indices().nth(cursor).map_or(sql.len(), |(i, _)| i);
+    let tokens = tokenize(sql, schema.dialect);
+    let char_offset = |byte: usize| sql[..byte].chars().count();
+
+    let mut result = CompletionResult {
+        replace_start: char_offset(cursor),
+        replace_end: char_offset(cursor),
+        items: Vec::new(),
+    };
+
+    // The word being typed, if the cursor is in or just after one
+    let current = tokens.iter().find(|t| t.offset < cursor && cursor <= t.offset + t.text.len());
+    let mut prefix = "";
+    let mut replace_start = cursor;
+    if let Some(token) = current {
+        let end = token.offset + token.text.len();
+        match token.kind {
+            TokenKind::Word | TokenKind::QuotedIdentifier => {
+                prefix = token.text[..cursor - token.offset].trim_start_matches(['"', '`']);
+                replace_start = token.offset;
+                result.replace_start = char_offset(token.offset);
+                result.replace_end = char_offset(end);
+            }
+            TokenKind::LineComment => return result,
+            TokenKind::String | TokenKind::BlockComment if cursor < end || !token.terminated => return result,
+            _ => {}
+        }
+    }
+
+    // Significant tokens of the statement around the cursor
+    let significant: Vec<Token> = tokens.into_iter().filter(|t| !t.is_trivia()).collect();
+    let start = significant
+        .iter()
+        .rposition(|t| t.kind == TokenKind::Semicolon && t.offset < replace_start)
+        .map_or(0, |i| i + 1);
+    let end = significant[start..]
+        .iter()
+        .position(|t| t.kind == TokenKind::Semicolon && t.offset >= replace_start)
+        .map_or(significant.len(), |i| start + i);
+    let statement = &significant[start..end];
+    let before = statement.iter().take_while(|t| t.offset < replace_start).count();
+
+    let scope = collect_scope(statement, schema);
+    let context = context(statement, before, &scope, schema);
+
+    let mut c = Candidates { schema, prefix, seen: HashSet::new(), items: Vec::new() };
+    match context {
+        Context::StatementStart => c.keywords(STATEMENT_KEYWORDS.iter().copied(), 0),
+        Context::Table => c.tables(&scope),
+        Context::AfterTable => c.keywords(AFTER_TABLE_KEYWORDS.iter().copied(), 0),
+        Context::Qualified(parts) => c.qualified(&parts, &scope),
+        Context::JoinCondition { on } => {
+            c.joins(&scope, on);
+            c.scope_columns(&scope, true, 1);
+            c.functions(3);
+            c.all_keywords(4);
+        }
+        Context::InsertColumns(table) => {
+            let listed: Vec<String> = statement[..before]
+                .iter()
+                .filter(|t| matches!(t.kind, TokenKind::Word | TokenKind::QuotedIdentifier))
+                .map(|t| ident(t).0)
+                .collect();
+            for column in &table.columns {
+                if !listed.iter().any(|l| l.eq_ignore_ascii_case(&column.name)) {
+                    c.push(0, &column.name, CompletionKind::Column, Some(column.data_type.clone()), None);
+                }
+            }
+        }
+        Context::Expression => {
+            c.scope_columns(&scope, false, 0);
+            for entry in &scope {
+                c.push(1, &entry.name, CompletionKind::Alias, entry.table.map(|t| t.name.clone()), None);
+            }
+            c.functions(2);
+            c.all_keywords(3);
+        }
+    }
+
+    c.items.sort_by(|a, b| a.0.cmp(&b.0));
+    result.items = c.items.into_iter().take(MAX_COMPLETIONS).map(|(_, item)| item).collect();
+    result
+}
+
+/// Tables, aliases and CTEs referenced anywhere in the statement
+fn collect_scope<'m>(tokens: &[Token], schema: &'m SchemaMetadata) -> Vec<ScopeEntry<'m>> {
+    let mut scope: Vec<ScopeEntry> = cte_names(tokens)
+        .into_iter()
+        .map(|(name, position)| ScopeEntry { name, table: None, cte: true, position })
+        .collect();
+
+    for (i, token) in tokens.iter().enumerate() {
+        let list = token.is_keyword("FROM") || token.is_keyword("UPDATE") || token.is_keyword("USING");
+        if !list && !token.is_keyword("JOIN") && !token.is_keyword("INTO") {
+            continue;
+        }
+
+        let mut j = i + 1;
+        loop {
+            j = skip_words(tokens, j, &["LATERAL", "ONLY"]);
+            let (table, written, next) = match tokens.get(j) {
+                Some(t) if t.kind == TokenKind::LParen => match matching_paren(tokens, j) {
+                    Some(close) => (None, None, close + 1),
+                    None => break,
+                },
+                Some(t) if t.kind == TokenKind::Word && is_one_of(t, ALIAS_STOP_WORDS) => break,
+                Some(_) => {
+                    let Some((parts, next)) = dotted_name(tokens, j) else { break };
+                    let name = ident(&tokens[*parts.last().unwrap()]);
+                    let qualifier = (parts.len() > 1).then(|| ident(&tokens[parts[parts.len() - 2]]));
+                    let table = schema.find_table(qualifier.as_ref().map(|(s, q)| (s.as_str(), *q)), &name.0, name.1);
+                    (table, Some(name.0), next)
+                }
+                None => break,
+            };
+
+            // Optional alias, possibly with a column list
+            let mut k = next;
+            if tokens.get(k).is_some_and(|t| t.is_keyword("AS")) {
+                k += 1;
+            }
+            let alias = tokens.get(k).filter(|t| match t.kind {
+                TokenKind::QuotedIdentifier => true,
+                TokenKind::Word => !is_one_of(t, ALIAS_STOP_WORDS),
+                _ => false,
+            });
+            let name = match alias {
+                Some(alias) => {
+                    k += 1;
+                    Some(ident(alias).0)
+                }
+                None => {
+                    k = next;
+                    written
+                }
+            };
+            if let Some(name) = name {
+                scope.push(ScopeEntry { name, table, cte: false, position: j });
+            }
+
+            match tokens.get(k) {
+                Some(t) if list && t.kind == TokenKind::Comma => j = k + 1,
+                _ => break,
+            }
+        }
+    }
+
+    scope
+}
+
+/// Names defined by `WITH name AS (...)` and their token positions
+fn cte_names(tokens: &[Token]) -> Vec<(String, usize)> {
+    let mut names = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if !token.is_keyword("WITH") {
+            continue;
+        }
+        let mut j = skip_words(tokens, i + 1, &["RECURSIVE"]);
+        while let Some(name) = tokens.get(j).filter(|t| matches!(t.kind, TokenKind::Word | TokenKind::QuotedIdentifier)) {
+            names.push((ident(name).0, j));
+            j += 1;
+            if tokens.get(j).is_some_and(|t| t.kind == TokenKind::LParen) {
+                let Some(close) = matching_paren(tokens, j) else { break };
+                j = close + 1;
+            }
+            j = skip_words(tokens, j, &["AS", "NOT", "MATERIALIZED"]);
+            let Some(close) = tokens.get(j).filter(|t| t.kind == TokenKind::LParen).and_then(|_| matching_paren(tokens, j)) else {
+                break;
+            };
+            j = close + 1;
+            match tokens.get(j) {
+                Some(t) if t.kind == TokenKind::Comma => j += 1,
+                _ => break,
+            }
+        }
+    }
+    names
+}
+
+/// Work out what is expected at `tokens[before]`, given the tokens before it
+fn context<'m>(tokens: &[Token], before: usize, scope: &[ScopeEntry<'m>], schema: &'m SchemaMetadata) -> Context<'m> {
+    let Some(last) = before.checked_sub(1).map(|i| &tokens[i]) else {
+        return Context::StatementStart;
+    };
+
+    if last.kind == TokenKind::Dot {
+        // Collect `a.b.` backwards
+        let mut parts = Vec::new();
+        let mut i = before - 1;
+        while i > 0 && matches!(tokens[i - 1].kind, TokenKind::Word | TokenKind::QuotedIdentifier) {
+            parts.insert(0, ident(&tokens[i - 1]));
+            if i < 2 || tokens[i - 2].kind != TokenKind::Dot {
+                break;
+            }
+            i -= 2;
+        }
+        return Context::Qualified(parts);
+    }
+
+    if last.kind == TokenKind::Word && is_one_of(last, TABLE_KEYWORDS) && !in_function_call(tokens, before) {
+        return Context::Table;
+    }
+
+    // Find the clause the cursor is in, skipping over nested groups
+    let mut depth = 0;
+    for i in (0..before).rev() {
+        let token = &tokens[i];
+        match token.kind {
+            TokenKind::RParen => depth += 1,
+            TokenKind::LParen if depth > 0 => depth -= 1,
+            TokenKind::LParen => {
+                // INSERT INTO t (|
+                let name_end = i;
+                let into = (0..name_end).rev().find(|&k| tokens[k].is_keyword("INTO"));
+                if let Some(into) = into {
+                    if let Some((parts, end)) = dotted_name(tokens, into + 1) {
+                        if end == name_end {
+                            let (name, quoted) = ident(&tokens[*parts.last().unwrap()]);
+                            let qualifier = (parts.len() > 1).then(|| ident(&tokens[parts[0]]));
+                            if let Some(table) = schema.find_table(qualifier.as_ref().map(|(s, q)| (s.as_str(), *q)), &name, quoted) {
+                                return Context::InsertColumns(table);
+                            }
+                        }
+                    }
+                }
+                return Context::Expression;
+            }
+            TokenKind::Word if depth == 0 && is_one_of(token, CLAUSE_KEYWORDS) => {
+                let upper = token.text.to_ascii_uppercase();
+                return match upper.as_str() {
+                    "FROM" | "JOIN" | "UPDATE" | "USING" | "TABLE" | "INTO" => {
+                        if last.kind == TokenKind::Comma && upper != "JOIN" {
+                            Context::Table
+                        } else {
+                            Context::AfterTable
+                        }
+                    }
+                    "ON" if tokens[..i].iter().any(|t| t.is_keyword("JOIN")) && !scope.is_empty() => {
+                        Context::JoinCondition { on: i }
+                    }
+                    _ => Context::Expression,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    Context::Expression
+}
+
+/// Whether position `at` is inside the parentheses of a function call,
+/// such as `EXTRACT(YEAR FROM |`
+fn in_function_call(tokens: &[Token], at: usize) -> bool {
+    let mut depth = 0;
+    for i in (0..at).rev() {
+        match tokens[i].kind {
+            TokenKind::RParen => depth += 1,
+            TokenKind::LParen if depth > 0 => depth -= 1,
+            TokenKind::LParen => {
+                let subquery = tokens.get(i + 1).is_some_and(|t| t.is_keyword("SELECT") || t.is_keyword("WITH"));
+                return !subquery && i > 0 && tokens[i - 1].kind == TokenKind::Word;
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+fn is_one_of(token: &Token, words: &[&str]) -> bool {
+    words.iter().any(|w| token.is_keyword(w))
+}
+
+/// Collects ranked, de-duplicated completions
+struct Candidates<'m, 'p> {
+    schema: &'m SchemaMetadata,
+    prefix: &'p str,
+    seen: HashSet<(String, CompletionKind)>,
+    /// Sort key (match quality, priority, length, label) and item
+    items: Vec<((u8, u8, usize, String), Completion)>,
+}
+
+impl Candidates<'_, '_> {
+    /// How well `label` matches the prefix: at the start (of the name after
+    /// any qualifier), at a word boundary or anywhere. `None` if it doesn't match.
+    fn quality(&self, label: &str) -> Option<u8> {
+        if self.prefix.is_empty() {
+            return Some(0);
+        }
+        let label = label.to_lowercase();
+        let prefix = self.prefix.to_lowercase();
+        let name = label.rsplit_once('.').map_or(label.as_str(), |(_, name)| name);
+        if label.starts_with(&prefix) || name.starts_with(&prefix) {
+            Some(0)
+        } else if label.split(['_', '.']).any(|part| part.starts_with(&prefix)) {
+            Some(1)
+        } else if label.contains(&prefix) {
+            Some(2)
+        } else {
+            None
+        }
+    }
+
+    fn push(&mut self, priority: u8, label: &str, kind: CompletionKind, detail: Option<String>, insert_text: Option<String>) {
+        let Some(quality) = self.quality(label) else { return };
+        if !self.seen.insert((label.to_string(), kind)) {
+            return;
+        }
+        // Only suggest an insert text that differs from the label
+        let insert_text = insert_text.filter(|text| text != label);
+        self.items.push((
+            (quality, priority, label.len(), label.to_string()),
+            Completion { label: label.to_string(), kind, detail, insert_text },
+        ));
+    }
+
+    fn keywords<'w>(&mut self, words: impl Iterator<Item = &'w str>, priority: u8) {
+        for word in words {
+            self.push(priority, word, CompletionKind::Keyword, None, None);
+        }
+    }
+
+    fn all_keywords(&mut self, priority: u8) {
+        let schema = self.schema;
+        self.keywords(schema.keywords.iter().map(|k| k.word.as_str()), priority);
+    }
+
+    fn table(&mut self, priority: u8, table: &CachedTable, qualify: bool) {
+        let kind = if table.kind == "view" { CompletionKind::View } else { CompletionKind::Table };
+        let name = self.schema.quote_if_needed(&table.name);
+        if qualify {
+            let label = format!("{}.{}", table.schema, table.name);
+            let insert = format!("{}.{}", self.schema.quote_if_needed(&table.schema), name);
+            self.push(priority, &label, kind, Some(format!("{} columns", table.columns.len())), Some(insert));
+        } else {
+            self.push(priority, &table.name, kind, Some(table.schema.clone()), Some(name));
+        }
+    }
+
+    fn tables(&mut self, scope: &[ScopeEntry]) {
+        let schema = self.schema;
+        for entry in scope.iter().filter(|e| e.cte) {
+            self.push(0, &entry.name, CompletionKind::Alias, Some("CTE".to_string()), None);
+        }
+        for table in &schema.tables {
+            let visible = schema.search_path.contains(&table.schema)
+                && schema.find_table(None, &table.name, true).is_some_and(|t| std::ptr::eq(t, table));
+            // System catalogs are reachable unqualified but rarely wanted
+            let system = matches!(table.schema.as_str(), "pg_catalog" | "information_schema");
+            match (visible, system) {
+                (true, false) => self.table(0, table, false),
+                (true, true) => self.table(2, table, false),
+                (false, _) => self.table(3, table, true),
+            }
+        }
+        for name in &schema.schemas {
+            self.push(1, name, CompletionKind::Schema, None, Some(schema.quote_if_needed(name)));
+        }
+    }
+
+    fn columns(&mut self, priority: u8, table: &CachedTable, qualifier: Option<&str>) {
+        for column in &table.columns {
+            let name = self.schema.quote_if_needed(&column.name);
+            let detail = Some(format!("{} · {}", column.data_type, table.name));
+            match qualifier {
+                Some(q) => {
+                    let label = format!("{}.{}", q, column.name);
+                    let insert = format!("{}.{}", self.schema.quote_if_needed(q), name);
+                    self.push(priority, &label, CompletionKind::Column, detail, Some(insert));
+                }
+                None => self.push(priority, &column.name, CompletionKind::Column, detail, Some(name)),
+            }
+        }
+    }
+
+    /// Columns of every table in scope. Names shared by several tables are
+    /// qualified with their alias, as they would be ambiguous otherwise.
+    fn scope_columns(&mut self, scope: &[ScopeEntry], always_qualify: bool, priority: u8) {
+        let tables: Vec<(&str, &CachedTable)> = scope
+            .iter()
+            .filter_map(|e| e.table.map(|t| (e.name.as_str(), t)))
+            .collect();
+        for (alias, table) in &tables {
+            for column in &table.columns {
+                let shared = tables
+                    .iter()
+                    .filter(|(_, other)| other.columns.iter().any(|c| c.name == column.name))
+                    .count()
+                    > 1;
+                let name = self.schema.quote_if_needed(&column.name);
+                let detail = Some(format!("{} · {}", column.data_type, table.name));
+                if always_qualify || shared {
+                    let label = format!("{}.{}", alias, column.name);
+                    let insert = format!("{}.{}", self.schema.quote_if_needed(alias), name);
+                    self.push(priority, &label, CompletionKind::Column, detail, Some(insert));
+                } else {
+                    self.push(priority, &column.name, CompletionKind::Column, detail, Some(name));
+                }
+            }
+        }
+    }
+
+    fn functions(&mut self, priority: u8) {
+        let schema = self.schema;
+        for function in &schema.functions {
+            // Functions outside the search path need qualifying
+            let visible = function.schema.as_ref().is_none_or(|s| schema.search_path.contains(s));
+            let signature = format!("{}({})", function.name, function.arguments);
+            let detail = match &function.result {
+                Some(result) => format!("{} → {}", signature, result),
+                None => signature,
+            };
+            match (&function.schema, visible) {
+                (Some(s), false) => {
+                    let label = format!("{}.{}", s, function.name);
+                    self.push(priority + 1, &label, CompletionKind::Function, Some(detail), None);
+                }
+                _ => self.push(priority, &function.name, CompletionKind::Function, Some(detail), None),
+            }
+        }
+    }
+
+    fn qualified(&mut self, parts: &[(String, bool)], scope: &[ScopeEntry]) {
+        let schema = self.schema;
+        match parts {
+            [(qualifier, quoted)] => {
+                // An alias or table in scope
+                let entry = scope.iter().find(|e| e.name.eq_ignore_ascii_case(qualifier));
+                if let Some(entry) = entry {
+                    if let Some(table) = entry.table {
+                        self.columns(0, table, None);
+                    }
+                    return;
+                }
+
+                // A schema
+                for table in schema.tables.iter().filter(|t| t.schema.eq_ignore_ascii_case(qualifier)) {
+                    self.table(0, table, false);
+                }
+                for function in &schema.functions {
+                    if function.schema.as_ref().is_some_and(|s| s.eq_ignore_ascii_case(qualifier)) {
+                        let detail = format!("{}({})", function.name, function.arguments);
+                        self.push(1, &function.name, CompletionKind::Function, Some(detail), None);
+                    }
+                }
+
+                // A table written without a FROM clause yet
+                if let Some(table) = schema.find_table(None, qualifier, *quoted) {
+                    self.columns(0, table, None);
+                }
+            }
+            [.., (table_schema, schema_quoted), (name, quoted)] => {
+                if let Some(table) = schema.find_table(Some((table_schema, *schema_quoted)), name, *quoted) {
+                    self.columns(0, table, None);
+                }
+            }
+            [] => {}
+        }
+    }
+
+    /// Join conditions for the table joined by the `ON` at `on`, inferred
+    /// from `<table>_id` columns and shared `_id` column names
+    fn joins(&mut self, scope: &[ScopeEntry], on: usize) {
+        let joined: Vec<(&str, &CachedTable)> = scope
+            .iter()
+            .filter(|e| e.position < on)
+            .filter_map(|e| e.table.map(|t| (e.name.as_str(), t)))
+            .collect();
+        let Some((&(right_alias, right), left)) = joined.split_last() else { return };
+
+        for &(left_alias, left) in left {
+            for (from, from_alias, to, to_alias) in [
+                (right, right_alias, left, left_alias),
+                (left, left_alias, right, right_alias),
+            ] {
+                for column in &from.columns {
+                    let references = column.name.eq_ignore_ascii_case(&format!("{}_id", singular(&to.name)))
+                        || column.name.eq_ignore_ascii_case(&format!("{}_id", to.name));
+                    if references && to.columns.iter().any(|c| c.name == "id") {
+                        let label = format!("{}.{} = {}.id", from_alias, column.name, to_alias);
+                        self.push(0, &label, CompletionKind::Join, Some("join condition".to_string()), None);
+                    }
+                }
+            }
+
+            for column in right.columns.iter().filter(|c| c.name.ends_with("_id")) {
+                if left.columns.iter().any(|c| c.name == column.name) {
+                    let label = format!("{}.{} = {}.{}", right_alias, column.name, left_alias, column.name);
+                    self.push(0, &label, CompletionKind::Join, Some("join condition".to_string()), None);
+                }
+            }
+        }
+    }
+}
+
+/// Rough singular form of a table name: `users` → `user`, `categories` → `category`
+fn singular(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if let Some(stem) = name.strip_suffix('s').filter(|s| !s.ends_with('s')) {
+        stem.to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::schema_cache::CachedColumn;
+    use super::*;
+
+    fn schema(dialect: DatabaseType) -> SchemaMetadata {
+        let column = |name: &str| CachedColumn { name: name.to_string(), data_type: "integer".to_string(), comment: None };
+        let table = |name: &str, columns: Vec<CachedColumn>| CachedTable {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            kind: "table".to_string(),
+            columns,
+            comment: None,
+        };
+        SchemaMetadata {
+            dialect,
+            search_path: vec!["public".to_string()],
+            schemas: vec!["public".to_string()],
+            tables: vec![
+                table("users", vec![column("id"), column("name")]),
+                table("orders", vec![column("id"), column("user_id")]),
+                table("café", vec![column("id")]),
+            ],
+            functions: Vec::new(),
+            indexes: Vec::new(),
+            keywords: Vec::new(),
+            loaded_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Labels completed with the cursor at the end of `sql`
+    fn labels(sql: &str, dialect: DatabaseType) -> Vec<String> {
+        let schema = schema(dialect);
+        complete(sql, sql.chars().count(), &schema).items.into_iter().map(|c| c.label).collect()
+    }
+
+    #[test]
+    fn partial_statements() {
+        assert!(labels("SELECT * FROM ", DatabaseType::Postgres).contains(&"users".to_string()));
+        assert_eq!(labels("SELECT * FROM users u WHERE u.", DatabaseType::Postgres), ["id", "name"]);
+        assert_eq!(labels("INSERT INTO orders (id, ", DatabaseType::Postgres), ["user_id"]);
+        for sql in ["INSERT INTO", "UPDATE", ".", "(", "SELECT a. FROM", "WITH x AS (", "SELECT 5--1"] {
+            labels(sql, DatabaseType::Postgres);
+            labels(sql, DatabaseType::Mysql);
+        }
+    }
+
+    #[test]
+    fn unterminated_quoted_identifiers() {
+        assert_eq!(labels("SELECT * FROM \"caf", DatabaseType::Postgres), ["café"]);
+        assert_eq!(labels("SELECT * FROM `caf", DatabaseType::Mysql), ["café"]);
+        assert_eq!(labels("SELECT * FROM \"café", DatabaseType::Postgres), ["café"]);
+    }
+
+    #[test]
+    fn dialect_comments() {
+        assert!(labels("SELECT * FROM users -- ", DatabaseType::Postgres).is_empty());
+        assert!(labels("SELECT * FROM users # ", DatabaseType::Mysql).is_empty());
+        // In MySQL `--1` is two minus signs, not a comment
+        assert!(labels("SELECT 5--1 FROM ", DatabaseType::Mysql).contains(&"users".to_string()));
+        assert!(labels("SELECT 5--1 FROM ", DatabaseType::Postgres).is_empty());
+    }
+}
@@ -1,12 +1,22 @@
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::sync::Arc;
 use parking_lot::RwLock;
-use sqlx::{mysql::MySqlPoolOptions, postgres::PgPoolOptions, MySqlPool, PgPool, Row, Column};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use futures_util::TryStreamExt;
+use sqlx::mysql::{MySqlPoolOptions, MySqlRow};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgRow};
+use sqlx::pool::PoolConnection;
+use sqlx::{MySqlPool, PgPool, Row, Column, TypeInfo};
 use thiserror::Error;
 use uuid::Uuid;
-use std::time::Instant;
+use std::future::Future;
+use std::time::{Duration, Instant};
 
 use super::credentials;
+use super::errors::DatabaseErrorInfo;
+use super::notices;
+use super::result_cache::{self, ResultCache};
 use super::types::*;
 
 #[derive(Error, Debug)]
@@ -15,10 +25,364 @@ pub enum ConnectionError {
     NotFound(String),
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
+    /// Server error raised by user-submitted SQL, kept with the SQL to resolve positions
+    #[error("Database error: {source}")]
+    Query { source: sqlx::Error, sql: String },
     #[error("Credential error: {0}")]
     Credential(String),
     #[error("Unsupported database type")]
     UnsupportedType,
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    /// Failure in one of the app's local stores (history, saved queries)
+    #[error("{0}")]
+    Storage(String),
+    #[error("Query was cancelled")]
+    Cancelled,
+    #[error("Query timed out after {0} ms")]
+    Timeout(u64),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl ConnectionError {
+    /// Attach the submitted SQL to a server error so its position can be mapped
+    pub fn with_sql(self, sql: &str) -> Self {
+        match self {
+            ConnectionError::Database(source) if source.as_database_error().is_some() => {
+                ConnectionError::Query { source, sql: sql.to_string() }
+            }
+            other => other,
+        }
+    }
+
+    /// Machine-readable error category for the frontend
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ConnectionError::NotFound(_) => "notFound",
+            ConnectionError::Database(e) | ConnectionError::Query { source: e, .. } => match e {
+                sqlx::Error::Database(_) => "database",
+                sqlx::Error::Io(_)
+                | sqlx::Error::Tls(_)
+                | sqlx::Error::PoolTimedOut
+                | sqlx::Error::PoolClosed
+                | sqlx::Error::WorkerCrashed => "connection",
+                _ => "driver",
+            },
+            ConnectionError::Credential(_) => "credential",
+            ConnectionError::UnsupportedType => "unsupportedType",
+            ConnectionError::InvalidInput(_) => "invalidInput",
+            ConnectionError::Storage(_) => "storage",
+            ConnectionError::Cancelled => "cancelled",
+            ConnectionError::Timeout(_) => "timeout",
+            ConnectionError::Io(_) => "io",
+        }
+    }
+}
+
+/// Serialized as `{ kind, message, database? }` so the frontend can branch on
+/// `kind` and highlight `database.position` in the editor
+impl Serialize for ConnectionError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let database = match self {
+            ConnectionError::Database(e) => DatabaseErrorInfo::from_sqlx(e, None),
+            ConnectionError::Query { source, sql } => DatabaseErrorInfo::from_sqlx(source, Some(sql)),
+            _ => None,
+        };
+
+        let mut state = serializer.serialize_struct("ConnectionError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        match database {
+            Some(database) => state.serialize_field("database", &database)?,
+            None => state.skip_field("database")?,
+        }
+        state.end()
+    }
+}
+
+/// Default cap on rows read into memory for one result
+pub const DEFAULT_MAX_ROWS: usize = 100_000;
+
+/// Default cap on the decoded size of one result
+pub const DEFAULT_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Default length text, binary and JSON values are cut to before being returned
+pub const DEFAULT_MAX_CELL_BYTES: usize = 64 * 1024;
+
+/// Size caps applied while reading a result into memory
+#[derive(Debug, Clone, Copy)]
+pub struct ResultLimits {
+    pub max_rows: usize,
+    pub max_bytes: usize,
+    pub max_cell_bytes: usize,
+}
+
+impl Default for ResultLimits {
+    fn default() -> Self {
+        Self {
+            max_rows: DEFAULT_MAX_ROWS,
+            max_bytes: DEFAULT_MAX_BYTES,
+            max_cell_bytes: DEFAULT_MAX_CELL_BYTES,
+        }
+    }
+}
+
+impl ResultLimits {
+    pub fn from_params(params: &ExecuteQueryParams) -> Self {
+        let defaults = Self::default();
+        Self {
+            max_rows: params.max_rows.unwrap_or(defaults.max_rows),
+            max_bytes: params.max_bytes.unwrap_or(defaults.max_bytes),
+            max_cell_bytes: params.max_cell_bytes.unwrap_or(defaults.max_cell_bytes),
+        }
+    }
+
+    /// Limits for a result kept on disk: rows are only capped when asked and
+    /// bytes default to the cache's own budget
+    pub fn for_cache(params: &ExecuteQueryParams) -> Self {
+        Self {
+            max_rows: params.max_rows.unwrap_or(usize::MAX),
+            max_bytes: params.max_bytes.unwrap_or(result_cache::DEFAULT_MAX_BYTES as usize),
+            max_cell_bytes: params.max_cell_bytes.unwrap_or(DEFAULT_MAX_CELL_BYTES),
+        }
+    }
+}
+
+/// Decoded rows and diagnostics from a single execution
+struct QueryOutput {
+    columns: Vec<ColumnInfo>,
+    rows: Vec<serde_json::Value>,
+    notices: Vec<QueryNotice>,
+    truncated_reason: Option<TruncationReason>,
+    truncated_cells: Vec<TruncatedCell>,
+    stats: QueryStats,
+}
+
+/// Diagnostics of a query run through `DatabasePool::stream_query`
+pub struct StreamSummary {
+    pub notices: Vec<QueryNotice>,
+    pub stats: QueryStats,
+}
+
+/// Splits the time spent on one execution into disjoint phases: waiting for a
+/// connection, waiting for the first row, waiting for the rest and decoding
+struct QueryTimer {
+    started: Instant,
+    pool_wait: Duration,
+    first_row: Option<Duration>,
+    execution: Duration,
+    fetching: Duration,
+    decoding: Duration,
+    bytes: usize,
+    backend_id: Option<i64>,
+}
+
+impl QueryTimer {
+    fn new(started: Instant) -> Self {
+        Self {
+            started,
+            pool_wait: Duration::ZERO,
+            first_row: None,
+            execution: Duration::ZERO,
+            fetching: Duration::ZERO,
+            decoding: Duration::ZERO,
+            bytes: 0,
+            backend_id: None,
+        }
+    }
+
+    /// Mark the connection as acquired
+    fn acquired(&mut self) {
+        self.pool_wait = self.started.elapsed();
+    }
+
+    /// Await the next item from the server. Waiting for the first item counts
+    /// as execution, waiting for later ones as fetching.
+    async fn next<T>(&mut self, fut: impl Future<Output = T>) -> T {
+        let start = Instant::now();
+        let output = fut.await;
+        if self.first_row.is_none() {
+            self.execution += start.elapsed();
+            self.first_row = Some(self.started.elapsed());
+        } else {
+            self.fetching += start.elapsed();
+        }
+        output
+    }
+
+    /// Run local row conversion
+    fn decode<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let output = f();
+        self.decoding += start.elapsed();
+        output
+    }
+
+    fn finish(self) -> QueryStats {
+        QueryStats {
+            pool_wait_ms: millis(self.pool_wait),
+            first_row_ms: millis(self.first_row.unwrap_or_default()),
+            execution_ms: millis(self.execution),
+            fetch_ms: millis(self.fetching),
+            decode_ms: millis(self.decoding),
+            total_ms: millis(self.started.elapsed()),
+            bytes_received: self.bytes,
+            backend_id: self.backend_id,
+        }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Accumulates rows until a size cap is reached, shortening oversized cells
+struct RowCollector {
+    limits: ResultLimits,
+    columns: Vec<ColumnInfo>,
+    rows: Vec<serde_json::Value>,
+    bytes: usize,
+    truncated_reason: Option<TruncationReason>,
+    truncated_cells: Vec<TruncatedCell>,
+}
+
+impl RowCollector {
+    fn new(limits: ResultLimits) -> Self {
+        Self {
+            limits,
+            columns: vec![],
+            rows: vec![],
+            bytes: 0,
+            truncated_reason: None,
+            truncated_cells: vec![],
+        }
+    }
+
+    /// Add a row, returning false once no further rows should be read
+    fn push(&mut self, mut values: Vec<serde_json::Value>) -> bool {
+        if self.rows.len() >= self.limits.max_rows {
+            self.truncated_reason = Some(TruncationReason::RowLimit);
+            return false;
+        }
+
+        let mut row_bytes = 0;
+        for (index, value) in values.iter_mut().enumerate() {
+            if let Some(length) = cap_value(value, self.limits.max_cell_bytes) {
+                self.truncated_cells.push(TruncatedCell {
+                    row: self.rows.len(),
+                    column: self.columns.get(index).map(|c| c.name.clone()).unwrap_or_default(),
+                    length,
+                });
+            }
+            row_bytes += value_size(value);
+        }
+
+        if self.bytes + row_bytes > self.limits.max_bytes {
+            self.truncated_reason = Some(TruncationReason::ByteLimit);
+            self.truncated_cells.retain(|cell| cell.row < self.rows.len());
+            return false;
+        }
+
+        self.bytes += row_bytes;
+        self.rows.push(values_to_object(&self.columns, values));
+        true
+    }
+
+    fn finish(self, notices: Vec<QueryNotice>, stats: QueryStats) -> QueryOutput {
+        QueryOutput {
+            columns: self.columns,
+            rows: self.rows,
+            notices,
+            truncated_reason: self.truncated_reason,
+            truncated_cells: self.truncated_cells,
+            stats,
+        }
+    }
+}
+
+/// Cut a text, binary or JSON value longer than `max_bytes` to a text
+/// preview, returning its full length when it was shortened
+pub fn cap_value(value: &mut serde_json::Value, max_bytes: usize) -> Option<usize> {
+    match value {
+        serde_json::Value::String(text) => {
+            if text.len() <= max_bytes {
+                return None;
+            }
+            let length = text.len();
+            text.truncate(floor_char_boundary(text, max_bytes));
+            Some(length)
+        }
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            let mut text = value.to_string();
+            if text.len() <= max_bytes {
+                return None;
+            }
+            let length = text.len();
+            text.truncate(floor_char_boundary(&text, max_bytes));
+            *value = serde_json::Value::String(text);
+            Some(length)
+        }
+        _ => None,
+    }
+}
+
+/// Approximate size of a value once serialized
+pub fn value_size(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::Null => 4,
+        serde_json::Value::Bool(_) => 5,
+        serde_json::Value::Number(n) => n.to_string().len(),
+        serde_json::Value::String(s) => s.len() + 2,
+        other => other.to_string().len(),
+    }
+}
+
+/// Largest index no greater than `index` that falls on a char boundary of `text`
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    (0..=index.min(text.len()))
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+/// A pooled connection that is closed rather than returned to the pool when
+/// dropped before `release`, e.g. because the future using it was stopped while
+/// a statement was still running. Otherwise the next user of the pool could
+/// inherit the statement, or be hit by a cancel meant for it.
+pub(crate) struct InFlight<DB: sqlx::Database>(Option<PoolConnection<DB>>);
+
+impl<DB: sqlx::Database> InFlight<DB> {
+    pub(crate) async fn acquire(pool: &sqlx::Pool<DB>) -> Result<Self, sqlx::Error> {
+        Ok(Self(Some(pool.acquire().await?)))
+    }
+
+    /// The statement finished; hand the connection back to the pool
+    pub(crate) fn release(mut self) {
+        self.0.take();
+    }
+}
+
+impl<DB: sqlx::Database> std::ops::Deref for InFlight<DB> {
+    type Target = DB::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref().expect("connection already released")
+    }
+}
+
+impl<DB: sqlx::Database> std::ops::DerefMut for InFlight<DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.as_mut().expect("connection already released")
+    }
+}
+
+impl<DB: sqlx::Database> Drop for InFlight<DB> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.0.as_mut() {
+            conn.close_on_drop();
+        }
+    }
 }
 
 /// Holds either a Postgres or MySQL connection pool
@@ -27,12 +391,133 @@ pub enum DatabasePool {
     MySql(MySqlPool),
 }
 
+impl DatabasePool {
+    /// Quote an identifier for interpolation into SQL
+    pub fn quote_ident(&self, ident: &str) -> String {
+        match self {
+            DatabasePool::Postgres(_) => DatabaseType::Postgres.quote_ident(ident),
+            DatabasePool::MySql(_) => DatabaseType::Mysql.quote_ident(ident),
+        }
+    }
+
+    /// Run `sql` on a dedicated connection, handing each row's decoded values to
+    /// `on_row` as it arrives instead of buffering the result set; reading stops
+    /// early once `on_row` returns false. When given, `on_start` receives the
+    /// server-side connection ID before the query is sent, for `cancel_backend`;
+    /// without it the ID isn't looked up. Returns the notices (Postgres) or
+    /// warnings (MySQL) the query raised along with its timing.
+    pub async fn stream_query<S, F>(
+        &self,
+        sql: &str,
+        on_start: Option<S>,
+        mut on_row: F,
+    ) -> Result<StreamSummary, ConnectionError>
+    where
+        S: FnOnce(i64),
+        F: FnMut(&[ColumnInfo], Vec<serde_json::Value>) -> Result<bool, ConnectionError>,
+    {
+        let mut timer = QueryTimer::new(Instant::now());
+
+        match self {
+            DatabasePool::Postgres(pool) => {
+                let (streamed, notices) = notices::capture(async {
+                    let mut conn = InFlight::acquire(pool).await?;
+                    timer.acquired();
+                    if let Some(on_start) = on_start {
+                        let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+                            .fetch_one(&mut *conn)
+                            .await?;
+                        timer.backend_id = Some(pid as i64);
+                        on_start(pid as i64);
+                    }
+
+                    let mut rows = sqlx::query(sql).fetch(&mut *conn);
+                    let mut columns = None;
+                    while let Some(row) = timer.next(rows.try_next()).await? {
+                        timer.bytes += postgres_row_bytes(&row);
+                        let columns = columns.get_or_insert_with(|| postgres_columns(&row));
+                        if !timer.decode(|| on_row(columns, postgres_row_values(&row)))? {
+                            break;
+                        }
+                    }
+                    drop(rows);
+                    conn.release();
+                    Ok::<_, ConnectionError>(())
+                })
+                .await;
+
+                streamed?;
+                Ok(StreamSummary { notices, stats: timer.finish() })
+            }
+            DatabasePool::MySql(pool) => {
+                let mut conn = InFlight::acquire(pool).await?;
+                timer.acquired();
+                if let Some(on_start) = on_start {
+                    let id: u64 = sqlx::query_scalar("SELECT CONNECTION_ID()")
+                        .fetch_one(&mut *conn)
+                        .await?;
+                    timer.backend_id = Some(id as i64);
+                    on_start(id as i64);
+                }
+
+                {
+                    let mut rows = sqlx::query(sql).fetch(&mut *conn);
+                    let mut columns = None;
+                    while let Some(row) = timer.next(rows.try_next()).await? {
+                        timer.bytes += mysql_row_bytes(&row);
+                        let columns = columns.get_or_insert_with(|| mysql_columns(&row));
+                        if !timer.decode(|| on_row(columns, mysql_row_values(&row)))? {
+                            break;
+                        }
+                    }
+                }
+                let stats = timer.finish();
+
+                // Warnings belong to the session, so read them back before releasing it
+                let warnings = notices::fetch_mysql_warnings(&mut conn).await?;
+                conn.release();
+                Ok(StreamSummary { notices: warnings, stats })
+            }
+        }
+    }
+
+    /// Ask the server to cancel the statement running on `backend_id`
+    pub async fn cancel_backend(&self, backend_id: i64) -> Result<(), ConnectionError> {
+        match self {
+            DatabasePool::Postgres(pool) => {
+                sqlx::query("SELECT pg_cancel_backend($1)")
+                    .bind(backend_id as i32)
+                    .execute(pool)
+                    .await?;
+            }
+            DatabasePool::MySql(pool) => {
+                // KILL can't be prepared; the ID is an integer so formatting is safe
+                sqlx::raw_sql(&format!("KILL QUERY {}", backend_id))
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What a pool on a connection is connected to: a database on its server
+/// and, on Postgres, a schema to put first on the search path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PoolTarget {
+    pub connection_id: String,
+    pub database: String,
+    pub schema: Option<String>,
+}
+
 /// Manages database connections and configurations
 pub struct ConnectionManager {
     /// Saved connection configurations
     configs: RwLock<HashMap<String, ConnectionConfig>>,
     /// Active connection pools
     pools: RwLock<HashMap<String, Arc<DatabasePool>>>,
+    /// Pools for other targets on active connections, opened on first use
+    targets: RwLock<HashMap<PoolTarget, Arc<DatabasePool>>>,
 }
 
 impl Default for ConnectionManager {
@@ -46,6 +531,7 @@ impl ConnectionManager {
         Self {
             configs: RwLock::new(HashMap::new()),
             pools: RwLock::new(HashMap::new()),
+            targets: RwLock::new(HashMap::new()),
         }
     }
 
@@ -126,32 +612,7 @@ impl ConnectionManager {
         let password = credentials::get_password(connection_id)
             .map_err(|e| ConnectionError::Credential(e.to_string()))?;
 
-        let connection_string = build_connection_string(
-            config.db_type,
-            &config.host,
-            config.port,
-            &config.database,
-            &config.username,
-            &password,
-        );
-
-        let pool = match config.db_type {
-            DatabaseType::Postgres => {
-                let pool = PgPoolOptions::new()
-                    .max_connections(5)
-                    .connect(&connection_string)
-                    .await?;
-                DatabasePool::Postgres(pool)
-            }
-            DatabaseType::Mysql => {
-                let pool = MySqlPoolOptions::new()
-                    .max_connections(5)
-                    .connect(&connection_string)
-                    .await?;
-                DatabasePool::MySql(pool)
-            }
-        };
-
+        let pool = open_pool(&config, &config.database, None, &password, 5).await?;
         self.pools.write().insert(connection_id.to_string(), Arc::new(pool));
         
         // Update last connected time
@@ -165,9 +626,18 @@ impl ConnectionManager {
     /// Disconnect from a connection
     pub fn disconnect(&self, connection_id: &str) -> Result<(), ConnectionError> {
         self.pools.write().remove(connection_id);
+        self.targets.write().retain(|target, _| target.connection_id != connection_id);
         Ok(())
     }
 
+    /// Get a saved connection configuration
+    pub fn get_config(&self, connection_id: &str) -> Result<ConnectionConfig, ConnectionError> {
+        self.configs.read()
+            .get(connection_id)
+            .cloned()
+            .ok_or_else(|| ConnectionError::NotFound(connection_id.to_string()))
+    }
+
     /// Get all saved connections
     pub fn list_connections(&self) -> Vec<ConnectionConfig> {
         self.configs.read().values().cloned().collect()
@@ -176,6 +646,7 @@ impl ConnectionManager {
     /// Delete a connection
     pub fn delete_connection(&self, connection_id: &str) -> Result<(), ConnectionError> {
         self.pools.write().remove(connection_id);
+        self.targets.write().retain(|target, _| target.connection_id != connection_id);
         self.configs.write().remove(connection_id);
         let _ = credentials::delete_password(connection_id);
         Ok(())
@@ -183,44 +654,161 @@ impl ConnectionManager {
 
     /// Execute a query
     pub async fn execute_query(&self, params: ExecuteQueryParams) -> Result<QueryResult, ConnectionError> {
-        let pool = self.pools.read()
-            .get(&params.connection_id)
-            .cloned()
-            .ok_or_else(|| ConnectionError::NotFound(params.connection_id.clone()))?;
+        let pool = self
+            .get_target_pool(&params.connection_id, params.database.as_deref(), params.schema.as_deref())
+            .await?;
 
+        let limits = ResultLimits::from_params(&params);
         let start = Instant::now();
         
-        let result = match pool.as_ref() {
-            DatabasePool::Postgres(pool) => execute_postgres_query(pool, &params.sql).await?,
-            DatabasePool::MySql(pool) => execute_mysql_query(pool, &params.sql).await?,
+        let output = match pool.as_ref() {
+            DatabasePool::Postgres(pool) => {
+                let (output, notices) = notices::capture(execute_postgres_query(
+                    pool,
+                    &params.sql,
+                    limits,
+                    params.include_backend_id,
+                ))
+                .await;
+                QueryOutput { notices, ..output.map_err(|e| e.with_sql(&params.sql))? }
+            }
+            DatabasePool::MySql(pool) => execute_mysql_query(pool, &params.sql, limits, params.include_backend_id)
+                .await
+                .map_err(|e| e.with_sql(&params.sql))?,
         };
 
         let execution_time_ms = start.elapsed().as_millis();
 
         Ok(QueryResult {
-            columns: result.0,
-            rows: result.1,
-            row_count: result.2,
+            row_count: output.rows.len(),
+            columns: output.columns,
+            rows: output.rows,
             execution_time_ms,
+            notices: output.notices,
+            result_id: None,
+            truncated: output.truncated_reason.is_some(),
+            truncated_reason: output.truncated_reason,
+            truncated_cells: output.truncated_cells,
+            stats: Some(output.stats),
         })
     }
 
-    /// List tables for a connection
-    pub async fn list_tables(&self, connection_id: &str) -> Result<Vec<TableInfo>, ConnectionError> {
-        let pool = self.pools.read()
-            .get(connection_id)
-            .cloned()
-            .ok_or_else(|| ConnectionError::NotFound(connection_id.to_string()))?;
+    /// Execute a query and keep its full result in the disk cache, returning
+    /// the first page along with the id for fetching further pages
+    pub async fn execute_cached(
+        &self,
+        params: ExecuteQueryParams,
+        cache: &ResultCache,
+    ) -> Result<QueryResult, ConnectionError> {
+        let pool = self
+            .get_target_pool(&params.connection_id, params.database.as_deref(), params.schema.as_deref())
+            .await?;
 
-        let config = self.configs.read()
+        let start = Instant::now();
+        let stored = cache
+            .store(pool, params.sql.clone(), ResultLimits::for_cache(&params), params.include_backend_id)
+            .await
+            .map_err(|e| e.with_sql(&params.sql))?;
+        let execution_time_ms = start.elapsed().as_millis();
+
+        let page = cache.page(GetResultPageParams {
+            result_id: stored.id.clone(),
+            offset: params.offset,
+            limit: params.limit,
+            sort: None,
+        })
+        .await?;
+
+        Ok(QueryResult {
+            columns: stored.columns,
+            rows: page.rows,
+            row_count: stored.total_rows,
+            execution_time_ms,
+            notices: stored.notices,
+            result_id: Some(stored.id),
+            truncated: stored.truncated_reason.is_some(),
+            truncated_reason: stored.truncated_reason,
+            truncated_cells: page.truncated_cells,
+            stats: Some(stored.stats),
+        })
+    }
+
+    /// List tables for a connection, in its own database or another one on
+    /// the same server, optionally limited to one schema
+    pub async fn list_tables(
+        &self,
+        connection_id: &str,
+        database: Option<&str>,
+        schema: Option<&str>,
+    ) -> Result<Vec<TableInfo>, ConnectionError> {
+        let config = self.get_config(connection_id)?;
+
+        // MySQL lists any database from information_schema, so needs no extra pool
+        let pool = match config.db_type {
+            DatabaseType::Postgres => self.get_target_pool(connection_id, database, None).await?,
+            DatabaseType::Mysql => self.get_pool(connection_id)?,
+        };
+
+        match pool.as_ref() {
+            DatabasePool::Postgres(pool) => list_postgres_tables(pool, schema).await,
+            DatabasePool::MySql(pool) => {
+                list_mysql_tables(pool, database.or(schema).unwrap_or(&config.database)).await
+            }
+        }
+    }
+
+    /// Get the active pool for a connection
+    pub fn get_pool(&self, connection_id: &str) -> Result<Arc<DatabasePool>, ConnectionError> {
+        self.pools.read()
             .get(connection_id)
             .cloned()
-            .ok_or_else(|| ConnectionError::NotFound(connection_id.to_string()))?;
+            .ok_or_else(|| ConnectionError::NotFound(connection_id.to_string()))
+    }
 
-        match pool.as_ref() {
-            DatabasePool::Postgres(pool) => list_postgres_tables(pool).await,
-            DatabasePool::MySql(pool) => list_mysql_tables(pool, &config.database).await,
+    /// Resolve a database and schema on a connection to the target a pool is
+    /// opened for. MySQL treats a schema as a database; `database` wins when
+    /// both are given. Without either this is the saved database.
+    pub fn target(
+        &self,
+        connection_id: &str,
+        database: Option<&str>,
+        schema: Option<&str>,
+    ) -> Result<PoolTarget, ConnectionError> {
+        let config = self.get_config(connection_id)?;
+        Ok(resolve_target(&config, database, schema))
+    }
+
+    /// Get the pool for `target`, opening it on first use. The connection
+    /// must be active; for its saved database its own pool is returned.
+    pub async fn target_pool(&self, target: &PoolTarget) -> Result<Arc<DatabasePool>, ConnectionError> {
+        let pool = self.get_pool(&target.connection_id)?;
+        let config = self.get_config(&target.connection_id)?;
+        if target.database == config.database && target.schema.is_none() {
+            return Ok(pool);
+        }
+
+        if let Some(pool) = self.targets.read().get(target) {
+            return Ok(pool.clone());
         }
+
+        let password = credentials::get_password(&target.connection_id)
+            .map_err(|e| ConnectionError::Credential(e.to_string()))?;
+        let pool = Arc::new(open_pool(&config, &target.database, target.schema.as_deref(), &password, 2).await?);
+
+        // Another caller may have opened the same target meanwhile; keep theirs
+        Ok(self.targets.write().entry(target.clone()).or_insert(pool).clone())
+    }
+
+    /// Get a pool for another database on a connection's server, and on
+    /// Postgres with `schema` first on the search path; see [`Self::target`]
+    pub async fn get_target_pool(
+        &self,
+        connection_id: &str,
+        database: Option<&str>,
+        schema: Option<&str>,
+    ) -> Result<Arc<DatabasePool>, ConnectionError> {
+        let target = self.target(connection_id, database, schema)?;
+        self.target_pool(&target).await
     }
 
     /// Check if connection is active
@@ -229,6 +817,64 @@ impl ConnectionManager {
     }
 }
 
+/// See [`ConnectionManager::target`]
+fn resolve_target(config: &ConnectionConfig, database: Option<&str>, schema: Option<&str>) -> PoolTarget {
+    let (database, schema) = match config.db_type {
+        DatabaseType::Postgres => (database.unwrap_or(&config.database), schema),
+        DatabaseType::Mysql => (database.or(schema).unwrap_or(&config.database), None),
+    };
+    PoolTarget {
+        connection_id: config.id.clone(),
+        database: database.to_string(),
+        schema: schema.map(str::to_string),
+    }
+}
+
+/// Open a pool to `database` with a connection's host and credentials. A
+/// Postgres `schema` is set as the session search path, ahead of `public`.
+async fn open_pool(
+    config: &ConnectionConfig,
+    database: &str,
+    schema: Option<&str>,
+    password: &str,
+    max_connections: u32,
+) -> Result<DatabasePool, ConnectionError> {
+    let connection_string = build_connection_string(
+        config.db_type,
+        &config.host,
+        config.port,
+        database,
+        &config.username,
+        password,
+    );
+
+    let pool = match config.db_type {
+        DatabaseType::Postgres => {
+            let mut options: PgConnectOptions = connection_string.parse()?;
+            if let Some(schema) = schema {
+                // Startup options are split on spaces unless backslash-escaped
+                let search_path = format!("\"{}\",public", schema.replace('"', "\"\""))
+                    .replace('\\', "\\\\")
+                    .replace(' ', "\\ ");
+                options = options.options([("search_path", search_path)]);
+            }
+            let pool = PgPoolOptions::new()
+                .max_connections(max_connections)
+                .connect_with(options)
+                .await?;
+            DatabasePool::Postgres(pool)
+        }
+        DatabaseType::Mysql => {
+            let pool = MySqlPoolOptions::new()
+                .max_connections(max_connections)
+                .connect(&connection_string)
+                .await?;
+            DatabasePool::MySql(pool)
+        }
+    };
+    Ok(pool)
+}
+
 fn build_connection_string(
     db_type: DatabaseType,
     host: &str,
@@ -250,72 +896,163 @@ fn build_connection_string(
 async fn execute_postgres_query(
     pool: &PgPool,
     sql: &str,
-) -> Result<(Vec<ColumnInfo>, Vec<serde_json::Value>, usize), ConnectionError> {
-    let rows = sqlx::query(sql).fetch_all(pool).await?;
-    
-    if rows.is_empty() {
-        return Ok((vec![], vec![], 0));
-    }
-
-    let columns: Vec<ColumnInfo> = rows[0]
-        .columns()
-        .iter()
-        .map(|col| ColumnInfo {
-            name: col.name().to_string(),
-            data_type: col.type_info().to_string(),
-            nullable: true, // PostgreSQL requires more complex checks
-        })
-        .collect();
+    limits: ResultLimits,
+    include_backend_id: bool,
+) -> Result<QueryOutput, ConnectionError> {
+    let mut timer = QueryTimer::new(Instant::now());
+    let mut conn = pool.acquire().await?;
+    timer.acquired();
 
-    let mut result_rows = Vec::with_capacity(rows.len());
-    for row in &rows {
-        let mut obj = serde_json::Map::new();
-        for (i, col) in columns.iter().enumerate() {
-            let value = extract_postgres_value(&row, i);
-            obj.insert(col.name.clone(), value);
+    let mut collector = RowCollector::new(limits);
+    {
+        let mut rows = sqlx::query(sql).fetch(&mut *conn);
+        while let Some(row) = timer.next(rows.try_next()).await? {
+            let more = timer.decode(|| {
+                if collector.columns.is_empty() {
+                    collector.columns = postgres_columns(&row);
+                }
+                collector.push(postgres_row_values(&row))
+            });
+            timer.bytes += postgres_row_bytes(&row);
+            if !more {
+                break;
+            }
         }
-        result_rows.push(serde_json::Value::Object(obj));
     }
 
-    let row_count = result_rows.len();
-    Ok((columns, result_rows, row_count))
+    let mut stats = timer.finish();
+    if include_backend_id {
+        let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(&mut *conn)
+            .await?;
+        stats.backend_id = Some(pid as i64);
+    }
+    Ok(collector.finish(vec![], stats))
 }
 
 async fn execute_mysql_query(
     pool: &MySqlPool,
     sql: &str,
-) -> Result<(Vec<ColumnInfo>, Vec<serde_json::Value>, usize), ConnectionError> {
-    let rows = sqlx::query(sql).fetch_all(pool).await?;
-    
-    if rows.is_empty() {
-        return Ok((vec![], vec![], 0));
+    limits: ResultLimits,
+    include_backend_id: bool,
+) -> Result<QueryOutput, ConnectionError> {
+    // Warnings belong to the session, so read them back on the same connection
+    let mut timer = QueryTimer::new(Instant::now());
+    let mut conn = pool.acquire().await?;
+    timer.acquired();
+
+    let mut collector = RowCollector::new(limits);
+    {
+        let mut rows = sqlx::query(sql).fetch(&mut *conn);
+        while let Some(row) = timer.next(rows.try_next()).await? {
+            let more = timer.decode(|| {
+                if collector.columns.is_empty() {
+                    collector.columns = mysql_columns(&row);
+                }
+                collector.push(mysql_row_values(&row))
+            });
+            timer.bytes += mysql_row_bytes(&row);
+            if !more {
+                break;
+            }
+        }
+    }
+
+    let mut stats = timer.finish();
+    let notices = notices::fetch_mysql_warnings(&mut conn).await?;
+    // Looked up after SHOW WARNINGS, since any statement clears them
+    if include_backend_id {
+        let id: u64 = sqlx::query_scalar("SELECT CONNECTION_ID()")
+            .fetch_one(&mut *conn)
+            .await?;
+        stats.backend_id = Some(id as i64);
     }
+    Ok(collector.finish(notices, stats))
+}
+
+fn postgres_columns(row: &PgRow) -> Vec<ColumnInfo> {
+    row.columns()
+        .iter()
+        .map(|col| ColumnInfo {
+            name: col.name().to_string(),
+            data_type: col.type_info().to_string(),
+            nullable: true, // PostgreSQL requires more complex checks
+        })
+        .collect()
+}
 
-    let columns: Vec<ColumnInfo> = rows[0]
-        .columns()
+fn mysql_columns(row: &MySqlRow) -> Vec<ColumnInfo> {
+    row.columns()
         .iter()
         .map(|col| ColumnInfo {
             name: col.name().to_string(),
             data_type: col.type_info().to_string(),
             nullable: true,
         })
-        .collect();
+        .collect()
+}
 
-    let mut result_rows = Vec::with_capacity(rows.len());
-    for row in &rows {
-        let mut obj = serde_json::Map::new();
-        for (i, col) in columns.iter().enumerate() {
-            let value = extract_mysql_value(&row, i);
-            obj.insert(col.name.clone(), value);
-        }
-        result_rows.push(serde_json::Value::Object(obj));
+fn postgres_row_values(row: &PgRow) -> Vec<serde_json::Value> {
+    (0..row.len()).map(|i| extract_postgres_value(row, i)).collect()
+}
+
+fn mysql_row_values(row: &MySqlRow) -> Vec<serde_json::Value> {
+    (0..row.len()).map(|i| extract_mysql_value(row, i)).collect()
+}
+
+/// Size of a row's raw values as received from the server
+fn postgres_row_bytes(row: &PgRow) -> usize {
+    (0..row.len())
+        .filter_map(|i| row.try_get_unchecked::<Option<&[u8]>, _>(i).ok().flatten())
+        .map(<[u8]>::len)
+        .sum()
+}
+
+fn mysql_row_bytes(row: &MySqlRow) -> usize {
+    (0..row.len())
+        .filter_map(|i| row.try_get_unchecked::<Option<&[u8]>, _>(i).ok().flatten())
+        .map(<[u8]>::len)
+        .sum()
+}
+
+/// Build the keyed row object the frontend expects from positional values
+pub fn values_to_object(columns: &[ColumnInfo], values: Vec<serde_json::Value>) -> serde_json::Value {
+    let mut obj = serde_json::Map::new();
+    for (col, value) in columns.iter().zip(values) {
+        obj.insert(col.name.clone(), value);
     }
+    serde_json::Value::Object(obj)
+}
 
-    let row_count = result_rows.len();
-    Ok((columns, result_rows, row_count))
+/// Bind `values` in order as the nearest SQL types: booleans, integers,
+/// floats and text, with arrays and objects bound as JSON
+pub(crate) fn bind_json<'q, DB>(
+    mut query: sqlx::query::Query<'q, DB, <DB as sqlx::Database>::Arguments<'q>>,
+    values: &'q [serde_json::Value],
+) -> sqlx::query::Query<'q, DB, <DB as sqlx::Database>::Arguments<'q>>
+where
+    DB: sqlx::Database,
+    bool: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    i64: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    Option<f64>: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    &'q str: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    &'q serde_json::Value: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+{
+    for value in values {
+        query = match value {
+            serde_json::Value::Bool(b) => query.bind(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => query.bind(i),
+                None => query.bind(n.as_f64()),
+            },
+            serde_json::Value::String(s) => query.bind(s.as_str()),
+            other => query.bind(other),
+        };
+    }
+    query
 }
 
-fn extract_postgres_value(row: &sqlx::postgres::PgRow, index: usize) -> serde_json::Value {
+pub fn extract_postgres_value(row: &sqlx::postgres::PgRow, index: usize) -> serde_json::Value {
     // Try to get as various types
     if let Ok(v) = row.try_get::<Option<i64>, _>(index) {
         return v.map(|n| serde_json::Value::Number(n.into())).unwrap_or(serde_json::Value::Null);
@@ -324,7 +1061,7 @@ fn extract_postgres_value(row: &sqlx::postgres::PgRow, index: usize) -> serde_js
         return v.map(|n| serde_json::Value::Number(n.into())).unwrap_or(serde_json::Value::Null);
     }
     if let Ok(v) = row.try_get::<Option<f64>, _>(index) {
-        return v.and_then(|n| serde_json::Number::from_f64(n))
+        return v.and_then(serde_json::Number::from_f64)
             .map(serde_json::Value::Number)
             .unwrap_or(serde_json::Value::Null);
     }
@@ -334,10 +1071,16 @@ fn extract_postgres_value(row: &sqlx::postgres::PgRow, index: usize) -> serde_js
     if let Ok(v) = row.try_get::<Option<String>, _>(index) {
         return v.map(serde_json::Value::String).unwrap_or(serde_json::Value::Null);
     }
+    if let Ok(v) = row.try_get::<Option<serde_json::Value>, _>(index) {
+        return v.unwrap_or(serde_json::Value::Null);
+    }
+    if let Ok(v) = row.try_get::<Option<Vec<u8>>, _>(index) {
+        return v.map(|bytes| hex_value("\\x", &bytes)).unwrap_or(serde_json::Value::Null);
+    }
     serde_json::Value::Null
 }
 
-fn extract_mysql_value(row: &sqlx::mysql::MySqlRow, index: usize) -> serde_json::Value {
+pub fn extract_mysql_value(row: &sqlx::mysql::MySqlRow, index: usize) -> serde_json::Value {
     if let Ok(v) = row.try_get::<Option<i64>, _>(index) {
         return v.map(|n| serde_json::Value::Number(n.into())).unwrap_or(serde_json::Value::Null);
     }
@@ -345,7 +1088,7 @@ fn extract_mysql_value(row: &sqlx::mysql::MySqlRow, index: usize) -> serde_json:
         return v.map(|n| serde_json::Value::Number(n.into())).unwrap_or(serde_json::Value::Null);
     }
     if let Ok(v) = row.try_get::<Option<f64>, _>(index) {
-        return v.and_then(|n| serde_json::Number::from_f64(n))
+        return v.and_then(serde_json::Number::from_f64)
             .map(serde_json::Value::Number)
             .unwrap_or(serde_json::Value::Null);
     }
@@ -355,33 +1098,82 @@ fn extract_mysql_value(row: &sqlx::mysql::MySqlRow, index: usize) -> serde_json:
     if let Ok(v) = row.try_get::<Option<String>, _>(index) {
         return v.map(serde_json::Value::String).unwrap_or(serde_json::Value::Null);
     }
+    // JSON also decodes from text and binary columns, so only use it for JSON ones
+    if row.column(index).type_info().name() == "JSON" {
+        if let Ok(v) = row.try_get::<Option<serde_json::Value>, _>(index) {
+            return v.unwrap_or(serde_json::Value::Null);
+        }
+    }
+    if let Ok(v) = row.try_get::<Option<Vec<u8>>, _>(index) {
+        return v.map(|bytes| hex_value("0x", &bytes)).unwrap_or(serde_json::Value::Null);
+    }
     serde_json::Value::Null
 }
 
-async fn list_postgres_tables(pool: &PgPool) -> Result<Vec<TableInfo>, ConnectionError> {
+/// Binary values are shown as hex in the dialect's literal syntax
+fn hex_value(prefix: &str, bytes: &[u8]) -> serde_json::Value {
+    let mut text = String::with_capacity(prefix.len() + bytes.len() * 2);
+    text.push_str(prefix);
+    for byte in bytes {
+        let _ = write!(text, "{:02x}", byte);
+    }
+    serde_json::Value::String(text)
+}
+
+async fn list_postgres_tables(pool: &PgPool, schema: Option<&str>) -> Result<Vec<TableInfo>, ConnectionError> {
+    // Estimates and sizes of a partitioned table are the sums over its partitions.
+    // reltuples is -1 until a table is first vacuumed or analyzed.
     let query = r#"
-        SELECT 
-            table_name as name,
-            table_schema as schema,
-            table_type as type
-        FROM information_schema.tables
-        WHERE table_schema NOT IN ('pg_catalog', 'information_schema')
-        ORDER BY table_schema, table_name
+        SELECT
+            c.relname AS name,
+            n.nspname AS schema,
+            c.relkind::text AS kind,
+            s.row_count,
+            s.total_bytes,
+            s.table_bytes,
+            s.index_bytes,
+            s.toast_bytes
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        LEFT JOIN LATERAL (
+            SELECT
+                CASE WHEN bool_and(p.reltuples >= 0) THEN sum(p.reltuples)::int8 END AS row_count,
+                sum(pg_total_relation_size(p.oid))::int8 AS total_bytes,
+                sum(pg_relation_size(p.oid))::int8 AS table_bytes,
+                sum(pg_indexes_size(p.oid))::int8 AS index_bytes,
+                sum(CASE WHEN p.reltoastrelid <> 0 THEN pg_total_relation_size(p.reltoastrelid) ELSE 0 END)::int8
+                    AS toast_bytes
+            FROM pg_class p
+            WHERE CASE
+                WHEN c.relkind = 'p' THEN p.oid IN (SELECT relid FROM pg_partition_tree(c.oid) WHERE isleaf)
+                ELSE p.oid = c.oid
+            END
+        ) s ON c.relkind IN ('r', 'p', 'm')
+        WHERE c.relkind IN ('r', 'p', 'f', 'v', 'm')
+          AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+          AND n.nspname !~ '^pg_(toast|temp_|toast_temp_)'
+          AND has_table_privilege(c.oid, 'SELECT, INSERT, UPDATE, DELETE, TRUNCATE, REFERENCES, TRIGGER')
+          AND ($1::text IS NULL OR n.nspname = $1)
+        ORDER BY n.nspname, c.relname
     "#;
 
-    let rows = sqlx::query(query).fetch_all(pool).await?;
+    let rows = sqlx::query(query).bind(schema).fetch_all(pool).await?;
     
     let tables = rows
         .iter()
         .map(|row| TableInfo {
             name: row.try_get("name").unwrap_or_default(),
             schema: row.try_get("schema").unwrap_or_default(),
-            table_type: if row.try_get::<String, _>("type").unwrap_or_default() == "VIEW" {
-                "view".to_string()
-            } else {
-                "table".to_string()
+            table_type: match row.try_get::<String, _>("kind").unwrap_or_default().as_str() {
+                "v" => "view".to_string(),
+                "m" => "materializedView".to_string(),
+                _ => "table".to_string(),
             },
-            row_count: None,
+            row_count: row.try_get("row_count").ok().flatten(),
+            total_bytes: row.try_get("total_bytes").ok().flatten(),
+            table_bytes: row.try_get("table_bytes").ok().flatten(),
+            index_bytes: row.try_get("index_bytes").ok().flatten(),
+            toast_bytes: row.try_get("toast_bytes").ok().flatten(),
         })
         .collect();
 
@@ -389,11 +1181,15 @@ async fn list_postgres_tables(pool: &PgPool) -> Result<Vec<TableInfo>, Connectio
 }
 
 async fn list_mysql_tables(pool: &MySqlPool, database: &str) -> Result<Vec<TableInfo>, ConnectionError> {
+    // TABLE_ROWS is exact for MyISAM and a sampled estimate for InnoDB
     let query = r#"
         SELECT 
             TABLE_NAME as name,
             TABLE_SCHEMA as `schema`,
-            TABLE_TYPE as type
+            TABLE_TYPE as type,
+            CAST(TABLE_ROWS AS SIGNED) as row_count,
+            CAST(DATA_LENGTH AS SIGNED) as table_bytes,
+            CAST(INDEX_LENGTH AS SIGNED) as index_bytes
         FROM information_schema.tables
         WHERE TABLE_SCHEMA = ?
         ORDER BY TABLE_NAME
@@ -406,17 +1202,173 @@ async fn list_mysql_tables(pool: &MySqlPool, database: &str) -> Result<Vec<Table
     
     let tables = rows
         .iter()
-        .map(|row| TableInfo {
-            name: row.try_get("name").unwrap_or_default(),
-            schema: row.try_get("schema").unwrap_or_default(),
-            table_type: if row.try_get::<String, _>("type").unwrap_or_default() == "VIEW" {
-                "view".to_string()
-            } else {
-                "table".to_string()
-            },
-            row_count: None,
+        .map(|row| {
+            let table_bytes: Option<i64> = row.try_get("table_bytes").ok().flatten();
+            let index_bytes: Option<i64> = row.try_get("index_bytes").ok().flatten();
+            TableInfo {
+                name: row.try_get("name").unwrap_or_default(),
+                schema: row.try_get("schema").unwrap_or_default(),
+                table_type: if row.try_get::<String, _>("type").unwrap_or_default() == "VIEW" {
+                    "view".to_string()
+                } else {
+                    "table".to_string()
+                },
+                row_count: row.try_get("row_count").ok().flatten(),
+                total_bytes: table_bytes.zip(index_bytes).map(|(table, index)| table + index),
+                table_bytes,
+                index_bytes,
+                toast_bytes: None,
+            }
         })
         .collect();
 
     Ok(tables)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn collector(max_rows: usize, max_bytes: usize, max_cell_bytes: usize) -> RowCollector {
+        let mut collector = RowCollector::new(ResultLimits { max_rows, max_bytes, max_cell_bytes });
+        collector.columns = ["id", "body"]
+            .iter()
+            .map(|name| ColumnInfo {
+                name: name.to_string(),
+                data_type: "TEXT".to_string(),
+                nullable: true,
+            })
+            .collect();
+        collector
+    }
+
+    #[test]
+    fn resolve_target_defaults_to_the_saved_database() {
+        let mut config = ConnectionConfig {
+            id: "c1".to_string(),
+            name: "local".to_string(),
+            db_type: DatabaseType::Postgres,
+            host: "localhost".to_string(),
+            port: 5432,
+            database: "app".to_string(),
+            username: "postgres".to_string(),
+            created_at: None,
+            last_connected: None,
+        };
+        let target = |config: &ConnectionConfig, database, schema| {
+            let target = resolve_target(config, database, schema);
+            assert_eq!(target.connection_id, "c1");
+            (target.database, target.schema)
+        };
+
+        assert_eq!(target(&config, None, None), ("app".to_string(), None));
+        assert_eq!(target(&config, None, Some("audit")), ("app".to_string(), Some("audit".to_string())));
+        assert_eq!(target(&config, Some("logs"), Some("audit")), ("logs".to_string(), Some("audit".to_string())));
+
+        // A MySQL schema is a database
+        config.db_type = DatabaseType::Mysql;
+        assert_eq!(target(&config, None, Some("audit")), ("audit".to_string(), None));
+        assert_eq!(target(&config, Some("logs"), Some("audit")), ("logs".to_string(), None));
+        assert_eq!(target(&config, None, None), ("app".to_string(), None));
+    }
+
+    #[test]
+    fn quote_ident_doubles_the_quote_character() {
+        assert_eq!(DatabaseType::Postgres.quote_ident(r#"my "table""#), r#""my ""table""""#);
+        assert_eq!(DatabaseType::Mysql.quote_ident("odd`name"), "`odd``name`");
+        assert_eq!(DatabaseType::Mysql.quote_ident(r#"a"b"#), r#"`a"b`"#);
+    }
+
+    #[test]
+    fn cap_value_shortens_text_and_json() {
+        let mut text = json!("abcdefgh");
+        assert_eq!(cap_value(&mut text, 3), Some(8));
+        assert_eq!(text, json!("abc"));
+
+        // Never splits a multi-byte character
+        let mut accented = json!("éé");
+        assert_eq!(cap_value(&mut accented, 3), Some(4));
+        assert_eq!(accented, json!("é"));
+
+        let mut document = json!({"key": "value"});
+        assert_eq!(cap_value(&mut document, 6), Some(15));
+        assert_eq!(document, json!("{\"key\""));
+
+        let mut small = json!([1, 2]);
+        assert_eq!(cap_value(&mut small, 64), None);
+        assert_eq!(small, json!([1, 2]));
+
+        let mut number = json!(123456789);
+        assert_eq!(cap_value(&mut number, 2), None);
+    }
+
+    #[test]
+    fn hex_value_uses_dialect_prefix() {
+        assert_eq!(hex_value("\\x", &[0xde, 0xad, 0x01]), json!("\\xdead01"));
+        assert_eq!(hex_value("0x", &[]), json!("0x"));
+    }
+
+    #[test]
+    fn collector_reports_truncated_cells() {
+        let mut collector = collector(10, 1024, 4);
+        assert!(collector.push(vec![json!(1), json!("short")]));
+        assert!(collector.push(vec![json!(2), json!({"a": 1})]));
+
+        let output = collector.finish(vec![], QueryStats::default());
+        assert_eq!(output.rows[0]["body"], json!("shor"));
+        assert_eq!(output.rows[1]["body"], json!("{\"a\""));
+        assert_eq!(output.truncated_reason, None);
+        assert_eq!(output.truncated_cells.len(), 2);
+        assert_eq!(output.truncated_cells[0].row, 0);
+        assert_eq!(output.truncated_cells[0].column, "body");
+        assert_eq!(output.truncated_cells[0].length, 5);
+        assert_eq!(output.truncated_cells[1].row, 1);
+    }
+
+    #[test]
+    fn collector_stops_at_row_limit() {
+        let mut collector = collector(2, 1024, 64);
+        assert!(collector.push(vec![json!(1), json!("a")]));
+        assert!(collector.push(vec![json!(2), json!("b")]));
+        assert!(!collector.push(vec![json!(3), json!("c")]));
+
+        let output = collector.finish(vec![], QueryStats::default());
+        assert_eq!(output.rows.len(), 2);
+        assert_eq!(output.truncated_reason, Some(TruncationReason::RowLimit));
+    }
+
+    #[test]
+    fn collector_stops_at_byte_limit_and_drops_cells_of_unread_rows() {
+        let mut collector = collector(10, 15, 8);
+        assert!(collector.push(vec![json!(1), json!("0123")]));
+        assert!(!collector.push(vec![json!(2), json!("a much longer value")]));
+
+        let output = collector.finish(vec![], QueryStats::default());
+        assert_eq!(output.rows.len(), 1);
+        assert_eq!(output.truncated_reason, Some(TruncationReason::ByteLimit));
+        assert!(output.truncated_cells.is_empty());
+    }
+
+    #[tokio::test]
+    async fn timer_phases_do_not_overlap() {
+        let mut timer = QueryTimer::new(Instant::now());
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        timer.acquired();
+
+        timer.next(tokio::time::sleep(Duration::from_millis(20))).await;
+        timer.decode(|| std::thread::sleep(Duration::from_millis(10)));
+        timer.next(tokio::time::sleep(Duration::from_millis(15))).await;
+        timer.next(tokio::time::sleep(Duration::from_millis(15))).await;
+
+        let stats = timer.finish();
+        assert!(stats.pool_wait_ms >= 5.0);
+        assert!(stats.execution_ms >= 20.0);
+        assert!(stats.first_row_ms >= stats.pool_wait_ms + stats.execution_ms);
+        assert!(stats.fetch_ms >= 30.0);
+        assert!(stats.decode_ms >= 10.0);
+        let phases = stats.pool_wait_ms + stats.execution_ms + stats.fetch_ms + stats.decode_ms;
+        assert!(phases <= stats.total_ms);
+        assert_eq!(stats.backend_id, None);
+    }
+}
@@ -0,0 +1,676 @@
+use sqlx::mysql::MySqlPool;
+use sqlx::postgres::PgPool;
+use sqlx::Row;
+
+use super::connection::{ConnectionError, DatabasePool};
+use super::objects::{self, pg_text, PgObject};
+use super::types::*;
+
+/// Runnable DDL for an object, one statement per paragraph. Postgres DDL is
+/// assembled from the catalogs; MySQL's comes from `SHOW CREATE`.
+pub async fn generate(pool: &DatabasePool, params: &GenerateDdlParams) -> Result<String, ConnectionError> {
+    match pool {
+        DatabasePool::Postgres(pg) => postgres(pg, &params.object, &params.options).await,
+        DatabasePool::MySql(my) => {
+            let options = &params.options;
+            if options.include_grants {
+                return Err(ConnectionError::InvalidInput("Grants are only generated for Postgres".to_string()));
+            }
+            if !options.include_comments {
+                return Err(ConnectionError::InvalidInput(
+                    "MySQL's SHOW CREATE output always includes comments".to_string(),
+                ));
+            }
+            let statement = |definition: String| {
+                terminated(&if options.include_owner { definition } else { strip_definer(&definition) })
+            };
+
+            let mut statements = vec![statement(objects::definition(pool, &params.object).await?)];
+            if options.include_dependents && params.object.kind == ObjectKind::Table {
+                for trigger in mysql_triggers(my, &params.object).await? {
+                    let trigger = GetObjectDefinitionParams {
+                        kind: ObjectKind::Trigger,
+                        name: trigger,
+                        arguments: None,
+                        table: Some(params.object.name.clone()),
+                        ..params.object.clone()
+                    };
+                    statements.push(statement(objects::definition(pool, &trigger).await?));
+                }
+            }
+            Ok(statements.join("\n\n"))
+        }
+    }
+}
+
+/// Postgres DDL for an object, also used by `get_object_definition` for tables
+pub async fn postgres(
+    pool: &PgPool,
+    object: &GetObjectDefinitionParams,
+    options: &DdlOptions,
+) -> Result<String, ConnectionError> {
+    let target = resolve(pool, object).await?;
+    let dependents = options.include_dependents;
+
+    let mut statements = Vec::new();
+    if dependents && object.kind == ObjectKind::Table {
+        for prerequisite in prerequisites(pool, object, &target).await? {
+            statements.extend(object_statements(pool, &prerequisite, options).await?);
+        }
+    }
+
+    statements.extend(match object.kind {
+        ObjectKind::Table => table_statements(pool, &target, options).await?,
+        _ => object_statements(pool, &target, options).await?,
+    });
+
+    if dependents && matches!(object.kind, ObjectKind::Table | ObjectKind::View | ObjectKind::MaterializedView) {
+        // Each trigger's function comes first, unless it's built in or belongs
+        // to an extension, and only once when triggers share it
+        let query = format!(
+            r#"
+            SELECT
+                t.tgname AS name,
+                CASE WHEN pn.nspname NOT IN ('pg_catalog', 'information_schema') AND {}
+                    THEN p.oid::int8 END AS function_oid,
+                pn.nspname AS function_schema,
+                p.proname AS function_name,
+                pg_get_function_identity_arguments(p.oid) AS function_arguments
+            FROM pg_trigger t
+            JOIN pg_proc p ON p.oid = t.tgfoid
+            JOIN pg_namespace pn ON pn.oid = p.pronamespace
+            WHERE t.tgrelid = $1::int8::oid AND NOT t.tgisinternal AND t.tgparentid = 0
+            ORDER BY t.tgname
+            "#,
+            objects::pg_user_defined("pg_proc", "p.oid")
+        );
+        let rows = sqlx::query(&query).bind(target.object.oid).fetch_all(pool).await?;
+        let mut functions = Vec::new();
+        for row in rows {
+            if let Some(oid) = row.try_get::<Option<i64>, _>("function_oid")? {
+                if !functions.contains(&oid) {
+                    functions.push(oid);
+                    let function = GetObjectDefinitionParams {
+                        kind: ObjectKind::Function,
+                        schema: Some(row.try_get("function_schema")?),
+                        name: row.try_get("function_name")?,
+                        arguments: Some(row.try_get("function_arguments")?),
+                        table: None,
+                        ..object.clone()
+                    };
+                    let function = resolve(pool, &function).await?;
+                    statements.extend(object_statements(pool, &function, options).await?);
+                }
+            }
+
+            let trigger = GetObjectDefinitionParams {
+                kind: ObjectKind::Trigger,
+                schema: Some(target.object.schema.clone()),
+                name: row.try_get("name")?,
+                arguments: None,
+                table: Some(target.object.name.clone()),
+                ..object.clone()
+            };
+            let trigger = resolve(pool, &trigger).await?;
+            statements.extend(object_statements(pool, &trigger, options).await?);
+        }
+    }
+
+    if dependents && object.kind == ObjectKind::Table {
+        // Every level below the table, parents before their own partitions
+        let rows = sqlx::query(
+            r#"
+            SELECT n.nspname AS schema, c.relname AS name
+            FROM pg_partition_tree($1::int8::oid::regclass) pt
+            JOIN pg_class c ON c.oid = pt.relid
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE pt.level > 0
+            ORDER BY pt.level, n.nspname, c.relname
+            "#,
+        )
+        .bind(target.object.oid)
+        .fetch_all(pool)
+        .await?;
+        for row in rows {
+            let partition = GetObjectDefinitionParams {
+                schema: Some(row.try_get("schema")?),
+                name: row.try_get("name")?,
+                ..object.clone()
+            };
+            let partition = resolve(pool, &partition).await?;
+            statements.extend(table_statements(pool, &partition, options).await?);
+        }
+    }
+
+    Ok(statements.join("\n\n"))
+}
+
+/// A resolved Postgres object with what its trailing statements need
+struct DdlObject {
+    object: PgObject,
+    owner: Option<String>,
+    comment: Option<String>,
+    /// `GRANT` statements for every role other than the owner
+    grants: Vec<String>,
+}
+
+/// `GRANT` statements for an ACL column, one per grantee, on the object named
+/// by `$2`. Routines and types are usable by PUBLIC unless revoked, so for
+/// those a missing PUBLIC entry in a set ACL becomes a `REVOKE`.
+fn pg_grants(acl: &str, owner: &str, keyword: &str, public_default: bool) -> String {
+    let revoke = if public_default {
+        format!(
+            "CASE WHEN {acl} IS NOT NULL AND NOT EXISTS (SELECT 1 FROM aclexplode({acl}) a WHERE a.grantee = 0)
+                THEN ARRAY[format('REVOKE ALL ON %s %s FROM PUBLIC;', {keyword}, $2::text)]
+                ELSE ARRAY[]::text[] END || "
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        "{revoke}ARRAY(
+            SELECT format('GRANT %s ON %s %s TO %s%s;',
+                string_agg(a.privilege_type, ', ' ORDER BY a.privilege_type), {keyword}, $2::text,
+                CASE WHEN a.grantee = 0 THEN 'PUBLIC' ELSE quote_ident(pg_get_userbyid(a.grantee)) END,
+                CASE WHEN a.is_grantable THEN ' WITH GRANT OPTION' ELSE '' END)
+            FROM aclexplode({acl}) a
+            WHERE a.grantee <> {owner}
+            GROUP BY a.grantee, a.is_grantable
+            ORDER BY a.grantee, a.is_grantable
+        )"
+    )
+}
+
+/// Find the object the same way `get_object_definition` does, with its
+/// owner, comment and grants
+async fn resolve(pool: &PgPool, params: &GetObjectDefinitionParams) -> Result<DdlObject, ConnectionError> {
+    let object = objects::resolve_postgres(pool, params).await?;
+
+    // Every query takes the object's OID ($1) and, for its grants, its
+    // qualified name ($2)
+    let query = match object.params.kind {
+        ObjectKind::Function | ObjectKind::Procedure => format!(
+            r#"
+            SELECT
+                quote_ident(pg_get_userbyid(p.proowner)) AS owner,
+                obj_description(p.oid, 'pg_proc') AS comment,
+                {} AS grants
+            FROM pg_proc p
+            WHERE p.oid = $1::int8::oid
+            "#,
+            pg_grants(
+                "p.proacl",
+                "p.proowner",
+                "CASE p.prokind WHEN 'p' THEN 'PROCEDURE' ELSE 'FUNCTION' END",
+                true,
+            )
+        ),
+        ObjectKind::Type => format!(
+            r#"
+            SELECT
+                quote_ident(pg_get_userbyid(t.typowner)) AS owner,
+                obj_description(t.oid, 'pg_type') AS comment,
+                {} AS grants
+            FROM pg_type t
+            WHERE t.oid = $1::int8::oid
+            "#,
+            pg_grants("t.typacl", "t.typowner", "CASE t.typtype WHEN 'd' THEN 'DOMAIN' ELSE 'TYPE' END", true)
+        ),
+        // Triggers belong to their table and have neither owner nor privileges
+        ObjectKind::Trigger => r#"
+            SELECT NULL::text AS owner, obj_description(t.oid, 'pg_trigger') AS comment, ARRAY[]::text[] AS grants
+            FROM pg_trigger t
+            WHERE t.oid = $1::int8::oid
+            "#
+        .to_string(),
+        _ => format!(
+            r#"
+            SELECT
+                quote_ident(pg_get_userbyid(c.relowner)) AS owner,
+                obj_description(c.oid, 'pg_class') AS comment,
+                {} AS grants
+            FROM pg_class c
+            WHERE c.oid = $1::int8::oid
+            "#,
+            pg_grants(
+                "c.relacl",
+                "c.relowner",
+                "CASE c.relkind WHEN 'S' THEN 'SEQUENCE' ELSE 'TABLE' END",
+                false,
+            )
+        ),
+    };
+
+    let mut query = sqlx::query(&query).bind(object.oid);
+    if object.params.kind != ObjectKind::Trigger {
+        query = query.bind(&object.ident);
+    }
+    let row = query
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| ConnectionError::NotFound(object.ident.clone()))?;
+    Ok(DdlObject {
+        owner: row.try_get("owner")?,
+        comment: row.try_get("comment")?,
+        grants: row.try_get("grants")?,
+        object,
+    })
+}
+
+/// A string literal for Postgres with standard_conforming_strings on
+pub(crate) fn pg_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Drop the `DEFINER=user@host` clause MySQL puts in the header of views,
+/// routines, triggers and events, so the object is created by whoever runs it
+pub(crate) fn strip_definer(statement: &str) -> String {
+    let header = &statement[..statement.find('\n').unwrap_or(statement.len())];
+    let Some(start) = header.find(" DEFINER=") else {
+        return statement.to_string();
+    };
+
+    // The account is `user`@`host`, with backticks inside doubled, or CURRENT_USER
+    let rest = &statement[start + " DEFINER=".len()..];
+    let bytes = rest.as_bytes();
+    let mut end = 0;
+    let mut quoted = false;
+    while end < bytes.len() {
+        match bytes[end] {
+            b'`' if quoted && bytes.get(end + 1) == Some(&b'`') => end += 1,
+            b'`' => quoted = !quoted,
+            b' ' | b'\t' | b'\n' if !quoted => break,
+            _ => {}
+        }
+        end += 1;
+    }
+    format!("{}{}", &statement[..start], &rest[end..])
+}
+
+/// End a statement with a semicolon unless it already has one
+pub(crate) fn terminated(statement: &str) -> String {
+    let statement = statement.trim_end();
+    if statement.ends_with(';') {
+        statement.to_string()
+    } else {
+        format!("{};", statement)
+    }
+}
+
+/// The object's `CREATE` statement followed by its comment, owner and grants
+async fn object_statements(
+    pool: &PgPool,
+    target: &DdlObject,
+    options: &DdlOptions,
+) -> Result<Vec<String>, ConnectionError> {
+    let (mut statements, column_comments) = match target.object.params.kind {
+        ObjectKind::Table => table_body(pool, target).await?,
+        _ => (vec![terminated(&objects::postgres_definition(pool, &target.object).await?)], Vec::new()),
+    };
+    if target.object.params.kind == ObjectKind::MaterializedView {
+        statements.extend(indexes(pool, target).await?);
+    }
+
+    if options.include_comments {
+        if let Some(comment) = &target.comment {
+            statements.push(format!(
+                "COMMENT ON {} {} IS {};",
+                target.object.keyword,
+                target.object.ident,
+                pg_literal(comment)
+            ));
+        }
+        statements.extend(column_comments);
+    }
+    if options.include_owner {
+        if let Some(owner) = &target.owner {
+            statements.push(format!("ALTER {} {} OWNER TO {};", target.object.keyword, target.object.ident, owner));
+        }
+    }
+    if options.include_grants {
+        statements.extend(target.grants.iter().cloned());
+    }
+    Ok(statements)
+}
+
+/// A table with the serial sequences it owns, which have to exist before the
+/// column defaults that use them
+async fn table_statements(
+    pool: &PgPool,
+    target: &DdlObject,
+    options: &DdlOptions,
+) -> Result<Vec<String>, ConnectionError> {
+    // Identity sequences (deptype 'i') are recreated by the column itself
+    let owned = sqlx::query(
+        r#"
+        SELECT
+            n.nspname AS schema,
+            s.relname AS name,
+            format('ALTER SEQUENCE %I.%I OWNED BY %s.%I;', n.nspname, s.relname, $2::text, a.attname) AS owned_by
+        FROM pg_depend d
+        JOIN pg_class s ON s.oid = d.objid AND s.relkind = 'S'
+        JOIN pg_namespace n ON n.oid = s.relnamespace
+        JOIN pg_attribute a ON a.attrelid = d.refobjid AND a.attnum = d.refobjsubid
+        WHERE d.classid = 'pg_class'::regclass
+          AND d.refclassid = 'pg_class'::regclass
+          AND d.refobjid = $1::int8::oid
+          AND d.deptype = 'a'
+        ORDER BY s.relname
+        "#,
+    )
+    .bind(target.object.oid)
+    .bind(&target.object.ident)
+    .fetch_all(pool)
+    .await?;
+
+    let mut statements = Vec::new();
+    for row in &owned {
+        let sequence = GetObjectDefinitionParams {
+            kind: ObjectKind::Sequence,
+            schema: Some(row.try_get("schema")?),
+            name: row.try_get("name")?,
+            arguments: None,
+            table: None,
+            ..target.object.params.clone()
+        };
+        let sequence = resolve(pool, &sequence).await?;
+        statements.extend(object_statements(pool, &sequence, options).await?);
+    }
+    statements.extend(object_statements(pool, target, options).await?);
+    for row in &owned {
+        statements.push(row.try_get("owned_by")?);
+    }
+    Ok(statements)
+}
+
+/// `CREATE TABLE` with its foreign keys and indexes, and separately the
+/// comments on its columns
+async fn table_body(pool: &PgPool, target: &DdlObject) -> Result<(Vec<String>, Vec<String>), ConnectionError> {
+    let table = sqlx::query(
+        r#"
+        SELECT
+            c.relpersistence = 'u' AS unlogged,
+            (
+                SELECT format('%I.%I', pn.nspname, pc.relname)
+                FROM pg_inherits i
+                JOIN pg_class pc ON pc.oid = i.inhparent
+                JOIN pg_namespace pn ON pn.oid = pc.relnamespace
+                WHERE c.relispartition AND i.inhrelid = c.oid
+            ) AS parent,
+            pg_get_expr(c.relpartbound, c.oid) AS partition_bound,
+            CASE WHEN c.relkind = 'p' THEN pg_get_partkeydef(c.oid) END AS partition_key,
+            array_to_string(c.reloptions, ', ') AS options
+        FROM pg_class c
+        WHERE c.oid = $1::int8::oid
+        "#,
+    )
+    .bind(target.object.oid)
+    .fetch_one(pool)
+    .await?;
+    let parent: Option<String> = table.try_get("parent")?;
+
+    let columns = sqlx::query(
+        r#"
+        SELECT
+            format('%I', a.attname) AS name,
+            format_type(a.atttypid, a.atttypmod) AS data_type,
+            CASE WHEN a.attcollation <> t.typcollation
+                THEN format('%I.%I', cn.nspname, co.collname) END AS collation,
+            a.attnotnull AS not_null,
+            a.attidentity::text AS identity,
+            a.attgenerated::text AS generated,
+            pg_get_expr(d.adbin, d.adrelid) AS default_value,
+            col_description(a.attrelid, a.attnum) AS comment
+        FROM pg_attribute a
+        JOIN pg_type t ON t.oid = a.atttypid
+        LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+        LEFT JOIN pg_collation co ON co.oid = a.attcollation
+        LEFT JOIN pg_namespace cn ON cn.oid = co.collnamespace
+        WHERE a.attrelid = $1::int8::oid AND a.attnum > 0 AND NOT a.attisdropped
+        ORDER BY a.attnum
+        "#,
+    )
+    .bind(target.object.oid)
+    .fetch_all(pool)
+    .await?;
+
+    // Constraints a partition inherits come with `PARTITION OF`
+    let constraints: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT format('CONSTRAINT %I %s', con.conname, pg_get_constraintdef(con.oid))
+        FROM pg_constraint con
+        WHERE con.conrelid = $1::int8::oid
+          AND con.contype IN ('p', 'u', 'x', 'c')
+          AND con.conislocal
+        ORDER BY array_position(ARRAY['p', 'u', 'x', 'c'], con.contype::text), con.conname
+        "#,
+    )
+    .bind(target.object.oid)
+    .fetch_all(pool)
+    .await?;
+
+    let mut elements = Vec::new();
+    if parent.is_none() {
+        for column in &columns {
+            let mut element = format!("{} {}", pg_text(column, "name"), pg_text(column, "data_type"));
+            if let Some(collation) = column.try_get::<Option<String>, _>("collation")? {
+                element.push_str(&format!(" COLLATE {}", collation));
+            }
+            let default_value: Option<String> = column.try_get("default_value")?;
+            match (pg_text(column, "identity").as_str(), pg_text(column, "generated").as_str()) {
+                ("a", _) => element.push_str(" GENERATED ALWAYS AS IDENTITY"),
+                ("d", _) => element.push_str(" GENERATED BY DEFAULT AS IDENTITY"),
+                (_, "s") => {
+                    element.push_str(&format!(" GENERATED ALWAYS AS ({}) STORED", default_value.unwrap_or_default()))
+                }
+                (_, "v") => element.push_str(&format!(" GENERATED ALWAYS AS ({})", default_value.unwrap_or_default())),
+                _ => {
+                    if let Some(default_value) = default_value {
+                        element.push_str(&format!(" DEFAULT {}", default_value));
+                    }
+                }
+            }
+            if column.try_get::<bool, _>("not_null")? {
+                element.push_str(" NOT NULL");
+            }
+            elements.push(element);
+        }
+    }
+    elements.extend(constraints);
+
+    let keyword = if table.try_get::<bool, _>("unlogged")? { "UNLOGGED TABLE" } else { "TABLE" };
+    let mut create = format!("CREATE {} {}", keyword, target.object.ident);
+    if let Some(parent) = &parent {
+        create.push_str(&format!(" PARTITION OF {}", parent));
+    }
+    if !elements.is_empty() || parent.is_none() {
+        create.push_str(&format!(" (\n    {}\n)", elements.join(",\n    ")));
+    }
+    if let Some(bound) = table.try_get::<Option<String>, _>("partition_bound")? {
+        create.push_str(&format!("\n{}", bound));
+    }
+    if let Some(key) = table.try_get::<Option<String>, _>("partition_key")? {
+        create.push_str(&format!("\nPARTITION BY {}", key));
+    }
+    if let Some(reloptions) = table.try_get::<Option<String>, _>("options")? {
+        create.push_str(&format!("\nWITH ({})", reloptions));
+    }
+    create.push(';');
+
+    // Added afterwards so tables referencing each other can be created in any order
+    let foreign_keys: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT format('ALTER TABLE %s ADD CONSTRAINT %I %s;', $2::text, con.conname, pg_get_constraintdef(con.oid))
+        FROM pg_constraint con
+        WHERE con.conrelid = $1::int8::oid AND con.contype = 'f' AND con.conparentid = 0
+        ORDER BY con.conname
+        "#,
+    )
+    .bind(target.object.oid)
+    .bind(&target.object.ident)
+    .fetch_all(pool)
+    .await?;
+
+    let mut statements = vec![create];
+    statements.extend(foreign_keys);
+    statements.extend(indexes(pool, target).await?);
+
+    let mut comments = Vec::new();
+    for column in &columns {
+        if let Some(comment) = column.try_get::<Option<String>, _>("comment")? {
+            comments.push(format!(
+                "COMMENT ON COLUMN {}.{} IS {};",
+                target.object.ident,
+                pg_text(column, "name"),
+                pg_literal(&comment)
+            ));
+        }
+    }
+    Ok((statements, comments))
+}
+
+/// Indexes other than those backing constraints or attached to a parent's
+/// index. Those on a partitioned table are written without `ONLY`, so they
+/// also cover partitions attached later.
+async fn indexes(pool: &PgPool, target: &DdlObject) -> Result<Vec<String>, ConnectionError> {
+    let indexes = sqlx::query_scalar(
+        r#"
+        SELECT replace(pg_get_indexdef(i.indexrelid), ' ON ONLY ', ' ON ') || ';'
+        FROM pg_index i
+        JOIN pg_class ic ON ic.oid = i.indexrelid
+        WHERE i.indrelid = $1::int8::oid
+          AND NOT EXISTS (
+              SELECT 1 FROM pg_constraint con
+              WHERE con.conindid = i.indexrelid AND con.conrelid = i.indrelid AND con.contype IN ('p', 'u', 'x')
+          )
+          AND NOT EXISTS (SELECT 1 FROM pg_inherits inh WHERE inh.inhrelid = i.indexrelid)
+        ORDER BY ic.relname
+        "#,
+    )
+    .bind(target.object.oid)
+    .fetch_all(pool)
+    .await?;
+    Ok(indexes)
+}
+
+/// User-defined types of a table's columns and sequences its defaults use
+/// without owning them, resolved ready to be created first
+async fn prerequisites(
+    pool: &PgPool,
+    object: &GetObjectDefinitionParams,
+    target: &DdlObject,
+) -> Result<Vec<DdlObject>, ConnectionError> {
+    let query = format!(
+        r#"
+        SELECT 'type' AS kind, n.nspname AS schema, t.typname AS name
+        FROM pg_attribute a
+        JOIN pg_type at ON at.oid = a.atttypid
+        JOIN pg_type t ON t.oid = CASE WHEN at.typcategory = 'A' THEN at.typelem ELSE at.oid END
+        JOIN pg_namespace n ON n.oid = t.typnamespace
+        LEFT JOIN pg_class tc ON tc.oid = t.typrelid
+        WHERE a.attrelid = $1::int8::oid AND a.attnum > 0 AND NOT a.attisdropped
+          AND (t.typtype IN ('e', 'd', 'r') OR tc.relkind = 'c')
+          AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+          AND {}
+        UNION
+        SELECT 'sequence', n.nspname, s.relname
+        FROM pg_attrdef ad
+        JOIN pg_depend d ON d.classid = 'pg_attrdef'::regclass AND d.objid = ad.oid
+            AND d.refclassid = 'pg_class'::regclass
+        JOIN pg_class s ON s.oid = d.refobjid AND s.relkind = 'S'
+        JOIN pg_namespace n ON n.oid = s.relnamespace
+        WHERE ad.adrelid = $1::int8::oid
+          AND NOT EXISTS (
+              SELECT 1 FROM pg_depend o
+              WHERE o.classid = 'pg_class'::regclass AND o.objid = s.oid
+                AND o.refobjid = ad.adrelid AND o.deptype IN ('a', 'i')
+          )
+        ORDER BY 1 DESC, 2, 3
+        "#,
+        objects::pg_user_defined("pg_type", "t.oid"),
+    );
+    let rows = sqlx::query(&query).bind(target.object.oid).fetch_all(pool).await?;
+
+    let mut prerequisites = Vec::new();
+    for row in rows {
+        let kind = match pg_text(&row, "kind").as_str() {
+            "type" => ObjectKind::Type,
+            _ => ObjectKind::Sequence,
+        };
+        let params = GetObjectDefinitionParams {
+            kind,
+            schema: Some(row.try_get("schema")?),
+            name: row.try_get("name")?,
+            arguments: None,
+            table: None,
+            ..object.clone()
+        };
+        prerequisites.push(resolve(pool, &params).await?);
+    }
+    Ok(prerequisites)
+}
+
+/// Triggers on a MySQL table in firing order
+async fn mysql_triggers(pool: &MySqlPool, object: &GetObjectDefinitionParams) -> Result<Vec<String>, ConnectionError> {
+    let names = sqlx::query_scalar(
+        r#"
+        SELECT TRIGGER_NAME
+        FROM information_schema.TRIGGERS
+        WHERE EVENT_OBJECT_SCHEMA = COALESCE(?, DATABASE()) AND EVENT_OBJECT_TABLE = ?
+        ORDER BY ACTION_TIMING, EVENT_MANIPULATION, ACTION_ORDER
+        "#,
+    )
+    .bind(object.schema.as_deref())
+    .bind(&object.name)
+    .fetch_all(pool)
+    .await?;
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terminated_adds_a_single_semicolon() {
+        assert_eq!(terminated("CREATE VIEW v AS SELECT 1"), "CREATE VIEW v AS SELECT 1;");
+        assert_eq!(terminated("DROP TABLE t;\n"), "DROP TABLE t;");
+    }
+
+    #[test]
+    fn pg_literal_doubles_quotes() {
+        assert_eq!(pg_literal("it's"), "'it''s'");
+        assert_eq!(pg_literal(r"C:\temp"), r"'C:\temp'");
+    }
+
+    #[test]
+    fn pg_grants_revokes_the_public_default_only_where_there_is_one() {
+        let routine = pg_grants("p.proacl", "p.proowner", "'FUNCTION'", true);
+        assert!(routine.contains("REVOKE ALL ON %s %s FROM PUBLIC;"));
+        assert!(routine.contains("aclexplode(p.proacl)"));
+        assert!(routine.contains("WHERE a.grantee <> p.proowner"));
+
+        let table = pg_grants("c.relacl", "c.relowner", "'TABLE'", false);
+        assert!(!table.contains("REVOKE"));
+        assert!(table.starts_with("ARRAY("));
+    }
+
+    #[test]
+    fn strip_definer_removes_the_account() {
+        assert_eq!(
+            strip_definer("CREATE ALGORITHM=UNDEFINED DEFINER=`root`@`%` SQL SECURITY DEFINER VIEW `v` AS select 1"),
+            "CREATE ALGORITHM=UNDEFINED SQL SECURITY DEFINER VIEW `v` AS select 1"
+        );
+        assert_eq!(
+            strip_definer("CREATE DEFINER=`o``brien`@`local host` PROCEDURE `p`()\nBEGIN\nEND"),
+            "CREATE PROCEDURE `p`()\nBEGIN\nEND"
+        );
+        assert_eq!(strip_definer("CREATE DEFINER=CURRENT_USER TRIGGER `t` BEFORE INSERT"), "CREATE TRIGGER `t` BEFORE INSERT");
+        assert_eq!(strip_definer("CREATE TABLE `t` (\n  `a` int\n)"), "CREATE TABLE `t` (\n  `a` int\n)");
+        // Only the header counts, not text in the body
+        assert_eq!(
+            strip_definer("CREATE TABLE `t` (\n  `a` int COMMENT ' DEFINER=x'\n)"),
+            "CREATE TABLE `t` (\n  `a` int COMMENT ' DEFINER=x'\n)"
+        );
+    }
+}
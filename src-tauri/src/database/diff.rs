@@ -0,0 +1,1287 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::connection::{ConnectionError, DatabasePool};
+use super::ddl::{pg_literal, strip_definer, terminated};
+use super::introspection;
+use super::objects;
+use super::types::*;
+use sqlx::Row;
+
+/// Stands in for the schema qualifier while definitions from both sides are
+/// compared, so the same object in differently named schemas matches
+const SCHEMA_PLACEHOLDER: &str = "\u{1}.";
+
+/// Where a statement goes in the migration. Replaced objects are dropped
+/// before they're created again, routines exist before the defaults, checks
+/// and generated columns that call them, tables exist before the keys and
+/// views that reference them, and views are dropped before the columns they
+/// read. Routines and types that go away entirely are dropped last, once no
+/// column, default or table uses them any more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Phase {
+    DropViews,
+    DropReplacedRoutines,
+    DropForeignKeys,
+    DropIndexes,
+    DropTables,
+    CreateTypes,
+    CreateRoutines,
+    CreateTables,
+    AlterColumns,
+    AddIndexes,
+    AddForeignKeys,
+    CreateViews,
+    DropRoutines,
+    DropTypes,
+}
+
+impl Phase {
+    fn creates(self) -> bool {
+        matches!(
+            self,
+            Phase::CreateTypes
+                | Phase::CreateRoutines
+                | Phase::CreateTables
+                | Phase::AddIndexes
+                | Phase::AddForeignKeys
+                | Phase::CreateViews
+        )
+    }
+}
+
+struct Table {
+    columns: Vec<TableColumn>,
+    structure: TableStructure,
+    /// Postgres `CREATE INDEX` statements by index name, which keep the sort
+    /// order and operator classes that `TableIndex` leaves out
+    index_definitions: HashMap<String, String>,
+}
+
+/// A view, routine or type with its definition, schema qualifiers replaced
+/// by `SCHEMA_PLACEHOLDER`
+struct Defined {
+    object: DatabaseObject,
+    definition: String,
+}
+
+struct Snapshot {
+    schema: String,
+    tables: BTreeMap<String, Table>,
+    views: BTreeMap<String, Defined>,
+    routines: BTreeMap<String, Defined>,
+    types: BTreeMap<String, Defined>,
+}
+
+/// Compare two schemas of the same database type. The changes and the
+/// migration describe what turns `target` into `source`.
+pub async fn compare(
+    source_pool: &DatabasePool,
+    source: &SchemaRef,
+    target_pool: &DatabasePool,
+    target: &SchemaRef,
+    scope: &DiffScope,
+) -> Result<SchemaDiff, ConnectionError> {
+    let postgres = matches!(target_pool, DatabasePool::Postgres(_));
+    if postgres != matches!(source_pool, DatabasePool::Postgres(_)) {
+        return Err(ConnectionError::InvalidInput(
+            "Schemas can only be compared between connections of the same database type".to_string(),
+        ));
+    }
+
+    let (source, target) = tokio::try_join!(
+        snapshot(source_pool, source, scope),
+        snapshot(target_pool, target, scope)
+    )?;
+
+    let mut differ = Differ {
+        dialect: if postgres { DatabaseType::Postgres } else { DatabaseType::Mysql },
+        source_schema: source.schema.clone(),
+        target_schema: target.schema.clone(),
+        changes: Vec::new(),
+        statements: Vec::new(),
+    };
+    differ.types(&source, &target)?;
+    differ.tables(&source, &target);
+    differ.routines(&source, &target);
+    differ.views(&source, &target);
+    Ok(differ.finish())
+}
+
+async fn snapshot(pool: &DatabasePool, side: &SchemaRef, scope: &DiffScope) -> Result<Snapshot, ConnectionError> {
+    let schema = match (&side.schema, pool) {
+        (Some(schema), _) => Some(schema.clone()),
+        (None, DatabasePool::Postgres(pool)) => sqlx::query_scalar("SELECT current_schema()").fetch_one(pool).await?,
+        (None, DatabasePool::MySql(_)) if side.database.is_some() => side.database.clone(),
+        (None, DatabasePool::MySql(pool)) => sqlx::query_scalar("SELECT DATABASE()").fetch_one(pool).await?,
+    };
+    let schema = schema.ok_or_else(|| ConnectionError::InvalidInput("No schema selected; specify one".to_string()))?;
+
+    let wanted = |kind: ObjectKind| scope.kinds.is_empty() || scope.kinds.contains(&kind);
+    let params = ListObjectsParams {
+        connection_id: side.connection_id.clone(),
+        kinds: vec![
+            ObjectKind::Table,
+            ObjectKind::View,
+            ObjectKind::MaterializedView,
+            ObjectKind::Function,
+            ObjectKind::Procedure,
+            ObjectKind::Type,
+        ]
+        .into_iter()
+        .filter(|kind| wanted(*kind))
+        .collect(),
+        database: side.database.clone(),
+        schema: Some(schema.clone()),
+    };
+    // An empty kind list means every kind to list_objects
+    let objects = if params.kinds.is_empty() {
+        Vec::new()
+    } else {
+        objects::list(pool, &params).await?
+    };
+
+    let mut snapshot = Snapshot {
+        schema: schema.clone(),
+        tables: BTreeMap::new(),
+        views: BTreeMap::new(),
+        routines: BTreeMap::new(),
+        types: BTreeMap::new(),
+    };
+    let mut index_definitions: HashMap<(String, String), String> = HashMap::new();
+    if let DatabasePool::Postgres(pool) = pool {
+        let rows = sqlx::query(
+            r#"
+            SELECT c.relname AS table_name, ic.relname AS name, pg_get_indexdef(i.indexrelid) AS definition
+            FROM pg_index i
+            JOIN pg_class ic ON ic.oid = i.indexrelid
+            JOIN pg_class c ON c.oid = i.indrelid
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE n.nspname = $1
+            "#,
+        )
+        .bind(&schema)
+        .fetch_all(pool)
+        .await?;
+        for row in rows {
+            let definition: String = row.try_get("definition")?;
+            index_definitions.insert(
+                (row.try_get("table_name")?, row.try_get("name")?),
+                placeholder(&definition.replace(" ON ONLY ", " ON "), &schema),
+            );
+        }
+    }
+
+    // Columns and structures of the whole schema at once rather than table by table
+    let (mut columns, mut structures) = if objects.iter().any(|o| o.kind == ObjectKind::Table) {
+        tokio::try_join!(
+            introspection::schema_columns(pool, &schema),
+            introspection::schema_structures(pool, &schema)
+        )?
+    } else {
+        Default::default()
+    };
+
+    for object in objects {
+        if !scope.names.is_empty() && !scope.names.contains(&object.name) {
+            continue;
+        }
+        if object.kind == ObjectKind::Table {
+            let (Some(columns), Some(structure)) = (columns.remove(&object.name), structures.remove(&object.name)) else {
+                // Dropped since it was listed
+                continue;
+            };
+            let index_definitions = structure
+                .indexes
+                .iter()
+                .filter_map(|index| {
+                    let key = (object.name.clone(), index.name.clone());
+                    index_definitions.remove(&key).map(|definition| (index.name.clone(), definition))
+                })
+                .collect();
+            snapshot.tables.insert(
+                object.name.clone(),
+                Table {
+                    columns,
+                    structure,
+                    index_definitions,
+                },
+            );
+            continue;
+        }
+        // Aggregates and window functions have no definition to compare
+        if object.detail.is_some() && matches!(object.kind, ObjectKind::Function | ObjectKind::Procedure) {
+            continue;
+        }
+
+        let definition = objects::definition(
+            pool,
+            &GetObjectDefinitionParams {
+                connection_id: side.connection_id.clone(),
+                kind: object.kind,
+                database: side.database.clone(),
+                schema: Some(schema.clone()),
+                name: object.name.clone(),
+                arguments: object.arguments.clone(),
+                table: None,
+            },
+        )
+        .await?;
+        let definition = placeholder(&strip_definer(&definition), &schema);
+        let defined = Defined { object, definition };
+        match defined.object.kind {
+            ObjectKind::View | ObjectKind::MaterializedView => {
+                snapshot.views.insert(defined.object.name.clone(), defined);
+            }
+            ObjectKind::Function | ObjectKind::Procedure => {
+                let key = format!("{}({})", defined.object.name, defined.object.arguments.as_deref().unwrap_or(""));
+                snapshot.routines.insert(key, defined);
+            }
+            _ => {
+                snapshot.types.insert(defined.object.name.clone(), defined);
+            }
+        }
+    }
+    Ok(snapshot)
+}
+
+/// Replace qualifiers naming `schema`, quoted or not, with `SCHEMA_PLACEHOLDER`
+fn placeholder(text: &str, schema: &str) -> String {
+    let mut text = text.to_string();
+    for quoted in [
+        format!("\"{}\".", schema.replace('"', "\"\"")),
+        format!("`{}`.", schema.replace('`', "``")),
+        format!("{}.", schema),
+    ] {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text.as_str();
+        while let Some(i) = rest.find(&quoted) {
+            out.push_str(&rest[..i]);
+            // Only whole qualifiers, not the end of a longer name
+            let boundary = !out
+                .chars()
+                .last()
+                .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '"' | '`' | '$'));
+            out.push_str(if boundary { SCHEMA_PLACEHOLDER } else { &quoted });
+            rest = &rest[i + quoted.len()..];
+        }
+        out.push_str(rest);
+        text = out;
+    }
+    text
+}
+
+struct Differ {
+    dialect: DatabaseType,
+    source_schema: String,
+    target_schema: String,
+    changes: Vec<SchemaChange>,
+    statements: Vec<(Phase, bool, String)>,
+}
+
+impl Differ {
+    fn postgres(&self) -> bool {
+        self.dialect == DatabaseType::Postgres
+    }
+
+    fn quote(&self, ident: &str) -> String {
+        self.dialect.quote_ident(ident)
+    }
+
+    /// `name` in the target schema
+    fn qualified(&self, name: &str) -> String {
+        format!("{}.{}", self.quote(&self.target_schema), self.quote(name))
+    }
+
+    /// Put the target schema in place of `SCHEMA_PLACEHOLDER`
+    fn render(&self, text: &str) -> String {
+        text.replace(SCHEMA_PLACEHOLDER, &format!("{}.", self.quote(&self.target_schema)))
+    }
+
+    fn record(&mut self, change: SchemaChange, statements: Vec<(Phase, String)>) {
+        let destructive = change.destructive;
+        self.statements
+            .extend(statements.into_iter().map(|(phase, sql)| (phase, destructive, sql)));
+        self.changes.push(change);
+    }
+
+    fn finish(mut self) -> SchemaDiff {
+        // Stable, so statements keep their order within a phase
+        self.statements.sort_by_key(|(phase, _, _)| *phase);
+        let mut statements: Vec<String> = self
+            .statements
+            .iter()
+            .map(|(phase, destructive, sql)| {
+                // Creating never destroys, even as part of a destructive change
+                if *destructive && !phase.creates() {
+                    format!("-- Destructive\n{}", sql)
+                } else {
+                    sql.clone()
+                }
+            })
+            .collect();
+        // MySQL definitions name views and routines without their database
+        if !self.postgres() && !statements.is_empty() {
+            statements.insert(0, format!("USE {};", self.quote(&self.target_schema)));
+        }
+
+        SchemaDiff {
+            destructive: self.changes.iter().any(|c| c.destructive),
+            source_schema: self.source_schema,
+            target_schema: self.target_schema,
+            changes: self.changes,
+            migration: statements.join("\n\n"),
+        }
+    }
+
+    fn types(&mut self, source: &Snapshot, target: &Snapshot) -> Result<(), ConnectionError> {
+        for (name, defined) in &target.types {
+            if !source.types.contains_key(name) {
+                let sql = self.drop_type(defined);
+                self.record(change(ObjectKind::Type, name, DiffAction::Drop, true), vec![(Phase::DropTypes, sql)]);
+            }
+        }
+        for (name, defined) in &source.types {
+            let create = (Phase::CreateTypes, self.render(&defined.definition));
+            match target.types.get(name) {
+                None => self.record(change(ObjectKind::Type, name, DiffAction::Create, false), vec![create]),
+                Some(existing) if existing.definition != defined.definition => {
+                    if let Some(added) = added_enum_values(existing, defined) {
+                        let details = added.iter().map(|(label, _)| format!("add value {}", label)).collect();
+                        let statements = added
+                            .iter()
+                            .map(|(label, position)| {
+                                let sql =
+                                    format!("ALTER TYPE {} ADD VALUE {} {};", self.qualified(name), label, position);
+                                (Phase::CreateTypes, sql)
+                            })
+                            .collect();
+                        self.record(
+                            SchemaChange {
+                                details,
+                                ..change(ObjectKind::Type, name, DiffAction::Alter, false)
+                            },
+                            statements,
+                        );
+                        continue;
+                    }
+                    // Anything else needs the type recreated, which fails while columns use it
+                    return Err(ConnectionError::InvalidInput(format!(
+                        "Type {} differs in a way that can't be migrated in place; only adding enum values is supported",
+                        name
+                    )));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn drop_type(&self, defined: &Defined) -> String {
+        let keyword = match defined.object.detail.as_deref() {
+            Some(detail) if detail.starts_with("domain") => "DOMAIN",
+            _ => "TYPE",
+        };
+        format!("DROP {} {};", keyword, self.qualified(&defined.object.name))
+    }
+
+    fn routines(&mut self, source: &Snapshot, target: &Snapshot) {
+        for (key, defined) in &target.routines {
+            if !source.routines.contains_key(key) {
+                let sql = self.drop_routine(defined);
+                self.record(
+                    change(defined.object.kind, key, DiffAction::Drop, true),
+                    vec![(Phase::DropRoutines, sql)],
+                );
+            }
+        }
+        for (key, defined) in &source.routines {
+            let create = (Phase::CreateRoutines, terminated(&self.render(&defined.definition)));
+            match target.routines.get(key) {
+                None => self.record(change(defined.object.kind, key, DiffAction::Create, false), vec![create]),
+                Some(existing) if existing.definition != defined.definition => {
+                    // Postgres replaces in place; MySQL has no CREATE OR REPLACE for routines
+                    let statements = if self.postgres() {
+                        vec![create]
+                    } else {
+                        vec![(Phase::DropReplacedRoutines, self.drop_routine(existing)), create]
+                    };
+                    self.record(change(defined.object.kind, key, DiffAction::Alter, false), statements);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn drop_routine(&self, defined: &Defined) -> String {
+        let keyword = match defined.object.kind {
+            ObjectKind::Procedure => "PROCEDURE",
+            _ => "FUNCTION",
+        };
+        if self.postgres() {
+            format!(
+                "DROP {} {}({});",
+                keyword,
+                self.qualified(&defined.object.name),
+                defined.object.arguments.as_deref().unwrap_or("")
+            )
+        } else {
+            format!("DROP {} IF EXISTS {};", keyword, self.qualified(&defined.object.name))
+        }
+    }
+
+    fn views(&mut self, source: &Snapshot, target: &Snapshot) {
+        for (name, defined) in &target.views {
+            if !source.views.contains_key(name) {
+                let sql = self.drop_view(defined);
+                self.record(change(defined.object.kind, name, DiffAction::Drop, true), vec![(Phase::DropViews, sql)]);
+            }
+        }
+        for (name, defined) in &source.views {
+            let create = (Phase::CreateViews, terminated(&self.render(&defined.definition)));
+            match target.views.get(name) {
+                None => self.record(change(defined.object.kind, name, DiffAction::Create, false), vec![create]),
+                // Recreated rather than replaced, since replacing can't drop or retype columns
+                Some(existing)
+                    if existing.definition != defined.definition || existing.object.kind != defined.object.kind =>
+                {
+                    let drop = (Phase::DropViews, self.drop_view(existing));
+                    self.record(change(defined.object.kind, name, DiffAction::Alter, false), vec![drop, create]);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn drop_view(&self, defined: &Defined) -> String {
+        let keyword = match defined.object.kind {
+            ObjectKind::MaterializedView => "MATERIALIZED VIEW",
+            _ => "VIEW",
+        };
+        format!("DROP {} {};", keyword, self.qualified(&defined.object.name))
+    }
+
+    fn tables(&mut self, source: &Snapshot, target: &Snapshot) {
+        for (name, table) in &target.tables {
+            if source.tables.contains_key(name) {
+                continue;
+            }
+            // Keys go first so tables referencing each other drop in any order
+            let mut statements: Vec<(Phase, String)> = table
+                .structure
+                .foreign_keys
+                .iter()
+                .map(|key| (Phase::DropForeignKeys, self.drop_foreign_key(name, key)))
+                .collect();
+            statements.push((Phase::DropTables, format!("DROP TABLE {};", self.qualified(name))));
+            self.record(change(ObjectKind::Table, name, DiffAction::Drop, true), statements);
+        }
+
+        for (name, table) in &source.tables {
+            match target.tables.get(name) {
+                None => {
+                    let statements = self.create_table(name, table);
+                    self.record(change(ObjectKind::Table, name, DiffAction::Create, false), statements);
+                }
+                Some(existing) => self.alter_table(name, table, existing),
+            }
+        }
+    }
+
+    fn create_table(&self, name: &str, table: &Table) -> Vec<(Phase, String)> {
+        let mut elements: Vec<String> = table.columns.iter().map(|c| self.column_definition(c, true)).collect();
+        elements.extend(table.structure.constraints.iter().map(|c| self.constraint_clause(c)));
+
+        let mut statements = vec![(
+            Phase::CreateTables,
+            format!("CREATE TABLE {} (\n    {}\n);", self.qualified(name), elements.join(",\n    ")),
+        )];
+        if self.postgres() {
+            for column in table.columns.iter().filter(|c| c.comment.is_some()) {
+                statements.push((Phase::CreateTables, self.comment_on_column(name, column)));
+            }
+        }
+        for index in self.plain_indexes(&table.structure) {
+            statements.push((Phase::AddIndexes, self.create_index(name, table, index)));
+        }
+        for key in &table.structure.foreign_keys {
+            statements.push((Phase::AddForeignKeys, self.add_foreign_key(name, key)));
+        }
+        statements
+    }
+
+    fn alter_table(&mut self, name: &str, source: &Table, target: &Table) {
+        self.alter_columns(name, source, target);
+
+        // Constraints
+        let existing: BTreeMap<&str, &TableConstraint> =
+            target.structure.constraints.iter().map(|c| (c.name.as_str(), c)).collect();
+        let wanted: BTreeMap<&str, &TableConstraint> =
+            source.structure.constraints.iter().map(|c| (c.name.as_str(), c)).collect();
+        for (constraint_name, constraint) in &existing {
+            if !wanted.contains_key(constraint_name) {
+                let sql = self.drop_constraint(name, constraint);
+                self.record(
+                    part(name, TablePart::Constraint, constraint_name, DiffAction::Drop, Vec::new(), false),
+                    vec![(Phase::DropIndexes, sql)],
+                );
+            }
+        }
+        for (constraint_name, constraint) in &wanted {
+            let add = (
+                Phase::AddIndexes,
+                format!("ALTER TABLE {} ADD {};", self.qualified(name), self.constraint_clause(constraint)),
+            );
+            match existing.get(constraint_name) {
+                None => self.record(
+                    part(name, TablePart::Constraint, constraint_name, DiffAction::Create, Vec::new(), false),
+                    vec![add],
+                ),
+                Some(current)
+                    if current.kind != constraint.kind
+                        || placeholder(&current.definition, &self.target_schema)
+                            != placeholder(&constraint.definition, &self.source_schema) =>
+                {
+                    let details = vec![format!("{} -> {}", current.definition, constraint.definition)];
+                    let drop = (Phase::DropIndexes, self.drop_constraint(name, current));
+                    self.record(
+                        part(name, TablePart::Constraint, constraint_name, DiffAction::Alter, details, false),
+                        vec![drop, add],
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+
+        // Indexes that don't back a constraint
+        let existing: BTreeMap<&str, &TableIndex> =
+            self.plain_indexes(&target.structure).map(|i| (i.name.as_str(), i)).collect();
+        let wanted: BTreeMap<&str, &TableIndex> =
+            self.plain_indexes(&source.structure).map(|i| (i.name.as_str(), i)).collect();
+        for index_name in existing.keys() {
+            if !wanted.contains_key(index_name) {
+                let sql = self.drop_index(name, index_name);
+                self.record(
+                    part(name, TablePart::Index, index_name, DiffAction::Drop, Vec::new(), false),
+                    vec![(Phase::DropIndexes, sql)],
+                );
+            }
+        }
+        for (index_name, index) in &wanted {
+            let create = (Phase::AddIndexes, self.create_index(name, source, index));
+            match existing.get(index_name) {
+                None => self.record(
+                    part(name, TablePart::Index, index_name, DiffAction::Create, Vec::new(), false),
+                    vec![create],
+                ),
+                Some(current) => {
+                    let current_sql = self.create_index(name, target, current);
+                    if current_sql != create.1 {
+                        let details = vec![format!("{} -> {}", current_sql, create.1)];
+                        let drop = (Phase::DropIndexes, self.drop_index(name, index_name));
+                        self.record(
+                            part(name, TablePart::Index, index_name, DiffAction::Alter, details, false),
+                            vec![drop, create],
+                        );
+                    }
+                }
+            }
+        }
+
+        // Foreign keys, compared with references into the compared schema mapped across
+        let existing: BTreeMap<&str, &ForeignKey> =
+            target.structure.foreign_keys.iter().map(|k| (k.name.as_str(), k)).collect();
+        let wanted: BTreeMap<&str, &ForeignKey> =
+            source.structure.foreign_keys.iter().map(|k| (k.name.as_str(), k)).collect();
+        for (key_name, key) in &existing {
+            if !wanted.contains_key(key_name) {
+                let sql = self.drop_foreign_key(name, key);
+                self.record(
+                    part(name, TablePart::ForeignKey, key_name, DiffAction::Drop, Vec::new(), false),
+                    vec![(Phase::DropForeignKeys, sql)],
+                );
+            }
+        }
+        for (key_name, key) in &wanted {
+            let add = (Phase::AddForeignKeys, self.add_foreign_key(name, key));
+            match existing.get(key_name) {
+                None => self.record(
+                    part(name, TablePart::ForeignKey, key_name, DiffAction::Create, Vec::new(), false),
+                    vec![add],
+                ),
+                Some(current) => {
+                    let current_sql = self.add_foreign_key(name, current);
+                    if current_sql != add.1 {
+                        let details = vec![format!("{} -> {}", current_sql, add.1)];
+                        let drop = (Phase::DropForeignKeys, self.drop_foreign_key(name, current));
+                        self.record(
+                            part(name, TablePart::ForeignKey, key_name, DiffAction::Alter, details, false),
+                            vec![drop, add],
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn alter_columns(&mut self, name: &str, source: &Table, target: &Table) {
+        let table = self.qualified(name);
+        let existing: BTreeMap<&str, &TableColumn> = target.columns.iter().map(|c| (c.name.as_str(), c)).collect();
+        let wanted: HashSet<&str> = source.columns.iter().map(|c| c.name.as_str()).collect();
+
+        for column in &target.columns {
+            if !wanted.contains(column.name.as_str()) {
+                let sql = format!("ALTER TABLE {} DROP COLUMN {};", table, self.quote(&column.name));
+                self.record(
+                    part(name, TablePart::Column, &column.name, DiffAction::Drop, Vec::new(), true),
+                    vec![(Phase::AlterColumns, sql)],
+                );
+            }
+        }
+
+        for column in &source.columns {
+            let Some(current) = existing.get(column.name.as_str()) else {
+                let mut statements = vec![(
+                    Phase::AlterColumns,
+                    format!("ALTER TABLE {} ADD COLUMN {};", table, self.column_definition(column, true)),
+                )];
+                if self.postgres() && column.comment.is_some() {
+                    statements.push((Phase::AlterColumns, self.comment_on_column(name, column)));
+                }
+                self.record(
+                    part(name, TablePart::Column, &column.name, DiffAction::Create, Vec::new(), false),
+                    statements,
+                );
+                continue;
+            };
+
+            let wanted_default = column.default_value.as_deref().map(|d| placeholder(d, &self.source_schema));
+            let current_default = current.default_value.as_deref().map(|d| placeholder(d, &self.target_schema));
+            let wanted_type = placeholder(&column.data_type, &self.source_schema);
+            let retyped = wanted_type != placeholder(&current.data_type, &self.target_schema);
+            let regenerated =
+                column.generated != current.generated || column.generation_expression != current.generation_expression;
+
+            let mut details = Vec::new();
+            if retyped {
+                details.push(format!("type {} -> {}", current.data_type, column.data_type));
+            }
+            if column.nullable != current.nullable {
+                details.push(if column.nullable { "drop not null" } else { "set not null" }.to_string());
+            }
+            if wanted_default != current_default {
+                details.push(format!(
+                    "default {} -> {}",
+                    current.default_value.as_deref().unwrap_or("none"),
+                    column.default_value.as_deref().unwrap_or("none")
+                ));
+            }
+            if column.identity != current.identity || column.auto_increment != current.auto_increment {
+                details.push(format!(
+                    "identity {} -> {}",
+                    current.identity.as_deref().unwrap_or(if current.auto_increment { "auto" } else { "none" }),
+                    column.identity.as_deref().unwrap_or(if column.auto_increment { "auto" } else { "none" })
+                ));
+            }
+            if regenerated {
+                details.push(format!(
+                    "generated {} -> {}",
+                    current.generation_expression.as_deref().unwrap_or("none"),
+                    column.generation_expression.as_deref().unwrap_or("none")
+                ));
+            }
+            // Postgres collations are part of the type there, and not compared
+            if !self.postgres()
+                && (column.character_set != current.character_set || column.collation != current.collation)
+            {
+                details.push(format!(
+                    "collation {} -> {}",
+                    current.collation.as_deref().unwrap_or("none"),
+                    column.collation.as_deref().unwrap_or("none")
+                ));
+            }
+            if column.on_update != current.on_update {
+                details.push(format!(
+                    "on update {} -> {}",
+                    current.on_update.as_deref().unwrap_or("none"),
+                    column.on_update.as_deref().unwrap_or("none")
+                ));
+            }
+            let recommented = column.comment != current.comment;
+            if recommented {
+                details.push("comment changed".to_string());
+            }
+            if details.is_empty() {
+                continue;
+            }
+
+            let column_name = self.quote(&column.name);
+            let mut statements = Vec::new();
+            let mut destructive = retyped;
+            if !self.postgres() {
+                let definition = self.column_definition(column, false);
+                statements.push(format!("ALTER TABLE {} MODIFY COLUMN {};", table, definition));
+            } else if regenerated {
+                // Postgres can't turn a column into a generated one in place
+                destructive = true;
+                statements.push(format!("ALTER TABLE {} DROP COLUMN {};", table, column_name));
+                statements.push(format!("ALTER TABLE {} ADD COLUMN {};", table, self.column_definition(column, false)));
+            } else {
+                let alter = format!("ALTER TABLE {} ALTER COLUMN {}", table, column_name);
+                if column.identity.is_none() && current.identity.is_some() {
+                    statements.push(format!("{} DROP IDENTITY;", alter));
+                }
+                if retyped {
+                    let data_type = self.render(&wanted_type);
+                    statements.push(format!("{} TYPE {} USING {}::{};", alter, data_type, column_name, data_type));
+                }
+                if wanted_default != current_default {
+                    statements.push(match &wanted_default {
+                        Some(default) => format!("{} SET DEFAULT {};", alter, self.render(default)),
+                        None => format!("{} DROP DEFAULT;", alter),
+                    });
+                }
+                if column.nullable != current.nullable {
+                    statements.push(format!("{} {} NOT NULL;", alter, if column.nullable { "DROP" } else { "SET" }));
+                }
+                match (&column.identity, &current.identity) {
+                    (Some(identity), None) => {
+                        statements.push(format!("{} ADD GENERATED {} AS IDENTITY;", alter, identity))
+                    }
+                    (Some(identity), Some(existing)) if identity != existing => {
+                        statements.push(format!("{} SET GENERATED {};", alter, identity))
+                    }
+                    _ => {}
+                }
+            }
+            // Postgres keeps comments out of the column definition; a re-added column needs its comment back
+            if self.postgres() && (recommented || (regenerated && column.comment.is_some())) {
+                statements.push(self.comment_on_column(name, column));
+            }
+
+            let statements = statements.into_iter().map(|sql| (Phase::AlterColumns, sql)).collect();
+            self.record(
+                part(name, TablePart::Column, &column.name, DiffAction::Alter, details, destructive),
+                statements,
+            );
+        }
+    }
+
+    /// A column as written in `CREATE TABLE` or `ADD COLUMN`. New Postgres
+    /// columns whose default draws from a sequence become serial, since the
+    /// sequence itself isn't part of the diff.
+    fn column_definition(&self, column: &TableColumn, new: bool) -> String {
+        let mut data_type = self.render(&placeholder(&column.data_type, &self.source_schema));
+        let mut default_value = column
+            .default_value
+            .as_deref()
+            .map(|d| self.render(&placeholder(d, &self.source_schema)));
+
+        let sequence_default = default_value.as_deref().is_some_and(|d| d.starts_with("nextval("));
+        if self.postgres() && new && column.identity.is_none() && sequence_default {
+            let serial = match data_type.as_str() {
+                "integer" => Some("serial"),
+                "bigint" => Some("bigserial"),
+                "smallint" => Some("smallserial"),
+                _ => None,
+            };
+            if let Some(serial) = serial {
+                data_type = serial.to_string();
+                default_value = None;
+            }
+        }
+
+        let mut definition = format!("{} {}", self.quote(&column.name), data_type);
+        if !self.postgres() {
+            if let Some(character_set) = &column.character_set {
+                definition.push_str(&format!(" CHARACTER SET {}", character_set));
+            }
+            if let Some(collation) = &column.collation {
+                definition.push_str(&format!(" COLLATE {}", collation));
+            }
+        }
+        if let Some(generated) = &column.generated {
+            let expression = column.generation_expression.as_deref().unwrap_or_default();
+            definition.push_str(&format!(" GENERATED ALWAYS AS ({}) {}", expression, generated));
+        } else if let Some(identity) = &column.identity {
+            definition.push_str(&format!(" GENERATED {} AS IDENTITY", identity));
+        } else if let Some(default_value) = default_value {
+            let default_value = if self.postgres() {
+                default_value
+            } else {
+                mysql_default(&default_value, column.default_is_expression)
+            };
+            definition.push_str(&format!(" DEFAULT {}", default_value));
+        }
+        if !column.nullable {
+            definition.push_str(" NOT NULL");
+        } else if !self.postgres() {
+            definition.push_str(" NULL");
+        }
+        if self.postgres() {
+            return definition;
+        }
+        if let Some(on_update) = &column.on_update {
+            definition.push_str(&format!(" ON UPDATE {}", on_update));
+        }
+        if column.auto_increment {
+            definition.push_str(" AUTO_INCREMENT");
+        }
+        if let Some(comment) = &column.comment {
+            definition.push_str(&format!(" COMMENT {}", mysql_literal(comment)));
+        }
+        definition
+    }
+
+    fn comment_on_column(&self, table: &str, column: &TableColumn) -> String {
+        format!(
+            "COMMENT ON COLUMN {}.{} IS {};",
+            self.qualified(table),
+            self.quote(&column.name),
+            column.comment.as_deref().map(pg_literal).unwrap_or_else(|| "NULL".to_string())
+        )
+    }
+
+    fn constraint_clause(&self, constraint: &TableConstraint) -> String {
+        let definition = self.render(&placeholder(&constraint.definition, &self.source_schema));
+        // MySQL's primary key is always named PRIMARY
+        if !self.postgres() && constraint.kind == ConstraintKind::PrimaryKey {
+            definition
+        } else {
+            format!("CONSTRAINT {} {}", self.quote(&constraint.name), definition)
+        }
+    }
+
+    fn drop_constraint(&self, table: &str, constraint: &TableConstraint) -> String {
+        let table = self.qualified(table);
+        let name = self.quote(&constraint.name);
+        if self.postgres() {
+            return format!("ALTER TABLE {} DROP CONSTRAINT {};", table, name);
+        }
+        match constraint.kind {
+            ConstraintKind::PrimaryKey => format!("ALTER TABLE {} DROP PRIMARY KEY;", table),
+            ConstraintKind::Check => format!("ALTER TABLE {} DROP CHECK {};", table, name),
+            _ => format!("ALTER TABLE {} DROP INDEX {};", table, name),
+        }
+    }
+
+    /// Indexes other than those created for a primary key or unique constraint
+    fn plain_indexes<'a>(&self, structure: &'a TableStructure) -> impl Iterator<Item = &'a TableIndex> {
+        let constraints: HashSet<&str> = structure.constraints.iter().map(|c| c.name.as_str()).collect();
+        structure
+            .indexes
+            .iter()
+            .filter(move |index| !index.primary && !constraints.contains(index.name.as_str()))
+    }
+
+    fn create_index(&self, table_name: &str, table: &Table, index: &TableIndex) -> String {
+        if let Some(definition) = table.index_definitions.get(&index.name) {
+            return format!("{};", self.render(definition));
+        }
+
+        // Plain columns are quoted; expressions and MySQL prefix lengths are kept
+        let column_names: HashSet<&str> = table.columns.iter().map(|c| c.name.as_str()).collect();
+        let key = |item: &String| {
+            if column_names.contains(item.as_str()) {
+                return self.quote(item);
+            }
+            if let Some((column, length)) = item.strip_suffix(')').and_then(|i| i.split_once('(')) {
+                if !self.postgres() && column_names.contains(column) {
+                    return format!("{}({})", self.quote(column), length);
+                }
+            }
+            item.clone()
+        };
+        let keys: Vec<String> = index.columns.iter().map(key).collect();
+        let unique = if index.unique { "UNIQUE " } else { "" };
+
+        if self.postgres() {
+            let mut sql = format!(
+                "CREATE {}INDEX {} ON {} USING {} ({})",
+                unique,
+                self.quote(&index.name),
+                self.qualified(table_name),
+                index.method,
+                keys.join(", ")
+            );
+            if !index.include.is_empty() {
+                let include: Vec<String> = index.include.iter().map(key).collect();
+                sql.push_str(&format!(" INCLUDE ({})", include.join(", ")));
+            }
+            if let Some(predicate) = &index.predicate {
+                sql.push_str(&format!(" WHERE {}", predicate));
+            }
+            format!("{};", sql)
+        } else {
+            let kind = match index.method.as_str() {
+                "FULLTEXT" => "FULLTEXT ",
+                "SPATIAL" => "SPATIAL ",
+                _ => unique,
+            };
+            let using = if index.method == "HASH" { " USING HASH" } else { "" };
+            format!(
+                "CREATE {}INDEX {} ON {} ({}){};",
+                kind,
+                self.quote(&index.name),
+                self.qualified(table_name),
+                keys.join(", "),
+                using
+            )
+        }
+    }
+
+    fn drop_index(&self, table: &str, index: &str) -> String {
+        if self.postgres() {
+            format!("DROP INDEX {};", self.qualified(index))
+        } else {
+            format!("DROP INDEX {} ON {};", self.quote(index), self.qualified(table))
+        }
+    }
+
+    fn add_foreign_key(&self, table: &str, key: &ForeignKey) -> String {
+        let quote_all = |names: &[String]| names.iter().map(|n| self.quote(n)).collect::<Vec<_>>().join(", ");
+        // References within the compared schema follow it to the target
+        let referenced_schema = if key.referenced_schema == self.source_schema {
+            &self.target_schema
+        } else {
+            &key.referenced_schema
+        };
+        format!(
+            "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {}.{} ({}) ON UPDATE {} ON DELETE {};",
+            self.qualified(table),
+            self.quote(&key.name),
+            quote_all(&key.columns),
+            self.quote(referenced_schema),
+            self.quote(&key.referenced_table),
+            quote_all(&key.referenced_columns),
+            key.on_update,
+            key.on_delete
+        )
+    }
+
+    fn drop_foreign_key(&self, table: &str, key: &ForeignKey) -> String {
+        let keyword = if self.postgres() { "CONSTRAINT" } else { "FOREIGN KEY" };
+        format!("ALTER TABLE {} DROP {} {};", self.qualified(table), keyword, self.quote(&key.name))
+    }
+}
+
+fn change(object_kind: ObjectKind, name: &str, action: DiffAction, destructive: bool) -> SchemaChange {
+    SchemaChange {
+        object_kind,
+        name: name.to_string(),
+        action,
+        part: None,
+        part_name: None,
+        details: Vec::new(),
+        destructive,
+    }
+}
+
+fn part(
+    table: &str,
+    part: TablePart,
+    part_name: &str,
+    action: DiffAction,
+    details: Vec<String>,
+    destructive: bool,
+) -> SchemaChange {
+    SchemaChange {
+        part: Some(part),
+        part_name: Some(part_name.to_string()),
+        details,
+        ..change(ObjectKind::Table, table, action, destructive)
+    }
+}
+
+/// Labels of a Postgres enum from its `list_objects` detail, as SQL literals
+fn enum_labels(defined: &Defined) -> Option<Vec<String>> {
+    let list = defined.object.detail.as_deref()?.strip_prefix("enum (")?.strip_suffix(')')?;
+    let mut labels = Vec::new();
+    let mut rest = list;
+    while !rest.is_empty() {
+        rest = rest.trim_start_matches([',', ' ']);
+        let start = rest.find('\'')? + 1;
+        // A quote ends the literal unless it is doubled
+        let mut end = start;
+        loop {
+            end += rest[end..].find('\'')?;
+            if rest[end + 1..].starts_with('\'') {
+                end += 2;
+            } else {
+                break;
+            }
+        }
+        labels.push(rest[..=end].to_string());
+        rest = &rest[end + 1..];
+    }
+    Some(labels)
+}
+
+/// Values added to an enum, with where each goes, when the target's labels
+/// are all still there in the same order and can be extended with ADD VALUE
+fn added_enum_values(existing: &Defined, wanted: &Defined) -> Option<Vec<(String, String)>> {
+    let existing = enum_labels(existing)?;
+    let wanted = enum_labels(wanted)?;
+    let kept: Vec<&String> = wanted.iter().filter(|label| existing.contains(label)).collect();
+    if kept.len() != existing.len() || kept.iter().zip(&existing).any(|(a, b)| *a != b) {
+        return None;
+    }
+
+    let mut added = Vec::new();
+    for (i, label) in wanted.iter().enumerate() {
+        if existing.contains(label) {
+            continue;
+        }
+        let position = match i {
+            0 => format!("BEFORE {}", wanted.get(1)?),
+            _ => format!("AFTER {}", wanted[i - 1]),
+        };
+        added.push((label.clone(), position));
+    }
+    Some(added)
+}
+
+/// A MySQL default as written in a column definition. information_schema
+/// gives literals unquoted and expressions without the parentheses they
+/// need, except for the current-time functions that may go bare.
+fn mysql_default(value: &str, expression: bool) -> String {
+    if !expression {
+        return if value.parse::<f64>().is_ok() || value.starts_with("b'") {
+            value.to_string()
+        } else {
+            mysql_literal(value)
+        };
+    }
+    let upper = value.to_uppercase();
+    let bare = ["CURRENT_TIMESTAMP", "NOW(", "LOCALTIME"].iter().any(|f| upper.starts_with(f));
+    if bare || value.starts_with('(') {
+        value.to_string()
+    } else {
+        format!("({})", value)
+    }
+}
+
+fn mysql_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn differ(dialect: DatabaseType) -> Differ {
+        Differ {
+            dialect,
+            source_schema: "staging".to_string(),
+            target_schema: "prod".to_string(),
+            changes: Vec::new(),
+            statements: Vec::new(),
+        }
+    }
+
+    fn column(name: &str, data_type: &str) -> TableColumn {
+        TableColumn {
+            ordinal_position: 1,
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable: true,
+            default_value: None,
+            default_is_expression: false,
+            identity: None,
+            auto_increment: false,
+            generated: None,
+            generation_expression: None,
+            collation: None,
+            character_set: None,
+            on_update: None,
+            comment: None,
+            is_primary_key: false,
+            primary_key_position: None,
+        }
+    }
+
+    fn table(columns: Vec<TableColumn>) -> Table {
+        Table {
+            columns,
+            structure: TableStructure {
+                schema: String::new(),
+                name: "t".to_string(),
+                indexes: Vec::new(),
+                constraints: Vec::new(),
+                foreign_keys: Vec::new(),
+                referenced_by: Vec::new(),
+            },
+            index_definitions: HashMap::new(),
+        }
+    }
+
+    fn defined(kind: ObjectKind, name: &str, detail: &str, definition: &str) -> Defined {
+        Defined {
+            object: DatabaseObject {
+                schema: String::new(),
+                name: name.to_string(),
+                kind,
+                table: None,
+                arguments: None,
+                result: None,
+                detail: Some(detail.to_string()),
+                current_value: None,
+                comment: None,
+            },
+            definition: definition.to_string(),
+        }
+    }
+
+    fn snapshot(types: Vec<Defined>) -> Snapshot {
+        Snapshot {
+            schema: String::new(),
+            tables: BTreeMap::new(),
+            views: BTreeMap::new(),
+            routines: BTreeMap::new(),
+            types: types.into_iter().map(|d| (d.object.name.clone(), d)).collect(),
+        }
+    }
+
+    #[test]
+    fn routines_are_created_before_tables() {
+        assert!(Phase::CreateTypes < Phase::CreateRoutines);
+        assert!(Phase::CreateRoutines < Phase::CreateTables);
+        assert!(Phase::CreateTables < Phase::CreateViews);
+        assert!(Phase::CreateRoutines.creates());
+        assert!(!Phase::DropRoutines.creates());
+    }
+
+    #[test]
+    fn columns_stop_using_types_and_routines_before_they_are_dropped() {
+        let mut status = column("status", "mood");
+        let mut code = column("code", "text");
+        code.default_value = Some("next_code()".to_string());
+        let mut target = snapshot(vec![defined(ObjectKind::Type, "mood", "enum ('sad', 'ok')", "a")]);
+        target.tables.insert("users".to_string(), table(vec![status.clone(), code.clone()]));
+        target.routines.insert(
+            "next_code()".to_string(),
+            defined(ObjectKind::Function, "next_code", "function", "CREATE FUNCTION next_code()"),
+        );
+
+        status.data_type = "text".to_string();
+        code.default_value = None;
+        let mut source = snapshot(Vec::new());
+        source.tables.insert("users".to_string(), table(vec![status, code]));
+
+        let mut differ = differ(DatabaseType::Postgres);
+        differ.types(&source, &target).unwrap();
+        differ.tables(&source, &target);
+        differ.routines(&source, &target);
+        differ.views(&source, &target);
+        let migration = differ.finish().migration;
+        let statements: Vec<&str> = migration.split("\n\n").collect();
+        assert_eq!(
+            statements,
+            [
+                "-- Destructive\nALTER TABLE \"prod\".\"users\" ALTER COLUMN \"status\" TYPE text USING \"status\"::text;",
+                r#"ALTER TABLE "prod"."users" ALTER COLUMN "code" DROP DEFAULT;"#,
+                "-- Destructive\nDROP FUNCTION \"prod\".\"next_code\"();",
+                "-- Destructive\nDROP TYPE \"prod\".\"mood\";",
+            ]
+        );
+    }
+
+    #[test]
+    fn placeholder_replaces_whole_qualifiers_only() {
+        assert_eq!(
+            placeholder("SELECT * FROM app.users JOIN \"app\".orders", "app"),
+            format!("SELECT * FROM {p}users JOIN {p}orders", p = SCHEMA_PLACEHOLDER)
+        );
+        assert_eq!(placeholder("SELECT * FROM myapp.users", "app"), "SELECT * FROM myapp.users");
+    }
+
+    #[test]
+    fn mysql_defaults_quote_literals_and_wrap_expressions() {
+        assert_eq!(mysql_default("pending", false), "'pending'");
+        assert_eq!(mysql_default("it's", false), "'it''s'");
+        assert_eq!(mysql_default("0.5", false), "0.5");
+        assert_eq!(mysql_default("b'101'", false), "b'101'");
+        assert_eq!(mysql_default("curdate()", true), "(curdate())");
+        assert_eq!(mysql_default("CURRENT_TIMESTAMP(3)", true), "CURRENT_TIMESTAMP(3)");
+    }
+
+    #[test]
+    fn mysql_column_definition_keeps_every_attribute() {
+        let mut updated = column("updated_at", "timestamp");
+        updated.nullable = false;
+        updated.default_value = Some("CURRENT_TIMESTAMP".to_string());
+        updated.default_is_expression = true;
+        updated.on_update = Some("CURRENT_TIMESTAMP".to_string());
+        updated.comment = Some("Last change".to_string());
+        assert_eq!(
+            differ(DatabaseType::Mysql).column_definition(&updated, false),
+            "`updated_at` timestamp DEFAULT CURRENT_TIMESTAMP NOT NULL ON UPDATE CURRENT_TIMESTAMP COMMENT 'Last change'"
+        );
+
+        let mut name = column("name", "varchar(50)");
+        name.character_set = Some("utf8mb4".to_string());
+        name.collation = Some("utf8mb4_bin".to_string());
+        assert_eq!(
+            differ(DatabaseType::Mysql).column_definition(&name, false),
+            "`name` varchar(50) CHARACTER SET utf8mb4 COLLATE utf8mb4_bin NULL"
+        );
+    }
+
+    #[test]
+    fn mysql_modify_column_carries_comment_and_collation() {
+        let current = column("name", "varchar(50)");
+        let mut wanted = column("name", "varchar(50)");
+        wanted.collation = Some("utf8mb4_bin".to_string());
+        wanted.character_set = Some("utf8mb4".to_string());
+        wanted.comment = Some("Display name".to_string());
+
+        let mut differ = differ(DatabaseType::Mysql);
+        differ.alter_columns("users", &table(vec![wanted]), &table(vec![current]));
+        let (_, destructive, sql) = &differ.statements[0];
+        assert!(!destructive);
+        assert_eq!(
+            sql,
+            "ALTER TABLE `prod`.`users` MODIFY COLUMN `name` varchar(50) CHARACTER SET utf8mb4 COLLATE utf8mb4_bin NULL COMMENT 'Display name';"
+        );
+        assert_eq!(differ.changes[0].details, ["collation none -> utf8mb4_bin", "comment changed"]);
+    }
+
+    #[test]
+    fn postgres_comments_go_in_their_own_statement() {
+        let mut wanted = column("name", "text");
+        wanted.comment = Some("It's shown".to_string());
+
+        let mut differ = differ(DatabaseType::Postgres);
+        differ.alter_columns("users", &table(vec![wanted]), &table(vec![column("name", "text")]));
+        let sql: Vec<&str> = differ.statements.iter().map(|(_, _, sql)| sql.as_str()).collect();
+        assert_eq!(sql, [r#"COMMENT ON COLUMN "prod"."users"."name" IS 'It''s shown';"#]);
+    }
+
+    #[test]
+    fn enums_gain_values_in_place() {
+        let target = snapshot(vec![defined(ObjectKind::Type, "mood", "enum ('sad', 'ok')", "a")]);
+        let source = snapshot(vec![defined(ObjectKind::Type, "mood", "enum ('sad', 'ok', 'happy')", "b")]);
+        let mut differ = differ(DatabaseType::Postgres);
+        differ.types(&source, &target).unwrap();
+        assert_eq!(differ.statements[0].2, r#"ALTER TYPE "prod"."mood" ADD VALUE 'happy' AFTER 'ok';"#);
+    }
+
+    #[test]
+    fn other_type_changes_are_refused() {
+        let target = snapshot(vec![defined(ObjectKind::Type, "mood", "enum ('sad', 'ok')", "a")]);
+        let source = snapshot(vec![defined(ObjectKind::Type, "mood", "enum ('ok', 'sad')", "b")]);
+        assert!(matches!(
+            differ(DatabaseType::Postgres).types(&source, &target),
+            Err(ConnectionError::InvalidInput(_))
+        ));
+
+        let target = snapshot(vec![defined(ObjectKind::Type, "amount", "domain numeric", "a")]);
+        let source = snapshot(vec![defined(ObjectKind::Type, "amount", "domain numeric", "b")]);
+        assert!(differ(DatabaseType::Postgres).types(&source, &target).is_err());
+    }
+
+    #[test]
+    fn quoting_follows_the_dialect() {
+        assert_eq!(differ(DatabaseType::Postgres).qualified("a\"b"), r#""prod"."a""b""#);
+        assert_eq!(differ(DatabaseType::Mysql).qualified("a`b"), "`prod`.`a``b`");
+    }
+}
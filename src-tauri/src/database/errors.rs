@@ -0,0 +1,305 @@
+use serde::Serialize;
+use sqlx::error::ErrorKind;
+use sqlx::mysql::MySqlDatabaseError;
+use sqlx::postgres::{PgDatabaseError, PgErrorPosition};
+
+/// MySQL error number for syntax errors (ER_PARSE_ERROR)
+const MYSQL_PARSE_ERROR: u16 = 1064;
+
+/// Location of an error inside the submitted SQL
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorPosition {
+    /// Zero-based character offset
+    pub offset: usize,
+    /// One-based line number
+    pub line: usize,
+    /// One-based column, in characters
+    pub column: usize,
+}
+
+/// Structured details of an error reported by the database server
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DatabaseErrorInfo {
+    pub message: String,
+    /// SQLSTATE code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Vendor error number (MySQL)
+    #[serde(rename = "errorNumber", skip_serializing_if = "Option::is_none")]
+    pub error_number: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<ErrorPosition>,
+    /// Context for errors raised inside functions or internal queries
+    #[serde(rename = "where", skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    #[serde(rename = "dataType", skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<String>,
+    /// Constraint violation category, when the driver recognises one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub violation: Option<&'static str>,
+}
+
+impl DatabaseErrorInfo {
+    /// Extract server-provided details from a sqlx error. `sql` is the text that
+    /// was submitted and is used to turn positions into line/column pairs.
+    pub fn from_sqlx(error: &sqlx::Error, sql: Option<&str>) -> Option<Self> {
+        let db_error = error.as_database_error()?;
+
+        let mut info = DatabaseErrorInfo {
+            message: db_error.message().to_string(),
+            code: db_error.code().map(|c| c.into_owned()),
+            table: db_error.table().map(str::to_string),
+            constraint: db_error.constraint().map(str::to_string),
+            violation: match db_error.kind() {
+                ErrorKind::UniqueViolation => Some("unique"),
+                ErrorKind::ForeignKeyViolation => Some("foreignKey"),
+                ErrorKind::NotNullViolation => Some("notNull"),
+                ErrorKind::CheckViolation => Some("check"),
+                _ => None,
+            },
+            ..Default::default()
+        };
+
+        if let Some(pg) = db_error.try_downcast_ref::<PgDatabaseError>() {
+            info.severity = Some(format!("{:?}", pg.severity()).to_uppercase());
+            info.detail = pg.detail().map(str::to_string);
+            info.hint = pg.hint().map(str::to_string);
+            info.context = pg.r#where().map(str::to_string);
+            info.schema = pg.schema().map(str::to_string);
+            info.column = pg.column().map(str::to_string);
+            info.data_type = pg.data_type().map(str::to_string);
+
+            // Positions inside internally generated queries don't point into the user's SQL
+            if let (Some(PgErrorPosition::Original(position)), Some(sql)) = (pg.position(), sql) {
+                info.position = position_from_char_index(sql, position.saturating_sub(1));
+            }
+        } else if let Some(mysql) = db_error.try_downcast_ref::<MySqlDatabaseError>() {
+            info.error_number = Some(mysql.number());
+
+            if mysql.number() == MYSQL_PARSE_ERROR {
+                if let Some(sql) = sql {
+                    info.position = mysql_syntax_error_position(sql, mysql.message());
+                }
+            }
+        }
+
+        Some(info)
+    }
+}
+
+/// Map a zero-based character index into `sql` to a line/column position
+fn position_from_char_index(sql: &str, index: usize) -> Option<ErrorPosition> {
+    let mut line = 1;
+    let mut column = 1;
+
+    for (i, c) in sql.chars().enumerate() {
+        if i == index {
+            return Some(ErrorPosition { offset: index, line, column });
+        }
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+
+    // Errors at end of input (e.g. unterminated statements) point just past the text
+    (index == sql.chars().count()).then_some(ErrorPosition { offset: index, line, column })
+}
+
+/// MySQL error number for unknown columns (ER_BAD_FIELD_ERROR)
+pub const MYSQL_BAD_FIELD_ERROR: u16 = 1054;
+
+/// Vendor error number of a MySQL server error
+pub fn mysql_error_number(error: &sqlx::Error) -> Option<u16> {
+    error
+        .as_database_error()?
+        .try_downcast_ref::<MySqlDatabaseError>()
+        .map(|e| e.number())
+}
+
+/// Line/column position of a byte offset into `sql`
+pub fn position_at_byte(sql: &str, byte: usize) -> ErrorPosition {
+    let before = &sql[..byte];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    ErrorPosition {
+        offset: before.chars().count(),
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+/// MySQL syntax errors have the form `... near '<text>' at line <n>`.
+/// Locate `<text>` within that line to recover a column.
+fn mysql_syntax_error_position(sql: &str, message: &str) -> Option<ErrorPosition> {
+    let (before_line, line_part) = message.rsplit_once(" at line ")?;
+    let line: usize = line_part.trim().parse().ok()?;
+    let line_index = line.checked_sub(1)?;
+
+    let near = before_line
+        .split_once("near '")
+        .map(|(_, rest)| rest.strip_suffix('\'').unwrap_or(rest))
+        .unwrap_or_default();
+
+    let line_start: usize = sql
+        .split_inclusive('\n')
+        .take(line_index)
+        .map(|l| l.chars().count())
+        .sum();
+
+    let line_text = sql.split('\n').nth(line_index)?;
+
+    // MySQL truncates the quoted text, so match on a prefix that fits on the line
+    let column = if near.is_empty() {
+        line_text.chars().count() + 1
+    } else {
+        let probe: String = near.lines().next().unwrap_or_default().chars().take(40).collect();
+        line_text
+            .find(probe.as_str())
+            .map(|byte| line_text[..byte].chars().count() + 1)
+            .unwrap_or(1)
+    };
+
+    Some(ErrorPosition {
+        offset: line_start + column - 1,
+        line,
+        column,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+    use std::error::Error as StdError;
+
+    /// Stand-in for a driver error; the real Postgres/MySQL types can't be built outside sqlx
+    #[derive(Debug)]
+    struct FakeDbError {
+        kind: fn() -> ErrorKind,
+    }
+
+    impl std::fmt::Display for FakeDbError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("duplicate key value")
+        }
+    }
+
+    impl StdError for FakeDbError {}
+
+    impl sqlx::error::DatabaseError for FakeDbError {
+        fn message(&self) -> &str {
+            "duplicate key value"
+        }
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed("23505"))
+        }
+        fn as_error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+            self
+        }
+        fn as_error_mut(&mut self) -> &mut (dyn StdError + Send + Sync + 'static) {
+            self
+        }
+        fn into_error(self: Box<Self>) -> Box<dyn StdError + Send + Sync + 'static> {
+            self
+        }
+        fn constraint(&self) -> Option<&str> {
+            Some("users_email_key")
+        }
+        fn table(&self) -> Option<&str> {
+            Some("users")
+        }
+        fn kind(&self) -> ErrorKind {
+            (self.kind)()
+        }
+    }
+
+    #[test]
+    fn from_sqlx_reads_generic_database_fields() {
+        let error = sqlx::Error::Database(Box::new(FakeDbError { kind: || ErrorKind::UniqueViolation }));
+        let info = DatabaseErrorInfo::from_sqlx(&error, Some("INSERT INTO users VALUES (1)")).unwrap();
+        assert_eq!(info.message, "duplicate key value");
+        assert_eq!(info.code.as_deref(), Some("23505"));
+        assert_eq!(info.table.as_deref(), Some("users"));
+        assert_eq!(info.constraint.as_deref(), Some("users_email_key"));
+        assert_eq!(info.violation, Some("unique"));
+        assert!(info.position.is_none());
+        assert!(info.error_number.is_none());
+
+        let other = sqlx::Error::Database(Box::new(FakeDbError { kind: || ErrorKind::Other }));
+        assert_eq!(DatabaseErrorInfo::from_sqlx(&other, None).unwrap().violation, None);
+    }
+
+    #[test]
+    fn from_sqlx_ignores_non_server_errors() {
+        assert!(DatabaseErrorInfo::from_sqlx(&sqlx::Error::PoolTimedOut, Some("SELECT 1")).is_none());
+        assert!(DatabaseErrorInfo::from_sqlx(&sqlx::Error::RowNotFound, None).is_none());
+    }
+
+    #[test]
+    fn mysql_error_number_needs_a_mysql_error() {
+        let error = sqlx::Error::Database(Box::new(FakeDbError { kind: || ErrorKind::Other }));
+        assert_eq!(mysql_error_number(&error), None);
+        assert_eq!(mysql_error_number(&sqlx::Error::PoolTimedOut), None);
+    }
+
+    #[test]
+    fn maps_char_index_to_line_and_column() {
+        let sql = "SELECT 1,\n  émoji FROM t";
+        let pos = position_from_char_index(sql, 12).unwrap();
+        assert_eq!((pos.offset, pos.line, pos.column), (12, 2, 3));
+
+        let start = position_from_char_index(sql, 0).unwrap();
+        assert_eq!((start.line, start.column), (1, 1));
+
+        // One past the end is valid, further is not
+        let len = sql.chars().count();
+        let end = position_from_char_index(sql, len).unwrap();
+        assert_eq!((end.line, end.column), (2, 15));
+        assert!(position_from_char_index(sql, len + 1).is_none());
+    }
+
+    #[test]
+    fn locates_mysql_syntax_errors() {
+        let sql = "SELECT *\nFROM users\nWHER id = 1";
+        let message = "You have an error in your SQL syntax; check the manual that corresponds to \
+                       your MySQL server version for the right syntax to use near 'WHER id = 1' at line 3";
+        let pos = mysql_syntax_error_position(sql, message).unwrap();
+        assert_eq!((pos.line, pos.column, pos.offset), (3, 1, 20));
+
+        let sql = "SELECT a,, b FROM t";
+        let message = "... to use near ', b FROM t' at line 1";
+        let pos = mysql_syntax_error_position(sql, message).unwrap();
+        assert_eq!((pos.line, pos.column, pos.offset), (1, 10, 9));
+    }
+
+    #[test]
+    fn mysql_errors_at_end_of_input_point_past_the_line() {
+        let sql = "SELECT (1\nFROM t WHERE (a";
+        let pos = mysql_syntax_error_position(sql, "... to use near '' at line 2").unwrap();
+        assert_eq!((pos.line, pos.column, pos.offset), (2, 16, 25));
+    }
+
+    #[test]
+    fn rejects_unparseable_mysql_messages() {
+        assert!(mysql_syntax_error_position("SELECT 1", "Unknown column 'x'").is_none());
+        assert!(mysql_syntax_error_position("SELECT 1", "near 'x' at line 0").is_none());
+        assert!(mysql_syntax_error_position("SELECT 1", "near 'x' at line 5").is_none());
+        assert!(mysql_syntax_error_position("SELECT 1", "near 'x' at line two").is_none());
+    }
+}
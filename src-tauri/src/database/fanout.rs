@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::time::Instant;
+use futures_util::{stream, StreamExt};
+
+use super::connection::{ConnectionError, ConnectionManager};
+use super::types::*;
+
+/// Run `params.sql` on every listed connection, at most `parallelism` at a
+/// time. A failing connection is reported in its source entry and doesn't
+/// stop the others.
+pub async fn run(manager: &ConnectionManager, params: &FanOutQueryParams) -> FanOutResult {
+    let start = Instant::now();
+
+    let outcomes: Vec<(String, Result<QueryResult, _>)> = stream::iter(&params.connection_ids)
+        .map(|connection_id| async move {
+            let result = manager
+                .execute_query(ExecuteQueryParams {
+                    connection_id: connection_id.clone(),
+                    database: None,
+                    schema: None,
+                    sql: params.sql.clone(),
+                    limit: 0,
+                    offset: 0,
+                    variables: HashMap::new(),
+                    cache: false,
+                    max_rows: None,
+                    max_bytes: None,
+                    max_cell_bytes: None,
+                    include_backend_id: false,
+                })
+                .await;
+            (connection_id.clone(), result)
+        })
+        .buffered(params.parallelism.max(1))
+        .collect()
+        .await;
+
+    let outcomes = outcomes
+        .into_iter()
+        .map(|(connection_id, outcome)| {
+            let connection_name = manager
+                .get_config(&connection_id)
+                .map(|config| config.name)
+                .unwrap_or_else(|_| connection_id.clone());
+            (connection_id, connection_name, outcome)
+        })
+        .collect();
+
+    merge(params, outcomes, start.elapsed().as_millis())
+}
+
+/// Merge per-connection results into one, tagging each row with its
+/// connection's ID, since names needn't be unique. `outcomes` are
+/// (id, name, result) in request order.
+fn merge(
+    params: &FanOutQueryParams,
+    outcomes: Vec<(String, String, Result<QueryResult, ConnectionError>)>,
+    execution_time_ms: u128,
+) -> FanOutResult {
+    let mut columns = vec![ColumnInfo {
+        name: params.source_column.clone(),
+        data_type: "TEXT".to_string(),
+        nullable: false,
+    }];
+    let mut rows = Vec::new();
+    let mut sources = Vec::with_capacity(outcomes.len());
+
+    for (connection_id, connection_name, outcome) in outcomes {
+        // A result column of the same name would be overwritten by the tag
+        let outcome = outcome.and_then(|result| {
+            if result.columns.iter().any(|c| c.name == params.source_column) {
+                Err(ConnectionError::InvalidInput(format!(
+                    "The result already has a column named {}; choose another source column",
+                    params.source_column
+                )))
+            } else {
+                Ok(result)
+            }
+        });
+        let result = match outcome {
+            Ok(result) => result,
+            Err(e) => {
+                sources.push(FanOutSource {
+                    connection_id,
+                    connection_name,
+                    row_count: 0,
+                    execution_time_ms: 0,
+                    truncated: false,
+                    error: Some(e),
+                });
+                continue;
+            }
+        };
+
+        // Schemas may drift between databases, so take the union of columns
+        for column in &result.columns {
+            if !columns.iter().any(|c| c.name == column.name) {
+                columns.push(column.clone());
+            }
+        }
+
+        for mut row in result.rows {
+            if let serde_json::Value::Object(fields) = &mut row {
+                fields.insert(
+                    params.source_column.clone(),
+                    serde_json::Value::String(connection_id.clone()),
+                );
+            }
+            rows.push(row);
+        }
+
+        sources.push(FanOutSource {
+            connection_id,
+            connection_name,
+            row_count: result.row_count,
+            execution_time_ms: result.execution_time_ms,
+            truncated: result.truncated,
+            error: None,
+        });
+    }
+
+    FanOutResult {
+        row_count: rows.len(),
+        columns,
+        rows,
+        execution_time_ms,
+        sources,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn params() -> FanOutQueryParams {
+        FanOutQueryParams {
+            connection_ids: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            sql: "SELECT 1".to_string(),
+            variables: HashMap::new(),
+            parallelism: 2,
+            source_column: "source".to_string(),
+        }
+    }
+
+    fn result(columns: &[&str], rows: Vec<serde_json::Value>, truncated: bool) -> QueryResult {
+        QueryResult {
+            columns: columns
+                .iter()
+                .map(|name| ColumnInfo {
+                    name: name.to_string(),
+                    data_type: "INT4".to_string(),
+                    nullable: true,
+                })
+                .collect(),
+            row_count: rows.len(),
+            rows,
+            execution_time_ms: 3,
+            notices: Vec::new(),
+            result_id: None,
+            truncated,
+            truncated_reason: None,
+            truncated_cells: Vec::new(),
+            stats: None,
+        }
+    }
+
+    #[test]
+    fn merges_rows_with_source_and_column_union() {
+        let outcomes = vec![
+            ("a".to_string(), "Primary".to_string(), Ok(result(&["id"], vec![json!({"id": 1})], false))),
+            (
+                "b".to_string(),
+                "Replica".to_string(),
+                Ok(result(&["id", "extra"], vec![json!({"id": 2, "extra": true})], true)),
+            ),
+        ];
+
+        let merged = merge(&params(), outcomes, 10);
+        let names: Vec<_> = merged.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["source", "id", "extra"]);
+        assert_eq!(merged.row_count, 2);
+        assert_eq!(merged.rows[0], json!({"id": 1, "source": "a"}));
+        assert_eq!(merged.rows[1], json!({"id": 2, "extra": true, "source": "b"}));
+        assert!(merged.sources[1].truncated);
+        assert_eq!(merged.execution_time_ms, 10);
+    }
+
+    #[test]
+    fn failed_connection_is_reported_without_stopping_others() {
+        let outcomes = vec![
+            ("a".to_string(), "a".to_string(), Err(ConnectionError::NotFound("a".to_string()))),
+            ("b".to_string(), "Replica".to_string(), Ok(result(&["id"], vec![json!({"id": 2})], false))),
+        ];
+
+        let merged = merge(&params(), outcomes, 0);
+        assert_eq!(merged.row_count, 1);
+        assert_eq!(merged.sources.len(), 2);
+        assert!(matches!(merged.sources[0].error, Some(ConnectionError::NotFound(_))));
+        assert_eq!(merged.sources[0].row_count, 0);
+        assert!(merged.sources[1].error.is_none());
+        assert_eq!(merged.sources[1].row_count, 1);
+    }
+
+    #[test]
+    fn rows_from_same_named_connections_stay_apart() {
+        let outcomes = vec![
+            ("a".to_string(), "Replica".to_string(), Ok(result(&["id"], vec![json!({"id": 1})], false))),
+            ("b".to_string(), "Replica".to_string(), Ok(result(&["id"], vec![json!({"id": 1})], false))),
+        ];
+
+        let merged = merge(&params(), outcomes, 0);
+        assert_ne!(merged.rows[0], merged.rows[1]);
+        assert_eq!(merged.rows[1]["source"], "b");
+    }
+
+    #[test]
+    fn result_column_named_like_the_source_column_is_refused() {
+        let outcomes = vec![
+            ("a".to_string(), "Primary".to_string(), Ok(result(&["source"], vec![json!({"source": 7})], false))),
+            ("b".to_string(), "Replica".to_string(), Ok(result(&["id"], vec![json!({"id": 2})], false))),
+        ];
+
+        let merged = merge(&params(), outcomes, 0);
+        assert!(matches!(merged.sources[0].error, Some(ConnectionError::InvalidInput(_))));
+        assert_eq!(merged.rows, vec![json!({"id": 2, "source": "b"})]);
+        let names: Vec<_> = merged.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["source", "id"]);
+    }
+
+    #[tokio::test]
+    async fn unknown_connections_fail_individually() {
+        let manager = ConnectionManager::new();
+        let merged = run(&manager, &params()).await;
+
+        assert!(merged.rows.is_empty());
+        assert_eq!(merged.sources.len(), 3);
+        let ids: Vec<_> = merged.sources.iter().map(|s| s.connection_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert!(merged.sources.iter().all(|s| matches!(s.error, Some(ConnectionError::NotFound(_)))));
+    }
+}
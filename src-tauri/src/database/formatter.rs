@@ -0,0 +1,720 @@
+use super::lexer::{matching_paren, tokenize, Token, TokenKind};
+use super::types::*;
+
+/// Words whose case is normalised. Deliberately limited to words that are
+/// rarely used as identifiers, since those keep the case they were written in;
+/// the few common names here are only re-cased where `used_as_keyword` allows.
+const KEYWORDS: &[&str] = &[
+    "ADD", "ALL", "ALTER", "ANALYZE", "AND", "ANY", "AS", "ASC", "BEGIN", "BETWEEN", "BY",
+    "CASCADE", "CASE", "CAST", "CHECK", "COLLATE", "COLUMN", "COMMIT", "CONFLICT", "CONSTRAINT",
+    "CREATE", "CROSS", "DEFAULT", "DELETE", "DESC", "DISTINCT", "DO", "DROP", "DUPLICATE", "ELSE",
+    "END", "ESCAPE", "EXCEPT", "EXISTS", "EXPLAIN", "FALSE", "FETCH", "FILTER", "FOR", "FOREIGN",
+    "FROM", "FULL", "GRANT", "GROUP", "HAVING", "IF", "ILIKE", "IN", "INDEX", "INNER", "INSERT",
+    "INTERSECT", "INTERVAL", "INTO", "IS", "JOIN", "KEY", "LATERAL", "LEFT", "LIKE", "LIMIT",
+    "MATERIALIZED", "NATURAL", "NOT", "NOTHING", "NULL", "NULLS", "OFFSET", "ON", "OR", "ORDER",
+    "OUTER", "OVER", "PARTITION", "PRIMARY", "RECURSIVE", "REFERENCES", "RETURNING", "REVOKE",
+    "RIGHT", "ROLLBACK", "SELECT", "SET", "SIMILAR", "TABLE", "THEN", "TO", "TRUE", "TRUNCATE",
+    "UNION", "UNIQUE", "UPDATE", "USING", "VALUES", "VIEW", "WHEN", "WHERE", "WINDOW", "WITH",
+    "WITHIN",
+];
+
+/// Keywords that are called like functions, so no space goes before their `(`
+const FUNCTION_KEYWORDS: &[&str] = &["ANY", "CAST", "IF", "LEFT", "RIGHT"];
+
+/// Keywords after which a name is followed by a column list, as in
+/// `INSERT INTO t (a, b)`, rather than being a function call
+const OBJECT_KEYWORDS: &[&str] = &["INTO", "REFERENCES", "TABLE", "VIEW"];
+
+/// Keywords that start a subquery when they open a parenthesised group
+const SUBQUERY_KEYWORDS: &[&str] = &["DELETE", "INSERT", "SELECT", "UPDATE", "VALUES", "WITH"];
+
+const JOIN_MODIFIERS: &[&str] = &["CROSS", "FULL", "INNER", "LEFT", "NATURAL", "OUTER", "RIGHT"];
+
+/// Keywords that are common column and table names, with the words that
+/// precede them when they are used as keywords. Elsewhere they are names and
+/// keep their case, as in `SELECT key, index FROM t`.
+const NAME_KEYWORDS: &[(&str, &[&str])] = &[
+    ("COLUMN", &["ADD", "ALTER", "CHANGE", "DROP", "MODIFY", "ON", "RENAME"]),
+    ("INDEX", &["ADD", "ALTER", "CREATE", "DROP", "FORCE", "IGNORE", "UNIQUE", "USE"]),
+    ("KEY", &["DUPLICATE", "FOREIGN", "PRIMARY", "UNIQUE"]),
+    ("VIEW", &["ALTER", "CREATE", "DROP", "MATERIALIZED", "REPLACE"]),
+];
+
+/// Pretty-print `sql`. Only whitespace between tokens is changed; comments and
+/// literals are copied verbatim. The layout depends only on the token
+/// sequence, so formatting already formatted SQL gives the same text.
+pub fn format_sql(sql: &str, dialect: DatabaseType, options: &FormatOptions) -> String {
+    let tokens: Vec<Token> = tokenize(sql, dialect)
+        .into_iter()
+        .filter(|t| t.kind != TokenKind::Whitespace)
+        .collect();
+
+    let formatter = Formatter { options };
+    let mut w = Writer::new(options);
+
+    for (i, (statement, terminated)) in split_statements(&tokens).into_iter().enumerate() {
+        if i > 0 {
+            w.blank_line();
+        }
+        formatter.block(&mut w, statement, 0);
+        if terminated {
+            w.write(";", false);
+            w.prev = Some(Prev::new(TokenKind::Semicolon, ";"));
+        }
+    }
+
+    w.out
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ClauseKind {
+    /// Comma-separated items, one per line when they don't fit
+    List,
+    /// AND/OR-separated conditions, one per line when they don't fit
+    Condition,
+    Inline,
+}
+
+struct Clause<'t, 'a> {
+    header: &'t [Token<'a>],
+    body: &'t [Token<'a>],
+    kind: ClauseKind,
+    /// Extra indentation relative to the block, for join conditions
+    nested: bool,
+}
+
+/// A list item plus comments that followed its comma
+struct Item<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    comments: &'t [Token<'a>],
+}
+
+/// The last token written, for deciding spacing
+struct Prev {
+    kind: TokenKind,
+    upper: String,
+    /// A prefix operator such as unary minus
+    prefix: bool,
+}
+
+impl Prev {
+    fn new(kind: TokenKind, text: &str) -> Self {
+        Self { kind, upper: text.to_ascii_uppercase(), prefix: false }
+    }
+
+    fn is_keyword(&self) -> bool {
+        self.kind == TokenKind::Word && is_one_of(&self.upper, KEYWORDS)
+    }
+}
+
+struct Writer<'o> {
+    options: &'o FormatOptions,
+    out: String,
+    /// Indentation level of the current line
+    indent: usize,
+    /// A line comment was written, so the next token must start a new line
+    break_pending: bool,
+    prev: Option<Prev>,
+    /// Just wrote an object name after TABLE, INTO etc.
+    object_name: bool,
+}
+
+impl<'o> Writer<'o> {
+    fn new(options: &'o FormatOptions) -> Self {
+        Self {
+            options,
+            out: String::new(),
+            indent: 0,
+            break_pending: false,
+            prev: None,
+            object_name: false,
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn newline(&mut self, indent: usize) {
+        if !self.at_line_start() {
+            self.out.push('\n');
+        }
+        self.indent = indent;
+        self.break_pending = false;
+        self.prev = None;
+        self.object_name = false;
+    }
+
+    fn blank_line(&mut self) {
+        self.newline(0);
+        self.out.push('\n');
+    }
+
+    /// Current column, counting the indentation a fresh line is about to get
+    fn column(&self) -> usize {
+        if self.at_line_start() {
+            return self.indent * self.options.indent_width;
+        }
+        let line = self.out.rsplit('\n').next().unwrap_or_default();
+        line.chars().count()
+    }
+
+    fn write(&mut self, text: &str, space: bool) {
+        if self.break_pending {
+            self.newline(self.indent);
+        }
+        if self.at_line_start() {
+            self.out.extend(std::iter::repeat_n(' ', self.indent * self.options.indent_width));
+        } else if space {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+    }
+
+    /// Write one token; `next` is the token after it, if any
+    fn token(&mut self, token: &Token, next: Option<&Token>) {
+        let space = self.prev.as_ref().is_some_and(|prev| needs_space(prev, token, self.object_name));
+        let upper = token.text.to_ascii_uppercase();
+
+        let cased;
+        let text = if token.kind == TokenKind::Word
+            && self.options.keyword_case != KeywordCase::Preserve
+            && is_one_of(&upper, KEYWORDS)
+            && self.prev.as_ref().is_none_or(|prev| prev.kind != TokenKind::Dot)
+            && next.is_none_or(|next| next.kind != TokenKind::Dot)
+            && used_as_keyword(&upper, self.prev.as_ref(), next)
+        {
+            cased = match self.options.keyword_case {
+                KeywordCase::Lower => token.text.to_ascii_lowercase(),
+                _ => upper.clone(),
+            };
+            cased.as_str()
+        } else {
+            token.text
+        };
+
+        self.write(text, space);
+
+        let prefix = token.kind == TokenKind::Operator
+            && matches!(token.text, "-" | "+" | "~" | "@" | "@@")
+            && self.prev.as_ref().is_none_or(|prev| {
+                matches!(
+                    prev.kind,
+                    TokenKind::Operator | TokenKind::LParen | TokenKind::LBracket | TokenKind::Comma
+                ) || prev.is_keyword()
+            });
+
+        self.object_name = match token.kind {
+            TokenKind::Word if is_one_of(&upper, OBJECT_KEYWORDS) => true,
+            TokenKind::Word if is_one_of(&upper, KEYWORDS) => {
+                self.object_name && matches!(upper.as_str(), "IF" | "NOT" | "EXISTS")
+            }
+            TokenKind::Word | TokenKind::QuotedIdentifier | TokenKind::Dot => self.object_name,
+            _ => false,
+        };
+        self.prev = Some(Prev { kind: token.kind, upper, prefix });
+
+        if token.kind == TokenKind::LineComment {
+            self.break_pending = true;
+        }
+    }
+}
+
+struct Formatter<'o> {
+    options: &'o FormatOptions,
+}
+
+impl Formatter<'_> {
+    /// Write a statement or subquery, one clause per line
+    fn block(&self, w: &mut Writer, tokens: &[Token], indent: usize) {
+        for clause in split_clauses(tokens) {
+            let indent = indent + usize::from(clause.nested);
+            w.newline(indent);
+            self.inline(w, clause.header);
+
+            match clause.kind {
+                ClauseKind::List if !clause.body.is_empty() => {
+                    if self.fits(w, clause.body, 1) {
+                        self.inline(w, clause.body);
+                    } else {
+                        self.list(w, &split_items(clause.body), indent + 1);
+                    }
+                }
+                ClauseKind::Condition if !self.fits(w, clause.body, 1) => {
+                    let mut parts = split_conditions(clause.body).into_iter();
+                    if let Some(first) = parts.next() {
+                        self.inline(w, first);
+                    }
+                    for part in parts {
+                        w.newline(indent + 1);
+                        self.inline(w, part);
+                    }
+                }
+                _ => self.inline(w, clause.body),
+            }
+        }
+    }
+
+    /// Write tokens on the current line, laying out parenthesised groups
+    fn inline(&self, w: &mut Writer, tokens: &[Token]) {
+        let mut i = 0;
+        while i < tokens.len() {
+            if tokens[i].kind != TokenKind::LParen {
+                w.token(&tokens[i], tokens.get(i + 1));
+                i += 1;
+                continue;
+            }
+
+            let close = matching_paren(tokens, i).unwrap_or(tokens.len());
+            let inner = &tokens[i + 1..close];
+            let base = w.indent;
+            w.token(&tokens[i], inner.first());
+
+            if is_subquery(inner) {
+                self.block(w, inner, base + 1);
+                w.newline(base);
+            } else if inner.is_empty() || self.fits(w, inner, 1) {
+                self.inline(w, inner);
+            } else {
+                let items = split_items(inner);
+                if items.len() > 1 {
+                    self.list(w, &items, base + 1);
+                    w.newline(base);
+                } else {
+                    self.inline(w, inner);
+                }
+            }
+
+            if let Some(paren) = tokens.get(close) {
+                w.token(paren, tokens.get(close + 1));
+            }
+            i = close + 1;
+        }
+    }
+
+    /// Write list items one per line at `indent`
+    fn list(&self, w: &mut Writer, items: &[Item], indent: usize) {
+        let leading = self.options.comma_style == CommaStyle::Leading;
+
+        for (n, item) in items.iter().enumerate() {
+            w.newline(indent);
+            if leading && n > 0 {
+                w.write(",", false);
+                w.prev = Some(Prev::new(TokenKind::Comma, ","));
+            }
+
+            // With trailing commas the comma goes before comments ending the item,
+            // so a line comment doesn't swallow it
+            let body_end = if leading {
+                item.tokens.len()
+            } else {
+                item.tokens.iter().rposition(|t| !t.is_trivia()).map_or(0, |p| p + 1)
+            };
+            self.inline(w, &item.tokens[..body_end]);
+
+            if !leading && n + 1 < items.len() {
+                w.write(",", false);
+                w.prev = Some(Prev::new(TokenKind::Comma, ","));
+            }
+            for comment in item.tokens[body_end..].iter().chain(item.comments) {
+                w.token(comment, None);
+            }
+        }
+    }
+
+    /// Whether `tokens` fit on the current line with `reserve` columns to spare
+    fn fits(&self, w: &Writer, tokens: &[Token], reserve: usize) -> bool {
+        let space = usize::from(!w.at_line_start());
+        self.flat(tokens).is_some_and(|flat| {
+            w.column() + space + flat.chars().count() + reserve <= self.options.line_width
+        })
+    }
+
+    /// Render `tokens` on a single line, if they can be
+    fn flat(&self, tokens: &[Token]) -> Option<String> {
+        let breaks = tokens.iter().enumerate().any(|(i, t)| {
+            t.kind == TokenKind::LineComment
+                || (t.kind == TokenKind::LParen && is_subquery(&tokens[i + 1..]))
+        });
+        if breaks {
+            return None;
+        }
+
+        let options = FormatOptions { line_width: usize::MAX, ..self.options.clone() };
+        let mut w = Writer::new(&options);
+        Formatter { options: &options }.inline(&mut w, tokens);
+        Some(w.out)
+    }
+}
+
+fn is_one_of(upper: &str, words: &[&str]) -> bool {
+    words.contains(&upper)
+}
+
+/// Whether a keyword that is also a common name is used as a keyword here.
+/// DESC only sorts after an expression and FULL only starts a join.
+fn used_as_keyword(upper: &str, prev: Option<&Prev>, next: Option<&Token>) -> bool {
+    use TokenKind::*;
+
+    match upper {
+        "DESC" => prev.is_some_and(|prev| match prev.kind {
+            QuotedIdentifier | Number | String | RParen | RBracket => true,
+            Word => !is_one_of(&prev.upper, KEYWORDS),
+            _ => false,
+        }),
+        "FULL" => next.is_some_and(|next| next.is_keyword("JOIN") || next.is_keyword("OUTER")),
+        _ => match NAME_KEYWORDS.iter().find(|(word, _)| *word == upper) {
+            Some((_, after)) => prev.is_some_and(|prev| prev.kind == Word && is_one_of(&prev.upper, after)),
+            None => true,
+        },
+    }
+}
+
+fn needs_space(prev: &Prev, next: &Token, object_name: bool) -> bool {
+    use TokenKind::*;
+
+    if prev.prefix && matches!(next.kind, Word | QuotedIdentifier | Number | Parameter | LParen | String) {
+        return false;
+    }
+    if matches!(next.kind, Comma | Semicolon | RParen | RBracket | LBracket | Dot) {
+        return false;
+    }
+    if matches!(prev.kind, LParen | LBracket | Dot) {
+        return false;
+    }
+    if prev.upper == "::" || next.text == "::" {
+        return false;
+    }
+    if next.kind == LParen {
+        return match prev.kind {
+            Word => (prev.is_keyword() && !is_one_of(&prev.upper, FUNCTION_KEYWORDS)) || object_name,
+            QuotedIdentifier => object_name,
+            RParen | RBracket => false,
+            _ => true,
+        };
+    }
+    true
+}
+
+/// First token that isn't a comment
+fn first_significant<'t, 'a>(tokens: &'t [Token<'a>]) -> Option<&'t Token<'a>> {
+    tokens.iter().find(|t| !t.is_trivia())
+}
+
+fn is_subquery(inner: &[Token]) -> bool {
+    first_significant(inner).is_some_and(|t| {
+        t.kind == TokenKind::Word && is_one_of(&t.text.to_ascii_uppercase(), SUBQUERY_KEYWORDS)
+    })
+}
+
+/// Split tokens at top-level semicolons
+fn split_statements<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<(&'t [Token<'a>], bool)> {
+    let mut statements = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LParen => depth += 1,
+            TokenKind::RParen => depth -= 1,
+            TokenKind::Semicolon if depth <= 0 => {
+                statements.push((&tokens[start..i], true));
+                start = i + 1;
+                depth = 0;
+            }
+            _ => {}
+        }
+    }
+    if start < tokens.len() {
+        statements.push((&tokens[start..], false));
+    }
+
+    statements
+}
+
+/// Split a statement into clauses, each starting with its keyword(s)
+fn split_clauses<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<Clause<'t, 'a>> {
+    let first_word = first_significant(tokens)
+        .map(|t| t.text.to_ascii_uppercase())
+        .unwrap_or_default();
+
+    let mut clauses = Vec::new();
+    let mut start = 0;
+    let mut header_end = 0;
+    let mut current = String::new();
+    let mut depth = 0i32;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        match tokens[i].kind {
+            TokenKind::LParen => depth += 1,
+            TokenKind::RParen => depth -= 1,
+            TokenKind::Word if depth == 0 && i >= header_end && is_clause_start(tokens, i, &current, &first_word) => {
+                if i > start {
+                    clauses.push(clause(&tokens[start..header_end], &tokens[header_end..i], &current));
+                }
+                start = i;
+                header_end = i + header_len(tokens, i);
+                current = tokens[i].text.to_ascii_uppercase();
+                i = header_end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if start < tokens.len() {
+        clauses.push(clause(&tokens[start..header_end.max(start)], &tokens[header_end.max(start)..], &current));
+    }
+
+    clauses
+}
+
+fn clause<'t, 'a>(header: &'t [Token<'a>], body: &'t [Token<'a>], keyword: &str) -> Clause<'t, 'a> {
+    let kind = match keyword {
+        "SELECT" | "FROM" | "GROUP" | "ORDER" | "RETURNING" | "SET" | "WITH" | "VALUES" | "WINDOW" => ClauseKind::List,
+        "WHERE" | "HAVING" | "ON" => ClauseKind::Condition,
+        _ => ClauseKind::Inline,
+    };
+    Clause { header, body, kind, nested: keyword == "ON" }
+}
+
+fn is_clause_start(tokens: &[Token], i: usize, current: &str, first_word: &str) -> bool {
+    let word = tokens[i].text.to_ascii_uppercase();
+    let prev = tokens[..i].iter().rev().find(|t| !t.is_trivia());
+    let next = tokens[i + 1..].iter().find(|t| !t.is_trivia());
+    let prev_word = prev.map(|t| t.text.to_ascii_uppercase()).unwrap_or_default();
+
+    // Keywords used as expressions or names, as in `= VALUES(a)` or `t.order`
+    if prev.is_some_and(|t| matches!(t.kind, TokenKind::Operator | TokenKind::LParen | TokenKind::Comma | TokenKind::Dot))
+        || next.is_some_and(|t| t.kind == TokenKind::Dot)
+    {
+        return false;
+    }
+
+    match word.as_str() {
+        "SELECT" | "INSERT" => !matches!(prev_word.as_str(), "GRANT" | "REVOKE"),
+        "WHERE" | "HAVING" | "LIMIT" | "OFFSET" | "RETURNING" | "WINDOW" | "UNION" | "INTERSECT" | "EXCEPT"
+        | "VALUES" => true,
+        "FROM" => prev_word != "DISTINCT",
+        "GROUP" | "ORDER" => next.is_some_and(|t| t.is_keyword("BY")) && prev_word != "WITHIN",
+        // Not FK actions (`ON DELETE`), locking (`FOR UPDATE`), upserts or privileges
+        "UPDATE" | "DELETE" => !matches!(prev_word.as_str(), "ON" | "FOR" | "DO" | "KEY" | "GRANT" | "REVOKE"),
+        "SET" => first_word == "UPDATE" && !matches!(prev_word.as_str(), "DELETE" | "UPDATE"),
+        "FETCH" => next.is_some_and(|t| t.is_keyword("FIRST") || t.is_keyword("NEXT")),
+        "WITH" => prev.is_none(),
+        "JOIN" => true,
+        "ON" => current.ends_with("JOIN") || is_one_of(current, JOIN_MODIFIERS),
+        w if is_one_of(w, JOIN_MODIFIERS) && w != "OUTER" => tokens[i + 1..]
+            .iter()
+            .take_while(|t| t.kind == TokenKind::Word)
+            .take(3)
+            .any(|t| t.is_keyword("JOIN")),
+        _ => false,
+    }
+}
+
+/// Number of tokens forming the header of the clause starting at `i`
+fn header_len(tokens: &[Token], i: usize) -> usize {
+    let word = tokens[i].text.to_ascii_uppercase();
+    let next_is = |offset: usize, keywords: &[&str]| {
+        tokens
+            .get(i + offset)
+            .is_some_and(|t| t.kind == TokenKind::Word && is_one_of(&t.text.to_ascii_uppercase(), keywords))
+    };
+
+    match word.as_str() {
+        "GROUP" | "ORDER" => 2,
+        "UNION" | "INTERSECT" | "EXCEPT" if next_is(1, &["ALL", "DISTINCT"]) => 2,
+        "INSERT" if next_is(1, &["INTO"]) => 2,
+        "DELETE" if next_is(1, &["FROM"]) => 2,
+        "WITH" if next_is(1, &["RECURSIVE"]) => 2,
+        "SELECT" if next_is(1, &["DISTINCT", "ALL"]) && !next_is(2, &["ON"]) => 2,
+        w if is_one_of(w, JOIN_MODIFIERS) => {
+            let mut len = 1;
+            while next_is(len, JOIN_MODIFIERS) {
+                len += 1;
+            }
+            if next_is(len, &["JOIN"]) {
+                len += 1;
+            }
+            len
+        }
+        _ => 1,
+    }
+}
+
+/// Split at top-level commas. Comments directly after a comma stay with the
+/// item before it.
+fn split_items<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<Item<'t, 'a>> {
+    let mut items = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match tokens[i].kind {
+            TokenKind::LParen => depth += 1,
+            TokenKind::RParen => depth -= 1,
+            TokenKind::Comma if depth == 0 => {
+                let comments_end = tokens[i + 1..]
+                    .iter()
+                    .position(|t| !t.is_trivia())
+                    .map_or(tokens.len(), |p| i + 1 + p);
+                items.push(Item { tokens: &tokens[start..i], comments: &tokens[i + 1..comments_end] });
+                start = comments_end;
+                i = comments_end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    items.push(Item { tokens: &tokens[start..], comments: &[] });
+
+    items
+}
+
+/// Split at top-level AND/OR, keeping `BETWEEN x AND y` together. Every part
+/// after the first starts with its AND/OR.
+fn split_conditions<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<&'t [Token<'a>]> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut between = false;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LParen => depth += 1,
+            TokenKind::RParen => depth -= 1,
+            TokenKind::Word if depth == 0 => {
+                if token.is_keyword("BETWEEN") {
+                    between = true;
+                } else if token.is_keyword("AND") && between {
+                    between = false;
+                } else if (token.is_keyword("AND") || token.is_keyword("OR")) && i > start {
+                    parts.push(&tokens[start..i]);
+                    start = i;
+                }
+            }
+            _ => {}
+        }
+    }
+    parts.push(&tokens[start..]);
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERIES: &[&str] = &[
+        "select u.id, count(*) as n from users u left join orders o on o.user_id = u.id and o.total > 10 \
+         where u.name like 'a%' and u.id between 1 and 5 group by u.id having count(*) > 1 order by n desc limit 10",
+        "with recent as (select * from orders where created_at > now() - interval '1 day') \
+         insert into archive (id, total) select id, total from recent returning id",
+        "update users set name = 'x', -- rename\n email = null where id in (select user_id from orders); delete from t",
+        "select a /* inline */ from t # trailing\n union all select b from u",
+        "select 5--1, $tag$ body; $tag$, `quoted``name` from t",
+    ];
+
+    fn format(sql: &str, dialect: DatabaseType) -> String {
+        format_sql(sql, dialect, &FormatOptions::default())
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        for sql in QUERIES {
+            for dialect in [DatabaseType::Postgres, DatabaseType::Mysql] {
+                let once = format(sql, dialect);
+                assert_eq!(format(&once, dialect), once, "{}", sql);
+            }
+        }
+    }
+
+    #[test]
+    fn partial_statements_do_not_panic() {
+        for sql in QUERIES {
+            for (end, _) in sql.char_indices() {
+                format(&sql[..end], DatabaseType::Postgres);
+                format(&sql[..end], DatabaseType::Mysql);
+            }
+        }
+    }
+
+    #[test]
+    fn dialect_comments() {
+        // MySQL reads `--1` as two minus signs, Postgres as a comment
+        assert_eq!(format("select 5--1", DatabaseType::Mysql), "SELECT 5 - -1");
+        assert!(format("select 5--1", DatabaseType::Postgres).ends_with("--1"));
+        assert!(format("select a # note\nfrom t", DatabaseType::Mysql).contains("# note"));
+        assert!(format("select a /* x /* y */ z */ from t", DatabaseType::Postgres).contains("/* x /* y */ z */"));
+    }
+
+    #[test]
+    fn unterminated_literals_are_kept() {
+        assert!(format("select 'abc", DatabaseType::Postgres).ends_with("'abc"));
+        assert!(format("select * from \"café", DatabaseType::Postgres).ends_with("\"café"));
+        assert!(format("select 1 /* open", DatabaseType::Mysql).ends_with("/* open"));
+    }
+
+    fn format_with(sql: &str, options: FormatOptions) -> String {
+        format_sql(sql, DatabaseType::Postgres, &options)
+    }
+
+    #[test]
+    fn keyword_case() {
+        let sql = "Select id From users Where id = 1";
+        assert_eq!(format(sql, DatabaseType::Postgres), "SELECT id\nFROM users\nWHERE id = 1");
+        let lower = FormatOptions { keyword_case: KeywordCase::Lower, ..FormatOptions::default() };
+        assert_eq!(format_with(sql, lower), "select id\nfrom users\nwhere id = 1");
+        let preserve = FormatOptions { keyword_case: KeywordCase::Preserve, ..FormatOptions::default() };
+        assert_eq!(format_with(sql, preserve), "Select id\nFrom users\nWhere id = 1");
+    }
+
+    #[test]
+    fn names_that_are_keywords_keep_their_case() {
+        assert_eq!(
+            format("select key, index, column, view, desc, full from t order by key", DatabaseType::Postgres),
+            "SELECT key, index, column, view, desc, full\nFROM t\nORDER BY key"
+        );
+        assert_eq!(
+            format(
+                "alter table t add column c int, add primary key (id); select * from a full join b on a.k = b.k order by a.k desc",
+                DatabaseType::Postgres
+            ),
+            "ALTER TABLE t ADD COLUMN c int, ADD PRIMARY KEY (id);\n\nSELECT * FROM a\nFULL JOIN b\n  ON a.k = b.k\nORDER BY a.k DESC"
+        );
+        assert_eq!(
+            format("insert into t (a) values (1) on duplicate key update a = 2", DatabaseType::Mysql),
+            "INSERT INTO t (a)\nVALUES (1) ON DUPLICATE KEY UPDATE a = 2"
+        );
+    }
+
+    #[test]
+    fn lists_break_at_the_line_width() {
+        let sql = "select id, name, email from users";
+        assert_eq!(format(sql, DatabaseType::Postgres), "SELECT id, name, email\nFROM users");
+        let narrow = FormatOptions { line_width: 20, ..FormatOptions::default() };
+        assert_eq!(format_with(sql, narrow), "SELECT\n  id,\n  name,\n  email\nFROM users");
+    }
+
+    #[test]
+    fn leading_commas() {
+        let options = FormatOptions { line_width: 20, comma_style: CommaStyle::Leading, ..FormatOptions::default() };
+        assert_eq!(
+            format_with("select id, name, email from users", options),
+            "SELECT\n  id\n  , name\n  , email\nFROM users"
+        );
+    }
+
+    #[test]
+    fn indent_width() {
+        let options = FormatOptions { line_width: 20, indent_width: 4, ..FormatOptions::default() };
+        assert_eq!(
+            format_with("select id from t where a = 1 and b = 2 and c = 3", options),
+            "SELECT id\nFROM t\nWHERE a = 1\n    AND b = 2\n    AND c = 3"
+        );
+    }
+}
@@ -0,0 +1,709 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use sqlx::mysql::MySqlPool;
+use sqlx::postgres::PgPool;
+use sqlx::Row;
+
+use super::connection::{ConnectionError, DatabasePool};
+use super::introspection::{pg_action, pg_column_names};
+use super::types::*;
+
+/// Schema-qualified table name
+type TableKey = (String, String);
+
+/// What the graph is built from: foreign keys across all user schemas, and
+/// columns and unique keys of the schemas the chosen tables live in
+struct Catalog {
+    schema: String,
+    tables: Vec<String>,
+    keys: Vec<ForeignKey>,
+}
+
+/// Tables of a schema (or the requested subset) with the foreign keys between
+/// them and, with `hops`, the tables that many keys away
+pub async fn relationships(
+    pool: &DatabasePool,
+    params: &RelationshipGraphParams,
+) -> Result<RelationshipGraph, ConnectionError> {
+    let catalog = match pool {
+        DatabasePool::Postgres(pool) => postgres_catalog(pool, params.schema.as_deref()).await?,
+        DatabasePool::MySql(pool) => mysql_catalog(pool, params.schema.as_deref()).await?,
+    };
+
+    let mut start = Vec::new();
+    if params.tables.is_empty() {
+        start.extend(catalog.tables.iter().cloned());
+    } else {
+        for table in &params.tables {
+            if !catalog.tables.contains(table) {
+                return Err(ConnectionError::NotFound(format!("{}.{}", catalog.schema, table)));
+            }
+            start.push(table.clone());
+        }
+    }
+
+    // Breadth-first over keys in either direction, keeping each table's distance
+    let mut hops: BTreeMap<TableKey, u32> =
+        start.into_iter().map(|table| ((catalog.schema.clone(), table), 0)).collect();
+    let mut frontier: HashSet<TableKey> = hops.keys().cloned().collect();
+    for hop in 1..=params.hops {
+        let mut next = HashSet::new();
+        for key in &catalog.keys {
+            let from = (key.schema.clone(), key.table.clone());
+            let to = (key.referenced_schema.clone(), key.referenced_table.clone());
+            for (near, far) in [(&from, &to), (&to, &from)] {
+                if frontier.contains(near) && !hops.contains_key(far) {
+                    next.insert(far.clone());
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        for table in &next {
+            hops.insert(table.clone(), hop);
+        }
+        frontier = next;
+    }
+
+    let schemas: Vec<String> =
+        hops.keys().map(|(schema, _)| schema.clone()).collect::<BTreeSet<_>>().into_iter().collect();
+    let (mut columns, unique_keys) = match pool {
+        DatabasePool::Postgres(pool) => postgres_columns(pool, &schemas).await?,
+        DatabasePool::MySql(pool) => mysql_columns(pool, &schemas).await?,
+    };
+
+    let keys: Vec<&ForeignKey> = catalog
+        .keys
+        .iter()
+        .filter(|key| {
+            hops.contains_key(&(key.schema.clone(), key.table.clone()))
+                && hops.contains_key(&(key.referenced_schema.clone(), key.referenced_table.clone()))
+        })
+        .collect();
+
+    let mut foreign_key_columns: HashSet<(String, String, String)> = HashSet::new();
+    for key in &keys {
+        for column in &key.columns {
+            foreign_key_columns.insert((key.schema.clone(), key.table.clone(), column.clone()));
+        }
+    }
+
+    let nodes = hops
+        .iter()
+        .map(|((schema, name), hops)| {
+            let mut table_columns = columns.remove(&(schema.clone(), name.clone())).unwrap_or_default();
+            for column in &mut table_columns {
+                column.is_foreign_key =
+                    foreign_key_columns.contains(&(schema.clone(), name.clone(), column.name.clone()));
+            }
+            RelationshipNode {
+                schema: schema.clone(),
+                name: name.clone(),
+                columns: table_columns,
+                hops: *hops,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let edges = keys
+        .into_iter()
+        .map(|key| {
+            let table = (key.schema.clone(), key.table.clone());
+            let key_columns: BTreeSet<&String> = key.columns.iter().collect();
+            let one_to_one = unique_keys
+                .get(&table)
+                .is_some_and(|uniques| uniques.iter().any(|u| u.iter().collect::<BTreeSet<_>>() == key_columns));
+            let optional = nodes
+                .iter()
+                .find(|n| n.schema == key.schema && n.name == key.table)
+                .is_some_and(|n| n.columns.iter().any(|c| c.nullable && key.columns.contains(&c.name)));
+            RelationshipEdge {
+                name: key.name.clone(),
+                schema: key.schema.clone(),
+                table: key.table.clone(),
+                referenced_schema: key.referenced_schema.clone(),
+                referenced_table: key.referenced_table.clone(),
+                columns: key
+                    .columns
+                    .iter()
+                    .zip(&key.referenced_columns)
+                    .map(|(column, referenced)| ColumnMapping {
+                        column: column.clone(),
+                        referenced_column: referenced.clone(),
+                    })
+                    .collect(),
+                cardinality: if one_to_one {
+                    Cardinality::OneToOne
+                } else {
+                    Cardinality::ManyToOne
+                },
+                optional,
+                on_update: key.on_update.clone(),
+                on_delete: key.on_delete.clone(),
+            }
+        })
+        .collect();
+
+    Ok(RelationshipGraph { nodes, edges })
+}
+
+async fn postgres_catalog(pool: &PgPool, schema: Option<&str>) -> Result<Catalog, ConnectionError> {
+    let schema: String = sqlx::query_scalar("SELECT COALESCE($1, current_schema())::text")
+        .bind(schema)
+        .fetch_one(pool)
+        .await?;
+
+    // Partitions are drawn as their parent
+    let tables = sqlx::query_scalar(
+        r#"
+        SELECT c.relname::text
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1 AND c.relkind IN ('r', 'p') AND NOT c.relispartition
+        ORDER BY c.relname
+        "#,
+    )
+    .bind(&schema)
+    .fetch_all(pool)
+    .await?;
+
+    let query = format!(
+        r#"
+        SELECT
+            con.conname AS name,
+            sn.nspname AS schema,
+            sc.relname AS table,
+            {} AS columns,
+            tn.nspname AS referenced_schema,
+            tc.relname AS referenced_table,
+            {} AS referenced_columns,
+            con.confupdtype::text AS on_update,
+            con.confdeltype::text AS on_delete
+        FROM pg_constraint con
+        JOIN pg_class sc ON sc.oid = con.conrelid
+        JOIN pg_namespace sn ON sn.oid = sc.relnamespace
+        JOIN pg_class tc ON tc.oid = con.confrelid
+        JOIN pg_namespace tn ON tn.oid = tc.relnamespace
+        WHERE con.contype = 'f' AND con.conparentid = 0
+        ORDER BY sn.nspname, sc.relname, con.conname
+        "#,
+        pg_column_names("con.conkey", "con.conrelid"),
+        pg_column_names("con.confkey", "con.confrelid")
+    );
+    let keys = sqlx::query(&query)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| ForeignKey {
+            name: row.try_get("name").unwrap_or_default(),
+            schema: row.try_get("schema").unwrap_or_default(),
+            table: row.try_get("table").unwrap_or_default(),
+            columns: row.try_get("columns").unwrap_or_default(),
+            referenced_schema: row.try_get("referenced_schema").unwrap_or_default(),
+            referenced_table: row.try_get("referenced_table").unwrap_or_default(),
+            referenced_columns: row.try_get("referenced_columns").unwrap_or_default(),
+            on_update: pg_action(&row.try_get::<String, _>("on_update").unwrap_or_default()),
+            on_delete: pg_action(&row.try_get::<String, _>("on_delete").unwrap_or_default()),
+        })
+        .collect();
+
+    Ok(Catalog { schema, tables, keys })
+}
+
+type Columns = HashMap<TableKey, Vec<GraphColumn>>;
+/// Column sets of each table's unique indexes
+type UniqueKeys = HashMap<TableKey, Vec<Vec<String>>>;
+
+async fn postgres_columns(pool: &PgPool, schemas: &[String]) -> Result<(Columns, UniqueKeys), ConnectionError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            n.nspname AS schema,
+            c.relname AS table,
+            a.attname AS name,
+            format_type(a.atttypid, a.atttypmod) AS data_type,
+            NOT a.attnotnull AS nullable,
+            EXISTS (
+                SELECT 1 FROM pg_index i
+                WHERE i.indrelid = c.oid AND i.indisprimary AND a.attnum = ANY(i.indkey)
+            ) AS is_primary_key
+        FROM pg_attribute a
+        JOIN pg_class c ON c.oid = a.attrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = ANY($1) AND c.relkind IN ('r', 'p') AND a.attnum > 0 AND NOT a.attisdropped
+        ORDER BY n.nspname, c.relname, a.attnum
+        "#,
+    )
+    .bind(schemas)
+    .fetch_all(pool)
+    .await?;
+    let mut columns: Columns = HashMap::new();
+    for row in rows {
+        columns
+            .entry((row.try_get("schema")?, row.try_get("table")?))
+            .or_default()
+            .push(GraphColumn {
+                name: row.try_get("name")?,
+                data_type: row.try_get("data_type")?,
+                nullable: row.try_get("nullable")?,
+                is_primary_key: row.try_get("is_primary_key")?,
+                is_foreign_key: false,
+            });
+    }
+
+    // Partial and expression indexes don't make a plain column set unique
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT n.nspname AS schema, c.relname AS table, {} AS columns
+        FROM pg_index i
+        JOIN pg_class c ON c.oid = i.indrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = ANY($1)
+          AND i.indisunique
+          AND i.indpred IS NULL
+          AND i.indexprs IS NULL
+        "#,
+        pg_column_names("i.indkey[0:i.indnkeyatts - 1]", "i.indrelid")
+    ))
+    .bind(schemas)
+    .fetch_all(pool)
+    .await?;
+    let mut unique_keys: UniqueKeys = HashMap::new();
+    for row in rows {
+        unique_keys
+            .entry((row.try_get("schema")?, row.try_get("table")?))
+            .or_default()
+            .push(row.try_get("columns")?);
+    }
+    Ok((columns, unique_keys))
+}
+
+async fn mysql_catalog(pool: &MySqlPool, schema: Option<&str>) -> Result<Catalog, ConnectionError> {
+    let schema: Option<String> = sqlx::query_scalar("SELECT COALESCE(?, DATABASE())")
+        .bind(schema)
+        .fetch_one(pool)
+        .await?;
+    let schema =
+        schema.ok_or_else(|| ConnectionError::InvalidInput("No database selected; specify a schema".to_string()))?;
+
+    let tables = sqlx::query_scalar(
+        r#"
+        SELECT TABLE_NAME
+        FROM information_schema.TABLES
+        WHERE TABLE_SCHEMA = ? AND TABLE_TYPE = 'BASE TABLE'
+        ORDER BY TABLE_NAME
+        "#,
+    )
+    .bind(&schema)
+    .fetch_all(pool)
+    .await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            k.CONSTRAINT_NAME AS name,
+            k.TABLE_SCHEMA AS `schema`,
+            k.TABLE_NAME AS `table`,
+            k.COLUMN_NAME AS column_name,
+            k.REFERENCED_TABLE_SCHEMA AS referenced_schema,
+            k.REFERENCED_TABLE_NAME AS referenced_table,
+            k.REFERENCED_COLUMN_NAME AS referenced_column,
+            r.UPDATE_RULE AS on_update,
+            r.DELETE_RULE AS on_delete
+        FROM information_schema.KEY_COLUMN_USAGE k
+        JOIN information_schema.REFERENTIAL_CONSTRAINTS r
+            ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA
+            AND r.TABLE_NAME = k.TABLE_NAME
+            AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME
+        WHERE k.REFERENCED_TABLE_NAME IS NOT NULL
+          AND k.TABLE_SCHEMA NOT IN ('mysql', 'sys', 'performance_schema', 'information_schema')
+        ORDER BY k.TABLE_SCHEMA, k.TABLE_NAME, k.CONSTRAINT_NAME, k.ORDINAL_POSITION
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut keys: Vec<ForeignKey> = Vec::new();
+    for row in &rows {
+        let name: String = row.try_get("name").unwrap_or_default();
+        let from_schema: String = row.try_get("schema").unwrap_or_default();
+        let from_table: String = row.try_get("table").unwrap_or_default();
+        let column: String = row.try_get("column_name").unwrap_or_default();
+        let referenced_column: String = row.try_get("referenced_column").unwrap_or_default();
+        match keys.last_mut() {
+            Some(key) if key.name == name && key.schema == from_schema && key.table == from_table => {
+                key.columns.push(column);
+                key.referenced_columns.push(referenced_column);
+            }
+            _ => keys.push(ForeignKey {
+                name,
+                schema: from_schema,
+                table: from_table,
+                columns: vec![column],
+                referenced_schema: row.try_get("referenced_schema").unwrap_or_default(),
+                referenced_table: row.try_get("referenced_table").unwrap_or_default(),
+                referenced_columns: vec![referenced_column],
+                on_update: row.try_get("on_update").unwrap_or_default(),
+                on_delete: row.try_get("on_delete").unwrap_or_default(),
+            }),
+        }
+    }
+
+    Ok(Catalog { schema, tables, keys })
+}
+
+async fn mysql_columns(pool: &MySqlPool, schemas: &[String]) -> Result<(Columns, UniqueKeys), ConnectionError> {
+    let mut columns: Columns = HashMap::new();
+    let mut unique_keys: UniqueKeys = HashMap::new();
+    if schemas.is_empty() {
+        return Ok((columns, unique_keys));
+    }
+    let placeholders = vec!["?"; schemas.len()].join(", ");
+
+    let query = format!(
+        r#"
+        SELECT
+            c.TABLE_SCHEMA AS `schema`,
+            c.TABLE_NAME AS `table`,
+            c.COLUMN_NAME AS name,
+            c.COLUMN_TYPE AS data_type,
+            c.IS_NULLABLE = 'YES' AS nullable,
+            c.COLUMN_KEY = 'PRI' AS is_primary_key
+        FROM information_schema.COLUMNS c
+        WHERE c.TABLE_SCHEMA IN ({placeholders})
+        ORDER BY c.TABLE_SCHEMA, c.TABLE_NAME, c.ORDINAL_POSITION
+        "#
+    );
+    let mut query = sqlx::query(&query);
+    for schema in schemas {
+        query = query.bind(schema);
+    }
+    for row in query.fetch_all(pool).await? {
+        columns
+            .entry((row.try_get("schema")?, row.try_get("table")?))
+            .or_default()
+            .push(GraphColumn {
+                name: row.try_get("name")?,
+                data_type: row.try_get("data_type")?,
+                nullable: row.try_get::<i64, _>("nullable")? != 0,
+                is_primary_key: row.try_get::<i64, _>("is_primary_key")? != 0,
+                is_foreign_key: false,
+            });
+    }
+
+    // Functional key parts have no column name and can't match a foreign key
+    let query = format!(
+        r#"
+        SELECT TABLE_SCHEMA AS `schema`, TABLE_NAME AS `table`, INDEX_NAME AS name, COLUMN_NAME AS column_name
+        FROM information_schema.STATISTICS
+        WHERE TABLE_SCHEMA IN ({placeholders}) AND NON_UNIQUE = 0
+        ORDER BY TABLE_SCHEMA, TABLE_NAME, INDEX_NAME, SEQ_IN_INDEX
+        "#
+    );
+    let mut query = sqlx::query(&query);
+    for schema in schemas {
+        query = query.bind(schema);
+    }
+    let mut indexes: BTreeMap<(String, String, String), Option<Vec<String>>> = BTreeMap::new();
+    for row in query.fetch_all(pool).await? {
+        let column: Option<String> = row.try_get("column_name")?;
+        let index = indexes
+            .entry((row.try_get("schema")?, row.try_get("table")?, row.try_get("name")?))
+            .or_insert_with(|| Some(Vec::new()));
+        match column {
+            Some(column) => {
+                if let Some(index) = index {
+                    index.push(column);
+                }
+            }
+            None => *index = None,
+        }
+    }
+    for ((schema, table, _), index) in indexes {
+        if let Some(index) = index {
+            unique_keys.entry((schema, table)).or_default().push(index);
+        }
+    }
+    Ok((columns, unique_keys))
+}
+
+/// Node ID shared by the export formats: the bare name unless tables from
+/// several schemas appear
+fn node_label(graph: &RelationshipGraph, schema: &str, name: &str) -> String {
+    let one_schema = graph.nodes.iter().all(|n| n.schema == graph.nodes[0].schema);
+    if one_schema {
+        name.to_string()
+    } else {
+        format!("{}.{}", schema, name)
+    }
+}
+
+/// Graphviz DOT with each table as an HTML-like record, one row per column
+pub fn to_dot(graph: &RelationshipGraph) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+    let id = |schema: &str, name: &str| dot_string(&node_label(graph, schema, name));
+
+    let mut dot = String::from("digraph relationships {\n    rankdir=LR;\n    node [shape=plaintext];\n");
+    for node in &graph.nodes {
+        let mut label = format!(
+            "<TABLE BORDER=\"0\" CELLBORDER=\"1\" CELLSPACING=\"0\"><TR><TD BGCOLOR=\"lightgrey\"><B>{}</B></TD></TR>",
+            escape(&node_label(graph, &node.schema, &node.name))
+        );
+        for (i, column) in node.columns.iter().enumerate() {
+            let mut keys = Vec::new();
+            if column.is_primary_key {
+                keys.push("PK");
+            }
+            if column.is_foreign_key {
+                keys.push("FK");
+            }
+            let keys = if keys.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", keys.join(", "))
+            };
+            label.push_str(&format!(
+                "<TR><TD PORT=\"c{}\" ALIGN=\"LEFT\">{}: {}{}</TD></TR>",
+                i,
+                escape(&column.name),
+                escape(&column.data_type),
+                keys
+            ));
+        }
+        label.push_str("</TABLE>");
+        dot.push_str(&format!("    {} [label=<{}>];\n", id(&node.schema, &node.name), label));
+    }
+
+    for edge in &graph.edges {
+        // Anchor on the first column pair of the key
+        let port = |schema: &str, table: &str, column: Option<&String>| {
+            graph
+                .nodes
+                .iter()
+                .find(|n| n.schema == schema && n.name == table)
+                .and_then(|n| n.columns.iter().position(|c| Some(&c.name) == column))
+                .map(|i| format!(":c{}", i))
+                .unwrap_or_default()
+        };
+        let from = port(&edge.schema, &edge.table, edge.columns.first().map(|m| &m.column));
+        let to = port(
+            &edge.referenced_schema,
+            &edge.referenced_table,
+            edge.columns.first().map(|m| &m.referenced_column),
+        );
+        let tail = match edge.cardinality {
+            Cardinality::OneToOne => "teeodot",
+            Cardinality::ManyToOne => "crowodot",
+        };
+        let head = if edge.optional { "teeodot" } else { "teetee" };
+        dot.push_str(&format!(
+            "    {}{} -> {}{} [label={}, dir=both, arrowtail={}, arrowhead={}];\n",
+            id(&edge.schema, &edge.table),
+            from,
+            id(&edge.referenced_schema, &edge.referenced_table),
+            to,
+            dot_string(&edge.name),
+            tail,
+            head
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// A quoted DOT ID
+fn dot_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Mermaid identifier: word characters, with everything else replaced
+fn mermaid_word(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect()
+}
+
+/// Mermaid entity ID of each node, in node order. Names that are already
+/// words keep them; the others get their node's index appended until the ID
+/// is unused, so `order items` can't merge with `order_items`.
+fn mermaid_ids(graph: &RelationshipGraph) -> Vec<String> {
+    let labels: Vec<String> = graph.nodes.iter().map(|n| node_label(graph, &n.schema, &n.name)).collect();
+    let mut used: HashSet<String> = labels.iter().filter(|l| mermaid_word(l) == **l).cloned().collect();
+    labels
+        .iter()
+        .enumerate()
+        .map(|(i, label)| {
+            let word = mermaid_word(label);
+            if word == *label {
+                return word;
+            }
+            let mut id = format!("{}_{}", word, i);
+            while used.contains(&id) {
+                id.push('_');
+            }
+            used.insert(id.clone());
+            id
+        })
+        .collect()
+}
+
+/// Mermaid `erDiagram`. Entity IDs are restricted to word characters, so
+/// other names are shown through an alias.
+pub fn to_mermaid(graph: &RelationshipGraph) -> String {
+    let ids = mermaid_ids(graph);
+    let entity = |schema: &str, name: &str| {
+        graph
+            .nodes
+            .iter()
+            .position(|n| n.schema == schema && n.name == name)
+            .map(|i| ids[i].clone())
+            .unwrap_or_else(|| mermaid_word(&node_label(graph, schema, name)))
+    };
+
+    let mut mermaid = String::from("erDiagram\n");
+    for (node, id) in graph.nodes.iter().zip(&ids) {
+        let label = node_label(graph, &node.schema, &node.name);
+        if *id == label {
+            mermaid.push_str(&format!("    {} {{\n", id));
+        } else {
+            mermaid.push_str(&format!("    {}[\"{}\"] {{\n", id, label.replace('"', "'")));
+        }
+        for column in &node.columns {
+            let mut keys = Vec::new();
+            if column.is_primary_key {
+                keys.push("PK");
+            }
+            if column.is_foreign_key {
+                keys.push("FK");
+            }
+            let keys = if keys.is_empty() {
+                String::new()
+            } else {
+                format!(" {}", keys.join(", "))
+            };
+            mermaid.push_str(&format!(
+                "        {} {}{}\n",
+                mermaid_word(&column.data_type),
+                mermaid_word(&column.name),
+                keys
+            ));
+        }
+        mermaid.push_str("    }\n");
+    }
+
+    for edge in &graph.edges {
+        let from = match edge.cardinality {
+            Cardinality::OneToOne => "|o",
+            Cardinality::ManyToOne => "}o",
+        };
+        let to = if edge.optional { "o|" } else { "||" };
+        mermaid.push_str(&format!(
+            "    {} {}--{} {} : \"{}\"\n",
+            entity(&edge.schema, &edge.table),
+            from,
+            to,
+            entity(&edge.referenced_schema, &edge.referenced_table),
+            edge.name.replace('"', "'")
+        ));
+    }
+    mermaid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(schema: &str, name: &str, columns: &[&str]) -> RelationshipNode {
+        RelationshipNode {
+            schema: schema.to_string(),
+            name: name.to_string(),
+            columns: columns
+                .iter()
+                .map(|c| GraphColumn {
+                    name: c.to_string(),
+                    data_type: "integer".to_string(),
+                    nullable: false,
+                    is_primary_key: false,
+                    is_foreign_key: false,
+                })
+                .collect(),
+            hops: 0,
+        }
+    }
+
+    fn edge(name: &str, table: &str, column: &str, referenced_table: &str) -> RelationshipEdge {
+        RelationshipEdge {
+            name: name.to_string(),
+            schema: "public".to_string(),
+            table: table.to_string(),
+            referenced_schema: "public".to_string(),
+            referenced_table: referenced_table.to_string(),
+            columns: vec![ColumnMapping {
+                column: column.to_string(),
+                referenced_column: "id".to_string(),
+            }],
+            cardinality: Cardinality::ManyToOne,
+            optional: false,
+            on_update: "NO ACTION".to_string(),
+            on_delete: "CASCADE".to_string(),
+        }
+    }
+
+    #[test]
+    fn mermaid_ids_stay_unique_after_replacing_characters() {
+        let graph = RelationshipGraph {
+            nodes: vec![
+                node("public", "order items", &["id"]),
+                node("public", "order_items", &["id"]),
+                node("public", "order-items", &["id"]),
+            ],
+            edges: vec![edge("fk", "order items", "id", "order_items")],
+        };
+        assert_eq!(mermaid_ids(&graph), ["order_items_0", "order_items", "order-items"]);
+
+        let mermaid = to_mermaid(&graph);
+        assert!(mermaid.contains("    order_items_0[\"order items\"] {\n"));
+        assert!(mermaid.contains("    order_items {\n"));
+        assert!(mermaid.contains("    order_items_0 }o--|| order_items : \"fk\"\n"));
+    }
+
+    #[test]
+    fn mermaid_quotes_are_replaced_in_aliases_and_labels() {
+        let graph = RelationshipGraph {
+            nodes: vec![node("public", "say \"hi\"", &["a b"]), node("public", "t", &["id"])],
+            edges: vec![edge("the \"key\"", "say \"hi\"", "a b", "t")],
+        };
+        let mermaid = to_mermaid(&graph);
+        assert!(mermaid.contains("    say__hi__0[\"say 'hi'\"] {\n"));
+        assert!(mermaid.contains("        integer a_b\n"));
+        assert!(mermaid.contains(": \"the 'key'\"\n"));
+    }
+
+    #[test]
+    fn dot_escapes_ids_and_html_labels() {
+        let graph = RelationshipGraph {
+            nodes: vec![node("public", "a\"b\\", &["x<y>&z"]), node("public", "t", &["id"])],
+            edges: vec![edge("k\"1", "a\"b\\", "x<y>&z", "t")],
+        };
+        let dot = to_dot(&graph);
+        assert!(dot.contains("    \"a\\\"b\\\\\" [label=<"));
+        assert!(dot.contains("<B>a&quot;b\\</B>"));
+        assert!(dot.contains("x&lt;y&gt;&amp;z: integer"));
+        assert!(dot.contains("\"a\\\"b\\\\\":c0 -> \"t\":c0 [label=\"k\\\"1\", dir=both"));
+    }
+
+    #[test]
+    fn labels_are_qualified_when_schemas_mix() {
+        let graph = RelationshipGraph {
+            nodes: vec![node("public", "users", &[]), node("audit", "users", &[])],
+            edges: Vec::new(),
+        };
+        assert_eq!(node_label(&graph, "audit", "users"), "audit.users");
+        assert_eq!(mermaid_ids(&graph), ["public_users_0", "audit_users_1"]);
+    }
+}
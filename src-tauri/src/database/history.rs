@@ -0,0 +1,385 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicI64, Ordering};
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+use thiserror::Error;
+
+use super::connection::ConnectionError;
+use super::types::*;
+
+const HISTORY_FILE: &str = "history.db";
+
+/// Maximum number of entries kept before the oldest are pruned, until the
+/// user sets their own
+pub const DEFAULT_RETENTION: i64 = 10_000;
+
+const SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS query_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        connection_id TEXT NOT NULL,
+        sql TEXT NOT NULL,
+        started_at TEXT NOT NULL,
+        duration_ms INTEGER NOT NULL,
+        row_count INTEGER,
+        status TEXT NOT NULL,
+        error TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_query_history_connection
+        ON query_history (connection_id, id);
+
+    CREATE VIRTUAL TABLE IF NOT EXISTS query_history_fts
+        USING fts5(sql, error, content = 'query_history', content_rowid = 'id');
+
+    CREATE TRIGGER IF NOT EXISTS query_history_ai AFTER INSERT ON query_history BEGIN
+        INSERT INTO query_history_fts (rowid, sql, error) VALUES (new.id, new.sql, new.error);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS query_history_ad AFTER DELETE ON query_history BEGIN
+        INSERT INTO query_history_fts (query_history_fts, rowid, sql, error)
+            VALUES ('delete', old.id, old.sql, old.error);
+    END;
+
+    CREATE TABLE IF NOT EXISTS history_settings (
+        key TEXT PRIMARY KEY,
+        value INTEGER NOT NULL
+    );
+"#;
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("History database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Failed to create history directory: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("History retention must be at least one entry")]
+    InvalidRetention,
+}
+
+impl From<HistoryError> for ConnectionError {
+    fn from(error: HistoryError) -> Self {
+        match error {
+            HistoryError::InvalidRetention => ConnectionError::InvalidInput(error.to_string()),
+            _ => ConnectionError::Storage(error.to_string()),
+        }
+    }
+}
+
+/// Persistent log of executed queries, stored in SQLite in the app data dir
+pub struct QueryHistory {
+    /// `None` when the history database could not be opened; every
+    /// operation then becomes a no-op so queries keep working
+    pool: Option<SqlitePool>,
+    retention: AtomicI64,
+}
+
+impl QueryHistory {
+    /// Open (or create) the history database inside `data_dir`. `retention`
+    /// applies until a limit is saved with `set_retention`.
+    pub async fn open(data_dir: &Path, retention: i64) -> Result<Self, HistoryError> {
+        std::fs::create_dir_all(data_dir)?;
+
+        let options = SqliteConnectOptions::new()
+            .filename(data_dir.join(HISTORY_FILE))
+            .create_if_missing(true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+
+        sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+
+        let saved: Option<i64> = sqlx::query_scalar("SELECT value FROM history_settings WHERE key = 'retention'")
+            .fetch_optional(&pool)
+            .await?;
+
+        Ok(Self {
+            pool: Some(pool),
+            retention: AtomicI64::new(saved.unwrap_or(retention)),
+        })
+    }
+
+    /// A history that records nothing, used when the database is unavailable
+    pub fn disabled() -> Self {
+        Self { pool: None, retention: AtomicI64::new(0) }
+    }
+
+    /// Number of entries kept before the oldest are pruned
+    pub fn retention(&self) -> i64 {
+        self.retention.load(Ordering::Relaxed)
+    }
+
+    /// Save a new retention limit and prune down to it
+    pub async fn set_retention(&self, retention: i64) -> Result<(), HistoryError> {
+        if retention < 1 {
+            return Err(HistoryError::InvalidRetention);
+        }
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+
+        sqlx::query(
+            "INSERT INTO history_settings (key, value) VALUES ('retention', ?)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        )
+        .bind(retention)
+        .execute(pool)
+        .await?;
+        self.retention.store(retention, Ordering::Relaxed);
+
+        self.prune(pool).await
+    }
+
+    /// Record the outcome of an `execute_query` invocation
+    pub async fn record(
+        &self,
+        connection_id: &str,
+        sql: &str,
+        started_at: DateTime<Utc>,
+        duration_ms: i64,
+        outcome: &Result<QueryResult, ConnectionError>,
+    ) -> Result<(), HistoryError> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        let (status, row_count, error) = match outcome {
+            Ok(result) => (QueryStatus::Success, Some(result.row_count as i64), None),
+            Err(e) => (QueryStatus::Error, None, Some(e.to_string())),
+        };
+
+        sqlx::query(
+            "INSERT INTO query_history (connection_id, sql, started_at, duration_ms, row_count, status, error)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(connection_id)
+        .bind(sql)
+        .bind(started_at)
+        .bind(duration_ms)
+        .bind(row_count)
+        .bind(status_to_str(status))
+        .bind(error)
+        .execute(pool)
+        .await?;
+
+        self.prune(pool).await
+    }
+
+    /// List the most recent entries, optionally for a single connection
+    pub async fn list(&self, params: ListHistoryParams) -> Result<Vec<HistoryEntry>, HistoryError> {
+        let Some(pool) = &self.pool else {
+            return Ok(Vec::new());
+        };
+        let rows = sqlx::query(
+            "SELECT * FROM query_history
+             WHERE ?1 IS NULL OR connection_id = ?1
+             ORDER BY id DESC
+             LIMIT ?2 OFFSET ?3",
+        )
+        .bind(params.connection_id)
+        .bind(params.limit)
+        .bind(params.offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.iter().map(row_to_entry).collect())
+    }
+
+    /// Full-text search over SQL text and error messages
+    pub async fn search(&self, params: SearchHistoryParams) -> Result<Vec<HistoryEntry>, HistoryError> {
+        let Some(pool) = &self.pool else {
+            return Ok(Vec::new());
+        };
+        let match_expr = build_match_expression(&params.query);
+        if match_expr.is_empty() {
+            return self
+                .list(ListHistoryParams {
+                    connection_id: params.connection_id,
+                    limit: params.limit,
+                    offset: 0,
+                })
+                .await;
+        }
+
+        let rows = sqlx::query(
+            "SELECT h.* FROM query_history_fts f
+             JOIN query_history h ON h.id = f.rowid
+             WHERE query_history_fts MATCH ?1
+               AND (?2 IS NULL OR h.connection_id = ?2)
+             ORDER BY f.rank, h.id DESC
+             LIMIT ?3",
+        )
+        .bind(match_expr)
+        .bind(params.connection_id)
+        .bind(params.limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.iter().map(row_to_entry).collect())
+    }
+
+    /// Delete history, either for one connection or entirely
+    pub async fn clear(&self, connection_id: Option<&str>) -> Result<(), HistoryError> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        sqlx::query("DELETE FROM query_history WHERE ?1 IS NULL OR connection_id = ?1")
+            .bind(connection_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Drop the oldest entries beyond the retention limit
+    async fn prune(&self, pool: &SqlitePool) -> Result<(), HistoryError> {
+        sqlx::query(
+            "DELETE FROM query_history WHERE id <= (
+                SELECT id FROM query_history ORDER BY id DESC LIMIT 1 OFFSET ?
+             )",
+        )
+        .bind(self.retention())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+fn status_to_str(status: QueryStatus) -> &'static str {
+    match status {
+        QueryStatus::Success => "success",
+        QueryStatus::Error => "error",
+    }
+}
+
+fn row_to_entry(row: &SqliteRow) -> HistoryEntry {
+    HistoryEntry {
+        id: row.try_get("id").unwrap_or_default(),
+        connection_id: row.try_get("connection_id").unwrap_or_default(),
+        sql: row.try_get("sql").unwrap_or_default(),
+        started_at: row.try_get("started_at").unwrap_or_default(),
+        duration_ms: row.try_get("duration_ms").unwrap_or_default(),
+        row_count: row.try_get("row_count").unwrap_or_default(),
+        status: if row.try_get::<String, _>("status").unwrap_or_default() == "error" {
+            QueryStatus::Error
+        } else {
+            QueryStatus::Success
+        },
+        error: row.try_get("error").unwrap_or_default(),
+    }
+}
+
+/// Turn free text into an FTS5 prefix query, quoting each term so that
+/// user input can never be interpreted as FTS syntax
+fn build_match_expression(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(row_count: usize) -> Result<QueryResult, ConnectionError> {
+        Ok(QueryResult {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            row_count,
+            execution_time_ms: 0,
+            notices: Vec::new(),
+            result_id: None,
+            truncated: false,
+            truncated_reason: None,
+            truncated_cells: Vec::new(),
+            stats: None,
+        })
+    }
+
+    async fn open_temp(retention: i64) -> (QueryHistory, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("mesagrid-history-{}", uuid::Uuid::new_v4()));
+        (QueryHistory::open(&dir, retention).await.unwrap(), dir)
+    }
+
+    fn list_all(connection_id: Option<&str>) -> ListHistoryParams {
+        ListHistoryParams { connection_id: connection_id.map(String::from), limit: 100, offset: 0 }
+    }
+
+    #[test]
+    fn match_expression_quotes_terms_as_prefixes() {
+        assert_eq!(build_match_expression("select users"), "\"select\"* \"users\"*");
+        assert_eq!(build_match_expression("a\"b OR"), "\"a\"\"b\"* \"OR\"*");
+        assert_eq!(build_match_expression("   "), "");
+    }
+
+    #[tokio::test]
+    async fn records_lists_and_searches() {
+        let (history, dir) = open_temp(DEFAULT_RETENTION).await;
+        let now = Utc::now();
+        history.record("a", "SELECT * FROM users", now, 5, &result(3)).await.unwrap();
+        history
+            .record("b", "DELETE FROM orders", now, 1, &Err(ConnectionError::NotFound("b".into())))
+            .await
+            .unwrap();
+
+        let all = history.list(list_all(None)).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].sql, "DELETE FROM orders");
+        assert_eq!(all[0].status, QueryStatus::Error);
+        assert_eq!(all[1].row_count, Some(3));
+
+        let only_a = history.list(list_all(Some("a"))).await.unwrap();
+        assert_eq!(only_a.len(), 1);
+
+        let hits = history
+            .search(SearchHistoryParams { query: "use".into(), connection_id: None, limit: 10 })
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].connection_id, "a");
+
+        history.clear(Some("a")).await.unwrap();
+        assert_eq!(history.list(list_all(None)).await.unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn prunes_beyond_retention() {
+        let (history, dir) = open_temp(2).await;
+        for i in 0..4 {
+            history.record("a", &format!("SELECT {i}"), Utc::now(), 0, &result(1)).await.unwrap();
+        }
+        let entries = history.list(list_all(None)).await.unwrap();
+        let sql: Vec<_> = entries.iter().map(|e| e.sql.as_str()).collect();
+        assert_eq!(sql, ["SELECT 3", "SELECT 2"]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn retention_is_saved_and_applied() {
+        let (history, dir) = open_temp(DEFAULT_RETENTION).await;
+        for i in 0..4 {
+            history.record("a", &format!("SELECT {i}"), Utc::now(), 0, &result(1)).await.unwrap();
+        }
+        assert!(matches!(history.set_retention(0).await, Err(HistoryError::InvalidRetention)));
+
+        history.set_retention(3).await.unwrap();
+        assert_eq!(history.retention(), 3);
+        assert_eq!(history.list(list_all(None)).await.unwrap().len(), 3);
+        drop(history);
+
+        let reopened = QueryHistory::open(&dir, DEFAULT_RETENTION).await.unwrap();
+        assert_eq!(reopened.retention(), 3);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn disabled_history_is_a_no_op() {
+        let history = QueryHistory::disabled();
+        history.record("a", "SELECT 1", Utc::now(), 0, &result(1)).await.unwrap();
+        assert!(history.list(list_all(None)).await.unwrap().is_empty());
+        history.clear(None).await.unwrap();
+    }
+}
@@ -0,0 +1,886 @@
+use std::collections::HashMap;
+
+use sqlx::mysql::{MySqlPool, MySqlRow};
+use sqlx::postgres::PgPool;
+use sqlx::Row;
+
+use super::connection::{ConnectionError, DatabasePool, InFlight};
+use super::errors::{mysql_error_number, MYSQL_BAD_FIELD_ERROR};
+use super::types::*;
+
+/// Column definitions of a table or view, in table order. Without a schema,
+/// Postgres resolves the table through the search path and MySQL uses the
+/// connection's current database.
+pub async fn table_columns(
+    pool: &DatabasePool,
+    schema: Option<&str>,
+    table: &str,
+) -> Result<Vec<TableColumn>, ConnectionError> {
+    let columns: Vec<TableColumn> = columns(pool, schema, Some(table))
+        .await?
+        .into_iter()
+        .map(|(_, column)| column)
+        .collect();
+    if columns.is_empty() {
+        return Err(not_found(schema, table));
+    }
+    Ok(columns)
+}
+
+/// Columns of every table and view in a schema, by table name
+pub async fn schema_columns(
+    pool: &DatabasePool,
+    schema: &str,
+) -> Result<HashMap<String, Vec<TableColumn>>, ConnectionError> {
+    let mut tables: HashMap<String, Vec<TableColumn>> = HashMap::new();
+    for (table, column) in columns(pool, Some(schema), None).await? {
+        tables.entry(table).or_default().push(column);
+    }
+    Ok(tables)
+}
+
+/// Columns with their table's name, of one table or of all of them
+async fn columns(
+    pool: &DatabasePool,
+    schema: Option<&str>,
+    table: Option<&str>,
+) -> Result<Vec<(String, TableColumn)>, ConnectionError> {
+    let columns = match pool {
+        DatabasePool::Postgres(pool) => {
+            // The catalogs rather than information_schema, which hides columns
+            // of tables the user has no privileges on and truncates types
+            let query = r#"
+                SELECT
+                    c.relname AS table_name,
+                    a.attnum::int4 AS ordinal_position,
+                    a.attname AS name,
+                    format_type(a.atttypid, a.atttypmod) AS data_type,
+                    NOT a.attnotnull AS nullable,
+                    CASE WHEN a.attgenerated = '' THEN pg_get_expr(d.adbin, d.adrelid) END AS default_value,
+                    CASE a.attidentity WHEN 'a' THEN 'ALWAYS' WHEN 'd' THEN 'BY DEFAULT' END AS identity,
+                    CASE WHEN a.attgenerated = 's' THEN 'STORED' END AS generated,
+                    CASE WHEN a.attgenerated <> '' THEN pg_get_expr(d.adbin, d.adrelid) END AS generation_expression,
+                    CASE
+                        WHEN co.collname = 'default' THEN (
+                            SELECT datcollate FROM pg_database WHERE datname = current_database()
+                        )
+                        ELSE co.collname
+                    END AS collation,
+                    col_description(c.oid, a.attnum) AS comment,
+                    (
+                        SELECT k.n::int4
+                        FROM unnest(i.indkey) WITH ORDINALITY AS k(attnum, n)
+                        WHERE k.attnum = a.attnum
+                    ) AS primary_key_position
+                FROM pg_class c
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum > 0 AND NOT a.attisdropped
+                LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+                LEFT JOIN pg_collation co ON co.oid = a.attcollation
+                LEFT JOIN pg_index i ON i.indrelid = c.oid AND i.indisprimary
+                WHERE ($2::text IS NULL OR c.relname = $2)
+                  AND c.relkind IN ('r', 'p', 'v', 'm', 'f')
+                  AND (n.nspname = $1 OR ($1 IS NULL AND pg_table_is_visible(c.oid)))
+                ORDER BY c.relname, a.attnum
+            "#;
+            sqlx::query(query)
+                .bind(schema)
+                .bind(table)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| {
+                    let default_value: Option<String> = row.try_get("default_value").ok().flatten();
+                    let identity: Option<String> = row.try_get("identity").ok().flatten();
+                    let primary_key_position: Option<i32> = row.try_get("primary_key_position").ok().flatten();
+                    let column = TableColumn {
+                        ordinal_position: row.try_get("ordinal_position").unwrap_or_default(),
+                        name: row.try_get("name").unwrap_or_default(),
+                        data_type: row.try_get("data_type").unwrap_or_default(),
+                        nullable: row.try_get("nullable").unwrap_or_default(),
+                        auto_increment: postgres_auto_increment(identity.as_deref(), default_value.as_deref()),
+                        default_is_expression: default_value.is_some(),
+                        default_value,
+                        identity,
+                        generated: row.try_get("generated").ok().flatten(),
+                        generation_expression: row.try_get("generation_expression").ok().flatten(),
+                        collation: row.try_get("collation").ok().flatten(),
+                        character_set: None,
+                        on_update: None,
+                        comment: row.try_get("comment").ok().flatten(),
+                        is_primary_key: primary_key_position.is_some(),
+                        primary_key_position,
+                    };
+                    (row.try_get("table_name").unwrap_or_default(), column)
+                })
+                .collect()
+        }
+        DatabasePool::MySql(pool) => {
+            let query = r#"
+                SELECT
+                    c.TABLE_NAME AS table_name,
+                    CAST(c.ORDINAL_POSITION AS SIGNED) AS ordinal_position,
+                    c.COLUMN_NAME AS name,
+                    c.COLUMN_TYPE AS data_type,
+                    c.IS_NULLABLE = 'YES' AS nullable,
+                    c.COLUMN_DEFAULT AS default_value,
+                    c.EXTRA AS extra,
+                    c.GENERATION_EXPRESSION AS generation_expression,
+                    c.CHARACTER_SET_NAME AS character_set,
+                    c.COLLATION_NAME AS collation,
+                    c.COLUMN_COMMENT AS comment,
+                    CAST(k.ORDINAL_POSITION AS SIGNED) AS primary_key_position
+                FROM information_schema.COLUMNS c
+                LEFT JOIN information_schema.KEY_COLUMN_USAGE k
+                    ON k.TABLE_SCHEMA = c.TABLE_SCHEMA
+                    AND k.TABLE_NAME = c.TABLE_NAME
+                    AND k.COLUMN_NAME = c.COLUMN_NAME
+                    AND k.CONSTRAINT_NAME = 'PRIMARY'
+                WHERE c.TABLE_SCHEMA = COALESCE(?, DATABASE())
+                  AND (? IS NULL OR c.TABLE_NAME = ?)
+                ORDER BY c.TABLE_NAME, c.ORDINAL_POSITION
+            "#;
+            sqlx::query(query)
+                .bind(schema)
+                .bind(table)
+                .bind(table)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| {
+                    let extra = row.try_get::<String, _>("extra").unwrap_or_default().to_uppercase();
+                    let generated = mysql_generated(&extra);
+                    let primary_key_position: Option<i64> = row.try_get("primary_key_position").ok().flatten();
+                    let default_value: Option<String> = row.try_get("default_value").ok().flatten();
+                    let column = TableColumn {
+                        ordinal_position: row.try_get::<i64, _>("ordinal_position").unwrap_or_default() as i32,
+                        name: row.try_get("name").unwrap_or_default(),
+                        data_type: row.try_get("data_type").unwrap_or_default(),
+                        nullable: row.try_get::<i64, _>("nullable").unwrap_or_default() != 0,
+                        default_is_expression: mysql_default_is_expression(default_value.as_deref(), &extra),
+                        default_value,
+                        identity: None,
+                        auto_increment: extra.contains("AUTO_INCREMENT"),
+                        // Empty rather than NULL for ordinary columns
+                        generation_expression: if generated.is_some() {
+                            row.try_get("generation_expression").ok().flatten()
+                        } else {
+                            None
+                        },
+                        generated,
+                        collation: row.try_get("collation").ok().flatten(),
+                        character_set: row.try_get("character_set").ok().flatten(),
+                        on_update: mysql_on_update(&extra),
+                        comment: row
+                            .try_get::<Option<String>, _>("comment")
+                            .ok()
+                            .flatten()
+                            .filter(|c| !c.is_empty()),
+                        is_primary_key: primary_key_position.is_some(),
+                        primary_key_position: primary_key_position.map(|p| p as i32),
+                    };
+                    (row.try_get("table_name").unwrap_or_default(), column)
+                })
+                .collect()
+        }
+    };
+    Ok(columns)
+}
+
+/// Count a table's rows with `COUNT(*)` on a dedicated connection, handing
+/// the server-side connection ID to `on_start` so it can be cancelled. The
+/// connection goes back to the pool only once the count has finished.
+pub async fn count_rows(
+    pool: &DatabasePool,
+    schema: Option<&str>,
+    table: &str,
+    on_start: impl FnOnce(i64),
+) -> Result<i64, ConnectionError> {
+    let table = match schema {
+        Some(schema) => format!("{}.{}", pool.quote_ident(schema), pool.quote_ident(table)),
+        None => pool.quote_ident(table),
+    };
+    let sql = format!("SELECT COUNT(*) FROM {}", table);
+
+    let count = match pool {
+        DatabasePool::Postgres(pool) => {
+            let mut conn = InFlight::acquire(pool).await?;
+            let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()").fetch_one(&mut *conn).await?;
+            on_start(pid as i64);
+            let count = sqlx::query_scalar(&sql).fetch_one(&mut *conn).await;
+            conn.release();
+            count
+        }
+        DatabasePool::MySql(pool) => {
+            let mut conn = InFlight::acquire(pool).await?;
+            let id: u64 = sqlx::query_scalar("SELECT CONNECTION_ID()").fetch_one(&mut *conn).await?;
+            on_start(id as i64);
+            let count = sqlx::query_scalar(&sql).fetch_one(&mut *conn).await;
+            conn.release();
+            count
+        }
+    };
+    count.map_err(|e| ConnectionError::from(e).with_sql(&sql))
+}
+
+/// Databases on the server `pool` is connected to. `current` marks the one
+/// named in the saved connection.
+pub async fn databases(pool: &DatabasePool, current: &str) -> Result<Vec<DatabaseInfo>, ConnectionError> {
+    let databases: Vec<(String, Option<i64>)> = match pool {
+        DatabasePool::Postgres(pool) => {
+            // pg_database_size errors for databases the user can't connect to
+            let query = r#"
+                SELECT
+                    datname AS name,
+                    CASE WHEN has_database_privilege(oid, 'CONNECT') THEN pg_database_size(oid) END AS size_bytes
+                FROM pg_database
+                WHERE datallowconn AND NOT datistemplate
+                ORDER BY datname
+            "#;
+            sqlx::query(query)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| (row.try_get("name").unwrap_or_default(), row.try_get("size_bytes").ok().flatten()))
+                .collect()
+        }
+        DatabasePool::MySql(pool) => {
+            sqlx::query_scalar::<_, String>("SELECT SCHEMA_NAME FROM information_schema.SCHEMATA ORDER BY SCHEMA_NAME")
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .map(|name| (name, None))
+                .collect()
+        }
+    };
+
+    Ok(databases
+        .into_iter()
+        .map(|(name, size_bytes)| DatabaseInfo {
+            current: name == current,
+            name,
+            size_bytes,
+        })
+        .collect())
+}
+
+/// Schemas of the database `pool` is connected to
+pub async fn schemas(pool: &DatabasePool) -> Result<Vec<SchemaInfo>, ConnectionError> {
+    let schemas = match pool {
+        DatabasePool::Postgres(pool) => {
+            let query = r#"
+                SELECT
+                    n.nspname AS name,
+                    pg_get_userbyid(n.nspowner) AS owner,
+                    n.nspname IN ('pg_catalog', 'information_schema') AS system
+                FROM pg_namespace n
+                WHERE n.nspname !~ '^pg_(toast|temp_|toast_temp_)'
+                ORDER BY system, n.nspname
+            "#;
+            sqlx::query(query)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| SchemaInfo {
+                    name: row.try_get("name").unwrap_or_default(),
+                    owner: row.try_get("owner").ok(),
+                    system: row.try_get("system").unwrap_or_default(),
+                })
+                .collect()
+        }
+        DatabasePool::MySql(pool) => {
+            let query = r#"
+                SELECT
+                    SCHEMA_NAME AS name,
+                    SCHEMA_NAME IN ('information_schema', 'mysql', 'performance_schema', 'sys') AS `system`
+                FROM information_schema.SCHEMATA
+                ORDER BY `system`, SCHEMA_NAME
+            "#;
+            sqlx::query(query)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| SchemaInfo {
+                    name: row.try_get("name").unwrap_or_default(),
+                    owner: None,
+                    system: row.try_get::<i64, _>("system").unwrap_or_default() != 0,
+                })
+                .collect()
+        }
+    };
+    Ok(schemas)
+}
+
+/// Indexes, constraints and foreign keys in both directions of a table,
+/// resolved the same way as [`table_columns`]
+pub async fn table_structure(
+    pool: &DatabasePool,
+    schema: Option<&str>,
+    table: &str,
+) -> Result<TableStructure, ConnectionError> {
+    let structures = match pool {
+        DatabasePool::Postgres(pool) => postgres_structures(pool, schema, Some(table)).await?,
+        DatabasePool::MySql(pool) => mysql_structures(pool, schema, Some(table)).await?,
+    };
+    structures.into_iter().next().ok_or_else(|| not_found(schema, table))
+}
+
+/// Structures of every table and view in a schema, by table name, read with
+/// one query per kind of member rather than per table
+pub async fn schema_structures(
+    pool: &DatabasePool,
+    schema: &str,
+) -> Result<HashMap<String, TableStructure>, ConnectionError> {
+    let structures = match pool {
+        DatabasePool::Postgres(pool) => postgres_structures(pool, Some(schema), None).await?,
+        DatabasePool::MySql(pool) => mysql_structures(pool, Some(schema), None).await?,
+    };
+    Ok(structures.into_iter().map(|s| (s.name.clone(), s)).collect())
+}
+
+fn not_found(schema: Option<&str>, table: &str) -> ConnectionError {
+    match schema {
+        Some(schema) => ConnectionError::NotFound(format!("{}.{}", schema, table)),
+        None => ConnectionError::NotFound(table.to_string()),
+    }
+}
+
+/// SQL for the names of the columns numbered by `keys` (an `int2[]` or
+/// `int2vector`) of `relation`, in key order
+pub(crate) fn pg_column_names(keys: &str, relation: &str) -> String {
+    format!(
+        "ARRAY(SELECT a.attname::text FROM unnest({keys}) WITH ORDINALITY AS k(attnum, n) \
+         JOIN pg_attribute a ON a.attrelid = {relation} AND a.attnum = k.attnum ORDER BY k.n)"
+    )
+}
+
+/// Spelling of a `pg_constraint` referential action code
+pub(crate) fn pg_action(code: &str) -> String {
+    match code {
+        "r" => "RESTRICT",
+        "c" => "CASCADE",
+        "n" => "SET NULL",
+        "d" => "SET DEFAULT",
+        _ => "NO ACTION",
+    }
+    .to_string()
+}
+
+/// Structures of one relation, or of every relation in the schema without a table
+async fn postgres_structures(
+    pool: &PgPool,
+    schema: Option<&str>,
+    table: Option<&str>,
+) -> Result<Vec<TableStructure>, ConnectionError> {
+    let relations = sqlx::query(
+        r#"
+        SELECT c.oid::int8 AS oid, n.nspname AS schema, c.relname AS name
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE ($2::text IS NULL OR c.relname = $2)
+          AND c.relkind IN ('r', 'p', 'v', 'm', 'f')
+          AND (n.nspname = $1 OR ($1 IS NULL AND pg_table_is_visible(c.oid)))
+        ORDER BY c.relname
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(pool)
+    .await?;
+
+    let mut oids: Vec<i64> = Vec::new();
+    let mut structures: Vec<TableStructure> = Vec::new();
+    for row in &relations {
+        oids.push(row.try_get("oid")?);
+        structures.push(TableStructure {
+            schema: row.try_get("schema")?,
+            name: row.try_get("name")?,
+            indexes: Vec::new(),
+            constraints: Vec::new(),
+            foreign_keys: Vec::new(),
+            referenced_by: Vec::new(),
+        });
+    }
+    if structures.is_empty() {
+        return Ok(structures);
+    }
+    let positions: HashMap<i64, usize> = oids.iter().enumerate().map(|(i, oid)| (*oid, i)).collect();
+    let position = |oid: i64| positions.get(&oid).copied();
+
+    // Key columns of expression indexes have attnum 0; show the expression instead
+    let query = r#"
+        SELECT
+            i.indrelid::int8 AS relation,
+            ic.relname AS name,
+            ARRAY(
+                SELECT COALESCE(a.attname::text, pg_get_indexdef(i.indexrelid, k.n::int4, true))
+                FROM unnest(i.indkey) WITH ORDINALITY AS k(attnum, n)
+                LEFT JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = k.attnum AND k.attnum <> 0
+                ORDER BY k.n
+            ) AS columns,
+            i.indnkeyatts::int4 AS key_columns,
+            i.indisunique AS is_unique,
+            i.indisprimary AS is_primary,
+            am.amname AS method,
+            pg_get_expr(i.indpred, i.indrelid, true) AS predicate,
+            pg_relation_size(i.indexrelid) AS size_bytes
+        FROM pg_index i
+        JOIN pg_class ic ON ic.oid = i.indexrelid
+        JOIN pg_am am ON am.oid = ic.relam
+        WHERE i.indrelid = ANY($1::int8[]::oid[])
+        ORDER BY i.indisprimary DESC, ic.relname
+    "#;
+    for row in sqlx::query(query).bind(&oids).fetch_all(pool).await? {
+        let Some(i) = position(row.try_get("relation")?) else {
+            continue;
+        };
+        let mut columns: Vec<String> = row.try_get("columns").unwrap_or_default();
+        let key_columns = row.try_get::<i32, _>("key_columns").unwrap_or_default().max(0) as usize;
+        let include = columns.split_off(key_columns.min(columns.len()));
+        structures[i].indexes.push(TableIndex {
+            name: row.try_get("name").unwrap_or_default(),
+            columns,
+            include,
+            unique: row.try_get("is_unique").unwrap_or_default(),
+            primary: row.try_get("is_primary").unwrap_or_default(),
+            method: row.try_get("method").unwrap_or_default(),
+            predicate: row.try_get("predicate").ok().flatten(),
+            size_bytes: row.try_get("size_bytes").ok(),
+        });
+    }
+
+    let query = format!(
+        r#"
+        SELECT
+            con.conrelid::int8 AS relation,
+            con.conname AS name,
+            con.contype::text AS kind,
+            {} AS columns,
+            pg_get_constraintdef(con.oid, true) AS definition
+        FROM pg_constraint con
+        WHERE con.conrelid = ANY($1::int8[]::oid[])
+          AND con.contype IN ('p', 'u', 'x', 'c')
+        ORDER BY array_position(ARRAY['p', 'u', 'x', 'c'], con.contype::text), con.conname
+        "#,
+        pg_column_names("con.conkey", "con.conrelid")
+    );
+    for row in sqlx::query(&query).bind(&oids).fetch_all(pool).await? {
+        let Some(i) = position(row.try_get("relation")?) else {
+            continue;
+        };
+        structures[i].constraints.push(TableConstraint {
+            name: row.try_get("name").unwrap_or_default(),
+            kind: match row.try_get::<String, _>("kind").unwrap_or_default().as_str() {
+                "p" => ConstraintKind::PrimaryKey,
+                "u" => ConstraintKind::Unique,
+                "x" => ConstraintKind::Exclusion,
+                _ => ConstraintKind::Check,
+            },
+            columns: row.try_get("columns").unwrap_or_default(),
+            definition: row.try_get("definition").unwrap_or_default(),
+        });
+    }
+
+    // Constraints cloned onto partitions have a parent; report only the declared one
+    let query = format!(
+        r#"
+        SELECT
+            con.conname AS name,
+            con.conrelid::int8 AS relation,
+            con.confrelid::int8 AS referenced_relation,
+            sn.nspname AS schema,
+            sc.relname AS table,
+            {} AS columns,
+            tn.nspname AS referenced_schema,
+            tc.relname AS referenced_table,
+            {} AS referenced_columns,
+            con.confupdtype::text AS on_update,
+            con.confdeltype::text AS on_delete
+        FROM pg_constraint con
+        JOIN pg_class sc ON sc.oid = con.conrelid
+        JOIN pg_namespace sn ON sn.oid = sc.relnamespace
+        JOIN pg_class tc ON tc.oid = con.confrelid
+        JOIN pg_namespace tn ON tn.oid = tc.relnamespace
+        WHERE con.contype = 'f'
+          AND con.conparentid = 0
+          AND (con.conrelid = ANY($1::int8[]::oid[]) OR con.confrelid = ANY($1::int8[]::oid[]))
+        ORDER BY sn.nspname, sc.relname, con.conname
+        "#,
+        pg_column_names("con.conkey", "con.conrelid"),
+        pg_column_names("con.confkey", "con.confrelid")
+    );
+    for row in sqlx::query(&query).bind(&oids).fetch_all(pool).await? {
+        let key = ForeignKey {
+            name: row.try_get("name").unwrap_or_default(),
+            schema: row.try_get("schema").unwrap_or_default(),
+            table: row.try_get("table").unwrap_or_default(),
+            columns: row.try_get("columns").unwrap_or_default(),
+            referenced_schema: row.try_get("referenced_schema").unwrap_or_default(),
+            referenced_table: row.try_get("referenced_table").unwrap_or_default(),
+            referenced_columns: row.try_get("referenced_columns").unwrap_or_default(),
+            on_update: pg_action(&row.try_get::<String, _>("on_update").unwrap_or_default()),
+            on_delete: pg_action(&row.try_get::<String, _>("on_delete").unwrap_or_default()),
+        };
+        if let Some(i) = position(row.try_get("referenced_relation")?) {
+            structures[i].referenced_by.push(key.clone());
+        }
+        if let Some(i) = position(row.try_get("relation")?) {
+            structures[i].foreign_keys.push(key);
+        }
+    }
+
+    Ok(structures)
+}
+
+/// Structures of one table, or of every table in the database without one
+async fn mysql_structures(
+    pool: &MySqlPool,
+    schema: Option<&str>,
+    table: Option<&str>,
+) -> Result<Vec<TableStructure>, ConnectionError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT TABLE_SCHEMA AS `schema`, TABLE_NAME AS name
+        FROM information_schema.TABLES
+        WHERE TABLE_SCHEMA = COALESCE(?, DATABASE()) AND (? IS NULL OR TABLE_NAME = ?)
+        ORDER BY TABLE_NAME
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .bind(table)
+    .fetch_all(pool)
+    .await?;
+    let Some(first) = rows.first() else {
+        return Ok(Vec::new());
+    };
+    let schema: String = first.try_get("schema")?;
+    let mut structures: Vec<TableStructure> = Vec::new();
+    for row in &rows {
+        structures.push(TableStructure {
+            schema: schema.clone(),
+            name: row.try_get("name")?,
+            indexes: Vec::new(),
+            constraints: Vec::new(),
+            foreign_keys: Vec::new(),
+            referenced_by: Vec::new(),
+        });
+    }
+    let positions: HashMap<String, usize> =
+        structures.iter().enumerate().map(|(i, s)| (s.name.clone(), i)).collect();
+    let position = |row: &MySqlRow| {
+        let table: String = row.try_get("table_name").unwrap_or_default();
+        positions.get(&table).copied()
+    };
+
+    // EXPRESSION (functional key parts) was added in 8.0.13; older servers reject it
+    // as an unknown column
+    let query = r#"
+        SELECT
+            TABLE_NAME AS table_name,
+            INDEX_NAME AS name,
+            NON_UNIQUE = 0 AS is_unique,
+            INDEX_TYPE AS method,
+            COALESCE(
+                CONCAT(COLUMN_NAME, IF(SUB_PART IS NULL, '', CONCAT('(', SUB_PART, ')'))),
+                {expression}
+            ) AS column_name
+        FROM information_schema.STATISTICS
+        WHERE TABLE_SCHEMA = ? AND (? IS NULL OR TABLE_NAME = ?)
+        ORDER BY TABLE_NAME, INDEX_NAME <> 'PRIMARY', INDEX_NAME, SEQ_IN_INDEX
+    "#;
+    let rows = match sqlx::query(&query.replace("{expression}", "CONCAT('(', EXPRESSION, ')')"))
+        .bind(&schema)
+        .bind(table)
+        .bind(table)
+        .fetch_all(pool)
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) if mysql_error_number(&e) == Some(MYSQL_BAD_FIELD_ERROR) => sqlx::query(&query.replace("{expression}", "''"))
+            .bind(&schema)
+            .bind(table)
+            .bind(table)
+            .fetch_all(pool)
+            .await?,
+        Err(e) => return Err(e.into()),
+    };
+    for row in &rows {
+        let Some(i) = position(row) else {
+            continue;
+        };
+        let name: String = row.try_get("name").unwrap_or_default();
+        let column: String = row.try_get::<Option<String>, _>("column_name").ok().flatten().unwrap_or_default();
+        let indexes = &mut structures[i].indexes;
+        match indexes.last_mut() {
+            Some(index) if index.name == name => index.columns.push(column),
+            _ => indexes.push(TableIndex {
+                primary: name == "PRIMARY",
+                name,
+                columns: vec![column],
+                include: Vec::new(),
+                unique: row.try_get::<i64, _>("is_unique").unwrap_or_default() != 0,
+                method: row.try_get("method").unwrap_or_default(),
+                predicate: None,
+                size_bytes: None,
+            }),
+        }
+    }
+
+    // Persistent InnoDB statistics count pages; readable only with access to the mysql schema
+    if let Ok(rows) = sqlx::query(
+        r#"
+        SELECT
+            table_name AS table_name,
+            index_name AS name,
+            CAST(stat_value * @@innodb_page_size AS SIGNED) AS size_bytes
+        FROM mysql.innodb_index_stats
+        WHERE database_name = ? AND (? IS NULL OR table_name = ?) AND stat_name = 'size'
+        "#,
+    )
+    .bind(&schema)
+    .bind(table)
+    .bind(table)
+    .fetch_all(pool)
+    .await
+    {
+        for row in &rows {
+            let Some(i) = position(row) else {
+                continue;
+            };
+            let name: String = row.try_get("name").unwrap_or_default();
+            if let Some(index) = structures[i].indexes.iter_mut().find(|i| i.name == name) {
+                index.size_bytes = row.try_get("size_bytes").ok();
+            }
+        }
+    }
+
+    let query = r#"
+        SELECT
+            tc.TABLE_NAME AS table_name,
+            tc.CONSTRAINT_NAME AS name,
+            tc.CONSTRAINT_TYPE AS kind,
+            k.COLUMN_NAME AS column_name
+        FROM information_schema.TABLE_CONSTRAINTS tc
+        JOIN information_schema.KEY_COLUMN_USAGE k
+            ON k.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA
+            AND k.TABLE_NAME = tc.TABLE_NAME
+            AND k.CONSTRAINT_NAME = tc.CONSTRAINT_NAME
+        WHERE tc.TABLE_SCHEMA = ? AND (? IS NULL OR tc.TABLE_NAME = ?)
+          AND tc.CONSTRAINT_TYPE IN ('PRIMARY KEY', 'UNIQUE')
+        ORDER BY tc.TABLE_NAME, tc.CONSTRAINT_TYPE <> 'PRIMARY KEY', tc.CONSTRAINT_NAME, k.ORDINAL_POSITION
+    "#;
+    for row in &sqlx::query(query).bind(&schema).bind(table).bind(table).fetch_all(pool).await? {
+        let Some(i) = position(row) else {
+            continue;
+        };
+        let name: String = row.try_get("name").unwrap_or_default();
+        let column: String = row.try_get("column_name").unwrap_or_default();
+        let constraints = &mut structures[i].constraints;
+        match constraints.last_mut() {
+            Some(constraint) if constraint.name == name => constraint.columns.push(column),
+            _ => constraints.push(TableConstraint {
+                name,
+                kind: if row.try_get::<String, _>("kind").unwrap_or_default() == "PRIMARY KEY" {
+                    ConstraintKind::PrimaryKey
+                } else {
+                    ConstraintKind::Unique
+                },
+                columns: vec![column],
+                definition: String::new(),
+            }),
+        }
+    }
+    for constraint in structures.iter_mut().flat_map(|s| s.constraints.iter_mut()) {
+        let columns: Vec<String> = constraint.columns.iter().map(|c| DatabaseType::Mysql.quote_ident(c)).collect();
+        let keyword = match constraint.kind {
+            ConstraintKind::PrimaryKey => "PRIMARY KEY",
+            _ => "UNIQUE",
+        };
+        constraint.definition = format!("{} ({})", keyword, columns.join(", "));
+    }
+
+    // CHECK constraints are enforced (and listed) from 8.0.16
+    let query = r#"
+        SELECT tc.TABLE_NAME AS table_name, cc.CONSTRAINT_NAME AS name, cc.CHECK_CLAUSE AS clause
+        FROM information_schema.TABLE_CONSTRAINTS tc
+        JOIN information_schema.CHECK_CONSTRAINTS cc
+            ON cc.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA AND cc.CONSTRAINT_NAME = tc.CONSTRAINT_NAME
+        WHERE tc.TABLE_SCHEMA = ? AND (? IS NULL OR tc.TABLE_NAME = ?) AND tc.CONSTRAINT_TYPE = 'CHECK'
+        ORDER BY tc.TABLE_NAME, cc.CONSTRAINT_NAME
+    "#;
+    if let Ok(rows) = sqlx::query(query).bind(&schema).bind(table).bind(table).fetch_all(pool).await {
+        for row in &rows {
+            let Some(i) = position(row) else {
+                continue;
+            };
+            structures[i].constraints.push(TableConstraint {
+                name: row.try_get("name").unwrap_or_default(),
+                kind: ConstraintKind::Check,
+                columns: Vec::new(),
+                definition: format!("CHECK ({})", row.try_get::<String, _>("clause").unwrap_or_default()),
+            });
+        }
+    }
+
+    let query = r#"
+        SELECT
+            k.CONSTRAINT_NAME AS name,
+            k.TABLE_SCHEMA AS `schema`,
+            k.TABLE_NAME AS `table`,
+            k.COLUMN_NAME AS column_name,
+            k.REFERENCED_TABLE_SCHEMA AS referenced_schema,
+            k.REFERENCED_TABLE_NAME AS referenced_table,
+            k.REFERENCED_COLUMN_NAME AS referenced_column,
+            r.UPDATE_RULE AS on_update,
+            r.DELETE_RULE AS on_delete
+        FROM information_schema.KEY_COLUMN_USAGE k
+        JOIN information_schema.REFERENTIAL_CONSTRAINTS r
+            ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA
+            AND r.TABLE_NAME = k.TABLE_NAME
+            AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME
+        WHERE k.REFERENCED_TABLE_NAME IS NOT NULL
+          AND ((k.TABLE_SCHEMA = ? AND (? IS NULL OR k.TABLE_NAME = ?))
+            OR (k.REFERENCED_TABLE_SCHEMA = ? AND (? IS NULL OR k.REFERENCED_TABLE_NAME = ?)))
+        ORDER BY k.TABLE_SCHEMA, k.TABLE_NAME, k.CONSTRAINT_NAME, k.ORDINAL_POSITION
+    "#;
+    let rows = sqlx::query(query)
+        .bind(&schema)
+        .bind(table)
+        .bind(table)
+        .bind(&schema)
+        .bind(table)
+        .bind(table)
+        .fetch_all(pool)
+        .await?;
+    let mut keys: Vec<ForeignKey> = Vec::new();
+    for row in &rows {
+        let name: String = row.try_get("name").unwrap_or_default();
+        let from_schema: String = row.try_get("schema").unwrap_or_default();
+        let from_table: String = row.try_get("table").unwrap_or_default();
+        let column: String = row.try_get("column_name").unwrap_or_default();
+        let referenced_column: String = row.try_get("referenced_column").unwrap_or_default();
+        match keys.last_mut() {
+            Some(key) if key.name == name && key.schema == from_schema && key.table == from_table => {
+                key.columns.push(column);
+                key.referenced_columns.push(referenced_column);
+            }
+            _ => keys.push(ForeignKey {
+                name,
+                schema: from_schema,
+                table: from_table,
+                columns: vec![column],
+                referenced_schema: row.try_get("referenced_schema").unwrap_or_default(),
+                referenced_table: row.try_get("referenced_table").unwrap_or_default(),
+                referenced_columns: vec![referenced_column],
+                on_update: row.try_get("on_update").unwrap_or_default(),
+                on_delete: row.try_get("on_delete").unwrap_or_default(),
+            }),
+        }
+    }
+    for key in keys {
+        if key.referenced_schema == schema {
+            if let Some(&i) = positions.get(&key.referenced_table) {
+                structures[i].referenced_by.push(key.clone());
+            }
+        }
+        if key.schema == schema {
+            if let Some(&i) = positions.get(&key.table) {
+                structures[i].foreign_keys.push(key);
+            }
+        }
+    }
+
+    Ok(structures)
+}
+
+
+/// Identity columns and serials, whose default draws from a sequence.
+fn postgres_auto_increment(identity: Option<&str>, default_value: Option<&str>) -> bool {
+    identity.is_some() || default_value.is_some_and(|d| d.starts_with("nextval("))
+}
+
+/// Whether a MySQL default is an expression rather than an unquoted literal.
+/// 8.0 marks expressions `DEFAULT_GENERATED`; 5.7 allows only CURRENT_TIMESTAMP.
+fn mysql_default_is_expression(default_value: Option<&str>, extra: &str) -> bool {
+    default_value.is_some_and(|d| extra.contains("DEFAULT_GENERATED") || d.to_uppercase().starts_with("CURRENT_TIMESTAMP"))
+}
+
+/// The `ON UPDATE` expression from an upper-cased MySQL `EXTRA` column.
+fn mysql_on_update(extra: &str) -> Option<String> {
+    let (_, rest) = extra.split_once("ON UPDATE ")?;
+    rest.split_whitespace().next().map(str::to_string)
+}
+
+/// Generation kind from an upper-cased MySQL `EXTRA` column.
+fn mysql_generated(extra: &str) -> Option<String> {
+    if extra.contains("STORED GENERATED") {
+        Some("STORED".to_string())
+    } else if extra.contains("VIRTUAL GENERATED") {
+        Some("VIRTUAL".to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn postgres_auto_increment_covers_identity_and_serial() {
+        assert!(postgres_auto_increment(Some("ALWAYS"), None));
+        assert!(postgres_auto_increment(None, Some("nextval('users_id_seq'::regclass)")));
+        assert!(!postgres_auto_increment(None, Some("now()")));
+        assert!(!postgres_auto_increment(None, None));
+    }
+
+    #[test]
+    fn pg_action_spells_referential_actions() {
+        assert_eq!(pg_action("c"), "CASCADE");
+        assert_eq!(pg_action("n"), "SET NULL");
+        assert_eq!(pg_action("d"), "SET DEFAULT");
+        assert_eq!(pg_action("r"), "RESTRICT");
+        assert_eq!(pg_action("a"), "NO ACTION");
+    }
+
+    #[test]
+    fn pg_column_names_keeps_key_order() {
+        let sql = pg_column_names("con.confkey", "con.confrelid");
+        assert!(sql.contains("unnest(con.confkey) WITH ORDINALITY"));
+        assert!(sql.contains("a.attrelid = con.confrelid"));
+        assert!(sql.ends_with("ORDER BY k.n)"));
+    }
+
+    #[test]
+    fn not_found_names_the_qualified_table() {
+        assert!(matches!(not_found(Some("app"), "users"), ConnectionError::NotFound(name) if name == "app.users"));
+        assert!(matches!(not_found(None, "users"), ConnectionError::NotFound(name) if name == "users"));
+    }
+
+    #[test]
+    fn mysql_generated_reads_extra() {
+        assert_eq!(mysql_generated("STORED GENERATED").as_deref(), Some("STORED"));
+        assert_eq!(mysql_generated("VIRTUAL GENERATED").as_deref(), Some("VIRTUAL"));
+        assert_eq!(mysql_generated("DEFAULT_GENERATED"), None);
+        assert_eq!(mysql_generated("AUTO_INCREMENT"), None);
+    }
+
+    #[test]
+    fn mysql_defaults_are_expressions_when_generated() {
+        assert!(mysql_default_is_expression(Some("curdate()"), "DEFAULT_GENERATED"));
+        assert!(mysql_default_is_expression(Some("CURRENT_TIMESTAMP"), ""));
+        assert!(!mysql_default_is_expression(Some("pending"), ""));
+        assert!(!mysql_default_is_expression(None, "DEFAULT_GENERATED"));
+    }
+
+    #[test]
+    fn mysql_on_update_reads_extra() {
+        assert_eq!(
+            mysql_on_update("DEFAULT_GENERATED ON UPDATE CURRENT_TIMESTAMP(3)").as_deref(),
+            Some("CURRENT_TIMESTAMP(3)")
+        );
+        assert_eq!(mysql_on_update("ON UPDATE CURRENT_TIMESTAMP").as_deref(), Some("CURRENT_TIMESTAMP"));
+        assert_eq!(mysql_on_update("AUTO_INCREMENT"), None);
+    }
+}
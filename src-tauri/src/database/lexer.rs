@@ -0,0 +1,467 @@
+use super::types::DatabaseType;
+
+/// Kind of a lexical SQL token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Whitespace,
+    LineComment,
+    BlockComment,
+    /// String literal of any form: quoted, escape, bit or dollar-quoted
+    String,
+    QuotedIdentifier,
+    Number,
+    /// Keyword or bare identifier
+    Word,
+    /// Bind parameter such as `$1` or `?`
+    Parameter,
+    Operator,
+    Comma,
+    Semicolon,
+    Dot,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+}
+
+/// A token borrowed from the source text
+#[derive(Debug, Clone, Copy)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// Byte offset of the token in the source
+    pub offset: usize,
+    /// False for a string, quoted identifier or comment that runs to the end of input
+    pub terminated: bool,
+}
+
+impl Token<'_> {
+    /// Whether this is a word matching `keyword`, ignoring case
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    /// Whitespace and comments
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.kind,
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment
+        )
+    }
+}
+
+/// Split `sql` into tokens following the quoting and comment rules of `dialect`.
+/// Every byte of the input belongs to exactly one token, so concatenating the
+/// token texts reproduces the source.
+pub fn tokenize(sql: &str, dialect: DatabaseType) -> Vec<Token<'_>> {
+    let mut lexer = Lexer { sql, bytes: sql.as_bytes(), pos: 0, dialect };
+    let mut tokens = Vec::new();
+    while lexer.pos < sql.len() {
+        tokens.push(lexer.next_token());
+    }
+    tokens
+}
+
+struct Lexer<'a> {
+    sql: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    dialect: DatabaseType,
+}
+
+impl<'a> Lexer<'a> {
+    fn peek(&self, ahead: usize) -> Option<u8> {
+        self.bytes.get(self.pos + ahead).copied()
+    }
+
+    fn token(&self, kind: TokenKind, start: usize, terminated: bool) -> Token<'a> {
+        Token { kind, text: &self.sql[start..self.pos], offset: start, terminated }
+    }
+
+    /// Whether a `--` line comment starts at the current position. MySQL only
+    /// treats `--` as a comment when followed by whitespace or the end of input.
+    fn line_comment_starts(&self) -> bool {
+        self.peek(0) == Some(b'-')
+            && self.peek(1) == Some(b'-')
+            && (self.dialect != DatabaseType::Mysql || self.peek(2).is_none_or(|c| c.is_ascii_whitespace()))
+    }
+
+    fn next_token(&mut self) -> Token<'a> {
+        let start = self.pos;
+        let token = self.lex_token();
+        debug_assert!(self.pos > start, "lexer made no progress at byte {}", start);
+        if self.pos > start {
+            return token;
+        }
+        // Never return an empty token, which would stall `tokenize`
+        self.pos += self.sql[start..].chars().next().map_or(1, char::len_utf8);
+        self.token(TokenKind::Operator, start, true)
+    }
+
+    fn lex_token(&mut self) -> Token<'a> {
+        let start = self.pos;
+        let c = self.bytes[self.pos];
+        let mysql = self.dialect == DatabaseType::Mysql;
+
+        match c {
+            b' ' | b'\t' | b'\n' | b'\r' | b'\x0c' => {
+                while self.peek(0).is_some_and(|c| c.is_ascii_whitespace()) {
+                    self.pos += 1;
+                }
+                self.token(TokenKind::Whitespace, start, true)
+            }
+            b'-' if self.line_comment_starts() => self.line_comment(start),
+            b'#' if mysql => self.line_comment(start),
+            b'/' if self.peek(1) == Some(b'*') => self.block_comment(start),
+            b'\'' => {
+                let backslash = mysql;
+                self.quoted(start, b'\'', backslash, TokenKind::String)
+            }
+            b'"' if mysql => self.quoted(start, b'"', true, TokenKind::String),
+            b'"' => self.quoted(start, b'"', false, TokenKind::QuotedIdentifier),
+            b'`' if mysql => self.quoted(start, b'`', false, TokenKind::QuotedIdentifier),
+            // Postgres escape strings: E'it\'s'
+            b'e' | b'E' if !mysql && self.peek(1) == Some(b'\'') => {
+                self.pos += 1;
+                self.quoted(start, b'\'', true, TokenKind::String)
+            }
+            // Bit, hex and national strings: B'1010', X'ff', N'text'
+            b'b' | b'B' | b'x' | b'X' | b'n' | b'N' if self.peek(1) == Some(b'\'') => {
+                self.pos += 1;
+                self.quoted(start, b'\'', mysql, TokenKind::String)
+            }
+            b'$' if !mysql => {
+                if let Some(token) = self.dollar_string(start) {
+                    return token;
+                }
+                self.pos += 1;
+                if self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                    while self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                        self.pos += 1;
+                    }
+                    self.token(TokenKind::Parameter, start, true)
+                } else {
+                    self.token(TokenKind::Operator, start, true)
+                }
+            }
+            b'?' if mysql => {
+                self.pos += 1;
+                self.token(TokenKind::Parameter, start, true)
+            }
+            b'0'..=b'9' => self.number(start),
+            b'.' if self.peek(1).is_some_and(|c| c.is_ascii_digit()) => self.number(start),
+            b',' => self.single(TokenKind::Comma),
+            b';' => self.single(TokenKind::Semicolon),
+            b'.' => self.single(TokenKind::Dot),
+            b'(' => self.single(TokenKind::LParen),
+            b')' => self.single(TokenKind::RParen),
+            b'[' => self.single(TokenKind::LBracket),
+            b']' => self.single(TokenKind::RBracket),
+            c if is_word_start(c) => {
+                while self.peek(0).is_some_and(is_word_part) {
+                    self.pos += 1;
+                }
+                self.token(TokenKind::Word, start, true)
+            }
+            c if is_operator_char(c) => self.operator(start),
+            _ => {
+                // Anything unrecognised becomes a one-character operator
+                self.pos += self.sql[start..].chars().next().map_or(1, char::len_utf8);
+                self.token(TokenKind::Operator, start, true)
+            }
+        }
+    }
+
+    fn single(&mut self, kind: TokenKind) -> Token<'a> {
+        self.pos += 1;
+        self.token(kind, self.pos - 1, true)
+    }
+
+    fn line_comment(&mut self, start: usize) -> Token<'a> {
+        while self.peek(0).is_some_and(|c| c != b'\n') {
+            self.pos += 1;
+        }
+        // A trailing \r belongs to the line break, not the comment
+        if self.pos > start && self.bytes[self.pos - 1] == b'\r' {
+            self.pos -= 1;
+        }
+        self.token(TokenKind::LineComment, start, true)
+    }
+
+    fn block_comment(&mut self, start: usize) -> Token<'a> {
+        // Postgres block comments nest, MySQL's don't
+        let nests = self.dialect != DatabaseType::Mysql;
+        let mut depth = 0;
+        while self.pos < self.bytes.len() {
+            if self.peek(0) == Some(b'/') && self.peek(1) == Some(b'*') && (nests || depth == 0) {
+                depth += 1;
+                self.pos += 2;
+            } else if self.peek(0) == Some(b'*') && self.peek(1) == Some(b'/') {
+                depth -= 1;
+                self.pos += 2;
+                if depth == 0 {
+                    return self.token(TokenKind::BlockComment, start, true);
+                }
+            } else {
+                self.pos += 1;
+            }
+        }
+        self.token(TokenKind::BlockComment, start, false)
+    }
+
+    /// Read a literal delimited by `quote`, where a doubled quote is an escaped
+    /// quote and, if `backslash` is set, a backslash escapes the next character
+    fn quoted(&mut self, start: usize, quote: u8, backslash: bool, kind: TokenKind) -> Token<'a> {
+        self.pos += 1;
+        while let Some(c) = self.peek(0) {
+            if backslash && c == b'\\' {
+                self.pos += 2;
+            } else if c == quote {
+                if self.peek(1) == Some(quote) {
+                    self.pos += 2;
+                } else {
+                    self.pos += 1;
+                    return self.token(kind, start, true);
+                }
+            } else {
+                self.pos += 1;
+            }
+        }
+        self.pos = self.pos.min(self.bytes.len());
+        self.token(kind, start, false)
+    }
+
+    /// Read `$tag$ ... $tag$` if the input at `start` opens one
+    fn dollar_string(&mut self, start: usize) -> Option<Token<'a>> {
+        let rest = &self.sql[start + 1..];
+        let tag_len = rest.find('$')?;
+        let tag = &rest[..tag_len];
+        let valid_tag = tag.is_empty()
+            || (tag.bytes().all(is_word_part) && !tag.as_bytes()[0].is_ascii_digit());
+        if !valid_tag {
+            return None;
+        }
+
+        let delimiter = &self.sql[start..start + tag_len + 2];
+        let body_start = start + delimiter.len();
+        match self.sql[body_start..].find(delimiter) {
+            Some(end) => {
+                self.pos = body_start + end + delimiter.len();
+                Some(self.token(TokenKind::String, start, true))
+            }
+            None => {
+                self.pos = self.sql.len();
+                Some(self.token(TokenKind::String, start, false))
+            }
+        }
+    }
+
+    fn number(&mut self, start: usize) -> Token<'a> {
+        // Hex, octal and binary integers: 0xff, 0o17, 0b101
+        if self.peek(0) == Some(b'0') && matches!(self.peek(1), Some(b'x' | b'X' | b'o' | b'O' | b'b' | b'B')) {
+            self.pos += 2;
+            while self.peek(0).is_some_and(|c| c.is_ascii_hexdigit() || c == b'_') {
+                self.pos += 1;
+            }
+            return self.token(TokenKind::Number, start, true);
+        }
+
+        while self.peek(0).is_some_and(|c| c.is_ascii_digit() || c == b'_') {
+            self.pos += 1;
+        }
+        if self.peek(0) == Some(b'.') && self.peek(1).is_none_or(|c| c != b'.') {
+            self.pos += 1;
+            while self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        if matches!(self.peek(0), Some(b'e' | b'E')) {
+            let sign = usize::from(matches!(self.peek(1), Some(b'+' | b'-')));
+            if self.peek(1 + sign).is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1 + sign;
+                while self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+            }
+        }
+        self.token(TokenKind::Number, start, true)
+    }
+
+    fn operator(&mut self, start: usize) -> Token<'a> {
+        while let Some(c) = self.peek(0) {
+            let comment = self.line_comment_starts() || (c == b'/' && self.peek(1) == Some(b'*'));
+            if !is_operator_char(c) || comment || (self.dialect == DatabaseType::Mysql && c == b'#') {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        // As in Postgres, `a=-1` is `=` followed by `-1`: a multi-character
+        // operator only ends in + or - if it contains one of ~!@#%^&|`?
+        let text = &self.sql[start..self.pos];
+        if text.len() > 1 && !text.contains(['~', '!', '@', '#', '%', '^', '&', '|', '`', '?']) {
+            let trimmed = text.trim_end_matches(['+', '-']);
+            self.pos = start + trimmed.len().max(1);
+        }
+
+        self.token(TokenKind::Operator, start, true)
+    }
+}
+
+/// Identifier text with quotes removed, and whether it was quoted
+pub fn ident(token: &Token) -> (String, bool) {
+    if token.kind != TokenKind::QuotedIdentifier {
+        return (token.text.to_string(), false);
+    }
+    // An identifier still being typed has no closing quote
+    let quote = &token.text[..1];
+    let inner = if token.terminated {
+        &token.text[1..token.text.len() - 1]
+    } else {
+        &token.text[1..]
+    };
+    (inner.replace(&quote.repeat(2), quote), true)
+}
+
+/// Indexes of the name parts of `a.b.c` starting at `i`, and the index after it
+pub fn dotted_name(tokens: &[Token], mut i: usize) -> Option<(Vec<usize>, usize)> {
+    let mut parts = Vec::new();
+    loop {
+        if !tokens.get(i).is_some_and(|t| matches!(t.kind, TokenKind::Word | TokenKind::QuotedIdentifier)) {
+            return None;
+        }
+        parts.push(i);
+        i += 1;
+        match tokens.get(i) {
+            Some(t) if t.kind == TokenKind::Dot => i += 1,
+            _ => return Some((parts, i)),
+        }
+    }
+}
+
+/// Index of the first token from `i` on that isn't one of `words`
+pub fn skip_words(tokens: &[Token], mut i: usize, words: &[&str]) -> usize {
+    while tokens.get(i).is_some_and(|t| words.iter().any(|w| t.is_keyword(w))) {
+        i += 1;
+    }
+    i
+}
+
+/// Index of the `)` closing the `(` at `open`
+pub fn matching_paren(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token.kind {
+            TokenKind::LParen => depth += 1,
+            TokenKind::RParen => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_word_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_' || c >= 0x80
+}
+
+fn is_word_part(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || c >= 0x80
+}
+
+fn is_operator_char(c: u8) -> bool {
+    matches!(
+        c,
+        b'+' | b'-' | b'*' | b'/' | b'<' | b'>' | b'=' | b'~' | b'!' | b'@' | b'#' | b'%' | b'^' | b'&' | b'|' | b'`' | b'?' | b':'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(sql: &str, dialect: DatabaseType) -> Vec<(TokenKind, &str)> {
+        tokenize(sql, dialect).into_iter().filter(|t| t.kind != TokenKind::Whitespace).map(|t| (t.kind, t.text)).collect()
+    }
+
+    #[test]
+    fn tokens_reproduce_the_source() {
+        let inputs = [
+            "SELECT 5--1",
+            "UPDATE t SET a=a--1",
+            "select 'it''s', \"col\" -- note\r\nfrom t /* a /* b */ c */",
+            "SELECT `a``b` FROM t # note",
+            "SELECT $tag$ body $tag$, $1, E'\\n', x'ff', 1.5e-3",
+            "SELECT 'unterminated",
+            "SELECT \"café",
+            "/* open",
+            "--",
+        ];
+        for sql in inputs {
+            for dialect in [DatabaseType::Postgres, DatabaseType::Mysql] {
+                let tokens = tokenize(sql, dialect);
+                assert!(tokens.iter().all(|t| !t.text.is_empty()), "{:?}", sql);
+                assert_eq!(tokens.iter().map(|t| t.text).collect::<String>(), sql);
+            }
+        }
+    }
+
+    #[test]
+    fn mysql_double_dash_needs_whitespace() {
+        use TokenKind::*;
+        let mysql = DatabaseType::Mysql;
+        assert_eq!(kinds("SELECT 5--1", mysql), [(Word, "SELECT"), (Number, "5"), (Operator, "-"), (Operator, "-"), (Number, "1")]);
+        assert_eq!(kinds("a=a--1", mysql), [(Word, "a"), (Operator, "="), (Word, "a"), (Operator, "-"), (Operator, "-"), (Number, "1")]);
+        assert_eq!(kinds("a -- note", mysql), [(Word, "a"), (LineComment, "-- note")]);
+        assert_eq!(kinds("a --", mysql), [(Word, "a"), (LineComment, "--")]);
+        assert_eq!(kinds("a=1-- note", mysql), [(Word, "a"), (Operator, "="), (Number, "1"), (LineComment, "-- note")]);
+        assert_eq!(kinds("SELECT 5--1", DatabaseType::Postgres), [(Word, "SELECT"), (Number, "5"), (LineComment, "--1")]);
+    }
+
+    #[test]
+    fn dialect_comments() {
+        use TokenKind::*;
+        assert_eq!(kinds("a # note", DatabaseType::Mysql), [(Word, "a"), (LineComment, "# note")]);
+        assert_eq!(kinds("a # b", DatabaseType::Postgres), [(Word, "a"), (Operator, "#"), (Word, "b")]);
+        // Postgres block comments nest, MySQL's end at the first */
+        assert_eq!(kinds("/* a /* b */ c */", DatabaseType::Postgres), [(BlockComment, "/* a /* b */ c */")]);
+        assert_eq!(
+            kinds("/* a /* b */ c */", DatabaseType::Mysql),
+            [(BlockComment, "/* a /* b */"), (Word, "c"), (Operator, "*/")]
+        );
+    }
+
+    #[test]
+    fn unterminated_literals() {
+        let tokens = tokenize("SELECT 'abc", DatabaseType::Postgres);
+        let last = tokens.last().unwrap();
+        assert_eq!((last.kind, last.text, last.terminated), (TokenKind::String, "'abc", false));
+
+        let tokens = tokenize("SELECT 'a\\", DatabaseType::Mysql);
+        assert!(!tokens.last().unwrap().terminated);
+
+        let tokens = tokenize("/* open /* nested */", DatabaseType::Postgres);
+        assert!(!tokens[0].terminated);
+    }
+
+    #[test]
+    fn ident_strips_quotes_of_unterminated_identifiers() {
+        let tokens = tokenize("FROM \"café", DatabaseType::Postgres);
+        let last = tokens.last().unwrap();
+        assert!(!last.terminated);
+        assert_eq!(ident(last), ("café".to_string(), true));
+
+        let tokens = tokenize("FROM `a``b", DatabaseType::Mysql);
+        assert_eq!(ident(tokens.last().unwrap()), ("a`b".to_string(), true));
+
+        let tokens = tokenize("FROM \"", DatabaseType::Postgres);
+        assert_eq!(ident(tokens.last().unwrap()), (String::new(), true));
+
+        let tokens = tokenize("FROM \"Mixed\"\"Case\"", DatabaseType::Postgres);
+        assert_eq!(ident(tokens.last().unwrap()), ("Mixed\"Case".to_string(), true));
+    }
+}
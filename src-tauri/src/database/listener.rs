@@ -0,0 +1,246 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use parking_lot::RwLock;
+use sqlx::postgres::{PgListener, PgPool};
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+use super::connection::ConnectionError;
+use super::types::*;
+
+/// Number of recent notifications kept per connection
+pub const NOTIFICATION_BUFFER_SIZE: usize = 500;
+
+type NotificationSink = Arc<dyn Fn(&ChannelNotification) + Send + Sync>;
+
+enum ListenerCommand {
+    Listen(String, oneshot::Sender<Result<(), sqlx::Error>>),
+    Unlisten(String, oneshot::Sender<Result<(), sqlx::Error>>),
+}
+
+struct ListenerHandle {
+    commands: mpsc::UnboundedSender<ListenerCommand>,
+    channels: HashSet<String>,
+}
+
+/// Postgres LISTEN/NOTIFY subscriptions, one dedicated listener connection
+/// per MesaGrid connection
+pub struct NotificationHub {
+    listeners: Mutex<HashMap<String, ListenerHandle>>,
+    buffers: Arc<RwLock<HashMap<String, VecDeque<ChannelNotification>>>>,
+    sink: NotificationSink,
+}
+
+impl NotificationHub {
+    /// `sink` is called for every received notification, e.g. to forward it to the UI
+    pub fn new(sink: impl Fn(&ChannelNotification) + Send + Sync + 'static) -> Self {
+        Self {
+            listeners: Mutex::new(HashMap::new()),
+            buffers: Arc::new(RwLock::new(HashMap::new())),
+            sink: Arc::new(sink),
+        }
+    }
+
+    /// Start listening on `channel`, opening the listener connection if needed
+    pub async fn subscribe(
+        &self,
+        connection_id: &str,
+        pool: &PgPool,
+        channel: &str,
+    ) -> Result<(), ConnectionError> {
+        let mut listeners = self.listeners.lock().await;
+
+        // A listener whose task has exited (e.g. the server went away) is replaced
+        if listeners.get(connection_id).is_some_and(|handle| handle.commands.is_closed()) {
+            listeners.remove(connection_id);
+        }
+
+        if let Some(handle) = listeners.get_mut(connection_id) {
+            if !handle.channels.contains(channel) {
+                send_command(&handle.commands, |reply| ListenerCommand::Listen(channel.to_string(), reply)).await?;
+                handle.channels.insert(channel.to_string());
+            }
+            return Ok(());
+        }
+
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(channel).await?;
+
+        let (commands, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run_listener(
+            connection_id.to_string(),
+            listener,
+            receiver,
+            self.buffers.clone(),
+            self.sink.clone(),
+        ));
+
+        listeners.insert(
+            connection_id.to_string(),
+            ListenerHandle {
+                commands,
+                channels: HashSet::from([channel.to_string()]),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Stop listening on `channel`; the listener connection closes with the last channel
+    pub async fn unsubscribe(&self, connection_id: &str, channel: &str) -> Result<(), ConnectionError> {
+        let mut listeners = self.listeners.lock().await;
+
+        let Some(handle) = listeners.get_mut(connection_id) else {
+            return Ok(());
+        };
+        if !handle.channels.remove(channel) {
+            return Ok(());
+        }
+
+        if handle.channels.is_empty() {
+            // Dropping the sender ends the listener task and releases its connection
+            listeners.remove(connection_id);
+        } else {
+            send_command(&handle.commands, |reply| ListenerCommand::Unlisten(channel.to_string(), reply)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Channels currently subscribed on a connection
+    pub async fn subscriptions(&self, connection_id: &str) -> Vec<String> {
+        let mut channels: Vec<String> = self
+            .listeners
+            .lock()
+            .await
+            .get(connection_id)
+            .map(|handle| handle.channels.iter().cloned().collect())
+            .unwrap_or_default();
+        channels.sort();
+        channels
+    }
+
+    /// Recently received notifications for a connection, oldest first
+    pub fn recent(&self, connection_id: &str) -> Vec<ChannelNotification> {
+        self.buffers
+            .read()
+            .get(connection_id)
+            .map(|buffer| buffer.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Drop all subscriptions and buffered notifications for a connection
+    pub async fn stop(&self, connection_id: &str) {
+        self.listeners.lock().await.remove(connection_id);
+        self.buffers.write().remove(connection_id);
+    }
+}
+
+async fn send_command(
+    commands: &mpsc::UnboundedSender<ListenerCommand>,
+    command: impl FnOnce(oneshot::Sender<Result<(), sqlx::Error>>) -> ListenerCommand,
+) -> Result<(), ConnectionError> {
+    let (reply, response) = oneshot::channel();
+    commands
+        .send(command(reply))
+        .map_err(|_| sqlx::Error::WorkerCrashed)?;
+    response.await.map_err(|_| sqlx::Error::WorkerCrashed)??;
+    Ok(())
+}
+
+/// Append to the connection's ring buffer, dropping the oldest entry when full
+fn buffer_notification(
+    buffers: &RwLock<HashMap<String, VecDeque<ChannelNotification>>>,
+    notification: ChannelNotification,
+) {
+    let mut buffers = buffers.write();
+    let buffer = buffers.entry(notification.connection_id.clone()).or_default();
+    if buffer.len() == NOTIFICATION_BUFFER_SIZE {
+        buffer.pop_front();
+    }
+    buffer.push_back(notification);
+}
+
+async fn run_listener(
+    connection_id: String,
+    mut listener: PgListener,
+    mut commands: mpsc::UnboundedReceiver<ListenerCommand>,
+    buffers: Arc<RwLock<HashMap<String, VecDeque<ChannelNotification>>>>,
+    sink: NotificationSink,
+) {
+    loop {
+        tokio::select! {
+            command = commands.recv() => match command {
+                Some(ListenerCommand::Listen(channel, reply)) => {
+                    let _ = reply.send(listener.listen(&channel).await);
+                }
+                Some(ListenerCommand::Unlisten(channel, reply)) => {
+                    let _ = reply.send(listener.unlisten(&channel).await);
+                }
+                // The hub dropped this listener
+                None => break,
+            },
+            received = listener.recv() => match received {
+                Ok(notification) => {
+                    let notification = ChannelNotification {
+                        connection_id: connection_id.clone(),
+                        channel: notification.channel().to_string(),
+                        payload: notification.payload().to_string(),
+                        process_id: notification.process_id(),
+                        received_at: chrono::Utc::now(),
+                    };
+
+                    buffer_notification(&buffers, notification.clone());
+                    sink(&notification);
+                }
+                // Pool closed or reconnect failed; nothing left to listen on
+                Err(_) => break,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(connection_id: &str, payload: usize) -> ChannelNotification {
+        ChannelNotification {
+            connection_id: connection_id.to_string(),
+            channel: "events".to_string(),
+            payload: payload.to_string(),
+            process_id: 1,
+            received_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn buffer_keeps_the_most_recent_notifications() {
+        let hub = NotificationHub::new(|_| {});
+        for i in 0..NOTIFICATION_BUFFER_SIZE + 3 {
+            buffer_notification(&hub.buffers, notification("a", i));
+        }
+        buffer_notification(&hub.buffers, notification("b", 0));
+
+        let recent = hub.recent("a");
+        assert_eq!(recent.len(), NOTIFICATION_BUFFER_SIZE);
+        assert_eq!(recent[0].payload, "3");
+        assert_eq!(recent.last().unwrap().payload, (NOTIFICATION_BUFFER_SIZE + 2).to_string());
+        assert_eq!(hub.recent("b").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_clears_buffered_notifications() {
+        let hub = NotificationHub::new(|_| {});
+        buffer_notification(&hub.buffers, notification("a", 1));
+
+        hub.stop("a").await;
+        assert!(hub.recent("a").is_empty());
+        assert!(hub.subscriptions("a").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unsubscribing_unknown_channels_is_a_no_op() {
+        let hub = NotificationHub::new(|_| {});
+        hub.unsubscribe("missing", "events").await.unwrap();
+    }
+}
@@ -1,6 +1,35 @@
+pub mod benchmark;
+pub mod cells;
+pub mod completer;
 pub mod connection;
 pub mod credentials;
+pub mod ddl;
+pub mod diff;
+pub mod errors;
+pub mod fanout;
+pub mod formatter;
+pub mod graph;
+pub mod history;
+pub mod introspection;
+pub mod jobs;
+pub mod lexer;
+pub mod listener;
+pub mod notices;
+pub mod objects;
+pub mod result_cache;
+pub mod running;
+pub mod schema_cache;
+pub mod search;
+pub mod snippets;
 pub mod types;
+pub mod validator;
 
-pub use connection::ConnectionManager;
+pub use connection::{ConnectionError, ConnectionManager};
+pub use history::QueryHistory;
+pub use jobs::JobManager;
+pub use listener::NotificationHub;
+pub use result_cache::ResultCache;
+pub use running::RunningQueries;
+pub use schema_cache::SchemaCache;
+pub use snippets::SnippetLibrary;
 pub use types::*;
@@ -0,0 +1,161 @@
+use std::future::Future;
+use std::sync::Arc;
+use parking_lot::Mutex;
+use sqlx::{MySqlConnection, Row};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+use super::connection::ConnectionError;
+use super::types::*;
+
+/// Target sqlx uses when logging Postgres `NoticeResponse` messages
+const PG_NOTICE_TARGET: &str = "sqlx::postgres::notice";
+
+tokio::task_local! {
+    static COLLECTOR: Arc<Mutex<Vec<QueryNotice>>>;
+}
+
+/// sqlx does not hand Postgres notices to the caller; it only emits them as
+/// tracing events while the connection is polled. This layer picks those
+/// events up and routes them to whichever execution is running on the task.
+struct NoticeLayer;
+
+impl<S: Subscriber> Layer<S> for NoticeLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor(String::new());
+        event.record(&mut visitor);
+
+        let severity = match *event.metadata().level() {
+            Level::ERROR => NoticeSeverity::Error,
+            Level::WARN => NoticeSeverity::Warning,
+            Level::INFO => NoticeSeverity::Notice,
+            Level::DEBUG => NoticeSeverity::Debug,
+            Level::TRACE => NoticeSeverity::Info,
+        };
+
+        let _ = COLLECTOR.try_with(|notices| {
+            notices.lock().push(QueryNotice {
+                severity,
+                // The event carries only the message, not the SQLSTATE
+                code: None,
+                message: visitor.0,
+            })
+        });
+    }
+}
+
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0 = value.to_string();
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+}
+
+/// The notice layer, filtered to sqlx's notice events so it doesn't affect
+/// what other layers on the same subscriber receive
+pub fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    NoticeLayer.with_filter(filter_fn(|metadata| metadata.target() == PG_NOTICE_TARGET))
+}
+
+/// Install a subscriber carrying the notice layer. Must be called once at
+/// startup, before any Postgres connection is opened. Other layers, such as a
+/// log writer, belong on this subscriber alongside `layer()`.
+pub fn install() {
+    let _ = tracing_subscriber::registry().with(layer()).try_init();
+}
+
+/// Run `fut`, collecting any Postgres notices raised while it executes
+pub async fn capture<F: Future>(fut: F) -> (F::Output, Vec<QueryNotice>) {
+    let notices = Arc::new(Mutex::new(Vec::new()));
+    let output = COLLECTOR.scope(notices.clone(), fut).await;
+    let collected = std::mem::take(&mut *notices.lock());
+    (output, collected)
+}
+
+/// Fetch the warnings left by the previous statement on `conn`.
+/// Uses the text protocol so the diagnostics area isn't reset by a prepare.
+pub async fn fetch_mysql_warnings(conn: &mut MySqlConnection) -> Result<Vec<QueryNotice>, ConnectionError> {
+    let row = sqlx::raw_sql("SELECT @@warning_count").fetch_one(&mut *conn).await?;
+    let count = row
+        .try_get::<u64, _>(0)
+        .or_else(|_| row.try_get::<i64, _>(0).map(|n| n as u64))
+        .unwrap_or_default();
+
+    if count == 0 {
+        return Ok(vec![]);
+    }
+
+    let rows = sqlx::raw_sql("SHOW WARNINGS").fetch_all(&mut *conn).await?;
+
+    let warnings = rows
+        .iter()
+        .map(|row| QueryNotice {
+            severity: match row.try_get::<String, _>("Level").unwrap_or_default().as_str() {
+                "Error" => NoticeSeverity::Error,
+                "Note" => NoticeSeverity::Notice,
+                _ => NoticeSeverity::Warning,
+            },
+            code: row
+                .try_get::<u32, _>("Code")
+                .map(|code| code.to_string())
+                .ok(),
+            message: row.try_get("Message").unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscriber() -> impl Subscriber + Send + Sync {
+        tracing_subscriber::registry().with(layer())
+    }
+
+    #[tokio::test]
+    async fn captures_notices_raised_inside_the_future() {
+        let _guard = tracing::subscriber::set_default(subscriber());
+
+        let (output, notices) = capture(async {
+            tracing::warn!(target: "sqlx::postgres::notice", message = "table does not exist, skipping");
+            tracing::info!(target: "sqlx::postgres::notice", message = "relation created");
+            tracing::warn!(target: "some::other::target", message = "not a notice");
+            7
+        })
+        .await;
+
+        assert_eq!(output, 7);
+        assert_eq!(notices.len(), 2);
+        assert_eq!(notices[0].severity, NoticeSeverity::Warning);
+        assert_eq!(notices[0].message, "table does not exist, skipping");
+        assert_eq!(notices[1].severity, NoticeSeverity::Notice);
+    }
+
+    #[tokio::test]
+    async fn notices_outside_a_capture_are_dropped() {
+        let _guard = tracing::subscriber::set_default(subscriber());
+
+        tracing::warn!(target: "sqlx::postgres::notice", message = "stray");
+        let (_, notices) = capture(async {}).await;
+        assert!(notices.is_empty());
+    }
+}
@@ -0,0 +1,764 @@
+use sqlx::mysql::{MySqlPool, MySqlRow};
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::Row;
+
+use super::connection::{ConnectionError, DatabasePool};
+use super::ddl;
+use super::errors::mysql_error_number;
+use super::types::*;
+
+/// Schemas that hold the system catalogs rather than user objects
+const PG_USER_SCHEMAS: &str = r#"
+    n.nspname NOT IN ('pg_catalog', 'information_schema')
+    AND n.nspname !~ '^pg_(toast|temp_|toast_temp_)'
+    AND ($1::text IS NULL OR n.nspname = $1)
+"#;
+
+/// SQL excluding objects created by an extension or implicitly with another
+/// object (such as range constructors), which would otherwise bury the user's
+/// own functions and types
+pub(crate) fn pg_user_defined(catalog: &str, oid: &str) -> String {
+    format!(
+        "NOT EXISTS (SELECT 1 FROM pg_depend d \
+         WHERE d.classid = '{catalog}'::regclass AND d.objid = {oid} AND d.deptype IN ('e', 'i'))"
+    )
+}
+
+/// List objects of the requested kinds, each kind ordered by schema and name
+pub async fn list(pool: &DatabasePool, params: &ListObjectsParams) -> Result<Vec<DatabaseObject>, ConnectionError> {
+    let wanted = |kind: ObjectKind| params.kinds.is_empty() || params.kinds.contains(&kind);
+    let schema = params.schema.as_deref();
+
+    let mut objects = Vec::new();
+    match pool {
+        DatabasePool::Postgres(pool) => {
+            if wanted(ObjectKind::Table) || wanted(ObjectKind::View) || wanted(ObjectKind::MaterializedView) {
+                objects.extend(list_postgres_relations(pool, schema).await?);
+            }
+            if wanted(ObjectKind::Function) || wanted(ObjectKind::Procedure) {
+                objects.extend(list_postgres_routines(pool, schema).await?);
+            }
+            if wanted(ObjectKind::Trigger) {
+                objects.extend(list_postgres_triggers(pool, schema).await?);
+            }
+            if wanted(ObjectKind::Sequence) {
+                objects.extend(list_postgres_sequences(pool, schema).await?);
+            }
+            if wanted(ObjectKind::Type) {
+                objects.extend(list_postgres_types(pool, schema).await?);
+            }
+        }
+        DatabasePool::MySql(pool) => {
+            if wanted(ObjectKind::Table) || wanted(ObjectKind::View) {
+                objects.extend(list_mysql_relations(pool, schema).await?);
+            }
+            if wanted(ObjectKind::Function) || wanted(ObjectKind::Procedure) {
+                objects.extend(list_mysql_routines(pool, schema).await?);
+            }
+            if wanted(ObjectKind::Trigger) {
+                objects.extend(list_mysql_triggers(pool, schema).await?);
+            }
+            if wanted(ObjectKind::Event) {
+                objects.extend(list_mysql_events(pool, schema).await?);
+            }
+        }
+    }
+
+    // Relations and routines are fetched together; drop the kinds not asked for
+    objects.retain(|o| wanted(o.kind));
+    Ok(objects)
+}
+
+/// `CREATE` statement (or equivalent source) of a single object
+pub async fn definition(pool: &DatabasePool, params: &GetObjectDefinitionParams) -> Result<String, ConnectionError> {
+    match pool {
+        DatabasePool::Postgres(pool) if params.kind == ObjectKind::Table => {
+            let options = DdlOptions {
+                include_owner: false,
+                ..Default::default()
+            };
+            ddl::postgres(pool, params, &options).await
+        }
+        DatabasePool::Postgres(pool) => postgres_definition(pool, &resolve_postgres(pool, params).await?).await,
+        DatabasePool::MySql(pool) => mysql_definition(pool, params).await,
+    }
+}
+
+fn object(schema: String, name: String, kind: ObjectKind) -> DatabaseObject {
+    DatabaseObject {
+        schema,
+        name,
+        kind,
+        table: None,
+        arguments: None,
+        result: None,
+        detail: None,
+        current_value: None,
+        comment: None,
+    }
+}
+
+async fn list_postgres_relations(pool: &PgPool, schema: Option<&str>) -> Result<Vec<DatabaseObject>, ConnectionError> {
+    let query = format!(
+        r#"
+        SELECT
+            n.nspname AS schema,
+            c.relname AS name,
+            c.relkind::text AS kind,
+            obj_description(c.oid, 'pg_class') AS comment
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE c.relkind IN ('r', 'p', 'f', 'v', 'm')
+          AND NOT c.relispartition
+          AND {PG_USER_SCHEMAS}
+        ORDER BY n.nspname, c.relname
+        "#
+    );
+    let rows = sqlx::query(&query).bind(schema).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let kind = match row.try_get::<String, _>("kind").unwrap_or_default().as_str() {
+                "v" => ObjectKind::View,
+                "m" => ObjectKind::MaterializedView,
+                _ => ObjectKind::Table,
+            };
+            DatabaseObject {
+                comment: row.try_get("comment").ok().flatten(),
+                ..object(pg_text(row, "schema"), pg_text(row, "name"), kind)
+            }
+        })
+        .collect())
+}
+
+async fn list_postgres_routines(pool: &PgPool, schema: Option<&str>) -> Result<Vec<DatabaseObject>, ConnectionError> {
+    let query = format!(
+        r#"
+        SELECT
+            n.nspname AS schema,
+            p.proname AS name,
+            p.prokind = 'p' AS is_procedure,
+            pg_get_function_identity_arguments(p.oid) AS arguments,
+            pg_get_function_result(p.oid) AS result,
+            CASE p.prokind WHEN 'a' THEN 'aggregate' WHEN 'w' THEN 'window' END AS detail,
+            obj_description(p.oid, 'pg_proc') AS comment
+        FROM pg_proc p
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE {PG_USER_SCHEMAS}
+          AND {}
+        ORDER BY n.nspname, p.proname, arguments
+        "#,
+        pg_user_defined("pg_proc", "p.oid")
+    );
+    let rows = sqlx::query(&query).bind(schema).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let kind = if row.try_get("is_procedure").unwrap_or_default() {
+                ObjectKind::Procedure
+            } else {
+                ObjectKind::Function
+            };
+            DatabaseObject {
+                arguments: row.try_get("arguments").ok(),
+                result: row.try_get("result").ok().flatten(),
+                detail: row.try_get("detail").ok().flatten(),
+                comment: row.try_get("comment").ok().flatten(),
+                ..object(pg_text(row, "schema"), pg_text(row, "name"), kind)
+            }
+        })
+        .collect())
+}
+
+async fn list_postgres_triggers(pool: &PgPool, schema: Option<&str>) -> Result<Vec<DatabaseObject>, ConnectionError> {
+    // Triggers cloned onto partitions have a parent; list only the declared one
+    let query = format!(
+        r#"
+        SELECT
+            n.nspname AS schema,
+            t.tgname AS name,
+            c.relname AS table_name,
+            t.tgtype::int4 AS tgtype,
+            t.tgenabled = 'D' AS disabled,
+            obj_description(t.oid, 'pg_trigger') AS comment
+        FROM pg_trigger t
+        JOIN pg_class c ON c.oid = t.tgrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE NOT t.tgisinternal
+          AND t.tgparentid = 0
+          AND {PG_USER_SCHEMAS}
+        ORDER BY n.nspname, c.relname, t.tgname
+        "#
+    );
+    let rows = sqlx::query(&query).bind(schema).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let mut detail = pg_trigger_detail(row.try_get("tgtype").unwrap_or_default());
+            if row.try_get("disabled").unwrap_or_default() {
+                detail.push_str(" (disabled)");
+            }
+            DatabaseObject {
+                table: Some(pg_text(row, "table_name")),
+                detail: Some(detail),
+                comment: row.try_get("comment").ok().flatten(),
+                ..object(pg_text(row, "schema"), pg_text(row, "name"), ObjectKind::Trigger)
+            }
+        })
+        .collect())
+}
+
+/// Describe `pg_trigger.tgtype`, e.g. `BEFORE INSERT OR UPDATE FOR EACH ROW`
+fn pg_trigger_detail(tgtype: i32) -> String {
+    let timing = if tgtype & 2 != 0 {
+        "BEFORE"
+    } else if tgtype & 64 != 0 {
+        "INSTEAD OF"
+    } else {
+        "AFTER"
+    };
+    let events: Vec<&str> = [(4, "INSERT"), (16, "UPDATE"), (8, "DELETE"), (32, "TRUNCATE")]
+        .iter()
+        .filter(|(bit, _)| tgtype & bit != 0)
+        .map(|(_, event)| *event)
+        .collect();
+    let level = if tgtype & 1 != 0 { "ROW" } else { "STATEMENT" };
+    format!("{} {} FOR EACH {}", timing, events.join(" OR "), level)
+}
+
+async fn list_postgres_sequences(pool: &PgPool, schema: Option<&str>) -> Result<Vec<DatabaseObject>, ConnectionError> {
+    // Same privilege check as the pg_sequences view, which errors otherwise
+    let query = format!(
+        r#"
+        SELECT
+            n.nspname AS schema,
+            c.relname AS name,
+            format_type(s.seqtypid, NULL) AS detail,
+            CASE WHEN has_sequence_privilege(c.oid, 'SELECT,USAGE')
+                THEN pg_sequence_last_value(c.oid::regclass)
+            END AS current_value,
+            obj_description(c.oid, 'pg_class') AS comment
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_sequence s ON s.seqrelid = c.oid
+        WHERE c.relkind = 'S'
+          AND {PG_USER_SCHEMAS}
+        ORDER BY n.nspname, c.relname
+        "#
+    );
+    let rows = sqlx::query(&query).bind(schema).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| DatabaseObject {
+            detail: row.try_get("detail").ok(),
+            current_value: row.try_get("current_value").ok().flatten(),
+            comment: row.try_get("comment").ok().flatten(),
+            ..object(pg_text(row, "schema"), pg_text(row, "name"), ObjectKind::Sequence)
+        })
+        .collect())
+}
+
+async fn list_postgres_types(pool: &PgPool, schema: Option<&str>) -> Result<Vec<DatabaseObject>, ConnectionError> {
+    // Composite types of tables and views are implicit; only standalone ones count
+    let query = format!(
+        r#"
+        SELECT
+            n.nspname AS schema,
+            t.typname AS name,
+            CASE t.typtype
+                WHEN 'e' THEN 'enum (' || COALESCE((
+                    SELECT string_agg(quote_literal(e.enumlabel), ', ' ORDER BY e.enumsortorder)
+                    FROM pg_enum e WHERE e.enumtypid = t.oid
+                ), '') || ')'
+                WHEN 'd' THEN 'domain over ' || format_type(t.typbasetype, t.typtypmod)
+                WHEN 'r' THEN 'range of ' || (
+                    SELECT format_type(r.rngsubtype, NULL) FROM pg_range r WHERE r.rngtypid = t.oid
+                )
+                ELSE 'composite'
+            END AS detail,
+            obj_description(t.oid, 'pg_type') AS comment
+        FROM pg_type t
+        JOIN pg_namespace n ON n.oid = t.typnamespace
+        WHERE (
+                t.typtype IN ('e', 'd', 'r')
+                OR (t.typtype = 'c' AND (SELECT c.relkind FROM pg_class c WHERE c.oid = t.typrelid) = 'c')
+            )
+          AND {PG_USER_SCHEMAS}
+          AND {}
+        ORDER BY n.nspname, t.typname
+        "#,
+        pg_user_defined("pg_type", "t.oid")
+    );
+    let rows = sqlx::query(&query).bind(schema).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| DatabaseObject {
+            detail: row.try_get("detail").ok().flatten(),
+            comment: row.try_get("comment").ok().flatten(),
+            ..object(pg_text(row, "schema"), pg_text(row, "name"), ObjectKind::Type)
+        })
+        .collect())
+}
+
+pub(crate) fn pg_text(row: &PgRow, column: &str) -> String {
+    row.try_get(column).unwrap_or_default()
+}
+
+/// A Postgres object found by kind and name
+pub(crate) struct PgObject {
+    /// The lookup with the schema, and the arguments or table, filled in
+    pub params: GetObjectDefinitionParams,
+    pub oid: i64,
+    pub schema: String,
+    pub name: String,
+    /// Qualified name as written after the keyword, with arguments for routines
+    pub ident: String,
+    /// Object type as written in `ALTER` and `COMMENT ON`
+    pub keyword: String,
+}
+
+/// Find an object in the given schema or, without one, in the first schema on
+/// the search path that has a match. Overloaded routines need `arguments` and
+/// triggers repeated across tables need `table` to single one out.
+pub(crate) async fn resolve_postgres(
+    pool: &PgPool,
+    params: &GetObjectDefinitionParams,
+) -> Result<PgObject, ConnectionError> {
+    // Every query takes the name ($2) and a kind-specific filter ($3), and
+    // returns the columns of `PgObject`; the schema ($1) is matched below
+    let query = match params.kind {
+        ObjectKind::Table | ObjectKind::View | ObjectKind::MaterializedView | ObjectKind::Sequence => {
+            r#"
+            SELECT
+                c.oid::int8 AS oid,
+                n.nspname AS schema,
+                c.relname AS name,
+                NULL::text AS arguments,
+                NULL::text AS "table",
+                format('%I.%I', n.nspname, c.relname) AS ident,
+                CASE c.relkind WHEN 'v' THEN 'VIEW' WHEN 'm' THEN 'MATERIALIZED VIEW'
+                    WHEN 'S' THEN 'SEQUENCE' ELSE 'TABLE' END AS keyword
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE c.relname = $2
+              AND c.relkind::text = ANY(string_to_array($3, ','))
+            "#
+        }
+        ObjectKind::Function | ObjectKind::Procedure => {
+            r#"
+            SELECT
+                p.oid::int8 AS oid,
+                n.nspname AS schema,
+                p.proname AS name,
+                pg_get_function_identity_arguments(p.oid) AS arguments,
+                NULL::text AS "table",
+                format('%I.%I(%s)', n.nspname, p.proname, pg_get_function_identity_arguments(p.oid)) AS ident,
+                CASE p.prokind WHEN 'p' THEN 'PROCEDURE' ELSE 'FUNCTION' END AS keyword
+            FROM pg_proc p
+            JOIN pg_namespace n ON n.oid = p.pronamespace
+            WHERE p.proname = $2
+              AND p.prokind NOT IN ('a', 'w')
+              AND ($3::text IS NULL OR pg_get_function_identity_arguments(p.oid) = $3)
+            "#
+        }
+        ObjectKind::Type => {
+            r#"
+            SELECT
+                t.oid::int8 AS oid,
+                n.nspname AS schema,
+                t.typname AS name,
+                NULL::text AS arguments,
+                NULL::text AS "table",
+                format('%I.%I', n.nspname, t.typname) AS ident,
+                CASE t.typtype WHEN 'd' THEN 'DOMAIN' ELSE 'TYPE' END AS keyword
+            FROM pg_type t
+            JOIN pg_namespace n ON n.oid = t.typnamespace
+            LEFT JOIN pg_class tc ON tc.oid = t.typrelid
+            WHERE t.typname = $2
+              AND (t.typtype IN ('e', 'd', 'r') OR tc.relkind = 'c')
+              AND $3::text IS NULL
+            "#
+        }
+        ObjectKind::Trigger => {
+            r#"
+            SELECT
+                t.oid::int8 AS oid,
+                n.nspname AS schema,
+                t.tgname AS name,
+                NULL::text AS arguments,
+                c.relname AS "table",
+                format('%I ON %I.%I', t.tgname, n.nspname, c.relname) AS ident,
+                'TRIGGER' AS keyword
+            FROM pg_trigger t
+            JOIN pg_class c ON c.oid = t.tgrelid
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE t.tgname = $2
+              AND NOT t.tgisinternal
+              AND t.tgparentid = 0
+              AND ($3::text IS NULL OR c.relname = $3)
+            "#
+        }
+        ObjectKind::Event => {
+            return Err(ConnectionError::InvalidInput("Postgres has no scheduled events".to_string()))
+        }
+    };
+    let query = format!(
+        "{query}
+              AND ($1::text = n.nspname OR ($1 IS NULL AND n.nspname = ANY(current_schemas(false))))
+            ORDER BY array_position(current_schemas(false), n.nspname::text)"
+    );
+
+    let filter = match params.kind {
+        ObjectKind::Table => Some("r,p".to_string()),
+        ObjectKind::View => Some("v".to_string()),
+        ObjectKind::MaterializedView => Some("m".to_string()),
+        ObjectKind::Sequence => Some("S".to_string()),
+        ObjectKind::Function | ObjectKind::Procedure => params.arguments.clone(),
+        ObjectKind::Trigger => params.table.clone(),
+        _ => None,
+    };
+
+    let rows = sqlx::query(&query)
+        .bind(params.schema.as_deref())
+        .bind(&params.name)
+        .bind(filter)
+        .fetch_all(pool)
+        .await?;
+
+    // Only the first schema on the search path that has a match counts
+    let first_schema = rows.first().map(|row| pg_text(row, "schema"));
+    let matches: Vec<&PgRow> = rows
+        .iter()
+        .filter(|row| Some(pg_text(row, "schema")) == first_schema)
+        .collect();
+    let row = match matches.as_slice() {
+        [] => return Err(not_found(params)),
+        [row] => *row,
+        _ => {
+            return Err(ConnectionError::InvalidInput(match params.kind {
+                ObjectKind::Trigger => {
+                    format!("Several tables have a trigger named {}; specify the table", params.name)
+                }
+                _ => format!("{} is overloaded; specify its arguments", params.name),
+            }))
+        }
+    };
+
+    let schema = pg_text(row, "schema");
+    Ok(PgObject {
+        params: GetObjectDefinitionParams {
+            schema: Some(schema.clone()),
+            arguments: row.try_get("arguments")?,
+            table: row.try_get("table")?,
+            ..params.clone()
+        },
+        oid: row.try_get("oid")?,
+        schema,
+        name: pg_text(row, "name"),
+        ident: pg_text(row, "ident"),
+        keyword: pg_text(row, "keyword"),
+    })
+}
+
+/// Source of a resolved object other than a table, whose DDL is assembled by
+/// `ddl::postgres`
+pub(crate) async fn postgres_definition(pool: &PgPool, target: &PgObject) -> Result<String, ConnectionError> {
+    // Every query takes the object's OID ($1); those that spell out the name
+    // take it qualified ($2)
+    let query = match target.params.kind {
+        ObjectKind::View | ObjectKind::MaterializedView => {
+            r#"
+            SELECT format(
+                'CREATE %s %s AS%s%s',
+                CASE c.relkind WHEN 'm' THEN 'MATERIALIZED VIEW' ELSE 'OR REPLACE VIEW' END,
+                $2::text, E'\n', pg_get_viewdef(c.oid, true)
+            )
+            FROM pg_class c
+            WHERE c.oid = $1::int8::oid
+            "#
+        }
+        ObjectKind::Function | ObjectKind::Procedure => "SELECT pg_get_functiondef(p.oid) FROM pg_proc p WHERE p.oid = $1::int8::oid",
+        ObjectKind::Trigger => {
+            "SELECT pg_get_triggerdef(t.oid, true) || ';' FROM pg_trigger t WHERE t.oid = $1::int8::oid"
+        }
+        ObjectKind::Sequence => {
+            r#"
+            SELECT format(
+                'CREATE SEQUENCE %s AS %s INCREMENT BY %s MINVALUE %s MAXVALUE %s START WITH %s CACHE %s%s;',
+                $2::text, format_type(s.seqtypid, NULL), s.seqincrement,
+                s.seqmin, s.seqmax, s.seqstart, s.seqcache,
+                CASE WHEN s.seqcycle THEN ' CYCLE' ELSE ' NO CYCLE' END
+            )
+            FROM pg_sequence s
+            WHERE s.seqrelid = $1::int8::oid
+            "#
+        }
+        ObjectKind::Type => {
+            r#"
+            SELECT
+                CASE t.typtype
+                    WHEN 'e' THEN format('CREATE TYPE %s AS ENUM (%s);', $2::text, (
+                        SELECT string_agg(quote_literal(e.enumlabel), ', ' ORDER BY e.enumsortorder)
+                        FROM pg_enum e WHERE e.enumtypid = t.oid
+                    ))
+                    WHEN 'd' THEN format('CREATE DOMAIN %s AS %s', $2::text, format_type(t.typbasetype, t.typtypmod))
+                        || COALESCE(' DEFAULT ' || t.typdefault, '')
+                        || CASE WHEN t.typnotnull THEN ' NOT NULL' ELSE '' END
+                        || COALESCE((
+                            SELECT string_agg(
+                                format(E'\n    CONSTRAINT %I %s', con.conname, pg_get_constraintdef(con.oid, true)),
+                                '' ORDER BY con.conname
+                            )
+                            FROM pg_constraint con WHERE con.contypid = t.oid AND con.contype = 'c'
+                        ), '')
+                        || ';'
+                    WHEN 'r' THEN format('CREATE TYPE %s AS RANGE (SUBTYPE = %s);', $2::text, (
+                        SELECT format_type(r.rngsubtype, NULL) FROM pg_range r WHERE r.rngtypid = t.oid
+                    ))
+                    ELSE format(E'CREATE TYPE %s AS (\n    %s\n);', $2::text, (
+                        SELECT string_agg(format('%I %s', a.attname, format_type(a.atttypid, a.atttypmod)),
+                            E',\n    ' ORDER BY a.attnum)
+                        FROM pg_attribute a
+                        WHERE a.attrelid = t.typrelid AND a.attnum > 0 AND NOT a.attisdropped
+                    ))
+                END
+            FROM pg_type t
+            WHERE t.oid = $1::int8::oid
+            "#
+        }
+        ObjectKind::Table | ObjectKind::Event => {
+            return Err(ConnectionError::InvalidInput(format!(
+                "No {} definition for {}",
+                target.keyword, target.ident
+            )))
+        }
+    };
+
+    let mut query = sqlx::query_scalar(query).bind(target.oid);
+    if !matches!(target.params.kind, ObjectKind::Function | ObjectKind::Procedure | ObjectKind::Trigger) {
+        query = query.bind(&target.ident);
+    }
+    let definition: Option<String> = query.fetch_optional(pool).await?.flatten();
+    // Dropped between the lookup and now
+    definition.ok_or_else(|| not_found(&target.params))
+}
+
+async fn list_mysql_relations(pool: &MySqlPool, schema: Option<&str>) -> Result<Vec<DatabaseObject>, ConnectionError> {
+    let query = r#"
+        SELECT
+            TABLE_SCHEMA AS `schema`,
+            TABLE_NAME AS name,
+            TABLE_TYPE = 'VIEW' AS is_view,
+            TABLE_COMMENT AS comment
+        FROM information_schema.TABLES
+        WHERE TABLE_SCHEMA = COALESCE(?, DATABASE())
+        ORDER BY TABLE_NAME
+    "#;
+    let rows = sqlx::query(query).bind(schema).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let kind = if row.try_get::<i64, _>("is_view").unwrap_or_default() != 0 {
+                ObjectKind::View
+            } else {
+                ObjectKind::Table
+            };
+            DatabaseObject {
+                comment: mysql_comment(row, "comment"),
+                ..object(mysql_text(row, "schema"), mysql_text(row, "name"), kind)
+            }
+        })
+        .collect())
+}
+
+async fn list_mysql_routines(pool: &MySqlPool, schema: Option<&str>) -> Result<Vec<DatabaseObject>, ConnectionError> {
+    let query = r#"
+        SELECT
+            ROUTINE_SCHEMA AS `schema`,
+            ROUTINE_NAME AS name,
+            ROUTINE_TYPE = 'PROCEDURE' AS is_procedure,
+            DTD_IDENTIFIER AS result,
+            ROUTINE_COMMENT AS comment,
+            (
+                SELECT GROUP_CONCAT(
+                    CONCAT_WS(' ', p.PARAMETER_MODE, p.PARAMETER_NAME, p.DTD_IDENTIFIER)
+                    ORDER BY p.ORDINAL_POSITION SEPARATOR ', '
+                )
+                FROM information_schema.PARAMETERS p
+                WHERE p.SPECIFIC_SCHEMA = r.ROUTINE_SCHEMA
+                  AND p.SPECIFIC_NAME = r.SPECIFIC_NAME
+                  AND p.ORDINAL_POSITION > 0
+            ) AS arguments
+        FROM information_schema.ROUTINES r
+        WHERE ROUTINE_SCHEMA = COALESCE(?, DATABASE())
+        ORDER BY ROUTINE_NAME
+    "#;
+    let rows = sqlx::query(query).bind(schema).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let kind = if row.try_get::<i64, _>("is_procedure").unwrap_or_default() != 0 {
+                ObjectKind::Procedure
+            } else {
+                ObjectKind::Function
+            };
+            DatabaseObject {
+                arguments: Some(row.try_get::<Option<String>, _>("arguments").ok().flatten().unwrap_or_default()),
+                result: row.try_get("result").ok().flatten(),
+                comment: mysql_comment(row, "comment"),
+                ..object(mysql_text(row, "schema"), mysql_text(row, "name"), kind)
+            }
+        })
+        .collect())
+}
+
+async fn list_mysql_triggers(pool: &MySqlPool, schema: Option<&str>) -> Result<Vec<DatabaseObject>, ConnectionError> {
+    let query = r#"
+        SELECT
+            TRIGGER_SCHEMA AS `schema`,
+            TRIGGER_NAME AS name,
+            EVENT_OBJECT_TABLE AS table_name,
+            CONCAT(ACTION_TIMING, ' ', EVENT_MANIPULATION, ' FOR EACH ', ACTION_ORIENTATION) AS detail
+        FROM information_schema.TRIGGERS
+        WHERE TRIGGER_SCHEMA = COALESCE(?, DATABASE())
+        ORDER BY EVENT_OBJECT_TABLE, ACTION_ORDER, TRIGGER_NAME
+    "#;
+    let rows = sqlx::query(query).bind(schema).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| DatabaseObject {
+            table: Some(mysql_text(row, "table_name")),
+            detail: row.try_get("detail").ok(),
+            ..object(mysql_text(row, "schema"), mysql_text(row, "name"), ObjectKind::Trigger)
+        })
+        .collect())
+}
+
+async fn list_mysql_events(pool: &MySqlPool, schema: Option<&str>) -> Result<Vec<DatabaseObject>, ConnectionError> {
+    let query = r#"
+        SELECT
+            EVENT_SCHEMA AS `schema`,
+            EVENT_NAME AS name,
+            CONCAT(
+                IF(EVENT_TYPE = 'RECURRING',
+                    CONCAT('EVERY ', INTERVAL_VALUE, ' ', INTERVAL_FIELD),
+                    CONCAT('AT ', EXECUTE_AT)),
+                IF(STATUS = 'ENABLED', '', CONCAT(' (', LOWER(STATUS), ')'))
+            ) AS detail,
+            EVENT_COMMENT AS comment
+        FROM information_schema.EVENTS
+        WHERE EVENT_SCHEMA = COALESCE(?, DATABASE())
+        ORDER BY EVENT_NAME
+    "#;
+    let rows = sqlx::query(query).bind(schema).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| DatabaseObject {
+            detail: row.try_get("detail").ok().flatten(),
+            comment: mysql_comment(row, "comment"),
+            ..object(mysql_text(row, "schema"), mysql_text(row, "name"), ObjectKind::Event)
+        })
+        .collect())
+}
+
+fn mysql_text(row: &MySqlRow, column: &str) -> String {
+    row.try_get(column).unwrap_or_default()
+}
+
+/// MySQL stores a missing comment as an empty string
+fn mysql_comment(row: &MySqlRow, column: &str) -> Option<String> {
+    row.try_get::<Option<String>, _>(column).ok().flatten().filter(|c| !c.is_empty())
+}
+
+async fn mysql_definition(pool: &MySqlPool, params: &GetObjectDefinitionParams) -> Result<String, ConnectionError> {
+    let (statement, column) = match params.kind {
+        ObjectKind::Table => ("TABLE", "Create Table"),
+        ObjectKind::View => ("VIEW", "Create View"),
+        ObjectKind::Function => ("FUNCTION", "Create Function"),
+        ObjectKind::Procedure => ("PROCEDURE", "Create Procedure"),
+        ObjectKind::Trigger => ("TRIGGER", "SQL Original Statement"),
+        ObjectKind::Event => ("EVENT", "Create Event"),
+        ObjectKind::MaterializedView | ObjectKind::Sequence | ObjectKind::Type => {
+            return Err(ConnectionError::InvalidInput(
+                "Materialized views, sequences and types are only available on Postgres".to_string(),
+            ))
+        }
+    };
+
+    let database: Option<String> = match &params.schema {
+        Some(schema) => Some(schema.clone()),
+        None => sqlx::query_scalar("SELECT DATABASE()").fetch_one(pool).await?,
+    };
+    let database = database
+        .ok_or_else(|| ConnectionError::InvalidInput("No database selected; specify a schema".to_string()))?;
+
+    let sql = format!(
+        "SHOW CREATE {} {}.{}",
+        statement,
+        DatabaseType::Mysql.quote_ident(&database),
+        DatabaseType::Mysql.quote_ident(&params.name)
+    );
+    let row = match sqlx::query(&sql).fetch_optional(pool).await {
+        Ok(row) => row,
+        // No such table, routine, trigger or event
+        Err(e) if matches!(mysql_error_number(&e), Some(1146 | 1305 | 1360 | 1539)) => None,
+        Err(e) => return Err(e.into()),
+    };
+    let row = row.ok_or_else(|| not_found(params))?;
+
+    // Routine bodies are NULL for users who don't own them
+    let definition = match row.try_get::<Option<String>, _>(column) {
+        Ok(definition) => definition,
+        Err(_) => row
+            .try_get::<Option<Vec<u8>>, _>(column)?
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned()),
+    };
+    definition.ok_or_else(|| {
+        ConnectionError::InvalidInput(format!("Not permitted to read the definition of {}", params.name))
+    })
+}
+
+fn not_found(params: &GetObjectDefinitionParams) -> ConnectionError {
+    match &params.schema {
+        Some(schema) => ConnectionError::NotFound(format!("{}.{}", schema, params.name)),
+        None => ConnectionError::NotFound(params.name.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pg_trigger_detail_reads_tgtype_bits() {
+        // ROW | BEFORE | INSERT | UPDATE
+        assert_eq!(pg_trigger_detail(1 | 2 | 4 | 16), "BEFORE INSERT OR UPDATE FOR EACH ROW");
+        assert_eq!(pg_trigger_detail(8), "AFTER DELETE FOR EACH STATEMENT");
+        assert_eq!(pg_trigger_detail(1 | 64 | 4), "INSTEAD OF INSERT FOR EACH ROW");
+        assert_eq!(pg_trigger_detail(32), "AFTER TRUNCATE FOR EACH STATEMENT");
+    }
+
+    #[test]
+    fn pg_user_defined_excludes_extension_and_implicit_members() {
+        let sql = pg_user_defined("pg_proc", "p.oid");
+        assert!(sql.starts_with("NOT EXISTS"));
+        assert!(sql.contains("d.classid = 'pg_proc'::regclass AND d.objid = p.oid"));
+        assert!(sql.contains("d.deptype IN ('e', 'i')"));
+    }
+
+    #[test]
+    fn not_found_qualifies_with_the_schema() {
+        let params = GetObjectDefinitionParams {
+            connection_id: "c".to_string(),
+            kind: ObjectKind::View,
+            database: None,
+            schema: Some("app".to_string()),
+            name: "active_users".to_string(),
+            arguments: None,
+            table: None,
+        };
+        assert!(matches!(not_found(&params), ConnectionError::NotFound(name) if name == "app.active_users"));
+        let unqualified = GetObjectDefinitionParams { schema: None, ..params };
+        assert!(matches!(not_found(&unqualified), ConnectionError::NotFound(name) if name == "active_users"));
+    }
+}
@@ -0,0 +1,939 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use parking_lot::RwLock;
+use uuid::Uuid;
+
+use super::connection::{cap_value, values_to_object, ConnectionError, DatabasePool, ResultLimits, StreamSummary};
+use super::types::*;
+
+/// Total disk space cached results may use before the least recently used are evicted
+pub const DEFAULT_MAX_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Cached results not read for this long are evicted
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Size of one entry in the row offset and permutation files
+const INDEX_ENTRY_SIZE: u64 = 8;
+
+/// Approximate bytes of sort keys held in memory at once while sorting; larger
+/// results are sorted in runs of this size and merged from disk
+const SORT_RUN_BYTES: usize = 32 * 1024 * 1024;
+
+/// A cached result on disk. Rows are stored as compact positional JSON arrays
+/// in `<id>.rows`; `<id>.idx` holds the little-endian u64 start offset of every
+/// row plus the end of the file, so any row can be read with two seeks. Sorted
+/// views are written as permutation files of row numbers and reused. Values
+/// are stored in full and cut to previews of `max_cell_bytes` when paged.
+struct CachedResult {
+    columns: Vec<ColumnInfo>,
+    total_rows: usize,
+    bytes: u64,
+    max_cell_bytes: usize,
+    last_access: Instant,
+    sorted: HashMap<(usize, bool), PathBuf>,
+}
+
+/// Summary of a freshly cached result
+pub struct StoredResult {
+    pub id: String,
+    pub columns: Vec<ColumnInfo>,
+    pub total_rows: usize,
+    /// Notices (Postgres) or warnings (MySQL) raised by the query
+    pub notices: Vec<QueryNotice>,
+    /// Set when the row or byte limit stopped the result being stored in full
+    pub truncated_reason: Option<TruncationReason>,
+    pub stats: QueryStats,
+}
+
+/// Disk-backed cache of full query results, so large results can be paged and
+/// sorted without re-running the query or holding rows in memory
+pub struct ResultCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    max_age: Duration,
+    entries: RwLock<HashMap<String, CachedResult>>,
+}
+
+impl ResultCache {
+    /// Results are written to a directory of this process under `dir`, so
+    /// other running instances keep theirs. Cached results only live for one
+    /// session: the directory is emptied on startup and removed on drop.
+    pub fn new(dir: PathBuf, max_bytes: u64, max_age: Duration) -> Result<Self, std::io::Error> {
+        let dir = dir.join(std::process::id().to_string());
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            dir,
+            max_bytes,
+            max_age,
+            entries: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Run `sql` and stream its result into the cache, up to the row and byte limits
+    pub async fn store(
+        &self,
+        pool: Arc<DatabasePool>,
+        sql: String,
+        limits: ResultLimits,
+        include_backend_id: bool,
+    ) -> Result<StoredResult, ConnectionError> {
+        let id = Uuid::new_v4().to_string();
+        let rows_path = self.rows_path(&id);
+        let index_path = self.index_path(&id);
+
+        // The files are written with blocking I/O, so the query is driven from the blocking pool
+        let runtime = tokio::runtime::Handle::current();
+        let written = tokio::task::spawn_blocking(move || {
+            runtime.block_on(write_result(&pool, &sql, limits, include_backend_id, &rows_path, &index_path))
+        })
+        .await
+        .map_err(std::io::Error::other)?;
+
+        let (written, summary) = match written {
+            Ok(written) => written,
+            Err(e) => {
+                self.remove_files(&id);
+                return Err(e);
+            }
+        };
+
+        self.entries.write().insert(
+            id.clone(),
+            CachedResult {
+                columns: written.columns.clone(),
+                total_rows: written.total_rows,
+                bytes: written.bytes,
+                max_cell_bytes: limits.max_cell_bytes,
+                last_access: Instant::now(),
+                sorted: HashMap::new(),
+            },
+        );
+        self.evict(Some(&id));
+
+        Ok(StoredResult {
+            id,
+            columns: written.columns,
+            total_rows: written.total_rows,
+            notices: summary.notices,
+            truncated_reason: written.truncated_reason,
+            stats: summary.stats,
+        })
+    }
+
+    /// Read a page of a cached result, optionally sorted by one column
+    pub async fn page(&self, params: GetResultPageParams) -> Result<ResultPage, ConnectionError> {
+        self.evict(Some(&params.result_id));
+
+        let (columns, total_rows, max_cell_bytes, sorted_path) = {
+            let mut entries = self.entries.write();
+            let entry = entries
+                .get_mut(&params.result_id)
+                .ok_or_else(|| ConnectionError::NotFound(params.result_id.clone()))?;
+            entry.last_access = Instant::now();
+
+            let sort_key = match &params.sort {
+                Some(sort) => {
+                    let column = entry
+                        .columns
+                        .iter()
+                        .position(|c| c.name == sort.column)
+                        .ok_or_else(|| ConnectionError::InvalidInput(format!("Unknown column: {}", sort.column)))?;
+                    Some((column, sort.descending))
+                }
+                None => None,
+            };
+
+            (
+                entry.columns.clone(),
+                entry.total_rows,
+                entry.max_cell_bytes,
+                sort_key.map(|key| (key, entry.sorted.get(&key).cloned())),
+            )
+        };
+
+        let offset = (params.offset.max(0) as usize).min(total_rows);
+        let limit = (params.limit.max(0) as usize).min(total_rows - offset);
+
+        let rows_path = self.rows_path(&params.result_id);
+        let index_path = self.index_path(&params.result_id);
+        let sort_path = |(column, descending): (usize, bool)| {
+            self.dir.join(format!(
+                "{}.{}.{}.perm",
+                params.result_id,
+                column,
+                if descending { "desc" } else { "asc" }
+            ))
+        };
+        let to_build = match sorted_path {
+            Some((key, None)) => Some((key, sort_path(key))),
+            _ => None,
+        };
+        let permutation = match &sorted_path {
+            Some((_, Some(path))) => Some(path.clone()),
+            _ => to_build.as_ref().map(|(_, path)| path.clone()),
+        };
+
+        let build = to_build.clone();
+        let values = tokio::task::spawn_blocking(move || {
+            if let Some(((column, descending), path)) = &build {
+                sort_permutation(&rows_path, &index_path, total_rows, *column, *descending, path, SORT_RUN_BYTES)?;
+            }
+
+            let row_numbers: Vec<u64> = match &permutation {
+                None => (offset as u64..(offset + limit) as u64).collect(),
+                Some(path) => read_u64_range(path, offset, limit)?,
+            };
+
+            let mut rows_file = File::open(&rows_path)?;
+            let mut index_file = File::open(&index_path)?;
+            row_numbers
+                .into_iter()
+                .map(|row_number| read_row(&mut rows_file, &mut index_file, row_number))
+                .collect::<Result<Vec<_>, std::io::Error>>()
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+        if let Some((key, path)) = to_build {
+            let mut entries = self.entries.write();
+            if let Some(entry) = entries.get_mut(&params.result_id) {
+                entry.bytes += total_rows as u64 * INDEX_ENTRY_SIZE;
+                entry.sorted.insert(key, path);
+            }
+        }
+
+        let mut truncated_cells = Vec::new();
+        let rows: Vec<_> = values
+            .into_iter()
+            .enumerate()
+            .map(|(row, mut values)| {
+                for (column, value) in columns.iter().zip(values.iter_mut()) {
+                    if let Some(length) = cap_value(value, max_cell_bytes) {
+                        truncated_cells.push(TruncatedCell {
+                            row,
+                            column: column.name.clone(),
+                            length,
+                        });
+                    }
+                }
+                values_to_object(&columns, values)
+            })
+            .collect();
+
+        Ok(ResultPage {
+            result_id: params.result_id,
+            has_more: offset + rows.len() < total_rows,
+            columns,
+            rows,
+            offset,
+            total_rows,
+            truncated_cells,
+        })
+    }
+
+    /// Read one cell of a cached result in full; `row` is in query order
+    pub async fn cell(
+        &self,
+        result_id: &str,
+        row: usize,
+        column: &str,
+    ) -> Result<(ColumnInfo, serde_json::Value), ConnectionError> {
+        let (index, column, total_rows) = {
+            let mut entries = self.entries.write();
+            let entry = entries
+                .get_mut(result_id)
+                .ok_or_else(|| ConnectionError::NotFound(result_id.to_string()))?;
+            entry.last_access = Instant::now();
+
+            let index = entry
+                .columns
+                .iter()
+                .position(|c| c.name == column)
+                .ok_or_else(|| ConnectionError::InvalidInput(format!("Unknown column: {}", column)))?;
+            (index, entry.columns[index].clone(), entry.total_rows)
+        };
+
+        if row >= total_rows {
+            return Err(ConnectionError::InvalidInput(format!("Row {} is out of range", row)));
+        }
+
+        let rows_path = self.rows_path(result_id);
+        let index_path = self.index_path(result_id);
+        let mut values = tokio::task::spawn_blocking(move || {
+            let mut rows_file = File::open(rows_path)?;
+            let mut index_file = File::open(index_path)?;
+            read_row(&mut rows_file, &mut index_file, row as u64)
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+        let value = if index < values.len() {
+            values.swap_remove(index)
+        } else {
+            serde_json::Value::Null
+        };
+        Ok((column, value))
+    }
+
+    /// Drop a cached result and its files
+    pub fn release(&self, result_id: &str) {
+        if self.entries.write().remove(result_id).is_some() {
+            self.remove_files(result_id);
+        }
+    }
+
+    /// Evict results idle for longer than the max age, then the least recently
+    /// used until the cache fits its size budget. `keep` is never evicted.
+    fn evict(&self, keep: Option<&str>) {
+        let now = Instant::now();
+        let mut entries = self.entries.write();
+
+        let mut expired: Vec<String> = entries
+            .iter()
+            .filter(|(id, entry)| Some(id.as_str()) != keep && now.duration_since(entry.last_access) > self.max_age)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let mut remaining: Vec<(&String, &CachedResult)> = entries
+            .iter()
+            .filter(|(id, _)| !expired.contains(id))
+            .collect();
+        remaining.sort_by_key(|(_, entry)| entry.last_access);
+
+        let mut total: u64 = remaining.iter().map(|(_, entry)| entry.bytes).sum();
+        for (id, entry) in remaining {
+            if total <= self.max_bytes {
+                break;
+            }
+            if Some(id.as_str()) != keep {
+                total -= entry.bytes;
+                expired.push(id.clone());
+            }
+        }
+
+        for id in expired {
+            entries.remove(&id);
+            self.remove_files(&id);
+        }
+    }
+
+    fn remove_files(&self, result_id: &str) {
+        let _ = std::fs::remove_file(self.rows_path(result_id));
+        let _ = std::fs::remove_file(self.index_path(result_id));
+        if let Ok(dir) = std::fs::read_dir(&self.dir) {
+            let prefix = format!("{}.", result_id);
+            for entry in dir.flatten() {
+                if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+    }
+
+    fn rows_path(&self, result_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.rows", result_id))
+    }
+
+    fn index_path(&self, result_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.idx", result_id))
+    }
+}
+
+impl Drop for ResultCache {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// What `write_result` put on disk
+struct WrittenResult {
+    columns: Vec<ColumnInfo>,
+    total_rows: usize,
+    bytes: u64,
+    truncated_reason: Option<TruncationReason>,
+}
+
+/// Stream the query result into the rows and index files until a row or byte
+/// limit is reached, returning what was written and the query's diagnostics
+async fn write_result(
+    pool: &DatabasePool,
+    sql: &str,
+    limits: ResultLimits,
+    include_backend_id: bool,
+    rows_path: &Path,
+    index_path: &Path,
+) -> Result<(WrittenResult, StreamSummary), ConnectionError> {
+    let mut rows = BufWriter::new(File::create(rows_path)?);
+    let mut index = BufWriter::new(File::create(index_path)?);
+
+    let mut columns = Vec::new();
+    let mut total_rows = 0;
+    let mut offset: u64 = 0;
+    let mut truncated_reason = None;
+
+    // The ID is only looked up when asked for, since nothing cancels a cached query
+    let on_start = include_backend_id.then_some(|_| {});
+    let summary = pool
+        .stream_query(sql, on_start, |row_columns, values| {
+            if total_rows == 0 {
+                columns = row_columns.to_vec();
+            }
+            if total_rows >= limits.max_rows {
+                truncated_reason = Some(TruncationReason::RowLimit);
+                return Ok(false);
+            }
+
+            let encoded = serde_json::to_vec(&values).map_err(std::io::Error::from)?;
+            if offset + encoded.len() as u64 > limits.max_bytes as u64 {
+                truncated_reason = Some(TruncationReason::ByteLimit);
+                return Ok(false);
+            }
+
+            rows.write_all(&encoded)?;
+            index.write_all(&offset.to_le_bytes())?;
+
+            offset += encoded.len() as u64;
+            total_rows += 1;
+            Ok(true)
+        })
+        .await?;
+
+    index.write_all(&offset.to_le_bytes())?;
+    rows.flush()?;
+    index.flush()?;
+
+    let written = WrittenResult {
+        columns,
+        total_rows,
+        bytes: offset + (total_rows as u64 + 1) * INDEX_ENTRY_SIZE,
+        truncated_reason,
+    };
+    Ok((written, summary))
+}
+
+/// A sort key with the row it came from. Ties keep the original row order.
+struct SortEntry {
+    key: serde_json::Value,
+    row_number: u64,
+    descending: bool,
+}
+
+impl Ord for SortEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ordering = compare_values(&self.key, &other.key);
+        let ordering = if self.descending { ordering.reverse() } else { ordering };
+        ordering.then(self.row_number.cmp(&other.row_number))
+    }
+}
+
+impl PartialOrd for SortEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SortEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SortEntry {}
+
+/// Sort the result by one column and write the row-number permutation to
+/// `target`. Keys are sorted in runs of about `run_bytes` which are spilled to
+/// disk and merged, so memory stays bounded however large the result is. The
+/// permutation is written under a unique name and renamed into place, so
+/// concurrent sorts on the same key never expose a partly written file.
+fn sort_permutation(
+    rows_path: &Path,
+    index_path: &Path,
+    total_rows: usize,
+    column: usize,
+    descending: bool,
+    target: &Path,
+    run_bytes: usize,
+) -> Result<(), std::io::Error> {
+    let mut rows_file = BufReader::new(File::open(rows_path)?);
+    let mut offsets = BufReader::new(File::open(index_path)?);
+
+    let mut partial = target.as_os_str().to_owned();
+    partial.push(format!(".{}.partial", Uuid::new_v4()));
+    let partial = PathBuf::from(partial);
+
+    let mut runs = Vec::new();
+    let mut run: Vec<SortEntry> = Vec::new();
+    let mut run_size = 0;
+    let mut buffer = Vec::new();
+    let mut start = read_u64(&mut offsets)?;
+
+    let result = (|| {
+        for row_number in 0..total_rows as u64 {
+            let end = read_u64(&mut offsets)?;
+            buffer.resize((end - start) as usize, 0);
+            rows_file.read_exact(&mut buffer)?;
+            start = end;
+
+            let mut values: Vec<serde_json::Value> = serde_json::from_slice(&buffer)?;
+            let key = if column < values.len() {
+                values.swap_remove(column)
+            } else {
+                serde_json::Value::Null
+            };
+
+            run_size += key_size(&key);
+            run.push(SortEntry { key, row_number, descending });
+            if run_size >= run_bytes {
+                runs.push(write_run(&partial, runs.len(), &mut run)?);
+                run_size = 0;
+            }
+        }
+
+        let mut writer = BufWriter::new(File::create(&partial)?);
+        if runs.is_empty() {
+            // Everything fit in memory, no merge needed
+            run.sort();
+            for entry in &run {
+                writer.write_all(&entry.row_number.to_le_bytes())?;
+            }
+        } else {
+            if !run.is_empty() {
+                runs.push(write_run(&partial, runs.len(), &mut run)?);
+            }
+            merge_runs(&runs, descending, &mut writer)?;
+        }
+        writer.flush()?;
+        drop(writer);
+
+        match std::fs::rename(&partial, target) {
+            // Windows can't replace a file that is being read; whatever is
+            // at `target` was renamed there complete by another sort
+            Err(_) if target.exists() => std::fs::remove_file(&partial),
+            renamed => renamed,
+        }
+    })();
+
+    for path in &runs {
+        let _ = std::fs::remove_file(path);
+    }
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+/// Rough in-memory footprint of a sort key
+fn key_size(key: &serde_json::Value) -> usize {
+    std::mem::size_of::<SortEntry>()
+        + match key {
+            serde_json::Value::String(s) => s.len(),
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => key.to_string().len(),
+            _ => 0,
+        }
+}
+
+/// Sort `run` and write it next to `target` as length-prefixed entries
+fn write_run(target: &Path, number: usize, run: &mut Vec<SortEntry>) -> Result<PathBuf, std::io::Error> {
+    run.sort();
+
+    let mut name = target.as_os_str().to_owned();
+    name.push(format!(".run{}", number));
+    let path = PathBuf::from(name);
+
+    let mut writer = BufWriter::new(File::create(&path)?);
+    for entry in run.drain(..) {
+        let key = serde_json::to_vec(&entry.key)?;
+        writer.write_all(&entry.row_number.to_le_bytes())?;
+        writer.write_all(&(key.len() as u64).to_le_bytes())?;
+        writer.write_all(&key)?;
+    }
+    writer.flush()?;
+
+    Ok(path)
+}
+
+fn read_run_entry(
+    reader: &mut BufReader<File>,
+    descending: bool,
+) -> Result<Option<SortEntry>, std::io::Error> {
+    let row_number = match read_u64(reader) {
+        Ok(row_number) => row_number,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut key = vec![0u8; read_u64(reader)? as usize];
+    reader.read_exact(&mut key)?;
+
+    Ok(Some(SortEntry {
+        key: serde_json::from_slice(&key)?,
+        row_number,
+        descending,
+    }))
+}
+
+/// K-way merge of sorted runs, holding one entry per run in memory
+fn merge_runs(runs: &[PathBuf], descending: bool, writer: &mut impl Write) -> Result<(), std::io::Error> {
+    let mut readers = runs
+        .iter()
+        .map(|path| File::open(path).map(BufReader::new))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut heap = BinaryHeap::new();
+    for (run, reader) in readers.iter_mut().enumerate() {
+        if let Some(entry) = read_run_entry(reader, descending)? {
+            heap.push(Reverse((entry, run)));
+        }
+    }
+
+    while let Some(Reverse((entry, run))) = heap.pop() {
+        writer.write_all(&entry.row_number.to_le_bytes())?;
+        if let Some(next) = read_run_entry(&mut readers[run], descending)? {
+            heap.push(Reverse((next, run)));
+        }
+    }
+
+    Ok(())
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, std::io::Error> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Read `count` consecutive u64 entries starting at entry `start`
+fn read_u64_range(path: &Path, start: usize, count: usize) -> Result<Vec<u64>, std::io::Error> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start as u64 * INDEX_ENTRY_SIZE))?;
+
+    let mut buffer = vec![0u8; count * INDEX_ENTRY_SIZE as usize];
+    file.read_exact(&mut buffer)?;
+
+    Ok(buffer
+        .chunks_exact(INDEX_ENTRY_SIZE as usize)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap_or_default()))
+        .collect())
+}
+
+fn read_row(
+    rows_file: &mut File,
+    index_file: &mut File,
+    row_number: u64,
+) -> Result<Vec<serde_json::Value>, std::io::Error> {
+    let mut bounds = [0u8; 16];
+    index_file.seek(SeekFrom::Start(row_number * INDEX_ENTRY_SIZE))?;
+    index_file.read_exact(&mut bounds)?;
+
+    let start = u64::from_le_bytes(bounds[..8].try_into().unwrap_or_default());
+    let end = u64::from_le_bytes(bounds[8..].try_into().unwrap_or_default());
+
+    let mut buffer = vec![0u8; (end - start) as usize];
+    rows_file.seek(SeekFrom::Start(start))?;
+    rows_file.read_exact(&mut buffer)?;
+
+    serde_json::from_slice(&buffer).map_err(std::io::Error::from)
+}
+
+/// Order JSON values the way the database would: numbers numerically, text
+/// lexically, and NULLs after everything else
+fn compare_values(a: &serde_json::Value, b: &serde_json::Value) -> Ordering {
+    use serde_json::Value;
+
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Bool(_) => 0,
+            Value::Number(_) => 1,
+            Value::String(_) => 2,
+            Value::Array(_) | Value::Object(_) => 3,
+            Value::Null => 4,
+        }
+    }
+
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(_), Value::Array(_))
+        | (Value::Object(_), Value::Object(_))
+        | (Value::Array(_), Value::Object(_))
+        | (Value::Object(_), Value::Array(_)) => a.to_string().cmp(&b.to_string()),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn temp_cache(max_bytes: u64) -> (tempfile::TempDir, ResultCache) {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResultCache::new(dir.path().to_path_buf(), max_bytes, DEFAULT_MAX_AGE).unwrap();
+        (dir, cache)
+    }
+
+    /// Write `rows` the way `write_result` does and register them in the cache
+    fn insert(cache: &ResultCache, rows: &[Vec<Value>]) -> String {
+        let id = Uuid::new_v4().to_string();
+        let mut rows_file = BufWriter::new(File::create(cache.rows_path(&id)).unwrap());
+        let mut index = BufWriter::new(File::create(cache.index_path(&id)).unwrap());
+
+        let mut offset: u64 = 0;
+        for row in rows {
+            let encoded = serde_json::to_vec(row).unwrap();
+            rows_file.write_all(&encoded).unwrap();
+            index.write_all(&offset.to_le_bytes()).unwrap();
+            offset += encoded.len() as u64;
+        }
+        index.write_all(&offset.to_le_bytes()).unwrap();
+        rows_file.flush().unwrap();
+        index.flush().unwrap();
+
+        let columns = ["id", "name"]
+            .iter()
+            .map(|name| ColumnInfo {
+                name: name.to_string(),
+                data_type: "TEXT".to_string(),
+                nullable: true,
+            })
+            .collect();
+        cache.entries.write().insert(
+            id.clone(),
+            CachedResult {
+                columns,
+                total_rows: rows.len(),
+                bytes: offset + (rows.len() as u64 + 1) * INDEX_ENTRY_SIZE,
+                max_cell_bytes: 8,
+                last_access: Instant::now(),
+                sorted: HashMap::new(),
+            },
+        );
+        id
+    }
+
+    fn page_params(id: &str, offset: i64, limit: i64, sort: Option<(&str, bool)>) -> GetResultPageParams {
+        GetResultPageParams {
+            result_id: id.to_string(),
+            offset,
+            limit,
+            sort: sort.map(|(column, descending)| ResultSort {
+                column: column.to_string(),
+                descending,
+            }),
+        }
+    }
+
+    fn ids(page: &ResultPage) -> Vec<Value> {
+        page.rows.iter().map(|row| row["id"].clone()).collect()
+    }
+
+    #[test]
+    fn compare_values_orders_like_the_database() {
+        assert_eq!(compare_values(&json!(2), &json!(10)), Ordering::Less);
+        assert_eq!(compare_values(&json!(1.5), &json!(1)), Ordering::Greater);
+        assert_eq!(compare_values(&json!("b"), &json!("a")), Ordering::Greater);
+        assert_eq!(compare_values(&json!(false), &json!(true)), Ordering::Less);
+        assert_eq!(compare_values(&json!(null), &json!("z")), Ordering::Greater);
+        assert_eq!(compare_values(&json!(5), &json!(null)), Ordering::Less);
+        assert_eq!(compare_values(&json!(null), &json!(null)), Ordering::Equal);
+    }
+
+    #[tokio::test]
+    async fn pages_in_insertion_order() {
+        let (_dir, cache) = temp_cache(DEFAULT_MAX_BYTES);
+        let rows: Vec<_> = (0..10).map(|i| vec![json!(i), json!(format!("row {}", i))]).collect();
+        let id = insert(&cache, &rows);
+
+        let page = cache.page(page_params(&id, 4, 3, None)).await.unwrap();
+        assert_eq!(ids(&page), vec![json!(4), json!(5), json!(6)]);
+        assert_eq!(page.rows[0]["name"], json!("row 4"));
+        assert_eq!(page.total_rows, 10);
+        assert!(page.has_more);
+
+        let last = cache.page(page_params(&id, 8, 5, None)).await.unwrap();
+        assert_eq!(ids(&last), vec![json!(8), json!(9)]);
+        assert!(!last.has_more);
+
+        let past_end = cache.page(page_params(&id, 50, 5, None)).await.unwrap();
+        assert!(past_end.rows.is_empty());
+    }
+
+    #[tokio::test]
+    async fn sorts_by_column_and_reuses_the_permutation() {
+        let (_dir, cache) = temp_cache(DEFAULT_MAX_BYTES);
+        let rows = vec![
+            vec![json!(1), json!("pear")],
+            vec![json!(2), json!(null)],
+            vec![json!(3), json!("apple")],
+            vec![json!(4), json!("fig")],
+        ];
+        let id = insert(&cache, &rows);
+
+        let page = cache.page(page_params(&id, 0, 10, Some(("name", false)))).await.unwrap();
+        assert_eq!(ids(&page), vec![json!(3), json!(4), json!(1), json!(2)]);
+        assert_eq!(cache.entries.read()[&id].sorted.len(), 1);
+
+        let page = cache.page(page_params(&id, 1, 2, Some(("name", false)))).await.unwrap();
+        assert_eq!(ids(&page), vec![json!(4), json!(1)]);
+        assert_eq!(cache.entries.read()[&id].sorted.len(), 1);
+
+        let page = cache.page(page_params(&id, 0, 10, Some(("name", true)))).await.unwrap();
+        assert_eq!(ids(&page), vec![json!(2), json!(1), json!(4), json!(3)]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_first_sorts_read_complete_permutations() {
+        let (_dir, cache) = temp_cache(DEFAULT_MAX_BYTES);
+        let rows: Vec<_> = (0..2000).map(|i| vec![json!(i), json!((i * 7919) % 2000)]).collect();
+        let id = insert(&cache, &rows);
+
+        let sorted = || cache.page(page_params(&id, 1990, 10, Some(("name", false))));
+        let (first, second) = tokio::join!(sorted(), sorted());
+        let mut expected: Vec<i64> = (0..2000).collect();
+        expected.sort_by_key(|i| (i * 7919) % 2000);
+        let expected: Vec<_> = expected[1990..].iter().map(|i| json!(i)).collect();
+        assert_eq!(ids(&first.unwrap()), expected);
+        assert_eq!(ids(&second.unwrap()), expected);
+
+        let partial = std::fs::read_dir(&cache.dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".partial"))
+            .count();
+        assert_eq!(partial, 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_results_and_columns() {
+        let (_dir, cache) = temp_cache(DEFAULT_MAX_BYTES);
+        let id = insert(&cache, &[vec![json!(1), json!("a")]]);
+
+        let missing = cache.page(page_params("missing", 0, 10, None)).await;
+        assert!(matches!(missing, Err(ConnectionError::NotFound(_))));
+
+        let bad_column = cache.page(page_params(&id, 0, 10, Some(("nope", false)))).await;
+        assert!(matches!(bad_column, Err(ConnectionError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn pages_cut_values_to_previews_but_keep_them_in_full() {
+        let (_dir, cache) = temp_cache(DEFAULT_MAX_BYTES);
+        let rows = vec![
+            vec![json!(1), json!("a long text value")],
+            vec![json!(2), json!("short")],
+            vec![json!(3), json!({"nested": [1, 2, 3]})],
+        ];
+        let id = insert(&cache, &rows);
+
+        let page = cache.page(page_params(&id, 0, 10, None)).await.unwrap();
+        assert_eq!(page.rows[0]["name"], json!("a long t"));
+        assert_eq!(page.rows[1]["name"], json!("short"));
+        assert_eq!(page.rows[2]["name"], json!("{\"nested"));
+
+        let cells: Vec<_> = page.truncated_cells.iter().map(|cell| (cell.row, cell.length)).collect();
+        assert_eq!(cells, vec![(0, 17), (2, 18)]);
+
+        let mut rows_file = File::open(cache.rows_path(&id)).unwrap();
+        let mut index_file = File::open(cache.index_path(&id)).unwrap();
+        assert_eq!(read_row(&mut rows_file, &mut index_file, 0).unwrap(), rows[0]);
+    }
+
+    #[tokio::test]
+    async fn cell_reads_the_full_value() {
+        let (_dir, cache) = temp_cache(DEFAULT_MAX_BYTES);
+        let rows = vec![vec![json!(1), json!("a long text value")], vec![json!(2), json!(null)]];
+        let id = insert(&cache, &rows);
+
+        let (column, value) = cache.cell(&id, 0, "name").await.unwrap();
+        assert_eq!(column.name, "name");
+        assert_eq!(value, json!("a long text value"));
+        assert_eq!(cache.cell(&id, 1, "name").await.unwrap().1, json!(null));
+
+        assert!(matches!(cache.cell(&id, 2, "name").await, Err(ConnectionError::InvalidInput(_))));
+        assert!(matches!(cache.cell(&id, 0, "nope").await, Err(ConnectionError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn external_sort_merges_runs_stably() {
+        let (_dir, cache) = temp_cache(DEFAULT_MAX_BYTES);
+        // Few distinct keys so ties have to keep their original order
+        let rows: Vec<_> = (0..200).map(|i| vec![json!(i), json!((i * 7) % 5)]).collect();
+        let id = insert(&cache, &rows);
+        let target = cache.dir.join("sorted.perm");
+
+        // A tiny run budget forces one run per row and a full k-way merge
+        sort_permutation(&cache.rows_path(&id), &cache.index_path(&id), rows.len(), 1, false, &target, 1).unwrap();
+        let permutation = read_u64_range(&target, 0, rows.len()).unwrap();
+
+        let mut expected: Vec<u64> = (0..200).collect();
+        expected.sort_by_key(|&i| ((i * 7) % 5, i));
+        assert_eq!(permutation, expected);
+
+        let in_memory = cache.dir.join("memory.perm");
+        sort_permutation(&cache.rows_path(&id), &cache.index_path(&id), rows.len(), 1, false, &in_memory, SORT_RUN_BYTES).unwrap();
+        assert_eq!(read_u64_range(&in_memory, 0, rows.len()).unwrap(), expected);
+
+        // Run files are cleaned up after the merge
+        let leftovers = std::fs::read_dir(&cache.dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".run"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn external_sort_descending_keeps_ties_in_order() {
+        let (_dir, cache) = temp_cache(DEFAULT_MAX_BYTES);
+        let rows: Vec<_> = (0..50).map(|i| vec![json!(i), json!(i % 3)]).collect();
+        let id = insert(&cache, &rows);
+        let target = cache.dir.join("desc.perm");
+
+        sort_permutation(&cache.rows_path(&id), &cache.index_path(&id), rows.len(), 1, true, &target, 64).unwrap();
+        let permutation = read_u64_range(&target, 0, rows.len()).unwrap();
+
+        let mut expected: Vec<u64> = (0..50).collect();
+        expected.sort_by_key(|&i| (std::cmp::Reverse(i % 3), i));
+        assert_eq!(permutation, expected);
+    }
+
+    #[test]
+    fn release_removes_entry_and_files() {
+        let (_dir, cache) = temp_cache(DEFAULT_MAX_BYTES);
+        let id = insert(&cache, &[vec![json!(1), json!("a")]]);
+        assert!(cache.rows_path(&id).exists());
+
+        cache.release(&id);
+        assert!(!cache.entries.read().contains_key(&id));
+        assert!(!cache.rows_path(&id).exists());
+        assert!(!cache.index_path(&id).exists());
+    }
+
+    #[test]
+    fn evicts_least_recently_used_over_budget() {
+        let (_dir, cache) = temp_cache(130);
+        let row = vec![json!(1), json!("x".repeat(40))];
+        let first = insert(&cache, std::slice::from_ref(&row));
+        let second = insert(&cache, std::slice::from_ref(&row));
+        cache.entries.write().get_mut(&first).unwrap().last_access -= Duration::from_secs(1);
+
+        let third = insert(&cache, &[row]);
+        cache.evict(Some(&third));
+
+        let entries = cache.entries.read();
+        assert!(!entries.contains_key(&first));
+        assert!(entries.contains_key(&second));
+        assert!(entries.contains_key(&third));
+        assert!(!cache.rows_path(&first).exists());
+    }
+}
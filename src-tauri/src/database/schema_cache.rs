@@ -0,0 +1,570 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use sqlx::Row;
+
+use super::connection::{ConnectionError, DatabasePool, PoolTarget};
+use super::lexer::{tokenize, TokenKind};
+use super::types::DatabaseType;
+
+/// Statements that can add, remove or rename objects, or change what is visible
+const DDL_KEYWORDS: &[&str] = &["ALTER", "COMMENT", "CREATE", "DROP", "GRANT", "IMPORT", "RENAME", "REVOKE"];
+
+/// MySQL built-in functions, which unlike Postgres' aren't listed in a catalog
+const MYSQL_BUILTIN_FUNCTIONS: &[&str] = &[
+    "ABS", "ADDDATE", "AVG", "CAST", "CEIL", "CHAR_LENGTH", "COALESCE", "CONCAT", "CONCAT_WS",
+    "CONVERT", "CONVERT_TZ", "COUNT", "CURDATE", "CURRENT_DATE", "CURRENT_TIMESTAMP", "CURTIME",
+    "DATE", "DATE_ADD", "DATE_FORMAT", "DATE_SUB", "DATEDIFF", "DAY", "DAYOFWEEK", "DENSE_RANK",
+    "EXTRACT", "FIELD", "FIND_IN_SET", "FIRST_VALUE", "FLOOR", "FORMAT", "FROM_UNIXTIME",
+    "GREATEST", "GROUP_CONCAT", "HOUR", "IF", "IFNULL", "INSTR", "JSON_ARRAY", "JSON_ARRAYAGG",
+    "JSON_CONTAINS", "JSON_EXTRACT", "JSON_OBJECT", "JSON_OBJECTAGG", "JSON_SET", "JSON_UNQUOTE",
+    "LAG", "LAST_INSERT_ID", "LEAD", "LEAST", "LEFT", "LENGTH", "LOCATE", "LOWER", "LPAD", "LTRIM",
+    "MAX", "MD5", "MIN", "MINUTE", "MOD", "MONTH", "NOW", "NULLIF", "RAND", "RANK", "REGEXP_LIKE",
+    "REGEXP_REPLACE", "REPLACE", "RIGHT", "ROUND", "ROW_NUMBER", "RPAD", "RTRIM", "SHA2", "STR_TO_DATE",
+    "SUBSTRING", "SUBSTRING_INDEX", "SUM", "TIMESTAMPDIFF", "TRIM", "TRUNCATE", "UNIX_TIMESTAMP",
+    "UPPER", "UUID", "WEEK", "YEAR",
+];
+
+/// MySQL reserved words, for servers older than 8.0 without information_schema.KEYWORDS
+const MYSQL_RESERVED_WORDS: &[&str] = &[
+    "ADD", "ALL", "ALTER", "ANALYZE", "AND", "AS", "ASC", "BETWEEN", "BY", "CASCADE", "CASE",
+    "CHANGE", "CHECK", "COLUMN", "CONSTRAINT", "CREATE", "CROSS", "DATABASE", "DEFAULT", "DELETE",
+    "DESC", "DESCRIBE", "DISTINCT", "DROP", "ELSE", "EXISTS", "EXPLAIN", "FALSE", "FOR", "FOREIGN",
+    "FROM", "GRANT", "GROUP", "HAVING", "IF", "IGNORE", "IN", "INDEX", "INNER", "INSERT", "INTERVAL",
+    "INTO", "IS", "JOIN", "KEY", "KEYS", "KILL", "LEFT", "LIKE", "LIMIT", "LOCK", "NATURAL", "NOT",
+    "NULL", "ON", "OR", "ORDER", "OUTER", "PRIMARY", "REFERENCES", "RENAME", "REPLACE", "RIGHT",
+    "SCHEMA", "SELECT", "SET", "SHOW", "TABLE", "THEN", "TO", "TRUE", "UNION", "UNIQUE", "UPDATE",
+    "USE", "USING", "VALUES", "WHEN", "WHERE", "WITH",
+];
+
+/// A column as recorded in the schema cache
+#[derive(Debug, Clone)]
+pub struct CachedColumn {
+    pub name: String,
+    pub data_type: String,
+    pub comment: Option<String>,
+}
+
+/// A table, view or other relation as recorded in the schema cache
+#[derive(Debug, Clone)]
+pub struct CachedTable {
+    pub schema: String,
+    pub name: String,
+    /// "table" or "view"
+    pub kind: String,
+    pub columns: Vec<CachedColumn>,
+    pub comment: Option<String>,
+}
+
+impl CachedTable {
+    pub fn column(&self, name: &str, quoted: bool, dialect: DatabaseType) -> Option<&CachedColumn> {
+        self.columns.iter().find(|c| ident_matches(&c.name, name, quoted, dialect))
+    }
+}
+
+/// A function, aggregate or procedure as recorded in the schema cache.
+/// Overloads are recorded separately.
+#[derive(Debug, Clone)]
+pub struct CachedFunction {
+    pub schema: Option<String>,
+    pub name: String,
+    /// Argument list as the server displays it, without parentheses
+    pub arguments: String,
+    pub result: Option<String>,
+    /// "function", "aggregate", "window" or "procedure"
+    pub kind: String,
+    pub comment: Option<String>,
+}
+
+/// An index as recorded in the schema cache
+#[derive(Debug, Clone)]
+pub struct CachedIndex {
+    pub schema: String,
+    pub table: String,
+    pub name: String,
+    pub unique: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedKeyword {
+    pub word: String,
+    /// Reserved words must be quoted to be used as names
+    pub reserved: bool,
+}
+
+/// Snapshot of the objects visible on a connection
+#[derive(Debug)]
+pub struct SchemaMetadata {
+    pub dialect: DatabaseType,
+    /// Schemas searched for unqualified names, in order. For MySQL this is the
+    /// current database.
+    pub search_path: Vec<String>,
+    /// All schemas (MySQL databases) on the server
+    pub schemas: Vec<String>,
+    pub tables: Vec<CachedTable>,
+    pub functions: Vec<CachedFunction>,
+    pub indexes: Vec<CachedIndex>,
+    pub keywords: Vec<CachedKeyword>,
+    pub loaded_at: DateTime<Utc>,
+}
+
+impl SchemaMetadata {
+    /// Resolve a possibly schema-qualified name the way the server would.
+    /// `quoted` says whether the name was written as a quoted identifier.
+    pub fn find_table(&self, schema: Option<(&str, bool)>, name: &str, quoted: bool) -> Option<&CachedTable> {
+        let matches = |table: &&CachedTable| ident_matches(&table.name, name, quoted, self.dialect);
+        match schema {
+            Some((schema, schema_quoted)) => self
+                .tables
+                .iter()
+                .filter(|t| ident_matches(&t.schema, schema, schema_quoted, self.dialect))
+                .find(matches),
+            None => self.search_path.iter().find_map(|schema| {
+                self.tables.iter().filter(|t| &t.schema == schema).find(matches)
+            }),
+        }
+    }
+
+    pub fn is_reserved(&self, word: &str) -> bool {
+        self.keywords.iter().any(|k| k.reserved && k.word.eq_ignore_ascii_case(word))
+    }
+
+    /// Quote `name` if it can't be written as a bare identifier
+    pub fn quote_if_needed(&self, name: &str) -> String {
+        let bare = match self.dialect {
+            // Unquoted names fold to lower case, so anything else needs quotes
+            DatabaseType::Postgres => {
+                name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+                    && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '$')
+            }
+            DatabaseType::Mysql => {
+                !name.is_empty()
+                    && !name.chars().all(|c| c.is_ascii_digit())
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+            }
+        };
+        if bare && !self.is_reserved(name) {
+            return name.to_string();
+        }
+        self.dialect.quote_ident(name)
+    }
+}
+
+/// Whether any statement in `sql` may change which objects exist or are visible
+pub fn changes_schema(sql: &str, dialect: DatabaseType) -> bool {
+    let mut statement_start = true;
+    for token in tokenize(sql, dialect) {
+        if token.is_trivia() {
+            continue;
+        }
+        if statement_start && DDL_KEYWORDS.iter().any(|k| token.is_keyword(k)) {
+            return true;
+        }
+        statement_start = token.kind == TokenKind::Semicolon;
+    }
+    false
+}
+
+/// Whether an identifier as written refers to the catalog name `actual`.
+/// Postgres folds unquoted names to lower case; MySQL name matching depends
+/// on server settings, so it is treated as case-insensitive.
+pub fn ident_matches(actual: &str, written: &str, quoted: bool, dialect: DatabaseType) -> bool {
+    match dialect {
+        DatabaseType::Postgres if quoted => actual == written,
+        DatabaseType::Postgres => actual == written.to_lowercase(),
+        DatabaseType::Mysql => actual.eq_ignore_ascii_case(written),
+    }
+}
+
+/// Per-connection cache of schema metadata, loaded on first use
+pub struct SchemaCache {
+    /// Keyed by target, as a connection's databases and search paths differ
+    entries: RwLock<HashMap<PoolTarget, Arc<SchemaMetadata>>>,
+}
+
+impl Default for SchemaCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SchemaCache {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Cached metadata for a target, loading it from `pool`, the target's
+    /// pool, if absent
+    pub async fn get(&self, target: &PoolTarget, pool: &DatabasePool) -> Result<Arc<SchemaMetadata>, ConnectionError> {
+        if let Some(metadata) = self.entries.read().get(target) {
+            return Ok(metadata.clone());
+        }
+        self.refresh(target, pool).await
+    }
+
+    /// Reload a target's metadata now
+    pub async fn refresh(&self, target: &PoolTarget, pool: &DatabasePool) -> Result<Arc<SchemaMetadata>, ConnectionError> {
+        let metadata = Arc::new(load(pool).await?);
+        self.entries.write().insert(target.clone(), metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Forget the metadata of every target on a connection so the next use
+    /// reloads it
+    pub fn invalidate(&self, connection_id: &str) {
+        self.entries.write().retain(|target, _| target.connection_id != connection_id);
+    }
+
+    /// Invalidate a connection's metadata if `sql`, which was just run on it,
+    /// may have changed the schema. DDL may name objects in any database on
+    /// the server, so every target is invalidated.
+    pub fn invalidate_after(&self, connection_id: &str, sql: &str, dialect: DatabaseType) {
+        if changes_schema(sql, dialect) {
+            self.invalidate(connection_id);
+        }
+    }
+}
+
+async fn load(pool: &DatabasePool) -> Result<SchemaMetadata, ConnectionError> {
+    match pool {
+        DatabasePool::Postgres(pool) => {
+            let search_path: Vec<String> = sqlx::query_scalar("SELECT current_schemas(true)::text[]")
+                .fetch_one(pool)
+                .await?;
+
+            let query = r#"
+                SELECT
+                    n.nspname AS schema,
+                    c.relname AS name,
+                    c.relkind IN ('v', 'm') AS is_view,
+                    obj_description(c.oid, 'pg_class') AS comment,
+                    a.attname AS column_name,
+                    format_type(a.atttypid, a.atttypmod) AS data_type,
+                    col_description(c.oid, a.attnum) AS column_comment
+                FROM pg_class c
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                LEFT JOIN pg_attribute a
+                    ON a.attrelid = c.oid AND a.attnum > 0 AND NOT a.attisdropped
+                WHERE c.relkind IN ('r', 'p', 'v', 'm', 'f')
+                  AND n.nspname !~ '^pg_toast'
+                ORDER BY n.nspname, c.relname, a.attnum
+            "#;
+            let rows = sqlx::query(query).fetch_all(pool).await?;
+            let tables = group_columns(rows.iter().map(|row| {
+                (
+                    table_row(
+                        row.try_get("schema").unwrap_or_default(),
+                        row.try_get("name").unwrap_or_default(),
+                        row.try_get("is_view").unwrap_or_default(),
+                        row.try_get("comment").ok().flatten(),
+                    ),
+                    row.try_get::<Option<String>, _>("column_name").ok().flatten().map(|name| CachedColumn {
+                        name,
+                        data_type: row.try_get("data_type").unwrap_or_default(),
+                        comment: row.try_get("column_comment").ok().flatten(),
+                    }),
+                )
+            }));
+
+            let schemas = sqlx::query_scalar(
+                "SELECT nspname::text FROM pg_namespace WHERE nspname !~ '^pg_(toast|temp_)' ORDER BY nspname",
+            )
+            .fetch_all(pool)
+            .await?;
+
+            // Skip functions that only exist to implement types, operators and handlers
+            let query = r#"
+                SELECT
+                    n.nspname AS schema,
+                    p.proname AS name,
+                    pg_get_function_identity_arguments(p.oid) AS arguments,
+                    pg_get_function_result(p.oid) AS result,
+                    CASE p.prokind
+                        WHEN 'a' THEN 'aggregate'
+                        WHEN 'w' THEN 'window'
+                        WHEN 'p' THEN 'procedure'
+                        ELSE 'function'
+                    END AS kind,
+                    obj_description(p.oid, 'pg_proc') AS comment
+                FROM pg_proc p
+                JOIN pg_namespace n ON n.oid = p.pronamespace
+                WHERE p.prorettype NOT IN (
+                        'internal'::regtype, 'cstring'::regtype, 'trigger'::regtype,
+                        'event_trigger'::regtype, 'language_handler'::regtype,
+                        'fdw_handler'::regtype, 'index_am_handler'::regtype,
+                        'table_am_handler'::regtype, 'tsm_handler'::regtype
+                    )
+                  AND NOT 'internal'::regtype = ANY(p.proargtypes)
+                  AND n.nspname <> 'information_schema'
+                ORDER BY n.nspname, p.proname
+            "#;
+            let functions = sqlx::query(query)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| CachedFunction {
+                    schema: row.try_get("schema").ok(),
+                    name: row.try_get("name").unwrap_or_default(),
+                    arguments: row.try_get("arguments").unwrap_or_default(),
+                    result: row.try_get("result").ok(),
+                    kind: row.try_get("kind").unwrap_or_default(),
+                    comment: row.try_get("comment").ok().flatten(),
+                })
+                .collect();
+
+            let query = r#"
+                SELECT n.nspname AS schema, t.relname AS table, c.relname AS name, i.indisunique AS unique
+                FROM pg_index i
+                JOIN pg_class c ON c.oid = i.indexrelid
+                JOIN pg_class t ON t.oid = i.indrelid
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname !~ '^pg_toast'
+                ORDER BY n.nspname, t.relname, c.relname
+            "#;
+            let indexes = sqlx::query(query)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| CachedIndex {
+                    schema: row.try_get("schema").unwrap_or_default(),
+                    table: row.try_get("table").unwrap_or_default(),
+                    name: row.try_get("name").unwrap_or_default(),
+                    unique: row.try_get("unique").unwrap_or_default(),
+                })
+                .collect();
+
+            // Type and function names ('T') can't be used as column names either
+            let keywords = sqlx::query("SELECT upper(word) AS word, catcode IN ('R', 'T') AS reserved FROM pg_get_keywords()")
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| CachedKeyword {
+                    word: row.try_get("word").unwrap_or_default(),
+                    reserved: row.try_get("reserved").unwrap_or_default(),
+                })
+                .collect();
+
+            Ok(SchemaMetadata {
+                dialect: DatabaseType::Postgres,
+                search_path,
+                schemas,
+                tables,
+                functions,
+                indexes,
+                keywords,
+                loaded_at: Utc::now(),
+            })
+        }
+        DatabasePool::MySql(pool) => {
+            let database: Option<String> = sqlx::query_scalar("SELECT DATABASE()")
+                .fetch_one(pool)
+                .await?;
+
+            let query = r#"
+                SELECT
+                    t.TABLE_SCHEMA AS `schema`,
+                    t.TABLE_NAME AS name,
+                    t.TABLE_TYPE = 'VIEW' AS is_view,
+                    IF(t.TABLE_TYPE = 'VIEW', NULL, NULLIF(t.TABLE_COMMENT, '')) AS comment,
+                    c.COLUMN_NAME AS column_name,
+                    c.COLUMN_TYPE AS data_type,
+                    NULLIF(c.COLUMN_COMMENT, '') AS column_comment
+                FROM information_schema.TABLES t
+                LEFT JOIN information_schema.COLUMNS c
+                    ON c.TABLE_SCHEMA = t.TABLE_SCHEMA AND c.TABLE_NAME = t.TABLE_NAME
+                ORDER BY t.TABLE_SCHEMA, t.TABLE_NAME, c.ORDINAL_POSITION
+            "#;
+            let rows = sqlx::query(query).fetch_all(pool).await?;
+            let tables = group_columns(rows.iter().map(|row| {
+                (
+                    table_row(
+                        row.try_get("schema").unwrap_or_default(),
+                        row.try_get("name").unwrap_or_default(),
+                        row.try_get::<i64, _>("is_view").unwrap_or_default() != 0,
+                        row.try_get("comment").ok().flatten(),
+                    ),
+                    row.try_get::<Option<String>, _>("column_name").ok().flatten().map(|name| CachedColumn {
+                        name,
+                        data_type: row.try_get("data_type").unwrap_or_default(),
+                        comment: row.try_get("column_comment").ok().flatten(),
+                    }),
+                )
+            }));
+
+            let schemas = sqlx::query_scalar("SELECT SCHEMA_NAME FROM information_schema.SCHEMATA ORDER BY SCHEMA_NAME")
+                .fetch_all(pool)
+                .await?;
+
+            let query = r#"
+                SELECT
+                    ROUTINE_SCHEMA AS `schema`,
+                    ROUTINE_NAME AS name,
+                    DTD_IDENTIFIER AS result,
+                    LOWER(ROUTINE_TYPE) AS kind,
+                    NULLIF(ROUTINE_COMMENT, '') AS comment,
+                    (
+                        SELECT GROUP_CONCAT(
+                            CONCAT_WS(' ', p.PARAMETER_MODE, p.PARAMETER_NAME, p.DTD_IDENTIFIER)
+                            ORDER BY p.ORDINAL_POSITION SEPARATOR ', '
+                        )
+                        FROM information_schema.PARAMETERS p
+                        WHERE p.SPECIFIC_SCHEMA = r.ROUTINE_SCHEMA
+                          AND p.SPECIFIC_NAME = r.SPECIFIC_NAME
+                          AND p.ORDINAL_POSITION > 0
+                    ) AS arguments
+                FROM information_schema.ROUTINES r
+                ORDER BY ROUTINE_SCHEMA, ROUTINE_NAME
+            "#;
+            let mut functions: Vec<CachedFunction> = sqlx::query(query)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| CachedFunction {
+                    schema: row.try_get("schema").ok(),
+                    name: row.try_get("name").unwrap_or_default(),
+                    arguments: row.try_get::<Option<String>, _>("arguments").ok().flatten().unwrap_or_default(),
+                    result: row.try_get::<Option<String>, _>("result").ok().flatten(),
+                    kind: row.try_get("kind").unwrap_or_default(),
+                    comment: row.try_get("comment").ok().flatten(),
+                })
+                .collect();
+            functions.extend(MYSQL_BUILTIN_FUNCTIONS.iter().map(|name| CachedFunction {
+                schema: None,
+                name: name.to_string(),
+                arguments: String::new(),
+                result: None,
+                kind: "function".to_string(),
+                comment: None,
+            }));
+
+            let query = r#"
+                SELECT DISTINCT
+                    TABLE_SCHEMA AS `schema`,
+                    TABLE_NAME AS `table`,
+                    INDEX_NAME AS name,
+                    NON_UNIQUE = 0 AS `unique`
+                FROM information_schema.STATISTICS
+                ORDER BY TABLE_SCHEMA, TABLE_NAME, INDEX_NAME
+            "#;
+            let indexes = sqlx::query(query)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| CachedIndex {
+                    schema: row.try_get("schema").unwrap_or_default(),
+                    table: row.try_get("table").unwrap_or_default(),
+                    name: row.try_get("name").unwrap_or_default(),
+                    unique: row.try_get::<i64, _>("unique").unwrap_or_default() != 0,
+                })
+                .collect();
+
+            // information_schema.KEYWORDS was added in 8.0
+            let keywords = match sqlx::query("SELECT WORD AS word, RESERVED AS reserved FROM information_schema.KEYWORDS")
+                .fetch_all(pool)
+                .await
+            {
+                Ok(rows) => rows
+                    .iter()
+                    .map(|row| CachedKeyword {
+                        word: row.try_get("word").unwrap_or_default(),
+                        reserved: row.try_get::<i64, _>("reserved").unwrap_or_default() != 0,
+                    })
+                    .collect(),
+                Err(_) => MYSQL_RESERVED_WORDS
+                    .iter()
+                    .map(|word| CachedKeyword { word: word.to_string(), reserved: true })
+                    .collect(),
+            };
+
+            Ok(SchemaMetadata {
+                dialect: DatabaseType::Mysql,
+                search_path: database.into_iter().collect(),
+                schemas,
+                tables,
+                functions,
+                indexes,
+                keywords,
+                loaded_at: Utc::now(),
+            })
+        }
+    }
+}
+
+/// A table without its columns, for `group_columns`
+fn table_row(schema: String, name: String, is_view: bool, comment: Option<String>) -> CachedTable {
+    CachedTable {
+        schema,
+        name,
+        kind: if is_view { "view" } else { "table" }.to_string(),
+        columns: Vec::new(),
+        comment,
+    }
+}
+
+/// Fold `(table, column)` rows, ordered by table, into tables
+fn group_columns(rows: impl Iterator<Item = (CachedTable, Option<CachedColumn>)>) -> Vec<CachedTable> {
+    let mut tables: Vec<CachedTable> = Vec::new();
+    for (table, column) in rows {
+        let same_table = tables.last().is_some_and(|t| t.schema == table.schema && t.name == table.name);
+        if !same_table {
+            tables.push(table);
+        }
+        if let (Some(column), Some(table)) = (column, tables.last_mut()) {
+            table.columns.push(column);
+        }
+    }
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_schema_after_mysql_double_dash() {
+        let mysql = DatabaseType::Mysql;
+        assert!(!changes_schema("UPDATE t SET a=a--1", mysql));
+        assert!(!changes_schema("SELECT 5--1", mysql));
+        assert!(!changes_schema("SELECT 1 --", mysql));
+        assert!(changes_schema("UPDATE t SET a=a--1; DROP TABLE t", mysql));
+        assert!(changes_schema("-- note\nCREATE TABLE t (a int)", mysql));
+        assert!(!changes_schema("SELECT 1 -- ; DROP TABLE t", mysql));
+        assert!(!changes_schema("SELECT 1 --; DROP TABLE t", DatabaseType::Postgres));
+        assert!(changes_schema("SELECT 1 --\n; DROP TABLE t", DatabaseType::Postgres));
+    }
+
+    #[test]
+    fn invalidate_drops_every_target_of_the_connection() {
+        let target = |connection_id: &str, database: &str, schema: Option<&str>| PoolTarget {
+            connection_id: connection_id.to_string(),
+            database: database.to_string(),
+            schema: schema.map(str::to_string),
+        };
+        let metadata = || {
+            Arc::new(SchemaMetadata {
+                dialect: DatabaseType::Postgres,
+                search_path: vec!["public".to_string()],
+                schemas: vec!["public".to_string()],
+                tables: Vec::new(),
+                functions: Vec::new(),
+                indexes: Vec::new(),
+                keywords: Vec::new(),
+                loaded_at: Utc::now(),
+            })
+        };
+
+        let cache = SchemaCache::new();
+        for key in [target("a", "app", None), target("a", "app", Some("audit")), target("a", "logs", None), target("b", "app", None)] {
+            cache.entries.write().insert(key, metadata());
+        }
+        cache.invalidate_after("a", "SELECT 1", DatabaseType::Postgres);
+        assert_eq!(cache.entries.read().len(), 4);
+
+        cache.invalidate_after("a", "ALTER TABLE t ADD COLUMN c int", DatabaseType::Postgres);
+        let remaining: Vec<PoolTarget> = cache.entries.read().keys().cloned().collect();
+        assert_eq!(remaining, vec![target("b", "app", None)]);
+    }
+}
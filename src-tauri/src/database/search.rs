@@ -0,0 +1,963 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use sqlx::Row;
+
+use super::connection::{bind_json, extract_mysql_value, extract_postgres_value, ConnectionError, DatabasePool, InFlight};
+use super::running::QueryHandle;
+use super::schema_cache::SchemaMetadata;
+use super::types::*;
+
+/// Scores for each way a name can match; the length of the unmatched part is
+/// subtracted so shorter names rank first
+const EXACT: u32 = 1000;
+const PREFIX: u32 = 800;
+const WORD_PREFIX: u32 = 600;
+const SUBSTRING: u32 = 400;
+const SUBSEQUENCE: u32 = 200;
+/// Comment matches rank below any name match
+const COMMENT_PHRASE: u32 = 100;
+const COMMENT_WORDS: u32 = 50;
+
+/// Longest comment excerpt returned as a hit's detail
+const MAX_DETAIL_CHARS: usize = 120;
+/// Longest excerpt of a matching value returned by a data search, and how
+/// much of it comes before the match
+const MAX_VALUE_CHARS: usize = 200;
+const VALUE_CONTEXT_CHARS: usize = 60;
+
+fn is_system_schema(schema: &str, dialect: DatabaseType) -> bool {
+    match dialect {
+        DatabaseType::Postgres => {
+            ["pg_catalog", "information_schema"].contains(&schema)
+                || schema.starts_with("pg_toast")
+                || schema.starts_with("pg_temp_")
+        }
+        DatabaseType::Mysql => ["information_schema", "mysql", "performance_schema", "sys"].contains(&schema),
+    }
+}
+
+/// Match `params.term` against the names and comments of cached objects,
+/// best first. A term containing a dot is also matched against qualified
+/// names, so `orders.total` finds that column.
+pub fn objects(metadata: &SchemaMetadata, params: &SearchObjectsParams) -> Vec<ObjectSearchHit> {
+    let term = params.term.trim().to_lowercase();
+    if term.is_empty() {
+        return Vec::new();
+    }
+    let wanted = |kind: SearchObjectKind| params.kinds.is_empty() || params.kinds.contains(&kind);
+    let in_scope = |schema: &str| {
+        if params.schemas.is_empty() {
+            params.include_system || !is_system_schema(schema, metadata.dialect)
+        } else {
+            params.schemas.iter().any(|s| s == schema)
+        }
+    };
+
+    let mut hits = Vec::new();
+    let mut add = |kind: SearchObjectKind, path: Vec<&str>, detail: Option<String>, comment: Option<&str>| {
+        let name = path[path.len() - 1];
+        let mut score = name_score(name, &term);
+        if term.contains('.') {
+            // Qualified forms from the table or schema down, e.g. `table.column`
+            for start in 0..path.len().saturating_sub(1) {
+                score = score.max(name_score(&path[start..].join("."), &term));
+            }
+        }
+        let (matched_field, score, detail) = match (score, comment.and_then(|c| comment_score(c, &term))) {
+            (Some(score), _) => (SearchMatchField::Name, score, detail),
+            (None, Some(score)) => (SearchMatchField::Comment, score, comment.map(excerpt)),
+            (None, None) => return,
+        };
+        hits.push(ObjectSearchHit {
+            kind,
+            schema: path[0].to_string(),
+            table: match kind {
+                SearchObjectKind::Column | SearchObjectKind::Index => Some(path[1].to_string()),
+                _ => None,
+            },
+            name: name.to_string(),
+            path: path.iter().map(|part| part.to_string()).collect(),
+            matched_field,
+            score,
+            detail,
+        });
+    };
+
+    if wanted(SearchObjectKind::Schema) {
+        for schema in metadata.schemas.iter().filter(|s| in_scope(s)) {
+            add(SearchObjectKind::Schema, vec![schema], None, None);
+        }
+    }
+    for table in metadata.tables.iter().filter(|t| in_scope(&t.schema)) {
+        let kind = if table.kind == "view" { SearchObjectKind::View } else { SearchObjectKind::Table };
+        if wanted(kind) {
+            add(kind, vec![&table.schema, &table.name], None, table.comment.as_deref());
+        }
+        if wanted(SearchObjectKind::Column) {
+            for column in &table.columns {
+                add(
+                    SearchObjectKind::Column,
+                    vec![&table.schema, &table.name, &column.name],
+                    Some(column.data_type.clone()),
+                    column.comment.as_deref(),
+                );
+            }
+        }
+    }
+    if wanted(SearchObjectKind::Function) {
+        // Built-in MySQL functions have no schema and aren't listed
+        for function in &metadata.functions {
+            let Some(schema) = function.schema.as_deref().filter(|s| in_scope(s)) else { continue };
+            let signature = format!("{} {}({})", function.kind, function.name, function.arguments);
+            let comment = function.comment.as_deref();
+            add(SearchObjectKind::Function, vec![schema, &function.name], Some(signature), comment);
+        }
+    }
+    if wanted(SearchObjectKind::Index) {
+        for index in metadata.indexes.iter().filter(|i| in_scope(&i.schema)) {
+            let detail = format!("{} on {}", if index.unique { "unique index" } else { "index" }, index.table);
+            add(SearchObjectKind::Index, vec![&index.schema, &index.table, &index.name], Some(detail), None);
+        }
+    }
+
+    // Among equal scores, tables and views before what they contain
+    let rank = |kind: SearchObjectKind| match kind {
+        SearchObjectKind::Table | SearchObjectKind::View => 0,
+        SearchObjectKind::Schema => 1,
+        SearchObjectKind::Function => 2,
+        SearchObjectKind::Column => 3,
+        SearchObjectKind::Index => 4,
+    };
+    hits.sort_by(|a, b| {
+        (Reverse(a.score), rank(a.kind), a.path.len(), &a.path)
+            .cmp(&(Reverse(b.score), rank(b.kind), b.path.len(), &b.path))
+    });
+    hits.truncate(params.limit.max(0) as usize);
+    hits
+}
+
+/// How well a name matches a lower-cased term: exactly, as a prefix, as the
+/// prefix of a word (after `_`, `.`, `-` or a lower-to-upper case change),
+/// as a substring, or as a subsequence of characters. `None` if it doesn't match.
+fn name_score(name: &str, term: &str) -> Option<u32> {
+    let lower = name.to_lowercase();
+    let starts = word_starts(name);
+    let extra = (lower.chars().count().saturating_sub(term.chars().count())).min(99) as u32;
+    if lower == term {
+        return Some(EXACT);
+    }
+    if lower.starts_with(term) {
+        return Some(PREFIX - extra);
+    }
+    if starts.iter().any(|&start| lower.get(start..).is_some_and(|word| word.starts_with(term))) {
+        return Some(WORD_PREFIX - extra);
+    }
+    if lower.contains(term) {
+        return Some(SUBSTRING - extra);
+    }
+
+    // Characters in order, rewarding runs and word starts and penalising gaps
+    let mut term_chars = term.chars().peekable();
+    let mut bonus: u32 = 0;
+    let mut gaps: u32 = 0;
+    let mut previous_matched = false;
+    for (i, c) in lower.char_indices() {
+        match term_chars.peek() {
+            Some(&t) if t == c => {
+                term_chars.next();
+                if previous_matched {
+                    bonus += 3;
+                }
+                if i == 0 || starts.contains(&i) {
+                    bonus += 5;
+                }
+                previous_matched = true;
+            }
+            Some(_) => {
+                gaps += 1;
+                previous_matched = false;
+            }
+            None => break,
+        }
+    }
+    if term_chars.peek().is_some() {
+        return None;
+    }
+    Some(SUBSEQUENCE + bonus.min(99) - gaps.min(99))
+}
+
+/// Byte offsets in `name` where a word other than the first begins
+fn word_starts(name: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut previous: Option<char> = None;
+    for (i, c) in name.char_indices() {
+        if let Some(p) = previous {
+            let after_separator = matches!(p, '_' | '.' | '-' | ' ') && !matches!(c, '_' | '.' | '-' | ' ');
+            let camel = p.is_lowercase() && c.is_uppercase();
+            if after_separator || camel {
+                starts.push(i);
+            }
+        }
+        previous = Some(c);
+    }
+    starts
+}
+
+/// Comments match on the whole term, or on all of its words in any order
+fn comment_score(comment: &str, term: &str) -> Option<u32> {
+    let comment = comment.to_lowercase();
+    if comment.contains(term) {
+        Some(COMMENT_PHRASE)
+    } else if term.split_whitespace().all(|word| comment.contains(word)) {
+        Some(COMMENT_WORDS)
+    } else {
+        None
+    }
+}
+
+fn excerpt(comment: &str) -> String {
+    if comment.chars().count() <= MAX_DETAIL_CHARS {
+        return comment.to_string();
+    }
+    let mut excerpt: String = comment.chars().take(MAX_DETAIL_CHARS).collect();
+    excerpt.push('…');
+    excerpt
+}
+
+/// How a column can be compared with a data search term
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnClass {
+    Text,
+    Integer,
+    Decimal,
+    Uuid,
+    Json,
+}
+
+struct SearchColumn {
+    name: String,
+    class: Option<ColumnClass>,
+    is_primary_key: bool,
+}
+
+struct SearchTable {
+    schema: String,
+    name: String,
+    /// A partitioned Postgres table, whose partitions can repeat a `ctid`
+    partitioned: bool,
+    columns: Vec<SearchColumn>,
+}
+
+impl SearchTable {
+    /// Whether a matching row can be located again: by its primary key, or
+    /// on Postgres by its `ctid`
+    fn identifiable(&self, postgres: bool) -> bool {
+        self.columns.iter().any(|c| c.is_primary_key) || (postgres && !self.partitioned)
+    }
+}
+
+/// The statement searching one table, with the key and searched columns its
+/// result holds
+struct TableQuery<'t> {
+    sql: String,
+    binds: Vec<serde_json::Value>,
+    keys: Vec<&'t str>,
+    searched: Vec<&'t str>,
+}
+
+/// A row found in one table: its key and the matching columns' values
+struct FoundRow {
+    key: HashMap<String, serde_json::Value>,
+    columns: Vec<(String, Option<String>)>,
+}
+
+/// The connection a data search runs on, so `cancel_query` can reach its
+/// current statement. Closed rather than pooled unless `release`d.
+enum SearchConnection {
+    Postgres(InFlight<sqlx::Postgres>),
+    MySql(InFlight<sqlx::MySql>),
+}
+
+impl SearchConnection {
+    async fn open(pool: &DatabasePool) -> Result<(Self, i64), ConnectionError> {
+        match pool {
+            DatabasePool::Postgres(pool) => {
+                let mut conn = InFlight::acquire(pool).await?;
+                let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()").fetch_one(&mut *conn).await?;
+                Ok((SearchConnection::Postgres(conn), pid as i64))
+            }
+            DatabasePool::MySql(pool) => {
+                let mut conn = InFlight::acquire(pool).await?;
+                let id: u64 = sqlx::query_scalar("SELECT CONNECTION_ID()").fetch_one(&mut *conn).await?;
+                Ok((SearchConnection::MySql(conn), id as i64))
+            }
+        }
+    }
+
+    /// No statement is left running; hand the connection back to the pool
+    fn release(self) {
+        match self {
+            SearchConnection::Postgres(conn) => conn.release(),
+            SearchConnection::MySql(conn) => conn.release(),
+        }
+    }
+
+    /// Run a statement from `table_query`, whose columns are the key columns
+    /// followed by a value and a match flag for each searched column
+    async fn fetch(&mut self, query: &TableQuery<'_>) -> Result<Vec<FoundRow>, ConnectionError> {
+        let TableQuery { sql, binds, keys, searched } = query;
+        let mut found = Vec::new();
+        match self {
+            SearchConnection::Postgres(conn) => {
+                let rows = bind_json(sqlx::query(sql), binds)
+                    .fetch_all(&mut **conn)
+                    .await
+                    .map_err(|e| ConnectionError::from(e).with_sql(sql))?;
+                for row in &rows {
+                    let mut columns = Vec::new();
+                    for (i, column) in searched.iter().enumerate() {
+                        let position = keys.len() + 2 * i;
+                        if row.try_get::<Option<bool>, _>(position + 1)?.unwrap_or(false) {
+                            columns.push((column.to_string(), row.try_get(position)?));
+                        }
+                    }
+                    found.push(FoundRow {
+                        key: keys
+                            .iter()
+                            .enumerate()
+                            .map(|(i, key)| (key.to_string(), extract_postgres_value(row, i)))
+                            .collect(),
+                        columns,
+                    });
+                }
+            }
+            SearchConnection::MySql(conn) => {
+                let rows = bind_json(sqlx::query(sql), binds)
+                    .fetch_all(&mut **conn)
+                    .await
+                    .map_err(|e| ConnectionError::from(e).with_sql(sql))?;
+                for row in &rows {
+                    let mut columns = Vec::new();
+                    for (i, column) in searched.iter().enumerate() {
+                        let position = keys.len() + 2 * i;
+                        if row.try_get::<Option<i64>, _>(position + 1)?.unwrap_or(0) != 0 {
+                            columns.push((column.to_string(), row.try_get(position)?));
+                        }
+                    }
+                    found.push(FoundRow {
+                        key: keys
+                            .iter()
+                            .enumerate()
+                            .map(|(i, key)| (key.to_string(), extract_mysql_value(row, i)))
+                            .collect(),
+                        columns,
+                    });
+                }
+            }
+        }
+        Ok(found)
+    }
+}
+
+/// Scan the tables in scope one at a time for rows whose columns match
+/// `params.term`, passing each match to `on_match` as it is found. Stops at
+/// the row cap or time budget with the matches so far, or fails with
+/// `Cancelled` when `handle` is cancelled.
+pub async fn data(
+    pool: &DatabasePool,
+    params: &SearchDataParams,
+    query_id: &str,
+    handle: &QueryHandle,
+    on_match: impl Fn(&DataSearchMatch),
+) -> Result<DataSearchSummary, ConnectionError> {
+    let start = Instant::now();
+    if params.term.trim().is_empty() {
+        return Err(ConnectionError::InvalidInput("A search term is required".to_string()));
+    }
+    let budget = Duration::from_millis(params.timeout_ms);
+    let max_rows = params.max_rows.max(0) as usize;
+
+    let tables = handle.run(budget, searchable_tables(pool, params)).await?;
+    let (mut conn, backend_id) = SearchConnection::open(pool).await?;
+    handle.started(backend_id);
+
+    let mut summary = DataSearchSummary {
+        query_id: query_id.to_string(),
+        tables_searched: 0,
+        tables_total: tables.len(),
+        columns_searched: 0,
+        rows: 0,
+        matches: 0,
+        truncated: false,
+        timed_out: false,
+        skipped_tables: Vec::new(),
+        errors: Vec::new(),
+        execution_time_ms: 0,
+    };
+    let mut stopped = false;
+    for table in &tables {
+        if summary.rows >= max_rows {
+            summary.truncated = true;
+            break;
+        }
+        let Some(remaining) = budget.checked_sub(start.elapsed()).filter(|d| !d.is_zero()) else {
+            summary.timed_out = true;
+            break;
+        };
+        if !table.identifiable(matches!(pool, DatabasePool::Postgres(_))) {
+            summary.skipped_tables.push(format!("{}.{}", table.schema, table.name));
+            continue;
+        }
+        let Some(query) = table_query(pool, table, params, max_rows - summary.rows) else {
+            // No column of this table can hold the term
+            summary.tables_searched += 1;
+            continue;
+        };
+
+        match handle.run(remaining, conn.fetch(&query)).await {
+            Ok(found) => {
+                summary.tables_searched += 1;
+                summary.columns_searched += query.searched.len();
+                summary.rows += found.len();
+                for row in found {
+                    for (column, value) in row.columns {
+                        summary.matches += 1;
+                        on_match(&DataSearchMatch {
+                            query_id: query_id.to_string(),
+                            schema: table.schema.clone(),
+                            table: table.name.clone(),
+                            column,
+                            key: row.key.clone(),
+                            value: value.map(|value| value_excerpt(&value, &params.term)),
+                        });
+                    }
+                }
+            }
+            // The statement may still be running or about to be cancelled,
+            // so the connection is closed when it drops instead of pooled
+            Err(ConnectionError::Timeout(_)) => {
+                summary.timed_out = true;
+                stopped = true;
+                break;
+            }
+            Err(ConnectionError::Cancelled) => return Err(ConnectionError::Cancelled),
+            Err(e) => summary.errors.push(format!("{}.{}: {}", table.schema, table.name, e)),
+        }
+    }
+    if !stopped {
+        conn.release();
+    }
+    if summary.rows >= max_rows && !summary.timed_out {
+        summary.truncated = true;
+    }
+
+    summary.execution_time_ms = start.elapsed().as_millis();
+    Ok(summary)
+}
+
+/// Tables in scope with their columns, skipping views, partitions (their
+/// parent covers them) and tables the user can't read
+async fn searchable_tables(
+    pool: &DatabasePool,
+    params: &SearchDataParams,
+) -> Result<Vec<SearchTable>, ConnectionError> {
+    let rows: Vec<(String, String, bool, String, Option<ColumnClass>, bool)> = match pool {
+        DatabasePool::Postgres(pool) => {
+            let schemas = if params.schemas.is_empty() {
+                vec![sqlx::query_scalar::<_, String>("SELECT current_schema()::text").fetch_one(pool).await?]
+            } else {
+                params.schemas.clone()
+            };
+            // Domains are searched as their base type
+            let query = r#"
+                SELECT
+                    n.nspname AS schema,
+                    c.relname AS table,
+                    c.relkind = 'p' AS partitioned,
+                    a.attname AS column,
+                    bt.typname::text AS type_name,
+                    bt.typcategory::text AS category,
+                    COALESCE(a.attnum = ANY(pk.conkey), false) AS is_primary_key
+                FROM pg_class c
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum > 0 AND NOT a.attisdropped
+                JOIN pg_type t ON t.oid = a.atttypid
+                JOIN pg_type bt ON bt.oid = CASE WHEN t.typtype = 'd' THEN t.typbasetype ELSE t.oid END
+                LEFT JOIN pg_constraint pk ON pk.conrelid = c.oid AND pk.contype = 'p'
+                WHERE n.nspname = ANY($1)
+                  AND c.relkind IN ('r', 'p', 'm')
+                  AND NOT c.relispartition
+                  AND has_table_privilege(c.oid, 'SELECT')
+                ORDER BY n.nspname, c.relname, a.attnum
+            "#;
+            sqlx::query(query)
+                .bind(&schemas)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| {
+                    let type_name: String = row.try_get("type_name").unwrap_or_default();
+                    let category: String = row.try_get("category").unwrap_or_default();
+                    let class = match (type_name.as_str(), category.as_str()) {
+                        (_, "S") | (_, "E") => Some(ColumnClass::Text),
+                        ("int2" | "int4" | "int8", _) => Some(ColumnClass::Integer),
+                        ("numeric" | "float4" | "float8", _) => Some(ColumnClass::Decimal),
+                        ("uuid", _) => Some(ColumnClass::Uuid),
+                        ("json" | "jsonb", _) => Some(ColumnClass::Json),
+                        _ => None,
+                    };
+                    (
+                        row.try_get("schema").unwrap_or_default(),
+                        row.try_get("table").unwrap_or_default(),
+                        row.try_get("partitioned").unwrap_or_default(),
+                        row.try_get("column").unwrap_or_default(),
+                        class,
+                        row.try_get("is_primary_key").unwrap_or_default(),
+                    )
+                })
+                .collect()
+        }
+        DatabasePool::MySql(pool) => {
+            let schemas = if params.schemas.is_empty() {
+                let database: Option<String> = sqlx::query_scalar("SELECT DATABASE()").fetch_one(pool).await?;
+                let database = database.ok_or_else(|| {
+                    ConnectionError::InvalidInput("No database selected; specify a schema".to_string())
+                })?;
+                vec![database]
+            } else {
+                params.schemas.clone()
+            };
+            let query = format!(
+                r#"
+                SELECT
+                    c.TABLE_SCHEMA AS `schema`,
+                    c.TABLE_NAME AS `table`,
+                    c.COLUMN_NAME AS `column`,
+                    c.DATA_TYPE AS type_name,
+                    c.COLUMN_KEY = 'PRI' AS is_primary_key
+                FROM information_schema.COLUMNS c
+                JOIN information_schema.TABLES t
+                    ON t.TABLE_SCHEMA = c.TABLE_SCHEMA AND t.TABLE_NAME = c.TABLE_NAME
+                WHERE t.TABLE_TYPE = 'BASE TABLE' AND c.TABLE_SCHEMA IN ({})
+                ORDER BY c.TABLE_SCHEMA, c.TABLE_NAME, c.ORDINAL_POSITION
+                "#,
+                vec!["?"; schemas.len()].join(", ")
+            );
+            let mut query = sqlx::query(&query);
+            for schema in &schemas {
+                query = query.bind(schema);
+            }
+            query
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|row| {
+                    let type_name: String = row.try_get("type_name").unwrap_or_default();
+                    let class = match type_name.to_lowercase().as_str() {
+                        "char" | "varchar" | "tinytext" | "text" | "mediumtext" | "longtext" | "enum" | "set" => {
+                            Some(ColumnClass::Text)
+                        }
+                        "tinyint" | "smallint" | "mediumint" | "int" | "bigint" => Some(ColumnClass::Integer),
+                        "decimal" | "float" | "double" => Some(ColumnClass::Decimal),
+                        "json" => Some(ColumnClass::Json),
+                        _ => None,
+                    };
+                    (
+                        row.try_get("schema").unwrap_or_default(),
+                        row.try_get("table").unwrap_or_default(),
+                        false,
+                        row.try_get("column").unwrap_or_default(),
+                        class,
+                        row.try_get::<i64, _>("is_primary_key").unwrap_or_default() != 0,
+                    )
+                })
+                .collect()
+        }
+    };
+
+    let mut tables: Vec<SearchTable> = Vec::new();
+    for (schema, table, partitioned, column, class, is_primary_key) in rows {
+        if !params.tables.is_empty() && !params.tables.contains(&table) {
+            continue;
+        }
+        if !tables.last().is_some_and(|t| t.schema == schema && t.name == table) {
+            tables.push(SearchTable { schema, name: table, partitioned, columns: Vec::new() });
+        }
+        if let Some(table) = tables.last_mut() {
+            table.columns.push(SearchColumn { name: column, class, is_primary_key });
+        }
+    }
+    Ok(tables)
+}
+
+/// Build the statement searching one table, or `None` if no column can hold
+/// the term. Predicates compare each column in a way suited to its type and
+/// the term is always bound, never interpolated.
+fn table_query<'t>(
+    pool: &DatabasePool,
+    table: &'t SearchTable,
+    params: &SearchDataParams,
+    limit: usize,
+) -> Option<TableQuery<'t>> {
+    let postgres = matches!(pool, DatabasePool::Postgres(_));
+    let term = params.term.as_str();
+    let number = term.trim();
+    let integer = number.parse::<i64>().ok();
+    let decimal = number.parse::<f64>().ok().filter(|n| n.is_finite()).map(|_| number.to_string());
+    let uuid = uuid::Uuid::parse_str(number).ok().map(|uuid| uuid.to_string());
+    let pattern = {
+        let escaped = term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        match params.match_mode {
+            DataMatchMode::Contains => format!("%{}%", escaped),
+            DataMatchMode::Prefix => format!("{}%", escaped),
+            DataMatchMode::Exact => escaped,
+        }
+    };
+
+    let mut binds = Vec::new();
+    let mut param = |bind: serde_json::Value| {
+        binds.push(bind);
+        if postgres {
+            format!("${}", binds.len())
+        } else {
+            "?".to_string()
+        }
+    };
+
+    let mut selected = Vec::new();
+    let mut searched = Vec::new();
+    for column in &table.columns {
+        let quoted = pool.quote_ident(&column.name);
+        let predicate = match (column.class, postgres) {
+            (Some(ColumnClass::Text), true) => match (params.match_mode, params.case_sensitive) {
+                (DataMatchMode::Exact, true) => format!("{}::text = {}", quoted, param(term.into())),
+                (DataMatchMode::Exact, false) => {
+                    format!("lower({}::text) = lower({})", quoted, param(term.into()))
+                }
+                (_, true) => format!("{}::text LIKE {}", quoted, param(pattern.clone().into())),
+                (_, false) => format!("{}::text ILIKE {}", quoted, param(pattern.clone().into())),
+            },
+            (Some(ColumnClass::Text), false) => match (params.match_mode, params.case_sensitive) {
+                (DataMatchMode::Exact, true) => {
+                    format!("CAST({} AS BINARY) = CAST({} AS BINARY)", quoted, param(term.into()))
+                }
+                (DataMatchMode::Exact, false) => {
+                    format!("LOWER({}) = LOWER({})", quoted, param(term.into()))
+                }
+                (_, true) => {
+                    format!("CAST({} AS BINARY) LIKE CAST({} AS BINARY)", quoted, param(pattern.clone().into()))
+                }
+                (_, false) => format!("LOWER({}) LIKE LOWER({})", quoted, param(pattern.clone().into())),
+            },
+            (Some(ColumnClass::Integer), _) => match integer {
+                Some(integer) => format!("{} = {}", quoted, param(integer.into())),
+                None => continue,
+            },
+            (Some(ColumnClass::Decimal), true) => match &decimal {
+                Some(decimal) => format!("{} = CAST({} AS numeric)", quoted, param(decimal.clone().into())),
+                None => continue,
+            },
+            (Some(ColumnClass::Decimal), false) => match &decimal {
+                Some(decimal) => format!("{} = {}", quoted, param(decimal.clone().into())),
+                None => continue,
+            },
+            (Some(ColumnClass::Uuid), _) => match &uuid {
+                Some(uuid) => format!("{} = CAST({} AS uuid)", quoted, param(uuid.clone().into())),
+                None => continue,
+            },
+            // Document text only makes sense to search for a fragment
+            (Some(ColumnClass::Json), true) if params.match_mode == DataMatchMode::Contains => {
+                let operator = if params.case_sensitive { "LIKE" } else { "ILIKE" };
+                format!("{}::text {} {}", quoted, operator, param(pattern.clone().into()))
+            }
+            (Some(ColumnClass::Json), false) if params.match_mode == DataMatchMode::Contains => {
+                let text = format!("CAST({} AS CHAR)", quoted);
+                if params.case_sensitive {
+                    format!("CAST({} AS BINARY) LIKE CAST({} AS BINARY)", text, param(pattern.clone().into()))
+                } else {
+                    format!("LOWER({}) LIKE LOWER({})", text, param(pattern.clone().into()))
+                }
+            }
+            _ => continue,
+        };
+        let i = searched.len();
+        let value = if postgres {
+            format!("{}::text", quoted)
+        } else {
+            format!("CAST({} AS CHAR)", quoted)
+        };
+        selected.push(format!("{} AS v{}, ({}) AS m{}", value, i, predicate, i));
+        searched.push(column.name.as_str());
+    }
+    if searched.is_empty() {
+        return None;
+    }
+
+    // Integer keys stay numbers; others are read as text, which
+    // `fetch_cell_value` casts back to the column's type
+    let mut keys: Vec<&str> = table.columns.iter().filter(|c| c.is_primary_key).map(|c| c.name.as_str()).collect();
+    let mut key_columns: Vec<String> = table
+        .columns
+        .iter()
+        .filter(|c| c.is_primary_key)
+        .enumerate()
+        .map(|(i, column)| {
+            let quoted = pool.quote_ident(&column.name);
+            let value = match (column.class, postgres) {
+                (Some(ColumnClass::Integer), true) => format!("{}::int8", quoted),
+                (Some(ColumnClass::Integer), false) => format!("CAST({} AS SIGNED)", quoted),
+                (_, true) => format!("{}::text", quoted),
+                (_, false) => format!("CAST({} AS CHAR)", quoted),
+            };
+            format!("{} AS k{}", value, i)
+        })
+        .collect();
+    if keys.is_empty() && postgres {
+        // `fetch_cell_value` reads the system column like any other
+        keys.push("ctid");
+        key_columns.push("ctid::text AS k0".to_string());
+    }
+    let columns: Vec<String> = key_columns.into_iter().chain(selected).collect();
+    let outer: Vec<String> = (0..keys.len())
+        .map(|i| format!("k{}", i))
+        .chain((0..searched.len()).flat_map(|i| [format!("v{}", i), format!("m{}", i)]))
+        .collect();
+    let any_match: Vec<String> = (0..searched.len()).map(|i| format!("m{}", i)).collect();
+
+    // The derived table names each predicate so it doubles as the match flag.
+    // The casts and patterns rule out plain indexes, so each table is read
+    // until the row limit is reached.
+    let sql = format!(
+        "SELECT {} FROM (SELECT {} FROM {}.{}) s WHERE {} LIMIT {}",
+        outer.join(", "),
+        columns.join(", "),
+        pool.quote_ident(&table.schema),
+        pool.quote_ident(&table.name),
+        any_match.join(" OR "),
+        limit
+    );
+    Some(TableQuery { sql, binds, keys, searched })
+}
+
+/// A window of a matching value around the first occurrence of the term
+fn value_excerpt(value: &str, term: &str) -> String {
+    let length = value.chars().count();
+    if length <= MAX_VALUE_CHARS {
+        return value.to_string();
+    }
+    let lower = value.to_lowercase();
+    let position = lower.find(&term.to_lowercase()).map_or(0, |byte| lower[..byte].chars().count());
+    let start = position.saturating_sub(VALUE_CONTEXT_CHARS).min(length - MAX_VALUE_CHARS);
+    let mut excerpt = String::new();
+    if start > 0 {
+        excerpt.push('…');
+    }
+    excerpt.extend(value.chars().skip(start).take(MAX_VALUE_CHARS));
+    if start + MAX_VALUE_CHARS < length {
+        excerpt.push('…');
+    }
+    excerpt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::schema_cache::{CachedColumn, CachedIndex, CachedTable};
+    use super::*;
+
+    fn metadata() -> SchemaMetadata {
+        let column = |name: &str, comment: Option<&str>| CachedColumn {
+            name: name.to_string(),
+            data_type: "integer".to_string(),
+            comment: comment.map(str::to_string),
+        };
+        let table = |schema: &str, name: &str, columns: Vec<CachedColumn>, comment: Option<&str>| CachedTable {
+            schema: schema.to_string(),
+            name: name.to_string(),
+            kind: "table".to_string(),
+            columns,
+            comment: comment.map(str::to_string),
+        };
+        SchemaMetadata {
+            dialect: DatabaseType::Postgres,
+            search_path: vec!["public".to_string()],
+            schemas: vec!["public".to_string(), "pg_catalog".to_string()],
+            tables: vec![
+                table("public", "orders", vec![column("id", None), column("total", Some("Gross amount in cents"))], None),
+                table("public", "order_items", vec![column("order_id", None)], Some("Lines of an order")),
+                table("pg_catalog", "pg_class", vec![column("relname", None)], None),
+            ],
+            functions: Vec::new(),
+            indexes: vec![CachedIndex {
+                schema: "public".to_string(),
+                table: "orders".to_string(),
+                name: "orders_pkey".to_string(),
+                unique: true,
+            }],
+            keywords: Vec::new(),
+            loaded_at: chrono::Utc::now(),
+        }
+    }
+
+    fn params(term: &str) -> SearchObjectsParams {
+        SearchObjectsParams {
+            connection_id: "c".to_string(),
+            database: None,
+            term: term.to_string(),
+            kinds: Vec::new(),
+            schemas: Vec::new(),
+            include_system: false,
+            limit: 50,
+        }
+    }
+
+    fn paths(hits: &[ObjectSearchHit]) -> Vec<String> {
+        hits.iter().map(|h| h.path.join(".")).collect()
+    }
+
+    #[test]
+    fn names_rank_exact_then_prefix_then_word_prefix() {
+        assert_eq!(name_score("orders", "orders"), Some(EXACT));
+        assert!(name_score("orders", "ord") > name_score("order_items", "items"));
+        assert!(name_score("order_items", "items") > name_score("orders", "der"));
+        assert!(name_score("orderItems", "items").is_some_and(|s| s >= WORD_PREFIX - 99));
+        assert!(name_score("order_items", "oit").is_some_and(|s| s < SUBSTRING - 99));
+        assert_eq!(name_score("orders", "xyz"), None);
+    }
+
+    #[test]
+    fn search_orders_hits_best_first() {
+        // Prefix matches, shorter names first
+        let hits = objects(&metadata(), &params("order"));
+        assert_eq!(
+            paths(&hits)[..3],
+            ["public.orders", "public.order_items.order_id", "public.order_items"]
+        );
+        assert_eq!(hits[0].score, PREFIX - 1);
+    }
+
+    #[test]
+    fn qualified_terms_match_columns() {
+        let hits = objects(&metadata(), &params("orders.total"));
+        assert_eq!(hits[0].path, ["public", "orders", "total"]);
+        assert_eq!(hits[0].kind, SearchObjectKind::Column);
+        assert_eq!(hits[0].table.as_deref(), Some("orders"));
+        assert_eq!(hits[0].score, EXACT);
+    }
+
+    #[test]
+    fn comments_match_below_names_with_an_excerpt() {
+        let hits = objects(&metadata(), &params("cents gross"));
+        assert_eq!(paths(&hits), ["public.orders.total"]);
+        assert_eq!(hits[0].matched_field, SearchMatchField::Comment);
+        assert_eq!(hits[0].score, COMMENT_WORDS);
+        assert_eq!(hits[0].detail.as_deref(), Some("Gross amount in cents"));
+        assert_eq!(excerpt(&"x".repeat(200)).chars().count(), MAX_DETAIL_CHARS + 1);
+    }
+
+    #[test]
+    fn system_schemas_kinds_and_limit_filter_hits() {
+        assert!(objects(&metadata(), &params("relname")).is_empty());
+        let system = SearchObjectsParams { include_system: true, ..params("relname") };
+        assert_eq!(paths(&objects(&metadata(), &system)), ["pg_catalog.pg_class.relname"]);
+
+        let indexes = SearchObjectsParams { kinds: vec![SearchObjectKind::Index], ..params("orders") };
+        let hits = objects(&metadata(), &indexes);
+        assert_eq!(paths(&hits), ["public.orders.orders_pkey"]);
+        assert_eq!(hits[0].detail.as_deref(), Some("unique index on orders"));
+
+        let limited = SearchObjectsParams { limit: 1, ..params("order") };
+        assert_eq!(objects(&metadata(), &limited).len(), 1);
+        assert!(objects(&metadata(), &params("  ")).is_empty());
+    }
+
+    fn lazy_pool(postgres: bool) -> DatabasePool {
+        if postgres {
+            DatabasePool::Postgres(sqlx::postgres::PgPoolOptions::new().connect_lazy("postgres://u@127.0.0.1:1/db").unwrap())
+        } else {
+            DatabasePool::MySql(sqlx::mysql::MySqlPoolOptions::new().connect_lazy("mysql://u@127.0.0.1:1/db").unwrap())
+        }
+    }
+
+    fn search_table(partitioned: bool, columns: &[(&str, Option<ColumnClass>, bool)]) -> SearchTable {
+        SearchTable {
+            schema: "app".to_string(),
+            name: "notes".to_string(),
+            partitioned,
+            columns: columns
+                .iter()
+                .map(|(name, class, is_primary_key)| SearchColumn {
+                    name: name.to_string(),
+                    class: *class,
+                    is_primary_key: *is_primary_key,
+                })
+                .collect(),
+        }
+    }
+
+    fn data_params(term: &str) -> SearchDataParams {
+        SearchDataParams {
+            connection_id: "c".to_string(),
+            database: None,
+            query_id: None,
+            term: term.to_string(),
+            schemas: Vec::new(),
+            tables: Vec::new(),
+            match_mode: DataMatchMode::Contains,
+            case_sensitive: false,
+            max_rows: 100,
+            timeout_ms: 1000,
+        }
+    }
+
+    #[tokio::test]
+    async fn postgres_tables_without_a_key_use_the_ctid() {
+        let pool = lazy_pool(true);
+        let table = search_table(false, &[("body", Some(ColumnClass::Text), false), ("size", Some(ColumnClass::Integer), false)]);
+        assert!(table.identifiable(true));
+
+        let query = table_query(&pool, &table, &data_params("50%_off"), 10).unwrap();
+        assert_eq!(query.keys, ["ctid"]);
+        assert_eq!(query.searched, ["body"]);
+        assert_eq!(
+            query.sql,
+            r#"SELECT k0, v0, m0 FROM (SELECT ctid::text AS k0, "body"::text AS v0, ("body"::text ILIKE $1) AS m0 FROM "app"."notes") s WHERE m0 LIMIT 10"#
+        );
+        assert_eq!(query.binds, [serde_json::json!(r"%50\%\_off%")]);
+    }
+
+    #[tokio::test]
+    async fn primary_keys_are_preferred_and_numbers_bound_as_integers() {
+        let pool = lazy_pool(true);
+        let table = search_table(false, &[("id", Some(ColumnClass::Integer), true), ("body", Some(ColumnClass::Text), false)]);
+        let query = table_query(&pool, &table, &data_params("42"), 5).unwrap();
+        assert_eq!(query.keys, ["id"]);
+        assert_eq!(query.searched, ["id", "body"]);
+        assert!(query.sql.starts_with(r#"SELECT k0, v0, m0, v1, m1 FROM (SELECT "id"::int8 AS k0, "#));
+        assert!(query.sql.ends_with("WHERE m0 OR m1 LIMIT 5"));
+        assert_eq!(query.binds, [serde_json::json!(42), serde_json::json!("%42%")]);
+    }
+
+    #[tokio::test]
+    async fn unidentifiable_tables_are_skipped() {
+        let keyless = search_table(false, &[("body", Some(ColumnClass::Text), false)]);
+        assert!(!keyless.identifiable(false));
+        assert!(!search_table(true, &[("body", Some(ColumnClass::Text), false)]).identifiable(true));
+        assert!(search_table(true, &[("id", Some(ColumnClass::Integer), true)]).identifiable(true));
+
+        // Nothing can hold a word in a numeric table
+        let numbers = search_table(false, &[("id", Some(ColumnClass::Integer), true)]);
+        assert!(table_query(&lazy_pool(false), &numbers, &data_params("abc"), 10).is_none());
+    }
+
+    #[test]
+    fn value_excerpts_centre_on_the_match() {
+        assert_eq!(value_excerpt("short", "or"), "short");
+        let long = format!("{}needle{}", "a".repeat(300), "b".repeat(300));
+        let excerpt = value_excerpt(&long, "NEEDLE");
+        assert!(excerpt.starts_with('…') && excerpt.ends_with('…'));
+        assert!(excerpt.contains("needle"));
+        assert_eq!(excerpt.chars().count(), MAX_VALUE_CHARS + 2);
+    }
+}
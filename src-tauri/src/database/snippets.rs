@@ -0,0 +1,593 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use chrono::Utc;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::connection::ConnectionError;
+use super::types::*;
+
+const SNIPPETS_FILE: &str = "snippets.db";
+
+/// Marker line that opens and closes the front-matter block in exported files
+const FRONT_MATTER_FENCE: &str = "-- ---";
+
+const SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS saved_queries (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        folder TEXT,
+        description TEXT,
+        sql TEXT NOT NULL,
+        connection_id TEXT,
+        db_type TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_saved_queries_folder ON saved_queries (folder, name);
+"#;
+
+#[derive(Error, Debug)]
+pub enum SnippetError {
+    #[error("Snippet database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("File error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Saved query not found: {0}")]
+    NotFound(String),
+    #[error("Missing value for template variable: {0}")]
+    MissingVariable(String),
+    #[error("Invalid snippet file {0}: {1}")]
+    InvalidFile(String, String),
+    #[error("Snippet library is unavailable")]
+    Unavailable,
+}
+
+impl From<SnippetError> for ConnectionError {
+    fn from(error: SnippetError) -> Self {
+        match error {
+            SnippetError::NotFound(_)
+            | SnippetError::MissingVariable(_)
+            | SnippetError::InvalidFile(..) => ConnectionError::InvalidInput(error.to_string()),
+            SnippetError::Database(_) | SnippetError::Io(_) | SnippetError::Unavailable => {
+                ConnectionError::Storage(error.to_string())
+            }
+        }
+    }
+}
+
+/// Library of saved queries and snippets, stored in SQLite in the app data dir
+pub struct SnippetLibrary {
+    /// `None` when the snippet database could not be opened at startup
+    pool: Option<SqlitePool>,
+}
+
+impl SnippetLibrary {
+    /// Open (or create) the snippet database inside `data_dir`
+    pub async fn open(data_dir: &Path) -> Result<Self, SnippetError> {
+        std::fs::create_dir_all(data_dir)?;
+
+        let options = SqliteConnectOptions::new()
+            .filename(data_dir.join(SNIPPETS_FILE))
+            .create_if_missing(true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+
+        sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+
+        Ok(Self { pool: Some(pool) })
+    }
+
+    /// A library whose operations all fail with `Unavailable`, used when the
+    /// database could not be opened so the rest of the app can still start
+    pub fn disabled() -> Self {
+        Self { pool: None }
+    }
+
+    fn pool(&self) -> Result<&SqlitePool, SnippetError> {
+        self.pool.as_ref().ok_or(SnippetError::Unavailable)
+    }
+
+    /// List saved queries, applying folder and scope filters
+    pub async fn list(&self, params: ListSavedQueriesParams) -> Result<Vec<SavedQuery>, SnippetError> {
+        let rows = sqlx::query(
+            "SELECT * FROM saved_queries
+             WHERE (?1 IS NULL OR folder = ?1 OR folder LIKE ?1 || '/%')
+               AND (?2 IS NULL OR connection_id IS NULL OR connection_id = ?2)
+               AND (?3 IS NULL OR db_type IS NULL OR db_type = ?3)
+             ORDER BY folder, name",
+        )
+        .bind(params.folder)
+        .bind(params.connection_id)
+        .bind(params.db_type.map(|t| t.as_str()))
+        .fetch_all(self.pool()?)
+        .await?;
+
+        Ok(rows.iter().map(row_to_saved_query).collect())
+    }
+
+    /// List the distinct folders in use
+    pub async fn folders(&self) -> Result<Vec<String>, SnippetError> {
+        let rows = sqlx::query(
+            "SELECT DISTINCT folder FROM saved_queries WHERE folder IS NOT NULL ORDER BY folder",
+        )
+        .fetch_all(self.pool()?)
+        .await?;
+
+        Ok(rows.iter().filter_map(|row| row.try_get("folder").ok()).collect())
+    }
+
+    /// Get a single saved query
+    pub async fn get(&self, id: &str) -> Result<SavedQuery, SnippetError> {
+        sqlx::query("SELECT * FROM saved_queries WHERE id = ?")
+            .bind(id)
+            .fetch_optional(self.pool()?)
+            .await?
+            .map(|row| row_to_saved_query(&row))
+            .ok_or_else(|| SnippetError::NotFound(id.to_string()))
+    }
+
+    /// Create a saved query, or update it when `params.id` is set
+    pub async fn save(&self, params: SaveQueryParams) -> Result<SavedQuery, SnippetError> {
+        let id = params.id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        let now = Utc::now();
+
+        sqlx::query(
+            "INSERT INTO saved_queries
+                (id, name, folder, description, sql, connection_id, db_type, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)
+             ON CONFLICT (id) DO UPDATE SET
+                name = excluded.name,
+                folder = excluded.folder,
+                description = excluded.description,
+                sql = excluded.sql,
+                connection_id = excluded.connection_id,
+                db_type = excluded.db_type,
+                updated_at = excluded.updated_at",
+        )
+        .bind(&id)
+        .bind(params.name)
+        .bind(params.folder.map(|f| normalize_folder(&f)).filter(|f| !f.is_empty()))
+        .bind(params.description)
+        .bind(params.sql)
+        .bind(params.connection_id)
+        .bind(params.db_type.map(|t| t.as_str()))
+        .bind(now)
+        .execute(self.pool()?)
+        .await?;
+
+        self.get(&id).await
+    }
+
+    /// Delete a saved query
+    pub async fn delete(&self, id: &str) -> Result<(), SnippetError> {
+        sqlx::query("DELETE FROM saved_queries WHERE id = ?")
+            .bind(id)
+            .execute(self.pool()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Resolve a saved query's template variables into runnable SQL
+    pub async fn render(
+        &self,
+        id: &str,
+        variables: &HashMap<String, String>,
+    ) -> Result<String, SnippetError> {
+        let saved = self.get(id).await?;
+        render_template(&saved.sql, variables)
+    }
+
+    /// Import `.sql` files from a file or directory, returning the saved entries.
+    /// Files carrying an `id` in their front-matter update the existing snippet.
+    pub async fn import(&self, path: &Path) -> Result<Vec<SavedQuery>, SnippetError> {
+        let files = if path.is_dir() {
+            let mut files = Vec::new();
+            collect_sql_files(path, &mut files)?;
+            files
+        } else {
+            vec![path.to_path_buf()]
+        };
+
+        let mut imported = Vec::with_capacity(files.len());
+        for file in files {
+            let content = std::fs::read_to_string(&file)?;
+
+            // Fall back to the directory layout for the folder when not in front-matter
+            let folder_from_path = file
+                .parent()
+                .filter(|_| path.is_dir())
+                .and_then(|parent| parent.strip_prefix(path).ok())
+                .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+                .filter(|rel| !rel.is_empty());
+
+            let mut params = parse_snippet_file(&file, &content)?;
+            if params.folder.is_none() {
+                params.folder = folder_from_path;
+            }
+
+            imported.push(self.save(params).await?);
+        }
+
+        Ok(imported)
+    }
+
+    /// Export saved queries as `.sql` files under `dir`, one sub-directory per folder
+    pub async fn export(&self, dir: &Path, folder: Option<String>) -> Result<Vec<PathBuf>, SnippetError> {
+        let queries = self
+            .list(ListSavedQueriesParams {
+                folder,
+                ..Default::default()
+            })
+            .await?;
+
+        let mut written = Vec::with_capacity(queries.len());
+        for query in &queries {
+            let mut target = dir.to_path_buf();
+            if let Some(folder) = &query.folder {
+                target.extend(folder.split('/').filter(|part| !part.is_empty() && *part != ".."));
+            }
+            std::fs::create_dir_all(&target)?;
+
+            let slug = slugify(&query.name, &query.id);
+            target.push(format!("{}.sql", slug));
+            if written.contains(&target) {
+                target.set_file_name(format!("{}-{}.sql", slug, query.id));
+            }
+            std::fs::write(&target, format_snippet_file(query))?;
+            written.push(target);
+        }
+
+        Ok(written)
+    }
+}
+
+/// Replace `{{name}}` placeholders with the supplied values.
+/// Every referenced variable must have a value.
+pub fn render_template(sql: &str, variables: &HashMap<String, String>) -> Result<String, SnippetError> {
+    let mut output = String::with_capacity(sql.len());
+    let mut rest = sql;
+
+    while let Some(start) = rest.find("{{") {
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            break;
+        };
+
+        let name = after_open[..end].trim();
+        output.push_str(&rest[..start]);
+        if is_variable_name(name) {
+            let value = variables
+                .get(name)
+                .ok_or_else(|| SnippetError::MissingVariable(name.to_string()))?;
+            output.push_str(value);
+        } else {
+            // Not a placeholder, keep the text as written
+            output.push_str(&rest[start..start + 2 + end + 2]);
+        }
+        rest = &after_open[end + 2..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Names of the `{{name}}` variables used in `sql`, in order of first use
+pub fn template_variables(sql: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = sql;
+
+    while let Some(start) = rest.find("{{") {
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            break;
+        };
+
+        let name = after_open[..end].trim();
+        if is_variable_name(name) && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &after_open[end + 2..];
+    }
+
+    names
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn row_to_saved_query(row: &SqliteRow) -> SavedQuery {
+    let sql: String = row.try_get("sql").unwrap_or_default();
+    SavedQuery {
+        id: row.try_get("id").unwrap_or_default(),
+        name: row.try_get("name").unwrap_or_default(),
+        folder: row.try_get("folder").unwrap_or_default(),
+        description: row.try_get("description").unwrap_or_default(),
+        variables: template_variables(&sql),
+        sql,
+        connection_id: row.try_get("connection_id").unwrap_or_default(),
+        db_type: row
+            .try_get::<Option<String>, _>("db_type")
+            .unwrap_or_default()
+            .and_then(|t| t.parse().ok()),
+        created_at: row.try_get("created_at").unwrap_or_default(),
+        updated_at: row.try_get("updated_at").unwrap_or_default(),
+    }
+}
+
+fn normalize_folder(folder: &str) -> String {
+    folder
+        .split(['/', '\\'])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn slugify(name: &str, fallback: &str) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if slug.is_empty() {
+        fallback.to_string()
+    } else {
+        slug
+    }
+}
+
+fn collect_sql_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), SnippetError> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_sql_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("sql")) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(())
+}
+
+/// Render a saved query as a `.sql` file. The front-matter is written as SQL
+/// comments so the file stays runnable as-is:
+///
+/// ```sql
+/// -- ---
+/// -- name: Active users
+/// -- folder: reports/users
+/// -- ---
+/// SELECT * FROM {{table}} WHERE last_seen > {{since}};
+/// ```
+fn format_snippet_file(query: &SavedQuery) -> String {
+    let mut out = String::new();
+    out.push_str(FRONT_MATTER_FENCE);
+    out.push('\n');
+    out.push_str(&format!("-- id: {}\n", query.id));
+    out.push_str(&format!("-- name: {}\n", query.name));
+    if let Some(folder) = &query.folder {
+        out.push_str(&format!("-- folder: {}\n", folder));
+    }
+    if let Some(description) = &query.description {
+        for line in description.lines() {
+            out.push_str(&format!("-- description: {}\n", line));
+        }
+    }
+    if let Some(connection_id) = &query.connection_id {
+        out.push_str(&format!("-- connection: {}\n", connection_id));
+    }
+    if let Some(db_type) = query.db_type {
+        out.push_str(&format!("-- type: {}\n", db_type.as_str()));
+    }
+    out.push_str(FRONT_MATTER_FENCE);
+    out.push('\n');
+    // The SQL is written byte-for-byte so an export/import round trip is lossless
+    out.push_str(&query.sql);
+    out
+}
+
+/// Parse a `.sql` file written by `format_snippet_file`. Files without
+/// front-matter are imported whole, named after the file.
+fn parse_snippet_file(path: &Path, content: &str) -> Result<SaveQueryParams, SnippetError> {
+    let file_name = path.display().to_string();
+    let mut params = SaveQueryParams {
+        id: None,
+        name: path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        folder: None,
+        description: None,
+        sql: content.to_string(),
+        connection_id: None,
+        db_type: None,
+    };
+
+    let mut lines = content.split_inclusive('\n');
+    let Some(first) = lines.next() else {
+        return Ok(params);
+    };
+    if first.trim_end() != FRONT_MATTER_FENCE {
+        return Ok(params);
+    }
+
+    let mut descriptions = Vec::new();
+    let mut consumed = first.len();
+    let mut closed = false;
+    for line in lines {
+        consumed += line.len();
+        let line = line.trim_end();
+        if line == FRONT_MATTER_FENCE {
+            closed = true;
+            break;
+        }
+
+        let Some((key, value)) = line
+            .strip_prefix("--")
+            .and_then(|rest| rest.split_once(':'))
+        else {
+            return Err(SnippetError::InvalidFile(file_name, format!("bad front-matter line: {}", line)));
+        };
+
+        let value = value.trim().to_string();
+        match key.trim() {
+            "id" => params.id = Some(value),
+            "name" => params.name = value,
+            "folder" => params.folder = Some(value),
+            "description" => descriptions.push(value),
+            "connection" => params.connection_id = Some(value),
+            "type" => {
+                params.db_type = Some(
+                    value
+                        .parse()
+                        .map_err(|e: String| SnippetError::InvalidFile(file_name.clone(), e))?,
+                )
+            }
+            // Unknown keys are ignored so newer exports still import
+            _ => {}
+        }
+    }
+
+    if !closed {
+        return Err(SnippetError::InvalidFile(file_name, "unterminated front-matter".to_string()));
+    }
+
+    if !descriptions.is_empty() {
+        params.description = Some(descriptions.join("\n"));
+    }
+    params.sql = content[consumed..].to_string();
+
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn saved(sql: &str) -> SavedQuery {
+        SavedQuery {
+            id: "abc".into(),
+            name: "Active users".into(),
+            folder: Some("reports/users".into()),
+            description: Some("first line\nsecond line".into()),
+            sql: sql.into(),
+            variables: template_variables(sql),
+            connection_id: Some("conn-1".into()),
+            db_type: Some(DatabaseType::Postgres),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn renders_variables() {
+        let sql = "SELECT * FROM {{ table }} WHERE id = {{id}} AND id <> {{id}}";
+        let out = render_template(sql, &vars(&[("table", "users"), ("id", "7")])).unwrap();
+        assert_eq!(out, "SELECT * FROM users WHERE id = 7 AND id <> 7");
+    }
+
+    #[test]
+    fn render_reports_missing_variable() {
+        let err = render_template("SELECT {{a}}, {{b}}", &vars(&[("a", "1")])).unwrap_err();
+        assert!(matches!(err, SnippetError::MissingVariable(name) if name == "b"));
+    }
+
+    #[test]
+    fn render_keeps_non_placeholders() {
+        let sql = "SELECT '{{not a var}}', '{{1x}}', '{{unclosed";
+        assert_eq!(render_template(sql, &HashMap::new()).unwrap(), sql);
+    }
+
+    #[test]
+    fn lists_variables_in_order_of_first_use() {
+        let sql = "{{b}} {{ a }} {{b}} {{not valid}} {{_c1}}";
+        assert_eq!(template_variables(sql), ["b", "a", "_c1"]);
+        assert!(template_variables("SELECT 1").is_empty());
+    }
+
+    #[test]
+    fn parses_front_matter() {
+        let content = "-- ---\n\
+                       -- id: 42\n\
+                       -- name: Daily totals\n\
+                       -- folder: reports\n\
+                       -- description: one\n\
+                       -- description: two\n\
+                       -- connection: conn-1\n\
+                       -- type: mysql\n\
+                       -- future-key: ignored\n\
+                       -- ---\n\
+                       SELECT 1;\n";
+        let params = parse_snippet_file(Path::new("x.sql"), content).unwrap();
+        assert_eq!(params.id.as_deref(), Some("42"));
+        assert_eq!(params.name, "Daily totals");
+        assert_eq!(params.folder.as_deref(), Some("reports"));
+        assert_eq!(params.description.as_deref(), Some("one\ntwo"));
+        assert_eq!(params.connection_id.as_deref(), Some("conn-1"));
+        assert_eq!(params.db_type, Some(DatabaseType::Mysql));
+        assert_eq!(params.sql, "SELECT 1;\n");
+    }
+
+    #[test]
+    fn files_without_front_matter_import_whole() {
+        let params = parse_snippet_file(Path::new("dir/top-users.sql"), "SELECT 1").unwrap();
+        assert_eq!(params.name, "top-users");
+        assert_eq!(params.sql, "SELECT 1");
+        assert!(params.id.is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_front_matter() {
+        let unterminated = parse_snippet_file(Path::new("a.sql"), "-- ---\n-- name: a\nSELECT 1");
+        assert!(matches!(unterminated, Err(SnippetError::InvalidFile(..))));
+
+        let bad_line = parse_snippet_file(Path::new("a.sql"), "-- ---\nname: a\n-- ---\n");
+        assert!(matches!(bad_line, Err(SnippetError::InvalidFile(..))));
+
+        let bad_type = parse_snippet_file(Path::new("a.sql"), "-- ---\n-- type: oracle\n-- ---\n");
+        assert!(matches!(bad_type, Err(SnippetError::InvalidFile(..))));
+    }
+
+    #[test]
+    fn export_round_trip_is_byte_exact() {
+        for sql in ["SELECT {{x}}", "SELECT 1;\n", "SELECT 1;\n\n", "", "-- ---\nSELECT 2"] {
+            let query = saved(sql);
+            let params = parse_snippet_file(Path::new("a.sql"), &format_snippet_file(&query)).unwrap();
+            assert_eq!(params.sql, sql);
+            assert_eq!(params.id.as_deref(), Some("abc"));
+            assert_eq!(params.name, query.name);
+            assert_eq!(params.folder, query.folder);
+            assert_eq!(params.description, query.description);
+            assert_eq!(params.db_type, query.db_type);
+        }
+    }
+
+    #[test]
+    fn normalizes_folders_and_slugs() {
+        assert_eq!(normalize_folder(" /reports\\users/ "), "reports/users");
+        assert_eq!(slugify("Active Users (30d)", "id"), "active-users-30d");
+        assert_eq!(slugify("???", "id"), "id");
+    }
+
+    #[tokio::test]
+    async fn disabled_library_reports_unavailable() {
+        let library = SnippetLibrary::disabled();
+        assert!(matches!(library.get("x").await, Err(SnippetError::Unavailable)));
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use super::connection::ConnectionError;
+use super::errors::ErrorPosition;
 
 /// Database type supported by MesaGrid
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -10,6 +13,35 @@ pub enum DatabaseType {
     Mysql,
 }
 
+impl DatabaseType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DatabaseType::Postgres => "postgres",
+            DatabaseType::Mysql => "mysql",
+        }
+    }
+
+    /// Quote an identifier for interpolation into SQL
+    pub fn quote_ident(&self, ident: &str) -> String {
+        match self {
+            DatabaseType::Postgres => format!("\"{}\"", ident.replace('"', "\"\"")),
+            DatabaseType::Mysql => format!("`{}`", ident.replace('`', "``")),
+        }
+    }
+}
+
+impl std::str::FromStr for DatabaseType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "postgres" | "postgresql" => Ok(DatabaseType::Postgres),
+            "mysql" => Ok(DatabaseType::Mysql),
+            other => Err(format!("Unknown database type: {}", other)),
+        }
+    }
+}
+
 /// Connection configuration (without password)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionConfig {
@@ -57,11 +89,35 @@ pub struct TestConnectionParams {
 pub struct ExecuteQueryParams {
     #[serde(rename = "connectionId")]
     pub connection_id: String,
+    /// Run against another database on the connection's server
+    pub database: Option<String>,
+    /// Schema to resolve unqualified names in (Postgres search path); on
+    /// MySQL the database to use when `database` isn't given
+    pub schema: Option<String>,
     pub sql: String,
     #[serde(default = "default_limit")]
     pub limit: i64,
     #[serde(default)]
     pub offset: i64,
+    /// Values for `{{name}}` template variables in `sql`
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    /// Keep the full result on disk and return only the requested page
+    #[serde(default)]
+    pub cache: bool,
+    /// Stop reading after this many rows
+    #[serde(rename = "maxRows")]
+    pub max_rows: Option<usize>,
+    /// Stop reading once the decoded rows reach this many bytes
+    #[serde(rename = "maxBytes")]
+    pub max_bytes: Option<usize>,
+    /// Text, binary and JSON values longer than this many bytes are cut to a preview
+    #[serde(rename = "maxCellBytes")]
+    pub max_cell_bytes: Option<usize>,
+    /// Look up the backend PID / connection ID for `stats`, which costs an
+    /// extra round trip
+    #[serde(rename = "includeBackendId", default)]
+    pub include_backend_id: bool,
 }
 
 fn default_limit() -> i64 {
@@ -77,6 +133,93 @@ pub struct QueryResult {
     pub row_count: usize,
     #[serde(rename = "executionTimeMs")]
     pub execution_time_ms: u128,
+    /// Notices (Postgres) and warnings (MySQL) raised during execution
+    pub notices: Vec<QueryNotice>,
+    /// Set when the result was cached; `rowCount` is then the full result size
+    /// and `rows` holds only the requested page
+    #[serde(rename = "resultId", skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    /// Set when reading stopped early because a size cap was reached
+    pub truncated: bool,
+    #[serde(rename = "truncatedReason", skip_serializing_if = "Option::is_none")]
+    pub truncated_reason: Option<TruncationReason>,
+    /// Cells whose value was cut to a preview
+    #[serde(rename = "truncatedCells", skip_serializing_if = "Vec::is_empty")]
+    pub truncated_cells: Vec<TruncatedCell>,
+    /// Timing breakdown of the execution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<QueryStats>,
+}
+
+/// Where the time went while running a query. Durations are in milliseconds
+/// with sub-millisecond precision. Pool wait, execution, fetch and decode
+/// don't overlap and add up to roughly the total.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryStats {
+    /// Waiting for a connection from the pool
+    #[serde(rename = "poolWaitMs")]
+    pub pool_wait_ms: f64,
+    /// From the start until the first row (or completion) arrived, including
+    /// the pool wait; a milestone rather than a phase
+    #[serde(rename = "firstRowMs")]
+    pub first_row_ms: f64,
+    /// From sending the query until the first row (or completion) arrived
+    #[serde(rename = "executionMs")]
+    pub execution_ms: f64,
+    /// Waiting for the rows after the first one
+    #[serde(rename = "fetchMs")]
+    pub fetch_ms: f64,
+    /// Converting rows into JSON values
+    #[serde(rename = "decodeMs")]
+    pub decode_ms: f64,
+    #[serde(rename = "totalMs")]
+    pub total_ms: f64,
+    /// Size of the row data received from the server
+    #[serde(rename = "bytesReceived")]
+    pub bytes_received: usize,
+    /// Postgres backend PID or MySQL connection ID that ran the query, when
+    /// requested with `includeBackendId` or needed for cancellation
+    #[serde(rename = "backendId", skip_serializing_if = "Option::is_none")]
+    pub backend_id: Option<i64>,
+}
+
+/// Which cap stopped a result from being read in full
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TruncationReason {
+    RowLimit,
+    ByteLimit,
+}
+
+/// A cell value that was shortened to a preview
+#[derive(Debug, Clone, Serialize)]
+pub struct TruncatedCell {
+    pub row: usize,
+    pub column: String,
+    /// Full length of the value in bytes
+    pub length: usize,
+}
+
+/// Severity of a server notice or warning
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NoticeSeverity {
+    Error,
+    Warning,
+    Notice,
+    Info,
+    Debug,
+}
+
+/// A notice or warning produced by the server while running a query
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryNotice {
+    pub severity: NoticeSeverity,
+    /// MySQL warning code. sqlx doesn't pass on the SQLSTATE of Postgres
+    /// notices, so they never have one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub message: String,
 }
 
 /// Column information
@@ -95,8 +238,20 @@ pub struct TableInfo {
     pub schema: String,
     #[serde(rename = "type")]
     pub table_type: String,
+    /// Estimated from planner statistics; absent for views and tables that
+    /// have never been analyzed. See `count_rows_exact` for an exact count.
     #[serde(rename = "rowCount", skip_serializing_if = "Option::is_none")]
     pub row_count: Option<i64>,
+    /// Table, indexes and TOAST together
+    #[serde(rename = "totalBytes", skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<i64>,
+    #[serde(rename = "tableBytes", skip_serializing_if = "Option::is_none")]
+    pub table_bytes: Option<i64>,
+    #[serde(rename = "indexBytes", skip_serializing_if = "Option::is_none")]
+    pub index_bytes: Option<i64>,
+    /// Out-of-line storage for large values (Postgres)
+    #[serde(rename = "toastBytes", skip_serializing_if = "Option::is_none")]
+    pub toast_bytes: Option<i64>,
 }
 
 /// Parameters for fetching table data
@@ -136,3 +291,1123 @@ pub struct TestConnectionResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
+
+/// Outcome of an executed query as recorded in history
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryStatus {
+    Success,
+    Error,
+}
+
+/// A single entry in the query history
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    pub sql: String,
+    #[serde(rename = "startedAt")]
+    pub started_at: DateTime<Utc>,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: i64,
+    #[serde(rename = "rowCount", skip_serializing_if = "Option::is_none")]
+    pub row_count: Option<i64>,
+    pub status: QueryStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Parameters for listing query history
+#[derive(Debug, Deserialize)]
+pub struct ListHistoryParams {
+    #[serde(rename = "connectionId")]
+    pub connection_id: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+/// Parameters for full-text search over query history
+#[derive(Debug, Deserialize)]
+pub struct SearchHistoryParams {
+    pub query: String,
+    #[serde(rename = "connectionId")]
+    pub connection_id: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+/// A saved query or snippet in the library
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedQuery {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub sql: String,
+    /// Template variables referenced in `sql`, in order of first use
+    pub variables: Vec<String>,
+    #[serde(rename = "connectionId", skip_serializing_if = "Option::is_none")]
+    pub connection_id: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub db_type: Option<DatabaseType>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Parameters for creating or updating a saved query
+#[derive(Debug, Deserialize)]
+pub struct SaveQueryParams {
+    /// Existing ID to update; a new ID is generated when omitted
+    pub id: Option<String>,
+    pub name: String,
+    pub folder: Option<String>,
+    pub description: Option<String>,
+    pub sql: String,
+    #[serde(rename = "connectionId")]
+    pub connection_id: Option<String>,
+    #[serde(rename = "type")]
+    pub db_type: Option<DatabaseType>,
+}
+
+/// Parameters for listing saved queries
+#[derive(Debug, Default, Deserialize)]
+pub struct ListSavedQueriesParams {
+    pub folder: Option<String>,
+    /// Only return snippets usable on this connection (unscoped ones included)
+    #[serde(rename = "connectionId")]
+    pub connection_id: Option<String>,
+    #[serde(rename = "type")]
+    pub db_type: Option<DatabaseType>,
+}
+
+/// A Postgres NOTIFY message received on a subscribed channel
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelNotification {
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    pub channel: String,
+    pub payload: String,
+    #[serde(rename = "processId")]
+    pub process_id: u32,
+    #[serde(rename = "receivedAt")]
+    pub received_at: DateTime<Utc>,
+}
+
+/// Lifecycle state of a background query job
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Summary of a background query job
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: String,
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    pub sql: String,
+    pub status: JobStatus,
+    #[serde(rename = "submittedAt")]
+    pub submitted_at: DateTime<Utc>,
+    #[serde(rename = "finishedAt", skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Rows written so far; final once the job has finished
+    #[serde(rename = "rowCount")]
+    pub row_count: usize,
+    #[serde(rename = "executionTimeMs", skip_serializing_if = "Option::is_none")]
+    pub execution_time_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Notices (Postgres) and warnings (MySQL) raised by a completed job
+    pub notices: Vec<QueryNotice>,
+}
+
+/// Parameters for submitting a background query job
+#[derive(Debug, Deserialize)]
+pub struct SubmitQueryJobParams {
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    pub sql: String,
+    /// Values for `{{name}}` template variables in `sql`
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+/// Parameters for reading a page of a job's spilled result
+#[derive(Debug, Deserialize)]
+pub struct GetJobResultParams {
+    #[serde(rename = "jobId")]
+    pub job_id: String,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+/// A page of rows from a job's result
+#[derive(Debug, Serialize)]
+pub struct JobResultPage {
+    pub job: JobInfo,
+    pub columns: Vec<ColumnInfo>,
+    pub rows: Vec<serde_json::Value>,
+    pub offset: usize,
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+}
+
+/// Sort order applied to a cached result
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResultSort {
+    pub column: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// Parameters for reading a page of a cached result
+#[derive(Debug, Deserialize)]
+pub struct GetResultPageParams {
+    #[serde(rename = "resultId")]
+    pub result_id: String,
+    #[serde(default)]
+    pub offset: i64,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    pub sort: Option<ResultSort>,
+}
+
+/// A page of rows from a cached result
+#[derive(Debug, Serialize)]
+pub struct ResultPage {
+    #[serde(rename = "resultId")]
+    pub result_id: String,
+    pub columns: Vec<ColumnInfo>,
+    pub rows: Vec<serde_json::Value>,
+    pub offset: usize,
+    #[serde(rename = "totalRows")]
+    pub total_rows: usize,
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+    /// Cells in `rows` whose value was cut to a preview
+    #[serde(rename = "truncatedCells", skip_serializing_if = "Vec::is_empty")]
+    pub truncated_cells: Vec<TruncatedCell>,
+}
+
+/// Parameters for reading one cell in full, either from a cached result
+/// (`resultId` + `row`) or from a table by primary key (`table` + `key`)
+#[derive(Debug, Deserialize)]
+pub struct FetchCellValueParams {
+    #[serde(rename = "connectionId")]
+    pub connection_id: Option<String>,
+    #[serde(rename = "resultId")]
+    pub result_id: Option<String>,
+    /// Row number within the cached result, in query order
+    pub row: Option<usize>,
+    pub schema: Option<String>,
+    pub table: Option<String>,
+    pub column: String,
+    /// Primary key column values identifying the row
+    #[serde(default)]
+    pub key: HashMap<String, serde_json::Value>,
+    /// Write the value to this file instead of returning it
+    #[serde(rename = "outputPath")]
+    pub output_path: Option<String>,
+}
+
+/// How a fetched cell value is represented in `value`
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CellEncoding {
+    Text,
+    Json,
+    Base64,
+}
+
+/// A cell value read in full
+#[derive(Debug, Serialize)]
+pub struct CellValue {
+    /// The value, or null when it was written to `path`
+    pub value: serde_json::Value,
+    pub encoding: CellEncoding,
+    /// Length of the value in bytes
+    pub length: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Parameters for running a statement repeatedly to measure its latency
+#[derive(Debug, Deserialize)]
+pub struct BenchmarkParams {
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    pub sql: String,
+    #[serde(default = "default_iterations")]
+    pub iterations: usize,
+    /// Number of runs in flight at once, each on its own pooled connection
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// Runs made before measuring, to warm caches and plans
+    #[serde(default)]
+    pub warmup: usize,
+    /// Commit each run instead of rolling it back
+    #[serde(rename = "allowWrites", default)]
+    pub allow_writes: bool,
+}
+
+fn default_iterations() -> usize {
+    10
+}
+
+fn default_concurrency() -> usize {
+    1
+}
+
+/// Latency distribution of a benchmark, in milliseconds
+#[derive(Debug, Serialize)]
+pub struct BenchmarkResult {
+    pub iterations: usize,
+    pub concurrency: usize,
+    pub warmup: usize,
+    #[serde(rename = "minMs")]
+    pub min_ms: f64,
+    #[serde(rename = "medianMs")]
+    pub median_ms: f64,
+    #[serde(rename = "meanMs")]
+    pub mean_ms: f64,
+    #[serde(rename = "p95Ms")]
+    pub p95_ms: f64,
+    #[serde(rename = "p99Ms")]
+    pub p99_ms: f64,
+    #[serde(rename = "maxMs")]
+    pub max_ms: f64,
+    /// Wall-clock time for all measured runs
+    #[serde(rename = "totalMs")]
+    pub total_ms: f64,
+    #[serde(rename = "queriesPerSecond")]
+    pub queries_per_second: f64,
+    /// Whether each run was rolled back
+    #[serde(rename = "rolledBack")]
+    pub rolled_back: bool,
+}
+
+/// Parameters for running one query on several connections
+#[derive(Debug, Deserialize)]
+pub struct FanOutQueryParams {
+    #[serde(rename = "connectionIds")]
+    pub connection_ids: Vec<String>,
+    pub sql: String,
+    /// Values for `{{name}}` template variables in `sql`
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    /// Connections queried at the same time
+    #[serde(default = "default_parallelism")]
+    pub parallelism: usize,
+    /// Name of the added column holding each row's connection ID; `sources`
+    /// gives the matching names
+    #[serde(rename = "sourceColumn", default = "default_source_column")]
+    pub source_column: String,
+}
+
+fn default_parallelism() -> usize {
+    4
+}
+
+fn default_source_column() -> String {
+    "source_connection".to_string()
+}
+
+/// Outcome of a fan-out query on one connection
+#[derive(Debug, Serialize)]
+pub struct FanOutSource {
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    #[serde(rename = "connectionName")]
+    pub connection_name: String,
+    #[serde(rename = "rowCount")]
+    pub row_count: usize,
+    #[serde(rename = "executionTimeMs")]
+    pub execution_time_ms: u128,
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ConnectionError>,
+}
+
+/// Rows from every connection that succeeded, merged in request order
+#[derive(Debug, Serialize)]
+pub struct FanOutResult {
+    pub columns: Vec<ColumnInfo>,
+    pub rows: Vec<serde_json::Value>,
+    #[serde(rename = "rowCount")]
+    pub row_count: usize,
+    #[serde(rename = "executionTimeMs")]
+    pub execution_time_ms: u128,
+    pub sources: Vec<FanOutSource>,
+}
+
+/// Keyword casing applied by the formatter
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordCase {
+    #[default]
+    Upper,
+    Lower,
+    Preserve,
+}
+
+/// Where the formatter places commas in lists broken over several lines
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommaStyle {
+    #[default]
+    Trailing,
+    Leading,
+}
+
+/// Formatter settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FormatOptions {
+    #[serde(rename = "keywordCase")]
+    pub keyword_case: KeywordCase,
+    /// Spaces per indentation level
+    #[serde(rename = "indentWidth")]
+    pub indent_width: usize,
+    #[serde(rename = "commaStyle")]
+    pub comma_style: CommaStyle,
+    /// Lists and parenthesised groups longer than this are broken over lines
+    #[serde(rename = "lineWidth")]
+    pub line_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            keyword_case: KeywordCase::Upper,
+            indent_width: 2,
+            comma_style: CommaStyle::Trailing,
+            line_width: 80,
+        }
+    }
+}
+
+/// Parameters for formatting SQL. The dialect is taken from the connection
+/// when one is given, otherwise from `dialect`.
+#[derive(Debug, Deserialize)]
+pub struct FormatSqlParams {
+    pub sql: String,
+    #[serde(rename = "connectionId")]
+    pub connection_id: Option<String>,
+    pub dialect: Option<DatabaseType>,
+    #[serde(default)]
+    pub options: FormatOptions,
+}
+
+/// Parameters for checking SQL before it is run. The dialect is taken from
+/// the connection when one is given, otherwise from `dialect`.
+#[derive(Debug, Deserialize)]
+pub struct ValidateSqlParams {
+    pub sql: String,
+    #[serde(rename = "connectionId")]
+    pub connection_id: Option<String>,
+    pub dialect: Option<DatabaseType>,
+    /// Check names against another database on the connection's server
+    pub database: Option<String>,
+    /// Schema to resolve unqualified names in, as for `execute_query`
+    pub schema: Option<String>,
+    /// Check table and column names against the connection's cached schema
+    #[serde(rename = "checkReferences", default = "default_check_references")]
+    pub check_references: bool,
+}
+
+fn default_check_references() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A problem found in SQL text, spanning `start` to `end` (exclusive)
+#[derive(Debug, Clone, Serialize)]
+pub struct SqlDiagnostic {
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    pub start: ErrorPosition,
+    pub end: ErrorPosition,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+/// Result of validating SQL
+#[derive(Debug, Serialize)]
+pub struct ValidateSqlResult {
+    /// No diagnostics of error severity
+    pub valid: bool,
+    pub diagnostics: Vec<SqlDiagnostic>,
+    /// Whether table and column references were checked against a schema
+    #[serde(rename = "referencesChecked")]
+    pub references_checked: bool,
+}
+
+/// Summary of a connection's cached schema metadata
+#[derive(Debug, Serialize)]
+pub struct SchemaCacheInfo {
+    #[serde(rename = "loadedAt")]
+    pub loaded_at: DateTime<Utc>,
+    pub schemas: usize,
+    pub tables: usize,
+    pub columns: usize,
+    pub functions: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionKind {
+    Keyword,
+    Schema,
+    Table,
+    View,
+    Column,
+    Function,
+    /// A table alias or CTE name in scope
+    Alias,
+    /// A join condition inferred from column names
+    Join,
+}
+
+/// A single completion suggestion
+#[derive(Debug, Clone, Serialize)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// Type, owning table or signature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Text to insert when it differs from the label, e.g. quoted or qualified
+    #[serde(rename = "insertText", skip_serializing_if = "Option::is_none")]
+    pub insert_text: Option<String>,
+}
+
+/// Completions for a cursor position, best first. The editor replaces the
+/// text between `replaceStart` and `replaceEnd` (character offsets) with the
+/// chosen item.
+#[derive(Debug, Serialize)]
+pub struct CompletionResult {
+    #[serde(rename = "replaceStart")]
+    pub replace_start: usize,
+    #[serde(rename = "replaceEnd")]
+    pub replace_end: usize,
+    pub items: Vec<Completion>,
+}
+
+/// Full definition of a table or view column from the catalogs
+#[derive(Debug, Clone, Serialize)]
+pub struct TableColumn {
+    /// 1-based position in the table
+    #[serde(rename = "ordinalPosition")]
+    pub ordinal_position: i32,
+    pub name: String,
+    /// Type including length, precision and scale, e.g. `numeric(10,2)`
+    #[serde(rename = "dataType")]
+    pub data_type: String,
+    pub nullable: bool,
+    /// Default expression as written in the catalog
+    #[serde(rename = "defaultValue", skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<String>,
+    /// Whether the default is an expression; MySQL gives literal defaults unquoted
+    #[serde(rename = "defaultIsExpression")]
+    pub default_is_expression: bool,
+    /// `ALWAYS` or `BY DEFAULT` for Postgres identity columns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    /// Filled by an identity, serial sequence or `AUTO_INCREMENT`
+    #[serde(rename = "autoIncrement")]
+    pub auto_increment: bool,
+    /// `STORED` or `VIRTUAL` for generated columns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated: Option<String>,
+    /// Expression of a generated column
+    #[serde(rename = "generationExpression", skip_serializing_if = "Option::is_none")]
+    pub generation_expression: Option<String>,
+    /// Collation, when the column's type is collatable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collation: Option<String>,
+    /// Character set of MySQL text columns
+    #[serde(rename = "characterSet", skip_serializing_if = "Option::is_none")]
+    pub character_set: Option<String>,
+    /// MySQL `ON UPDATE` expression, e.g. `CURRENT_TIMESTAMP`
+    #[serde(rename = "onUpdate", skip_serializing_if = "Option::is_none")]
+    pub on_update: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(rename = "isPrimaryKey")]
+    pub is_primary_key: bool,
+    /// 1-based position within a composite primary key
+    #[serde(rename = "primaryKeyPosition", skip_serializing_if = "Option::is_none")]
+    pub primary_key_position: Option<i32>,
+}
+
+/// Indexes, constraints and foreign keys of a table
+#[derive(Debug, Serialize)]
+pub struct TableStructure {
+    pub schema: String,
+    pub name: String,
+    pub indexes: Vec<TableIndex>,
+    pub constraints: Vec<TableConstraint>,
+    /// Foreign keys declared on this table
+    #[serde(rename = "foreignKeys")]
+    pub foreign_keys: Vec<ForeignKey>,
+    /// Foreign keys on other tables (or this one) that reference this table
+    #[serde(rename = "referencedBy")]
+    pub referenced_by: Vec<ForeignKey>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TableIndex {
+    pub name: String,
+    /// Key columns in index order; expressions for expression indexes
+    pub columns: Vec<String>,
+    /// Non-key columns stored in the index (`INCLUDE`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    pub unique: bool,
+    pub primary: bool,
+    /// Access method, e.g. `btree`, `gin`, `BTREE`, `FULLTEXT`
+    pub method: String,
+    /// `WHERE` clause of a partial index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicate: Option<String>,
+    #[serde(rename = "sizeBytes", skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConstraintKind {
+    PrimaryKey,
+    Unique,
+    Check,
+    Exclusion,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TableConstraint {
+    pub name: String,
+    pub kind: ConstraintKind,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+    /// Definition as it would appear in `ADD CONSTRAINT`, e.g. `CHECK (total > 0)`
+    pub definition: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForeignKey {
+    pub name: String,
+    /// Referencing table
+    pub schema: String,
+    pub table: String,
+    pub columns: Vec<String>,
+    #[serde(rename = "referencedSchema")]
+    pub referenced_schema: String,
+    #[serde(rename = "referencedTable")]
+    pub referenced_table: String,
+    #[serde(rename = "referencedColumns")]
+    pub referenced_columns: Vec<String>,
+    /// `NO ACTION`, `RESTRICT`, `CASCADE`, `SET NULL` or `SET DEFAULT`
+    #[serde(rename = "onUpdate")]
+    pub on_update: String,
+    #[serde(rename = "onDelete")]
+    pub on_delete: String,
+}
+
+/// Kinds of schema object that can be browsed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ObjectKind {
+    Table,
+    View,
+    MaterializedView,
+    Function,
+    Procedure,
+    Trigger,
+    Sequence,
+    /// Enum, composite, domain and range types (Postgres)
+    Type,
+    /// Scheduled events (MySQL)
+    Event,
+}
+
+/// Parameters for listing schema objects
+#[derive(Debug, Deserialize)]
+pub struct ListObjectsParams {
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    /// Kinds to include; all kinds when empty
+    #[serde(default)]
+    pub kinds: Vec<ObjectKind>,
+    /// List objects in another database on the connection's server
+    pub database: Option<String>,
+    /// Limit to one schema. Defaults to all user schemas on Postgres and the
+    /// current database on MySQL.
+    pub schema: Option<String>,
+}
+
+/// A schema object with a kind-specific summary
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseObject {
+    pub schema: String,
+    pub name: String,
+    pub kind: ObjectKind,
+    /// Table a trigger is defined on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    /// Argument list of a function or procedure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<String>,
+    /// Return type of a function
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    /// Trigger timing and events, type category or event schedule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Last value returned by a sequence, if it has been used
+    #[serde(rename = "currentValue", skip_serializing_if = "Option::is_none")]
+    pub current_value: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// Parameters for fetching the source of a schema object
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetObjectDefinitionParams {
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    pub kind: ObjectKind,
+    /// Look the object up in another database on the connection's server
+    pub database: Option<String>,
+    /// Defaults to the search path on Postgres and the current database on MySQL
+    pub schema: Option<String>,
+    pub name: String,
+    /// Argument list, as returned by `list_objects`, to pick one of several
+    /// overloaded functions
+    pub arguments: Option<String>,
+    /// Table of a trigger, needed on Postgres when names repeat across tables
+    pub table: Option<String>,
+}
+
+/// What `generate_ddl` emits besides the object's own `CREATE` statement
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DdlOptions {
+    /// `ALTER ... OWNER TO` on Postgres; on MySQL the `DEFINER` clause of
+    /// views, routines, triggers and events
+    #[serde(rename = "includeOwner")]
+    pub include_owner: bool,
+    /// `GRANT` statements for privileges given to other roles. Postgres only;
+    /// MySQL rejects it.
+    #[serde(rename = "includeGrants")]
+    pub include_grants: bool,
+    /// `COMMENT ON` for the object and a table's columns. MySQL's DDL always
+    /// carries its comments, so turning this off is rejected there.
+    #[serde(rename = "includeComments")]
+    pub include_comments: bool,
+    /// Also generate the user-defined types and sequences a table uses, and
+    /// its triggers and partitions
+    #[serde(rename = "includeDependents")]
+    pub include_dependents: bool,
+}
+
+impl Default for DdlOptions {
+    fn default() -> Self {
+        Self {
+            include_owner: true,
+            include_grants: false,
+            include_comments: true,
+            include_dependents: false,
+        }
+    }
+}
+
+/// Parameters for generating the DDL of a schema object
+#[derive(Debug, Deserialize)]
+pub struct GenerateDdlParams {
+    #[serde(flatten)]
+    pub object: GetObjectDefinitionParams,
+    #[serde(default)]
+    pub options: DdlOptions,
+}
+
+/// One side of a schema comparison
+#[derive(Debug, Clone, Deserialize)]
+pub struct SchemaRef {
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    /// Another database on the same server (Postgres)
+    pub database: Option<String>,
+    /// Defaults to the current schema on Postgres and the current database on MySQL
+    pub schema: Option<String>,
+}
+
+/// What `diff_schemas` compares
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DiffScope {
+    /// Tables, views, routines and types; empty for all of them
+    pub kinds: Vec<ObjectKind>,
+    /// Only objects with these names; empty for every object
+    pub names: Vec<String>,
+}
+
+/// What the migration does to an object or table member
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffAction {
+    Create,
+    Drop,
+    Alter,
+}
+
+/// Member of a table a change applies to
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TablePart {
+    Column,
+    Index,
+    Constraint,
+    ForeignKey,
+}
+
+/// A difference between the source and target schemas, described as what
+/// the migration does to the target
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaChange {
+    #[serde(rename = "objectKind")]
+    pub object_kind: ObjectKind,
+    /// Object name, with the argument list for routines
+    pub name: String,
+    pub action: DiffAction,
+    /// The table member that changed, when not the whole object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part: Option<TablePart>,
+    #[serde(rename = "partName", skip_serializing_if = "Option::is_none")]
+    pub part_name: Option<String>,
+    /// What differs, such as `type integer -> bigint`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+    /// Loses data, or drops an object others may depend on
+    pub destructive: bool,
+}
+
+/// Differences between two schemas and the SQL that brings the target in line
+#[derive(Debug, Serialize)]
+pub struct SchemaDiff {
+    #[serde(rename = "sourceSchema")]
+    pub source_schema: String,
+    #[serde(rename = "targetSchema")]
+    pub target_schema: String,
+    pub changes: Vec<SchemaChange>,
+    /// Statements in a runnable order, destructive ones marked with a comment
+    pub migration: String,
+    pub destructive: bool,
+}
+
+/// Parameters for building a foreign-key graph
+#[derive(Debug, Deserialize)]
+pub struct RelationshipGraphParams {
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    pub database: Option<String>,
+    /// Defaults to the current schema on Postgres and the current database on MySQL
+    pub schema: Option<String>,
+    /// Tables of the schema to start from; empty for all of them
+    #[serde(default)]
+    pub tables: Vec<String>,
+    /// Also include tables up to this many foreign keys away, in any schema
+    #[serde(default)]
+    pub hops: u32,
+}
+
+/// Text formats a relationship graph can be exported to
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphColumn {
+    pub name: String,
+    #[serde(rename = "dataType")]
+    pub data_type: String,
+    pub nullable: bool,
+    #[serde(rename = "isPrimaryKey")]
+    pub is_primary_key: bool,
+    #[serde(rename = "isForeignKey")]
+    pub is_foreign_key: bool,
+}
+
+/// A table in a relationship graph
+#[derive(Debug, Clone, Serialize)]
+pub struct RelationshipNode {
+    pub schema: String,
+    pub name: String,
+    pub columns: Vec<GraphColumn>,
+    /// Foreign keys away from the requested tables; 0 for the tables themselves
+    pub hops: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnMapping {
+    pub column: String,
+    #[serde(rename = "referencedColumn")]
+    pub referenced_column: String,
+}
+
+/// How many referencing rows each referenced row can have
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Cardinality {
+    /// The key columns are also unique in the referencing table
+    OneToOne,
+    ManyToOne,
+}
+
+/// A foreign key from `table` to `referencedTable`
+#[derive(Debug, Clone, Serialize)]
+pub struct RelationshipEdge {
+    pub name: String,
+    pub schema: String,
+    pub table: String,
+    #[serde(rename = "referencedSchema")]
+    pub referenced_schema: String,
+    #[serde(rename = "referencedTable")]
+    pub referenced_table: String,
+    pub columns: Vec<ColumnMapping>,
+    pub cardinality: Cardinality,
+    /// A key column is nullable, so a referencing row need not have a match
+    pub optional: bool,
+    #[serde(rename = "onUpdate")]
+    pub on_update: String,
+    #[serde(rename = "onDelete")]
+    pub on_delete: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelationshipGraph {
+    pub nodes: Vec<RelationshipNode>,
+    pub edges: Vec<RelationshipEdge>,
+}
+
+/// A database on a connection's server
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseInfo {
+    pub name: String,
+    /// The database the saved connection opens
+    pub current: bool,
+    /// Total size on disk, when the user may connect to it (Postgres)
+    #[serde(rename = "sizeBytes", skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<i64>,
+}
+
+/// A schema in a database. On MySQL schemas are the server's databases.
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Holds the system catalogs rather than user objects
+    pub system: bool,
+}
+
+/// Parameters for counting a table's rows exactly
+#[derive(Debug, Deserialize)]
+pub struct CountRowsParams {
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    /// Another database on the connection's server
+    pub database: Option<String>,
+    pub schema: Option<String>,
+    #[serde(rename = "tableName")]
+    pub table_name: String,
+    /// ID to pass to `cancel_query`
+    #[serde(rename = "queryId")]
+    pub query_id: Option<String>,
+    #[serde(rename = "timeoutMs", default = "default_count_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_count_timeout_ms() -> u64 {
+    30_000
+}
+
+/// Exact row count of a table
+#[derive(Debug, Serialize)]
+pub struct RowCountResult {
+    pub count: i64,
+    #[serde(rename = "executionTimeMs")]
+    pub execution_time_ms: u128,
+}
+
+/// Parameters for searching object names and comments in the schema cache
+#[derive(Debug, Deserialize)]
+pub struct SearchObjectsParams {
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    /// Search another database on the connection's server
+    pub database: Option<String>,
+    pub term: String,
+    /// Kinds to include; all kinds when empty
+    #[serde(default)]
+    pub kinds: Vec<SearchObjectKind>,
+    /// Schemas to search; all of them when empty
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    /// Also search the system catalogs
+    #[serde(rename = "includeSystem", default)]
+    pub include_system: bool,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchObjectKind {
+    Schema,
+    Table,
+    View,
+    Column,
+    Function,
+    Index,
+}
+
+/// What part of an object the search term matched
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMatchField {
+    Name,
+    Comment,
+}
+
+/// An object matching a search, with the names leading to it
+#[derive(Debug, Clone, Serialize)]
+pub struct ObjectSearchHit {
+    pub kind: SearchObjectKind,
+    pub schema: String,
+    /// Table a column or index belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    pub name: String,
+    /// Schema, table and object name, for display and navigation
+    pub path: Vec<String>,
+    #[serde(rename = "matchedField")]
+    pub matched_field: SearchMatchField,
+    /// Higher is a better match
+    pub score: u32,
+    /// Type, signature or comment text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Parameters for finding a value in the rows of a schema's tables
+#[derive(Debug, Deserialize)]
+pub struct SearchDataParams {
+    #[serde(rename = "connectionId")]
+    pub connection_id: String,
+    /// Another database on the connection's server
+    pub database: Option<String>,
+    /// ID to pass to `cancel_query`; also tags the streamed matches
+    #[serde(rename = "queryId")]
+    pub query_id: Option<String>,
+    pub term: String,
+    /// Schemas to scan. Defaults to the current schema on Postgres and the
+    /// current database on MySQL.
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    /// Tables of those schemas to scan; all of them when empty
+    #[serde(default)]
+    pub tables: Vec<String>,
+    #[serde(rename = "matchMode", default)]
+    pub match_mode: DataMatchMode,
+    #[serde(rename = "caseSensitive", default)]
+    pub case_sensitive: bool,
+    /// Stop after this many matching rows
+    #[serde(rename = "maxRows", default = "default_limit")]
+    pub max_rows: i64,
+    /// Stop scanning after this long, keeping the matches found so far
+    #[serde(rename = "timeoutMs", default = "default_search_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_search_timeout_ms() -> u64 {
+    60_000
+}
+
+/// How text columns are compared with the search term. Numeric and uuid
+/// columns are only compared for equality, when the term parses as one.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DataMatchMode {
+    #[default]
+    Contains,
+    Prefix,
+    Exact,
+}
+
+/// A column value matching a data search, emitted as a `data-search-match` event
+#[derive(Debug, Clone, Serialize)]
+pub struct DataSearchMatch {
+    #[serde(rename = "queryId")]
+    pub query_id: String,
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    /// Primary key column values, as taken by `fetch_cell_value`. Postgres
+    /// tables without one are keyed by `ctid`, which holds until the row is
+    /// updated.
+    pub key: HashMap<String, serde_json::Value>,
+    /// The matching value as text, cut to an excerpt around the match
+    pub value: Option<String>,
+}
+
+/// Outcome of a data search once it stops
+#[derive(Debug, Serialize)]
+pub struct DataSearchSummary {
+    #[serde(rename = "queryId")]
+    pub query_id: String,
+    #[serde(rename = "tablesSearched")]
+    pub tables_searched: usize,
+    /// Tables in scope, including any not reached before the search stopped
+    #[serde(rename = "tablesTotal")]
+    pub tables_total: usize,
+    #[serde(rename = "columnsSearched")]
+    pub columns_searched: usize,
+    pub rows: usize,
+    pub matches: usize,
+    /// Stopped at `maxRows`
+    pub truncated: bool,
+    /// Stopped at `timeoutMs`
+    #[serde(rename = "timedOut")]
+    pub timed_out: bool,
+    /// Tables left out because a match couldn't be located again: MySQL
+    /// tables and partitioned Postgres tables without a primary key
+    #[serde(rename = "skippedTables")]
+    pub skipped_tables: Vec<String>,
+    /// Tables that couldn't be searched, with the reason
+    pub errors: Vec<String>,
+    #[serde(rename = "executionTimeMs")]
+    pub execution_time_ms: u128,
+}
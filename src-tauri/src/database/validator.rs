@@ -0,0 +1,762 @@
+use std::collections::HashMap;
+
+use super::errors::position_at_byte;
+use super::lexer::{dotted_name, ident, matching_paren, skip_words, tokenize, Token, TokenKind};
+use super::schema_cache::{ident_matches, CachedTable, SchemaMetadata};
+use super::types::*;
+
+/// Words that may begin a statement, in either dialect
+const STATEMENT_KEYWORDS: &[&str] = &[
+    "ALTER", "ANALYZE", "BEGIN", "CALL", "COMMIT", "CREATE", "DEALLOCATE", "DECLARE", "DELETE",
+    "DROP", "EXECUTE", "EXPLAIN", "GRANT", "INSERT", "LOCK", "PREPARE", "RELEASE", "RENAME",
+    "REVOKE", "ROLLBACK", "SAVEPOINT", "SELECT", "SET", "SHOW", "START", "TABLE", "TRUNCATE",
+    "UPDATE", "VALUES", "WITH",
+];
+
+const POSTGRES_STATEMENT_KEYWORDS: &[&str] = &[
+    "ABORT", "ANALYSE", "CHECKPOINT", "CLOSE", "CLUSTER", "COMMENT", "COPY", "DISCARD", "DO", "END",
+    "FETCH", "IMPORT", "LISTEN", "LOAD", "MERGE", "MOVE", "NOTIFY", "REASSIGN", "REFRESH", "REINDEX",
+    "RESET", "SECURITY", "UNLISTEN", "VACUUM",
+];
+
+const MYSQL_STATEMENT_KEYWORDS: &[&str] = &[
+    "CHECK", "CHECKSUM", "DESC", "DESCRIBE", "DO", "FLUSH", "GET", "HANDLER", "HELP", "INSTALL",
+    "KILL", "LOAD", "OPTIMIZE", "PURGE", "REPAIR", "REPLACE", "RESET", "RESIGNAL", "SIGNAL",
+    "UNINSTALL", "UNLOCK", "USE", "XA",
+];
+
+/// Clause keywords that can't directly follow a comma, operator or a keyword
+/// that expects an operand
+const CLAUSE_KEYWORDS: &[&str] = &[
+    "EXCEPT", "FROM", "GROUP", "HAVING", "INTERSECT", "LIMIT", "ORDER", "UNION", "WHERE",
+];
+
+/// Keywords that must be followed by an expression, name or list
+const OPERAND_KEYWORDS: &[&str] = &[
+    "AND", "BY", "FROM", "HAVING", "JOIN", "LIMIT", "OFFSET", "ON", "OR", "SET", "WHERE",
+];
+
+/// Words that end a table reference rather than naming its alias
+const ALIAS_STOP_WORDS: &[&str] = &[
+    "CROSS", "DEFAULT", "EXCEPT", "FETCH", "FOR", "FORCE", "FROM", "FULL", "GROUP", "HAVING", "IGNORE",
+    "INNER", "INTERSECT", "JOIN", "LEFT", "LIMIT", "NATURAL", "OFFSET", "ON", "ORDER", "OUTER",
+    "PARTITION", "RETURNING", "RIGHT", "SELECT", "SET", "STRAIGHT_JOIN", "TABLESAMPLE", "UNION",
+    "USE", "USING", "VALUES", "WHERE", "WINDOW",
+];
+
+/// Statements whose table and column references are checked
+const DML_KEYWORDS: &[&str] = &["DELETE", "EXPLAIN", "INSERT", "REPLACE", "SELECT", "UPDATE", "WITH"];
+
+/// Check `sql` for syntax problems without sending it to the server. With
+/// `schema`, table and column references are also checked against it.
+/// The checks are token-based and deliberately conservative: anything that
+/// can't be resolved with confidence is left for the server to judge.
+pub fn validate(sql: &str, dialect: DatabaseType, schema: Option<&SchemaMetadata>) -> Vec<SqlDiagnostic> {
+    let tokens = tokenize(sql, dialect);
+    let mut v = Validator { sql, dialect, diagnostics: Vec::new() };
+
+    v.check_tokens(&tokens);
+
+    let significant: Vec<Token> = tokens.into_iter().filter(|t| !t.is_trivia()).collect();
+    let statements: Vec<&[Token]> = significant
+        .split(|t| t.kind == TokenKind::Semicolon)
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    // Objects created earlier in the script won't be in the schema yet
+    let created = created_names(&statements);
+
+    for statement in statements {
+        if v.check_statement(statement) {
+            if let Some(schema) = schema {
+                References::new(&mut v, schema, &created).check(statement);
+            }
+        }
+    }
+
+    v.diagnostics.sort_by_key(|d| d.start.offset);
+    v.diagnostics
+}
+
+struct Validator<'s> {
+    sql: &'s str,
+    dialect: DatabaseType,
+    diagnostics: Vec<SqlDiagnostic>,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, severity: DiagnosticSeverity, first: &Token, last: &Token, message: String, hint: Option<String>) {
+        self.diagnostics.push(SqlDiagnostic {
+            severity,
+            message,
+            start: position_at_byte(self.sql, first.offset),
+            end: position_at_byte(self.sql, last.offset + last.text.len()),
+            hint,
+        });
+    }
+
+    fn error(&mut self, token: &Token, message: String) {
+        self.report(DiagnosticSeverity::Error, token, token, message, None);
+    }
+
+    /// Problems visible in single tokens: unterminated literals and quoting
+    /// or placeholder syntax from the other dialect
+    fn check_tokens(&mut self, tokens: &[Token]) {
+        let postgres = self.dialect == DatabaseType::Postgres;
+        let mut in_backticks = false;
+
+        for (i, token) in tokens.iter().enumerate() {
+            if !token.terminated {
+                let message = match token.kind {
+                    TokenKind::BlockComment => "Unterminated /* comment",
+                    TokenKind::QuotedIdentifier => "Unterminated quoted identifier",
+                    _ if token.text.starts_with('$') => "Unterminated dollar-quoted string",
+                    _ => "Unterminated quoted string",
+                };
+                self.error(token, message.to_string());
+                continue;
+            }
+
+            if token.kind != TokenKind::Operator {
+                continue;
+            }
+            let next_is_number = tokens.get(i + 1).is_some_and(|t| t.kind == TokenKind::Number);
+            let hint = match token.text {
+                t if postgres && t.starts_with('`') => {
+                    // Only the opening backtick is reported
+                    in_backticks = !in_backticks;
+                    if !in_backticks {
+                        continue;
+                    }
+                    "Backtick-quoted identifiers are MySQL syntax; PostgreSQL uses double quotes"
+                }
+                "::" if !postgres => "`::` casts are PostgreSQL syntax; use CAST(value AS type)",
+                "$" if !postgres && next_is_number => "`$n` placeholders are PostgreSQL syntax; MySQL uses `?`",
+                _ => continue,
+            };
+            self.report(
+                DiagnosticSeverity::Error,
+                token,
+                token,
+                format!("Syntax error at or near \"{}\"", token.text),
+                Some(hint.to_string()),
+            );
+        }
+    }
+
+    /// Structural checks on one statement's significant tokens. Returns false
+    /// if the statement is too broken for reference checks to be meaningful.
+    fn check_statement(&mut self, tokens: &[Token]) -> bool {
+        let errors_before = self.errors();
+        let first = &tokens[0];
+
+        let known_start = match first.kind {
+            TokenKind::LParen => true,
+            TokenKind::Word => {
+                let upper = first.text.to_ascii_uppercase();
+                let dialect_keywords = match self.dialect {
+                    DatabaseType::Postgres => POSTGRES_STATEMENT_KEYWORDS,
+                    DatabaseType::Mysql => MYSQL_STATEMENT_KEYWORDS,
+                };
+                is_one_of(&upper, STATEMENT_KEYWORDS) || is_one_of(&upper, dialect_keywords)
+            }
+            _ => false,
+        };
+        if !known_start {
+            let suggestion = (first.kind == TokenKind::Word)
+                .then(|| closest(first.text, STATEMENT_KEYWORDS.iter().copied()))
+                .flatten();
+            self.report(
+                DiagnosticSeverity::Error,
+                first,
+                first,
+                format!("Syntax error at or near \"{}\"", first.text),
+                suggestion.map(|s| format!("Did you mean {}?", s)),
+            );
+            return false;
+        }
+
+        self.check_brackets(tokens);
+
+        for (i, token) in tokens.iter().enumerate() {
+            let next = tokens.get(i + 1);
+            let prev = i.checked_sub(1).map(|p| &tokens[p]);
+            let upper = token.text.to_ascii_uppercase();
+
+            match token.kind {
+                TokenKind::Comma => {
+                    let leading = prev.is_some_and(|p| {
+                        matches!(p.kind, TokenKind::LParen | TokenKind::Comma) || p.is_keyword("SELECT")
+                    });
+                    if leading {
+                        self.error(token, "Unexpected comma".to_string());
+                    } else if ends_operand(next) && next.is_none_or(|n| n.kind != TokenKind::Comma) {
+                        self.error(token, format!("Trailing comma before {}", describe(next)));
+                    }
+                }
+                // `*` also means "all columns", and `arr[1:]` is a slice with no upper
+                // bound. Backticks are reported by check_tokens.
+                TokenKind::Operator
+                    if token.text != "*"
+                        && !token.text.starts_with('`')
+                        && !(token.text == ":" && next.is_some_and(|n| n.kind == TokenKind::RBracket))
+                        && ends_operand(next) =>
+                {
+                    self.error(token, format!("Expected an expression after \"{}\" but found {}", token.text, describe(next)));
+                }
+                TokenKind::Word if is_one_of(&upper, OPERAND_KEYWORDS) && prev.is_none_or(|p| p.kind != TokenKind::Dot) => {
+                    // `IS DISTINCT FROM x` and `EXTRACT(... FROM x)` have the same shape, so only
+                    // a missing operand is flagged
+                    if ends_operand(next) {
+                        self.error(token, format!("Expected an expression after {} but found {}", upper, describe(next)));
+                    } else if matches!(upper.as_str(), "AND" | "OR")
+                        && prev.is_some_and(|p| p.kind == TokenKind::LParen || p.is_keyword("WHERE") || p.is_keyword("AND") || p.is_keyword("OR"))
+                    {
+                        self.error(token, format!("Unexpected {}", upper));
+                    }
+                }
+                _ => {}
+            }
+
+            self.check_dialect(tokens, i);
+        }
+
+        self.errors() == errors_before
+    }
+
+    fn errors(&self) -> usize {
+        self.diagnostics.iter().filter(|d| d.severity == DiagnosticSeverity::Error).count()
+    }
+
+    fn check_brackets(&mut self, tokens: &[Token]) {
+        let mut open: Vec<&Token> = Vec::new();
+        for token in tokens {
+            match token.kind {
+                TokenKind::LParen | TokenKind::LBracket => open.push(token),
+                TokenKind::RParen | TokenKind::RBracket => {
+                    let expected = if token.kind == TokenKind::RParen { TokenKind::LParen } else { TokenKind::LBracket };
+                    match open.pop() {
+                        Some(o) if o.kind == expected => {}
+                        Some(o) => {
+                            self.error(token, format!("\"{}\" does not match \"{}\"", token.text, o.text));
+                            return;
+                        }
+                        None => {
+                            self.error(token, format!("Unmatched \"{}\"", token.text));
+                            return;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        for token in open {
+            self.error(token, format!("Unclosed \"{}\"", token.text));
+        }
+    }
+
+    /// Syntax that is valid in one dialect but not the other
+    fn check_dialect(&mut self, tokens: &[Token], i: usize) {
+        let token = &tokens[i];
+        match self.dialect {
+            DatabaseType::Postgres => {
+                let limit_pair = token.is_keyword("LIMIT")
+                    && tokens.get(i + 2).is_some_and(|t| t.kind == TokenKind::Comma);
+                if limit_pair {
+                    self.report(
+                        DiagnosticSeverity::Error,
+                        token,
+                        &tokens[i + 2],
+                        "LIMIT #,# syntax is not supported".to_string(),
+                        Some("Use separate LIMIT and OFFSET clauses".to_string()),
+                    );
+                }
+            }
+            DatabaseType::Mysql => {
+                let (severity, hint) = if token.is_keyword("ILIKE") {
+                    (DiagnosticSeverity::Error, "ILIKE is PostgreSQL syntax; use LIKE, which is case-insensitive for most collations")
+                } else if token.is_keyword("RETURNING") {
+                    (DiagnosticSeverity::Warning, "MySQL does not support RETURNING (MariaDB 10.5 and later do)")
+                } else if token.is_keyword("FULL")
+                    && tokens[i + 1..].iter().take(2).any(|t| t.is_keyword("JOIN"))
+                {
+                    // `a FULL JOIN b` parses in MySQL, with FULL as an alias for `a`
+                    (DiagnosticSeverity::Warning, "MySQL does not support FULL OUTER JOIN; FULL is read as a table alias")
+                } else {
+                    return;
+                };
+                self.report(severity, token, token, format!("Unsupported syntax \"{}\"", token.text), Some(hint.to_string()));
+            }
+        }
+    }
+}
+
+/// Table and column reference checks for one statement
+struct References<'v, 's, 'm> {
+    v: &'v mut Validator<'s>,
+    schema: &'m SchemaMetadata,
+    created: &'v [String],
+    /// Names visible as qualifiers, lower-cased, mapped to the table they
+    /// refer to. `None` marks CTEs, subqueries and ambiguous names, whose
+    /// columns aren't checked.
+    scope: HashMap<String, Option<&'m CachedTable>>,
+    /// Token indexes that are part of table references
+    claimed: Vec<bool>,
+}
+
+impl<'v, 's, 'm> References<'v, 's, 'm> {
+    fn new(v: &'v mut Validator<'s>, schema: &'m SchemaMetadata, created: &'v [String]) -> Self {
+        Self { v, schema, created, scope: HashMap::new(), claimed: Vec::new() }
+    }
+
+    fn check(mut self, tokens: &[Token]) {
+        let verb = tokens[0].text.to_ascii_uppercase();
+        if !is_one_of(&verb, DML_KEYWORDS) {
+            return;
+        }
+        self.claimed = vec![false; tokens.len()];
+
+        for name in cte_names(tokens) {
+            self.scope.insert(name, None);
+        }
+
+        // Which paren groups are subqueries, so `FROM` inside function calls is ignored
+        let mut groups: Vec<bool> = Vec::new();
+        let mut targets: Vec<(usize, &'m CachedTable)> = Vec::new();
+
+        for i in 0..tokens.len() {
+            let token = &tokens[i];
+            match token.kind {
+                TokenKind::LParen => {
+                    groups.push(tokens.get(i + 1).is_some_and(|t| is_one_of(&t.text.to_ascii_uppercase(), DML_KEYWORDS)));
+                    continue;
+                }
+                TokenKind::RParen => {
+                    groups.pop();
+                    continue;
+                }
+                TokenKind::Word => {}
+                _ => continue,
+            }
+            if !groups.last().copied().unwrap_or(true) {
+                continue;
+            }
+
+            let prev = i.checked_sub(1).map(|p| &tokens[p]);
+            let upper = token.text.to_ascii_uppercase();
+            match upper.as_str() {
+                "FROM" if prev.is_none_or(|p| !p.is_keyword("DISTINCT")) => {
+                    self.table_list(tokens, i + 1, true);
+                }
+                "USING" if tokens.get(i + 1).is_some_and(|t| t.kind != TokenKind::LParen) => {
+                    self.table_list(tokens, i + 1, true);
+                }
+                "JOIN" => {
+                    self.table_list(tokens, i + 1, false);
+                }
+                "UPDATE" if prev.is_none_or(|p| p.kind == TokenKind::RParen || p.is_keyword("EXPLAIN")) => {
+                    let start = skip_words(tokens, i + 1, &["IGNORE", "LOW_PRIORITY"]);
+                    // SET targets are unambiguous unless MySQL's multi-table form is used
+                    if let [Some(table)] = self.table_list(tokens, start, true)[..] {
+                        let set = tokens[i..].iter().position(|t| t.is_keyword("SET") || t.is_keyword("JOIN"));
+                        if let Some(set) = set.filter(|&s| tokens[i + s].is_keyword("SET")) {
+                            targets.push((i + set + 1, table));
+                        }
+                    }
+                }
+                "INTO" if prev.is_some_and(|p| p.is_keyword("INSERT") || p.is_keyword("REPLACE") || p.is_keyword("IGNORE")) => {
+                    if let Some((end, table)) = self.table_ref(tokens, i + 1, false) {
+                        let end = self.alias(tokens, end, table);
+                        if let Some(table) = table {
+                            self.insert_columns(tokens, end, table);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (start, table) in targets {
+            self.set_columns(tokens, start, table);
+        }
+
+        self.qualified_columns(tokens);
+    }
+
+    /// Read comma-separated (if `list`) table references starting at `i`.
+    /// Returns what each reference resolved to.
+    fn table_list(&mut self, tokens: &[Token], mut i: usize, list: bool) -> Vec<Option<&'m CachedTable>> {
+        let mut tables = Vec::new();
+        loop {
+            i = skip_words(tokens, i, &["LATERAL", "ONLY"]);
+            let end = match tokens.get(i) {
+                Some(t) if t.kind == TokenKind::LParen => {
+                    let Some(close) = matching_paren(tokens, i) else { return tables };
+                    tables.push(None);
+                    self.alias(tokens, close + 1, None)
+                }
+                Some(_) => match self.table_ref(tokens, i, true) {
+                    Some((end, table)) => {
+                        tables.push(table);
+                        self.alias(tokens, end, table)
+                    }
+                    None => return tables,
+                },
+                None => return tables,
+            };
+            match tokens.get(end) {
+                Some(t) if list && t.kind == TokenKind::Comma => i = end + 1,
+                _ => return tables,
+            }
+        }
+    }
+
+    /// Resolve a dotted name at `i`, reporting it if it doesn't exist. Returns
+    /// the index after the name and the table, `None` for CTEs and functions.
+    /// `functions` says whether `name(...)` is a table function here rather
+    /// than a name followed by a column list.
+    fn table_ref(&mut self, tokens: &[Token], i: usize, functions: bool) -> Option<(usize, Option<&'m CachedTable>)> {
+        let token = tokens.get(i)?;
+        if token.kind == TokenKind::Word && is_one_of(&token.text.to_ascii_uppercase(), ALIAS_STOP_WORDS) {
+            return None;
+        }
+        let (parts, end) = dotted_name(tokens, i)?;
+        if functions && tokens.get(end).is_some_and(|t| t.kind == TokenKind::LParen) {
+            // Table function such as generate_series(...)
+            let close = matching_paren(tokens, end)?;
+            return Some((close + 1, None));
+        }
+        for claimed in &mut self.claimed[i..end] {
+            *claimed = true;
+        }
+
+        let (name, quoted) = ident(&tokens[*parts.last()?]);
+        let schema = (parts.len() > 1).then(|| ident(&tokens[parts[parts.len() - 2]]));
+
+        if schema.is_none() {
+            let lower = name.to_lowercase();
+            let dual = self.schema.dialect == DatabaseType::Mysql && lower == "dual";
+            if dual || self.scope.get(&lower).is_some_and(Option::is_none) || self.created.contains(&lower) {
+                return Some((end, None));
+            }
+        }
+
+        let found = self.schema.find_table(schema.as_ref().map(|(s, q)| (s.as_str(), *q)), &name, quoted);
+        match found {
+            Some(table) => {
+                self.register(&table.name, Some(table));
+                Some((end, Some(table)))
+            }
+            None => {
+                let candidates = self.schema.tables.iter().filter(|t| match &schema {
+                    Some((s, q)) => ident_matches(&t.schema, s, *q, self.schema.dialect),
+                    None => self.schema.search_path.contains(&t.schema),
+                });
+                let suggestion = closest(&name, candidates.map(|t| t.name.as_str()));
+                let written = &self.v.sql[token.offset..tokens[end - 1].offset + tokens[end - 1].text.len()];
+                self.v.report(
+                    DiagnosticSeverity::Error,
+                    token,
+                    &tokens[end - 1],
+                    format!("Unknown table {}", written),
+                    suggestion.map(|s| format!("Did you mean {}?", s)),
+                );
+                Some((end, None))
+            }
+        }
+    }
+
+    /// Read an optional alias at `i` and bring it into scope. Returns the index after it.
+    fn alias(&mut self, tokens: &[Token], mut i: usize, table: Option<&'m CachedTable>) -> usize {
+        if tokens.get(i).is_some_and(|t| t.is_keyword("AS")) {
+            i += 1;
+        }
+        let Some(token) = tokens.get(i) else { return i };
+        let is_alias = match token.kind {
+            TokenKind::QuotedIdentifier => true,
+            TokenKind::Word => !is_one_of(&token.text.to_ascii_uppercase(), ALIAS_STOP_WORDS),
+            _ => false,
+        };
+        if !is_alias {
+            return i;
+        }
+
+        self.register(&ident(token).0, table);
+        i += 1;
+        // Column aliases: AS t(a, b)
+        if tokens.get(i).is_some_and(|t| t.kind == TokenKind::LParen) {
+            if let Some(close) = matching_paren(tokens, i) {
+                self.register(&ident(token).0, None);
+                return close + 1;
+            }
+        }
+        i
+    }
+
+    fn register(&mut self, name: &str, table: Option<&'m CachedTable>) {
+        let key = name.to_lowercase();
+        let table = match (self.scope.get(&key), table) {
+            (None, table) => table,
+            (Some(Some(existing)), Some(table)) if std::ptr::eq(*existing, table) => Some(table),
+            // The same name bound to different tables in different subqueries
+            _ => None,
+        };
+        self.scope.insert(key, table);
+    }
+
+    /// Check the column list of `INSERT INTO t (a, b)`
+    fn insert_columns(&mut self, tokens: &[Token], i: usize, table: &CachedTable) {
+        if tokens.get(i).is_none_or(|t| t.kind != TokenKind::LParen)
+            || tokens.get(i + 1).is_some_and(|t| is_one_of(&t.text.to_ascii_uppercase(), DML_KEYWORDS))
+        {
+            return;
+        }
+        let Some(close) = matching_paren(tokens, i) else { return };
+        for token in &tokens[i + 1..close] {
+            if matches!(token.kind, TokenKind::Word | TokenKind::QuotedIdentifier) {
+                self.column(token, None, table);
+            }
+        }
+    }
+
+    /// Check the targets of `SET a = ..., b = ...`
+    fn set_columns(&mut self, tokens: &[Token], mut i: usize, table: &CachedTable) {
+        while let Some(token) = tokens.get(i) {
+            let assigns = tokens.get(i + 1).is_some_and(|t| t.text == "=");
+            if matches!(token.kind, TokenKind::Word | TokenKind::QuotedIdentifier) && assigns {
+                self.column(token, None, table);
+            }
+            // Skip the assigned expression up to the next top-level comma
+            let mut depth = 0;
+            loop {
+                i += 1;
+                match tokens.get(i).map(|t| t.kind) {
+                    None => return,
+                    Some(TokenKind::LParen) => depth += 1,
+                    Some(TokenKind::RParen) if depth == 0 => return,
+                    Some(TokenKind::RParen) => depth -= 1,
+                    Some(TokenKind::Comma) if depth == 0 => break,
+                    Some(TokenKind::Word) if depth == 0 && is_one_of(&tokens[i].text.to_ascii_uppercase(), CLAUSE_KEYWORDS) => return,
+                    Some(TokenKind::Word) if depth == 0 && tokens[i].is_keyword("RETURNING") => return,
+                    _ => {}
+                }
+            }
+            i += 1;
+        }
+    }
+
+    /// Check `alias.column` references whose qualifier is a known table
+    fn qualified_columns(&mut self, tokens: &[Token]) {
+        for i in 1..tokens.len().saturating_sub(1) {
+            if tokens[i].kind != TokenKind::Dot || self.claimed[i] {
+                continue;
+            }
+            let (qualifier, column) = (&tokens[i - 1], &tokens[i + 1]);
+            let is_name = |t: &Token| matches!(t.kind, TokenKind::Word | TokenKind::QuotedIdentifier);
+            let followed = tokens.get(i + 2).is_some_and(|t| matches!(t.kind, TokenKind::Dot | TokenKind::LParen));
+            if !is_name(qualifier) || !is_name(column) || followed {
+                continue;
+            }
+
+            let key = ident(qualifier).0.to_lowercase();
+            if let Some(Some(table)) = self.scope.get(&key).copied() {
+                self.column(column, Some(qualifier), table);
+            }
+        }
+    }
+
+    fn column(&mut self, token: &Token, qualifier: Option<&Token>, table: &CachedTable) {
+        let (name, quoted) = ident(token);
+        if table.column(&name, quoted, self.schema.dialect).is_some() {
+            return;
+        }
+
+        let suggestion = closest(&name, table.columns.iter().map(|c| c.name.as_str()));
+        let message = match qualifier {
+            Some(q) => format!("Unknown column {}.{}", q.text, token.text),
+            None => format!("Unknown column {} in table {}", token.text, table.name),
+        };
+        self.v.report(
+            DiagnosticSeverity::Error,
+            qualifier.unwrap_or(token),
+            token,
+            message,
+            suggestion.map(|s| format!("Did you mean {}?", s)),
+        );
+    }
+}
+
+fn is_one_of(upper: &str, words: &[&str]) -> bool {
+    words.contains(&upper)
+}
+
+/// Whether `next` can't start an operand: end of statement, a closing
+/// bracket, a comma or a clause keyword
+fn ends_operand(next: Option<&Token>) -> bool {
+    match next {
+        None => true,
+        Some(t) => match t.kind {
+            TokenKind::RParen | TokenKind::RBracket | TokenKind::Comma => true,
+            TokenKind::Word => is_one_of(&t.text.to_ascii_uppercase(), CLAUSE_KEYWORDS),
+            _ => false,
+        },
+    }
+}
+
+fn describe(token: Option<&Token>) -> String {
+    match token {
+        None => "end of statement".to_string(),
+        Some(t) => format!("\"{}\"", t.text),
+    }
+}
+
+/// Names defined by `WITH name AS (...)`, lower-cased
+fn cte_names(tokens: &[Token]) -> Vec<String> {
+    let mut names = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if !token.is_keyword("WITH") {
+            continue;
+        }
+        let mut j = skip_words(tokens, i + 1, &["RECURSIVE"]);
+        while let Some(name) = tokens.get(j).filter(|t| matches!(t.kind, TokenKind::Word | TokenKind::QuotedIdentifier)) {
+            names.push(ident(name).0.to_lowercase());
+            j += 1;
+            // Optional column list, then AS [NOT] MATERIALIZED (...)
+            if tokens.get(j).is_some_and(|t| t.kind == TokenKind::LParen) {
+                j = match matching_paren(tokens, j) {
+                    Some(close) => close + 1,
+                    None => return names,
+                };
+            }
+            j = skip_words(tokens, j, &["AS", "NOT", "MATERIALIZED"]);
+            j = match tokens.get(j).filter(|t| t.kind == TokenKind::LParen).and_then(|_| matching_paren(tokens, j)) {
+                Some(close) => close + 1,
+                None => break,
+            };
+            match tokens.get(j) {
+                Some(t) if t.kind == TokenKind::Comma => j += 1,
+                _ => break,
+            }
+        }
+    }
+    names
+}
+
+/// Tables and views created by `CREATE ... TABLE|VIEW name` statements, lower-cased
+fn created_names(statements: &[&[Token]]) -> Vec<String> {
+    statements
+        .iter()
+        .filter(|s| s[0].is_keyword("CREATE"))
+        .filter_map(|s| {
+            let object = s.iter().position(|t| t.is_keyword("TABLE") || t.is_keyword("VIEW"))?;
+            let start = skip_words(s, object + 1, &["IF", "NOT", "EXISTS"]);
+            let (parts, _) = dotted_name(s, start)?;
+            Some(ident(&s[*parts.last()?]).0.to_lowercase())
+        })
+        .collect()
+}
+
+/// The candidate closest to `name` by edit distance, if it is close enough
+/// to plausibly be a typo
+fn closest<'c>(name: &str, candidates: impl Iterator<Item = &'c str>) -> Option<&'c str> {
+    let name = name.to_lowercase();
+    let max = (name.chars().count() / 3).max(1);
+    candidates
+        .map(|c| (edit_distance(&name, &c.to_lowercase()), c))
+        .filter(|(d, _)| *d <= max)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::schema_cache::CachedColumn;
+    use super::*;
+
+    fn schema(dialect: DatabaseType) -> SchemaMetadata {
+        let column = |name: &str| CachedColumn { name: name.to_string(), data_type: "integer".to_string(), comment: None };
+        let users = CachedTable {
+            schema: "public".to_string(),
+            name: "users".to_string(),
+            kind: "table".to_string(),
+            columns: vec![column("id"), column("name")],
+            comment: None,
+        };
+        SchemaMetadata {
+            dialect,
+            search_path: vec!["public".to_string()],
+            schemas: vec!["public".to_string()],
+            tables: vec![users],
+            functions: Vec::new(),
+            indexes: Vec::new(),
+            keywords: Vec::new(),
+            loaded_at: chrono::Utc::now(),
+        }
+    }
+
+    fn messages(sql: &str, dialect: DatabaseType) -> Vec<String> {
+        let schema = schema(dialect);
+        validate(sql, dialect, Some(&schema)).into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn partial_statements_do_not_panic() {
+        let inputs = [
+            "INSERT INTO",
+            "INSERT INTO users (",
+            "REPLACE INTO",
+            "SELECT * FROM",
+            "SELECT * FROM users JOIN",
+            "SELECT * FROM users u WHERE u.",
+            "UPDATE users SET",
+            "UPDATE",
+            "DELETE FROM users USING",
+            "WITH x AS (",
+            "SELECT a. FROM users",
+            ".",
+            "SELECT 5--1",
+            "UPDATE users SET id=id--1",
+        ];
+        for sql in inputs {
+            for dialect in [DatabaseType::Postgres, DatabaseType::Mysql] {
+                messages(sql, dialect);
+            }
+        }
+    }
+
+    #[test]
+    fn unterminated_identifiers() {
+        assert_eq!(messages("SELECT * FROM \"café", DatabaseType::Postgres).len(), 2);
+        assert_eq!(messages("SELECT * FROM \"users", DatabaseType::Postgres), ["Unterminated quoted identifier"]);
+        messages("SELECT * FROM `café", DatabaseType::Mysql);
+    }
+
+    #[test]
+    fn unknown_references() {
+        assert_eq!(messages("SELECT * FROM usres", DatabaseType::Postgres), ["Unknown table usres"]);
+        assert_eq!(messages("SELECT u.nmae FROM users u", DatabaseType::Postgres).len(), 1);
+        assert!(messages("INSERT INTO users (id, name) VALUES (1, 'a')", DatabaseType::Postgres).is_empty());
+    }
+
+    #[test]
+    fn dialect_comments() {
+        assert!(messages("SELECT id FROM users # note", DatabaseType::Mysql).is_empty());
+        assert!(messages("SELECT id FROM users -- note", DatabaseType::Mysql).is_empty());
+        assert!(messages("SELECT id /* a /* b */ c */ FROM users", DatabaseType::Postgres).is_empty());
+    }
+}
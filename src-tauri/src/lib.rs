@@ -1,13 +1,74 @@
 mod commands;
 mod database;
 
-use database::ConnectionManager;
+use database::{
+    history, notices, result_cache, ConnectionManager, JobManager, JobStatus, NotificationHub,
+    QueryHistory, ResultCache, RunningQueries, SchemaCache, SnippetLibrary,
+};
+use tauri::{Emitter, Manager};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    notices::install();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .manage(ConnectionManager::new())
+        .manage(SchemaCache::new())
+        .manage(RunningQueries::new())
+        .setup(|app| {
+            let data_dir = app.path().app_data_dir()?;
+
+            // History and snippets are best-effort: a locked or corrupt
+            // database must not keep the app from starting
+            let history = tauri::async_runtime::block_on(QueryHistory::open(
+                &data_dir,
+                history::DEFAULT_RETENTION,
+            ))
+            .unwrap_or_else(|e| {
+                tracing::warn!("Query history disabled: {e}");
+                QueryHistory::disabled()
+            });
+            app.manage(history);
+
+            let snippets = tauri::async_runtime::block_on(SnippetLibrary::open(&data_dir))
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Snippet library disabled: {e}");
+                    SnippetLibrary::disabled()
+                });
+            app.manage(snippets);
+
+            let handle = app.handle().clone();
+            app.manage(NotificationHub::new(move |notification| {
+                let _ = handle.emit("pg-notification", notification);
+            }));
+
+            let handle = app.handle().clone();
+            let jobs_dir = std::env::temp_dir().join("mesagrid").join("jobs");
+            app.manage(JobManager::new(jobs_dir, move |job| {
+                if job.status != JobStatus::Running {
+                    if let Ok(config) = handle
+                        .state::<ConnectionManager>()
+                        .get_config(&job.connection_id)
+                    {
+                        handle.state::<SchemaCache>().invalidate_after(
+                            &job.connection_id,
+                            &job.sql,
+                            config.db_type,
+                        );
+                    }
+                }
+                let _ = handle.emit("query-job", job);
+            })?);
+
+            let results_dir = std::env::temp_dir().join("mesagrid").join("results");
+            app.manage(ResultCache::new(
+                results_dir,
+                result_cache::DEFAULT_MAX_BYTES,
+                result_cache::DEFAULT_MAX_AGE,
+            )?);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::create_connection,
             commands::test_connection,
@@ -17,6 +78,50 @@ pub fn run() {
             commands::delete_connection,
             commands::execute_query,
             commands::list_tables,
+            commands::count_rows_exact,
+            commands::search_data,
+            commands::cancel_query,
+            commands::list_databases,
+            commands::list_schemas,
+            commands::get_table_schema,
+            commands::get_table_structure,
+            commands::list_objects,
+            commands::get_object_definition,
+            commands::generate_ddl,
+            commands::diff_schemas,
+            commands::get_relationship_graph,
+            commands::export_relationship_graph,
+            commands::list_history,
+            commands::search_history,
+            commands::clear_history,
+            commands::get_history_retention,
+            commands::set_history_retention,
+            commands::list_saved_queries,
+            commands::list_snippet_folders,
+            commands::get_saved_query,
+            commands::save_query,
+            commands::delete_saved_query,
+            commands::render_saved_query,
+            commands::import_saved_queries,
+            commands::export_saved_queries,
+            commands::subscribe_channel,
+            commands::unsubscribe_channel,
+            commands::list_subscriptions,
+            commands::list_notifications,
+            commands::submit_query_job,
+            commands::list_jobs,
+            commands::get_job_result,
+            commands::cancel_job,
+            commands::get_result_page,
+            commands::release_result,
+            commands::fetch_cell_value,
+            commands::benchmark_query,
+            commands::execute_fan_out,
+            commands::format_sql,
+            commands::validate_sql,
+            commands::complete_sql,
+            commands::search_objects,
+            commands::refresh_schema_cache,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -2,11 +2,59 @@ import { invoke } from "@tauri-apps/api/core";
 import type {
   Connection,
   DatabaseType,
+  QueryNotice,
   QueryResult,
+  TruncatedCell,
   TableInfo,
   ColumnInfo,
 } from "@/stores/connection-store";
 
+// Errors: every command rejects with a `ConnectionError`
+export type ErrorKind =
+  | "notFound"
+  | "database"
+  | "connection"
+  | "driver"
+  | "credential"
+  | "unsupportedType"
+  | "invalidInput"
+  | "storage"
+  | "cancelled"
+  | "timeout"
+  | "io";
+
+export interface ErrorPosition {
+  /** Zero-based character offset */
+  offset: number;
+  line: number;
+  column: number;
+}
+
+export interface DatabaseErrorInfo {
+  message: string;
+  /** SQLSTATE code */
+  code?: string;
+  /** Vendor error number (MySQL) */
+  errorNumber?: number;
+  severity?: string;
+  detail?: string;
+  hint?: string;
+  position?: ErrorPosition;
+  where?: string;
+  schema?: string;
+  table?: string;
+  column?: string;
+  dataType?: string;
+  constraint?: string;
+  violation?: "unique" | "foreignKey" | "notNull" | "check";
+}
+
+export interface ConnectionError {
+  kind: ErrorKind;
+  message: string;
+  database?: DatabaseErrorInfo;
+}
+
 // Connection management
 export interface CreateConnectionParams {
   name: string;
@@ -29,9 +77,23 @@ export interface TestConnectionParams {
 
 export interface ExecuteQueryParams {
   connectionId: string;
+  database?: string;
+  schema?: string;
   sql: string;
   limit?: number;
   offset?: number;
+  /** Values for `{{name}}` template variables in `sql` */
+  variables?: Record<string, string>;
+  /** Keep the full result on disk and return only the requested page */
+  cache?: boolean;
+  /** Stop reading after this many rows */
+  maxRows?: number;
+  /** Stop reading once the decoded rows reach this many bytes */
+  maxBytes?: number;
+  /** Text, binary and JSON values longer than this many bytes are cut to a preview */
+  maxCellBytes?: number;
+  /** Report the backend PID / connection ID in `stats` (one extra round trip) */
+  includeBackendId?: boolean;
 }
 
 export interface GetTableDataParams {
@@ -90,16 +152,377 @@ export async function cancelQuery(queryId: string): Promise<void> {
 }
 
 // Table commands
-export async function listTables(connectionId: string): Promise<TableInfo[]> {
-  return invoke("list_tables", { connectionId });
+export async function listTables(
+  connectionId: string,
+  database?: string,
+  schema?: string
+): Promise<TableInfo[]> {
+  return invoke("list_tables", { connectionId, database, schema });
+}
+
+export interface CountRowsParams {
+  connectionId: string;
+  database?: string;
+  schema?: string;
+  tableName: string;
+  queryId?: string;
+  timeoutMs?: number;
+}
+
+export interface RowCountResult {
+  count: number;
+  executionTimeMs: number;
+}
+
+/** Cancel with `cancelQuery(params.queryId)` */
+export async function countRowsExact(
+  params: CountRowsParams
+): Promise<RowCountResult> {
+  return invoke("count_rows_exact", { params });
+}
+
+export interface DatabaseInfo {
+  name: string;
+  current: boolean;
+  sizeBytes?: number;
+}
+
+export async function listDatabases(
+  connectionId: string
+): Promise<DatabaseInfo[]> {
+  return invoke("list_databases", { connectionId });
+}
+
+export interface SchemaInfo {
+  name: string;
+  owner?: string;
+  system: boolean;
+}
+
+export async function listSchemas(
+  connectionId: string,
+  database?: string
+): Promise<SchemaInfo[]> {
+  return invoke("list_schemas", { connectionId, database });
+}
+
+export interface TableColumn {
+  ordinalPosition: number;
+  name: string;
+  dataType: string;
+  nullable: boolean;
+  defaultValue?: string;
+  defaultIsExpression: boolean;
+  identity?: "ALWAYS" | "BY DEFAULT";
+  autoIncrement: boolean;
+  generated?: "STORED" | "VIRTUAL";
+  generationExpression?: string;
+  collation?: string;
+  characterSet?: string;
+  onUpdate?: string;
+  comment?: string;
+  isPrimaryKey: boolean;
+  primaryKeyPosition?: number;
 }
 
 export async function getTableSchema(
   connectionId: string,
   tableName: string,
-  schema?: string
-): Promise<ColumnInfo[]> {
-  return invoke("get_table_schema", { connectionId, tableName, schema });
+  schema?: string,
+  database?: string
+): Promise<TableColumn[]> {
+  return invoke("get_table_schema", {
+    connectionId,
+    tableName,
+    schema,
+    database,
+  });
+}
+
+export interface TableIndex {
+  name: string;
+  columns: string[];
+  include?: string[];
+  unique: boolean;
+  primary: boolean;
+  method: string;
+  predicate?: string;
+  sizeBytes?: number;
+}
+
+export interface TableConstraint {
+  name: string;
+  kind: "primaryKey" | "unique" | "check" | "exclusion";
+  columns?: string[];
+  definition: string;
+}
+
+export interface ForeignKey {
+  name: string;
+  schema: string;
+  table: string;
+  columns: string[];
+  referencedSchema: string;
+  referencedTable: string;
+  referencedColumns: string[];
+  onUpdate: string;
+  onDelete: string;
+}
+
+export interface TableStructure {
+  schema: string;
+  name: string;
+  indexes: TableIndex[];
+  constraints: TableConstraint[];
+  foreignKeys: ForeignKey[];
+  referencedBy: ForeignKey[];
+}
+
+export async function getTableStructure(
+  connectionId: string,
+  tableName: string,
+  schema?: string,
+  database?: string
+): Promise<TableStructure> {
+  return invoke("get_table_structure", {
+    connectionId,
+    tableName,
+    schema,
+    database,
+  });
+}
+
+export type ObjectKind =
+  | "table"
+  | "view"
+  | "materializedView"
+  | "function"
+  | "procedure"
+  | "trigger"
+  | "sequence"
+  | "type"
+  | "event";
+
+export interface DatabaseObject {
+  schema: string;
+  name: string;
+  kind: ObjectKind;
+  table?: string;
+  arguments?: string;
+  result?: string;
+  detail?: string;
+  currentValue?: number;
+  comment?: string;
+}
+
+export interface ListObjectsParams {
+  connectionId: string;
+  kinds?: ObjectKind[];
+  database?: string;
+  schema?: string;
+}
+
+export async function listObjects(
+  params: ListObjectsParams
+): Promise<DatabaseObject[]> {
+  return invoke("list_objects", { params });
+}
+
+export interface GetObjectDefinitionParams {
+  connectionId: string;
+  kind: ObjectKind;
+  database?: string;
+  schema?: string;
+  name: string;
+  arguments?: string;
+  table?: string;
+}
+
+export async function getObjectDefinition(
+  params: GetObjectDefinitionParams
+): Promise<string> {
+  return invoke("get_object_definition", { params });
+}
+
+export interface DdlOptions {
+  /** Postgres `OWNER TO`; the `DEFINER` clause on MySQL */
+  includeOwner?: boolean;
+  /** Postgres only */
+  includeGrants?: boolean;
+  /** Must stay on for MySQL, whose DDL always carries comments */
+  includeComments?: boolean;
+  includeDependents?: boolean;
+}
+
+export interface GenerateDdlParams extends GetObjectDefinitionParams {
+  options?: DdlOptions;
+}
+
+export async function generateDdl(params: GenerateDdlParams): Promise<string> {
+  return invoke("generate_ddl", { params });
+}
+
+export interface SchemaRef {
+  connectionId: string;
+  database?: string;
+  schema?: string;
+}
+
+export interface DiffScope {
+  kinds?: ObjectKind[];
+  names?: string[];
+}
+
+export interface SchemaChange {
+  objectKind: ObjectKind;
+  name: string;
+  action: "create" | "drop" | "alter";
+  part?: "column" | "index" | "constraint" | "foreignKey";
+  partName?: string;
+  details?: string[];
+  destructive: boolean;
+}
+
+export interface SchemaDiff {
+  sourceSchema: string;
+  targetSchema: string;
+  changes: SchemaChange[];
+  migration: string;
+  destructive: boolean;
+}
+
+export async function diffSchemas(
+  source: SchemaRef,
+  target: SchemaRef,
+  scope?: DiffScope
+): Promise<SchemaDiff> {
+  return invoke("diff_schemas", { source, target, scope });
+}
+
+export interface RelationshipGraphParams {
+  connectionId: string;
+  database?: string;
+  schema?: string;
+  tables?: string[];
+  hops?: number;
+}
+
+export interface GraphColumn {
+  name: string;
+  dataType: string;
+  nullable: boolean;
+  isPrimaryKey: boolean;
+  isForeignKey: boolean;
+}
+
+export interface RelationshipNode {
+  schema: string;
+  name: string;
+  columns: GraphColumn[];
+  hops: number;
+}
+
+export interface RelationshipEdge {
+  name: string;
+  schema: string;
+  table: string;
+  referencedSchema: string;
+  referencedTable: string;
+  columns: { column: string; referencedColumn: string }[];
+  cardinality: "oneToOne" | "manyToOne";
+  optional: boolean;
+  onUpdate: string;
+  onDelete: string;
+}
+
+export interface RelationshipGraph {
+  nodes: RelationshipNode[];
+  edges: RelationshipEdge[];
+}
+
+export async function getRelationshipGraph(
+  params: RelationshipGraphParams
+): Promise<RelationshipGraph> {
+  return invoke("get_relationship_graph", { params });
+}
+
+export async function exportRelationshipGraph(
+  params: RelationshipGraphParams,
+  format: "dot" | "mermaid"
+): Promise<string> {
+  return invoke("export_relationship_graph", { params, format });
+}
+
+export type SearchObjectKind = "schema" | "table" | "view" | "column" | "function" | "index";
+
+export interface SearchObjectsParams {
+  connectionId: string;
+  database?: string;
+  term: string;
+  kinds?: SearchObjectKind[];
+  schemas?: string[];
+  includeSystem?: boolean;
+  limit?: number;
+}
+
+export interface ObjectSearchHit {
+  kind: SearchObjectKind;
+  schema: string;
+  table?: string;
+  name: string;
+  path: string[];
+  matchedField: "name" | "comment";
+  score: number;
+  detail?: string;
+}
+
+export async function searchObjects(
+  params: SearchObjectsParams
+): Promise<ObjectSearchHit[]> {
+  return invoke("search_objects", { params });
+}
+
+export interface SearchDataParams {
+  connectionId: string;
+  database?: string;
+  queryId?: string;
+  term: string;
+  schemas?: string[];
+  tables?: string[];
+  matchMode?: "contains" | "prefix" | "exact";
+  caseSensitive?: boolean;
+  maxRows?: number;
+  timeoutMs?: number;
+}
+
+/** Payload of the `data-search-match` event */
+export interface DataSearchMatch {
+  queryId: string;
+  schema: string;
+  table: string;
+  column: string;
+  key: Record<string, unknown>;
+  value: string | null;
+}
+
+export interface DataSearchSummary {
+  queryId: string;
+  tablesSearched: number;
+  tablesTotal: number;
+  columnsSearched: number;
+  rows: number;
+  matches: number;
+  truncated: boolean;
+  timedOut: boolean;
+  skippedTables: string[];
+  errors: string[];
+  executionTimeMs: number;
+}
+
+export async function searchData(
+  params: SearchDataParams
+): Promise<DataSearchSummary> {
+  return invoke("search_data", { params });
 }
 
 export async function getTableData(
@@ -158,3 +581,501 @@ export async function deleteRow(
     primaryKeyValue,
   });
 }
+
+// History commands
+export interface HistoryEntry {
+  id: number;
+  connectionId: string;
+  sql: string;
+  startedAt: string;
+  durationMs: number;
+  rowCount?: number;
+  status: "success" | "error";
+  error?: string;
+}
+
+export interface ListHistoryParams {
+  connectionId?: string;
+  limit?: number;
+  offset?: number;
+}
+
+export interface SearchHistoryParams {
+  query: string;
+  connectionId?: string;
+  limit?: number;
+}
+
+export async function listHistory(
+  params: ListHistoryParams
+): Promise<HistoryEntry[]> {
+  return invoke("list_history", { params });
+}
+
+export async function searchHistory(
+  params: SearchHistoryParams
+): Promise<HistoryEntry[]> {
+  return invoke("search_history", { params });
+}
+
+export async function clearHistory(connectionId?: string): Promise<void> {
+  return invoke("clear_history", { connectionId });
+}
+
+/** Number of history entries kept before the oldest are pruned */
+export async function getHistoryRetention(): Promise<number> {
+  return invoke("get_history_retention");
+}
+
+export async function setHistoryRetention(retention: number): Promise<void> {
+  return invoke("set_history_retention", { retention });
+}
+
+// Saved query commands
+export interface SavedQuery {
+  id: string;
+  name: string;
+  folder?: string;
+  description?: string;
+  sql: string;
+  /** Template variables referenced in `sql`, in order of first use */
+  variables: string[];
+  connectionId?: string;
+  type?: DatabaseType;
+  createdAt: string;
+  updatedAt: string;
+}
+
+export interface SaveQueryParams {
+  /** Existing ID to update; a new ID is generated when omitted */
+  id?: string;
+  name: string;
+  folder?: string;
+  description?: string;
+  sql: string;
+  connectionId?: string;
+  type?: DatabaseType;
+}
+
+export interface ListSavedQueriesParams {
+  folder?: string;
+  connectionId?: string;
+  type?: DatabaseType;
+}
+
+export async function listSavedQueries(
+  params: ListSavedQueriesParams
+): Promise<SavedQuery[]> {
+  return invoke("list_saved_queries", { params });
+}
+
+export async function listSnippetFolders(): Promise<string[]> {
+  return invoke("list_snippet_folders");
+}
+
+export async function getSavedQuery(id: string): Promise<SavedQuery> {
+  return invoke("get_saved_query", { id });
+}
+
+export async function saveQuery(params: SaveQueryParams): Promise<SavedQuery> {
+  return invoke("save_query", { params });
+}
+
+export async function deleteSavedQuery(id: string): Promise<void> {
+  return invoke("delete_saved_query", { id });
+}
+
+export async function renderSavedQuery(
+  id: string,
+  variables: Record<string, string>
+): Promise<string> {
+  return invoke("render_saved_query", { id, variables });
+}
+
+export async function importSavedQueries(path: string): Promise<SavedQuery[]> {
+  return invoke("import_saved_queries", { path });
+}
+
+export async function exportSavedQueries(
+  path: string,
+  folder?: string
+): Promise<string[]> {
+  return invoke("export_saved_queries", { path, folder });
+}
+
+// Postgres LISTEN/NOTIFY commands
+/** Payload of the `pg-notification` event */
+export interface ChannelNotification {
+  connectionId: string;
+  channel: string;
+  payload: string;
+  processId: number;
+  receivedAt: string;
+}
+
+export async function subscribeChannel(
+  connectionId: string,
+  channel: string
+): Promise<void> {
+  return invoke("subscribe_channel", { connectionId, channel });
+}
+
+export async function unsubscribeChannel(
+  connectionId: string,
+  channel: string
+): Promise<void> {
+  return invoke("unsubscribe_channel", { connectionId, channel });
+}
+
+export async function listSubscriptions(
+  connectionId: string
+): Promise<string[]> {
+  return invoke("list_subscriptions", { connectionId });
+}
+
+export async function listNotifications(
+  connectionId: string
+): Promise<ChannelNotification[]> {
+  return invoke("list_notifications", { connectionId });
+}
+
+// Background query job commands
+export type JobStatus = "running" | "completed" | "failed" | "cancelled";
+
+/** Also the payload of the `query-job` event */
+export interface JobInfo {
+  id: string;
+  connectionId: string;
+  sql: string;
+  status: JobStatus;
+  submittedAt: string;
+  finishedAt?: string;
+  /** Rows written so far; final once the job has finished */
+  rowCount: number;
+  executionTimeMs?: number;
+  error?: string;
+  notices: QueryNotice[];
+}
+
+export interface SubmitQueryJobParams {
+  connectionId: string;
+  sql: string;
+  variables?: Record<string, string>;
+}
+
+export interface GetJobResultParams {
+  jobId: string;
+  limit?: number;
+  offset?: number;
+}
+
+export interface JobResultPage {
+  job: JobInfo;
+  columns: ColumnInfo[];
+  rows: Record<string, unknown>[];
+  offset: number;
+  hasMore: boolean;
+}
+
+export async function submitQueryJob(
+  params: SubmitQueryJobParams
+): Promise<JobInfo> {
+  return invoke("submit_query_job", { params });
+}
+
+export async function listJobs(connectionId?: string): Promise<JobInfo[]> {
+  return invoke("list_jobs", { connectionId });
+}
+
+export async function getJobResult(
+  params: GetJobResultParams
+): Promise<JobResultPage> {
+  return invoke("get_job_result", { params });
+}
+
+export async function cancelJob(jobId: string): Promise<JobInfo> {
+  return invoke("cancel_job", { jobId });
+}
+
+// Result cache commands
+
+export interface ResultSort {
+  column: string;
+  descending?: boolean;
+}
+
+export interface GetResultPageParams {
+  resultId: string;
+  offset?: number;
+  limit?: number;
+  sort?: ResultSort;
+}
+
+export interface ResultPage {
+  resultId: string;
+  columns: ColumnInfo[];
+  rows: Record<string, unknown>[];
+  offset: number;
+  totalRows: number;
+  hasMore: boolean;
+  /** Cells in `rows` whose value was cut to a preview */
+  truncatedCells?: TruncatedCell[];
+}
+
+export async function getResultPage(
+  params: GetResultPageParams
+): Promise<ResultPage> {
+  return invoke("get_result_page", { params });
+}
+
+export async function releaseResult(resultId: string): Promise<void> {
+  return invoke("release_result", { resultId });
+}
+
+// Cell value commands
+
+/**
+ * Read one cell in full, either from a cached result (`resultId` + `row`) or
+ * from a table by primary key (`connectionId` + `table` + `key`)
+ */
+export interface FetchCellValueParams {
+  connectionId?: string;
+  resultId?: string;
+  /** Row number within the cached result, in query order */
+  row?: number;
+  schema?: string;
+  table?: string;
+  column: string;
+  /** Primary key column values identifying the row */
+  key?: Record<string, unknown>;
+  /** Write the value to this file instead of returning it */
+  outputPath?: string;
+}
+
+export type CellEncoding = "text" | "json" | "base64";
+
+export interface CellValue {
+  /** The value, or null when it was written to `path` */
+  value: unknown;
+  encoding: CellEncoding;
+  /** Length of the value in bytes */
+  length: number;
+  path?: string;
+}
+
+export async function fetchCellValue(
+  params: FetchCellValueParams
+): Promise<CellValue> {
+  return invoke("fetch_cell_value", { params });
+}
+
+// Benchmark commands
+
+export interface BenchmarkParams {
+  connectionId: string;
+  sql: string;
+  iterations?: number;
+  /** Number of runs in flight at once, each on its own pooled connection */
+  concurrency?: number;
+  /** Runs made before measuring, to warm caches and plans */
+  warmup?: number;
+  /** Commit each run instead of rolling it back */
+  allowWrites?: boolean;
+}
+
+/** Latency distribution of a benchmark, in milliseconds */
+export interface BenchmarkResult {
+  iterations: number;
+  concurrency: number;
+  warmup: number;
+  minMs: number;
+  medianMs: number;
+  meanMs: number;
+  p95Ms: number;
+  p99Ms: number;
+  maxMs: number;
+  /** Wall-clock time for all measured runs */
+  totalMs: number;
+  queriesPerSecond: number;
+  /** Whether each run was rolled back */
+  rolledBack: boolean;
+}
+
+export async function benchmarkQuery(
+  params: BenchmarkParams
+): Promise<BenchmarkResult> {
+  return invoke("benchmark_query", { params });
+}
+
+// Fan-out commands
+
+export interface FanOutQueryParams {
+  connectionIds: string[];
+  sql: string;
+  /** Values for `{{name}}` template variables in `sql` */
+  variables?: Record<string, string>;
+  /** Connections queried at the same time */
+  parallelism?: number;
+  /** Name of the added column holding each row's connection ID */
+  sourceColumn?: string;
+}
+
+/** Outcome of a fan-out query on one connection */
+export interface FanOutSource {
+  connectionId: string;
+  connectionName: string;
+  rowCount: number;
+  executionTimeMs: number;
+  truncated: boolean;
+  error?: ConnectionError;
+}
+
+/** Rows from every connection that succeeded, merged in request order */
+export interface FanOutResult {
+  columns: ColumnInfo[];
+  rows: Record<string, unknown>[];
+  rowCount: number;
+  executionTimeMs: number;
+  sources: FanOutSource[];
+}
+
+export async function executeFanOut(
+  params: FanOutQueryParams
+): Promise<FanOutResult> {
+  return invoke("execute_fan_out", { params });
+}
+
+// Formatting commands
+
+export interface FormatOptions {
+  keywordCase?: "upper" | "lower" | "preserve";
+  /** Spaces per indentation level */
+  indentWidth?: number;
+  commaStyle?: "trailing" | "leading";
+  /** Lists and parenthesised groups longer than this are broken over lines */
+  lineWidth?: number;
+}
+
+/**
+ * The dialect is taken from the connection when one is given, otherwise
+ * from `dialect`
+ */
+export interface FormatSqlParams {
+  sql: string;
+  connectionId?: string;
+  dialect?: DatabaseType;
+  options?: FormatOptions;
+}
+
+export async function formatSql(params: FormatSqlParams): Promise<string> {
+  return invoke("format_sql", { params });
+}
+
+// Validation commands
+
+/**
+ * The dialect and schema for reference checks come from the connection when
+ * one is given, otherwise the dialect is taken from `dialect`
+ */
+export interface ValidateSqlParams {
+  sql: string;
+  connectionId?: string;
+  dialect?: DatabaseType;
+  database?: string;
+  schema?: string;
+  /** Check table and column names against the connection's cached schema */
+  checkReferences?: boolean;
+}
+
+export type DiagnosticSeverity = "error" | "warning";
+
+/** A problem found in SQL text, spanning `start` to `end` (exclusive) */
+export interface SqlDiagnostic {
+  severity: DiagnosticSeverity;
+  message: string;
+  start: ErrorPosition;
+  end: ErrorPosition;
+  hint?: string;
+}
+
+export interface ValidateSqlResult {
+  /** No diagnostics of error severity */
+  valid: boolean;
+  diagnostics: SqlDiagnostic[];
+  /** Whether table and column references were checked against a schema */
+  referencesChecked: boolean;
+}
+
+export async function validateSql(
+  params: ValidateSqlParams
+): Promise<ValidateSqlResult> {
+  return invoke("validate_sql", { params });
+}
+
+// Completion and schema cache commands
+
+/**
+ * `alias` is a table alias or CTE name in scope; `join` is a join condition
+ * inferred from column names
+ */
+export type CompletionKind =
+  | "keyword"
+  | "schema"
+  | "table"
+  | "view"
+  | "column"
+  | "function"
+  | "alias"
+  | "join";
+
+export interface Completion {
+  label: string;
+  kind: CompletionKind;
+  /** Type, owning table or signature */
+  detail?: string;
+  /** Text to insert when it differs from the label, e.g. quoted or qualified */
+  insertText?: string;
+}
+
+/**
+ * Replace the text between `replaceStart` and `replaceEnd` (character
+ * offsets) with the chosen item
+ */
+export interface CompletionResult {
+  replaceStart: number;
+  replaceEnd: number;
+  items: Completion[];
+}
+
+/** Summary of a connection's cached schema metadata */
+export interface SchemaCacheInfo {
+  loadedAt: string;
+  schemas: number;
+  tables: number;
+  columns: number;
+  functions: number;
+}
+
+export async function completeSql(
+  connectionId: string,
+  sql: string,
+  cursorOffset: number,
+  database?: string,
+  schema?: string
+): Promise<CompletionResult> {
+  return invoke("complete_sql", {
+    connectionId,
+    sql,
+    cursorOffset,
+    database,
+    schema,
+  });
+}
+
+export async function refreshSchemaCache(
+  connectionId: string,
+  database?: string,
+  schema?: string
+): Promise<SchemaCacheInfo> {
+  return invoke("refresh_schema_cache", { connectionId, database, schema });
+}
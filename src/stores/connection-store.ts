@@ -30,6 +30,51 @@ export interface QueryResult {
   rows: Record<string, unknown>[];
   rowCount: number;
   executionTimeMs: number;
+  /** Notices (Postgres) and warnings (MySQL) raised during execution */
+  notices: QueryNotice[];
+  /** Set when the full result is cached on disk; `rows` is then the first page */
+  resultId?: string;
+  /** Set when reading stopped early because a size cap was reached */
+  truncated: boolean;
+  truncatedReason?: TruncationReason;
+  /** Cells whose value was cut to a preview */
+  truncatedCells?: TruncatedCell[];
+  /** Timing breakdown of the execution */
+  stats?: QueryStats;
+}
+
+/**
+ * Where the time went while running a query, in milliseconds. Pool wait,
+ * execution, fetch and decode don't overlap and add up to roughly the total.
+ */
+export interface QueryStats {
+  poolWaitMs: number;
+  /** From the start until the first row arrived, including the pool wait */
+  firstRowMs: number;
+  executionMs: number;
+  fetchMs: number;
+  decodeMs: number;
+  totalMs: number;
+  bytesReceived: number;
+  /** Postgres backend PID or MySQL connection ID, when requested */
+  backendId?: number;
+}
+
+/** Which cap stopped a result from being read in full */
+export type TruncationReason = "rowLimit" | "byteLimit";
+
+export interface TruncatedCell {
+  row: number;
+  column: string;
+  /** Full length of the value in bytes */
+  length: number;
+}
+
+export interface QueryNotice {
+  severity: "error" | "warning" | "notice" | "info" | "debug";
+  /** MySQL warning code; Postgres notices carry none */
+  code?: string;
+  message: string;
 }
 
 export interface ColumnInfo {
@@ -41,8 +86,12 @@ export interface ColumnInfo {
 export interface TableInfo {
   name: string;
   schema: string;
-  type: "table" | "view";
+  type: "table" | "view" | "materializedView";
   rowCount?: number;
+  totalBytes?: number;
+  tableBytes?: number;
+  indexBytes?: number;
+  toastBytes?: number;
 }
 
 interface ConnectionStore {